@@ -0,0 +1,48 @@
+//! Encrypts a file under a password and decrypts it again.
+//!
+//! ```text
+//! cargo run -p aes --example password_file -- <password> <path>
+//! ```
+//!
+//! The output file is `salt || nonce || ciphertext || tag`: the salt lets the
+//! key be derived again from the password, and `AES::seal` supplies the
+//! nonce and tag.
+use std::{env, fs, process};
+
+use aes::AES;
+use rand::{rngs::OsRng, RngCore};
+
+const SALT_SIZE: usize = 16;
+const ITERATIONS: u32 = 600_000;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let [_, password, path] = args.as_slice() else {
+        eprintln!("usage: password_file <password> <path>");
+        process::exit(1);
+    };
+
+    let contents = fs::read(path).expect("failed to read input file");
+
+    let mut salt = [0u8; SALT_SIZE];
+    OsRng.fill_bytes(&mut salt);
+
+    let aes = AES::from_password(password.as_bytes(), &salt, ITERATIONS).unwrap();
+    let mut encrypted = salt.to_vec();
+    encrypted.extend_from_slice(&aes.seal(&contents, &[]).unwrap());
+
+    let encrypted_path = format!("{path}.enc");
+    fs::write(&encrypted_path, &encrypted).expect("failed to write encrypted file");
+    println!("wrote {} bytes to {encrypted_path}", encrypted.len());
+
+    // Decrypting needs only the password and the file.
+    let encrypted = fs::read(&encrypted_path).expect("failed to read encrypted file");
+    let (salt, blob) = encrypted.split_at(SALT_SIZE);
+    let aes = AES::from_password(password.as_bytes(), salt, ITERATIONS).unwrap();
+    let decrypted = aes
+        .open(blob, &[])
+        .expect("wrong password or corrupted file");
+
+    assert_eq!(decrypted, contents);
+    println!("decrypted {} bytes", decrypted.len());
+}
@@ -0,0 +1,109 @@
+//! An AES core on the x86_64 AES-NI instructions, which run a whole round
+//! in hardware in constant time. `AesOps` picks it at runtime when CPUID
+//! reports support and falls back to a software core otherwise.
+//!
+//! The state is stored column by column, which is exactly the byte
+//! order the instructions expect, so blocks and round keys load directly.
+use std::arch::x86_64::{
+    __m128i, _mm_aesdec_si128, _mm_aesdeclast_si128, _mm_aesenc_si128, _mm_aesenclast_si128,
+    _mm_aesimc_si128, _mm_loadu_si128, _mm_storeu_si128, _mm_xor_si128,
+};
+
+use super::{key_schedule::KeySchedule, state::State};
+
+/// Returns whether the CPU supports the AES-NI instructions.
+///
+/// The standard library caches the CPUID query, so this is cheap to call
+/// for every block.
+pub fn is_available() -> bool {
+    std::arch::is_x86_feature_detected!("aes")
+}
+
+#[inline(always)]
+fn load(block: &[[u8; 4]; 4]) -> __m128i {
+    // SAFETY: `block` is 16 readable bytes; `loadu` has no alignment requirement.
+    unsafe { _mm_loadu_si128(block.as_ptr().cast()) }
+}
+
+#[inline(always)]
+fn store(value: __m128i, block: &mut [[u8; 4]; 4]) {
+    // SAFETY: `block` is 16 writable bytes; `storeu` has no alignment requirement.
+    unsafe { _mm_storeu_si128(block.as_mut_ptr().cast(), value) }
+}
+
+/// Encrypts `state` in place; equivalent to the reference `AesOps::encrypt`.
+///
+/// # Safety
+/// The CPU must support AES-NI, see `is_available`.
+#[target_feature(enable = "aes")]
+pub unsafe fn encrypt(state: &mut State, keys: &KeySchedule) {
+    let rounds = keys.rounds as usize;
+    let mut block = _mm_xor_si128(load(state.columns()), load(keys.round_key(0).columns()));
+
+    for round in 1..rounds {
+        block = _mm_aesenc_si128(block, load(keys.round_key(round).columns()));
+    }
+    block = _mm_aesenclast_si128(block, load(keys.round_key(rounds).columns()));
+
+    store(block, state.columns_mut());
+}
+
+/// Decrypts `state` in place with the equivalent inverse cipher, whose
+/// middle round keys pass through InvMixColumns (`aesimc`) first.
+///
+/// # Safety
+/// The CPU must support AES-NI, see `is_available`.
+#[target_feature(enable = "aes")]
+pub unsafe fn decrypt(state: &mut State, keys: &KeySchedule) {
+    let rounds = keys.rounds as usize;
+    let mut block = _mm_xor_si128(
+        load(state.columns()),
+        load(keys.round_key(rounds).columns()),
+    );
+
+    for round in (1..rounds).rev() {
+        let key = _mm_aesimc_si128(load(keys.round_key(round).columns()));
+        block = _mm_aesdec_si128(block, key);
+    }
+    block = _mm_aesdeclast_si128(block, load(keys.round_key(0).columns()));
+
+    store(block, state.columns_mut());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes_ops::AesOps;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn matches_reference_core() {
+        if !is_available() {
+            return;
+        }
+
+        let mut rng = StdRng::seed_from_u64(3768);
+
+        for key_len in [16, 24, 32] {
+            for _ in 0..50 {
+                let key: Vec<u8> = (0..key_len).map(|_| rng.gen()).collect();
+                let keys = KeySchedule::new(&key).unwrap();
+                let block = State::from_columns(rng.gen());
+
+                let mut expected = block;
+                AesOps::reference_encrypt(&mut expected, &keys);
+                let mut state = block;
+                // SAFETY: checked by `is_available` above.
+                unsafe { encrypt(&mut state, &keys) };
+                assert_eq!(state, expected);
+
+                let mut expected = block;
+                AesOps::reference_decrypt(&mut expected, &keys);
+                let mut state = block;
+                // SAFETY: checked by `is_available` above.
+                unsafe { decrypt(&mut state, &keys) };
+                assert_eq!(state, expected);
+            }
+        }
+    }
+}
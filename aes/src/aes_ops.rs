@@ -1,17 +1,24 @@
+#[cfg(target_arch = "x86_64")]
+use super::aes_ni;
+#[cfg(feature = "bitsliced")]
+use super::bitsliced;
+#[cfg(all(feature = "t-tables", not(feature = "bitsliced")))]
+use super::t_tables;
 use super::{
     constants::{
         AES_INVERSE_S_BOX, AES_S_BOX, INVERSE_TRANSFORMATION_MATRIX, TRANSFORMATION_MATRIX,
     },
+    definitions::BlockCipher,
     key_schedule::KeySchedule,
-    util::{galois_mul, xor_matrices},
+    state::State,
+    util::{galois_mul, rotate_left},
 };
 
 pub struct AesOps;
 
 impl AesOps {
     /// Performs AES encryption on the given state.
-    ///
-    /// The `state` is a mutable reference to a vector of 4-byte arrays,
+    /// The `state` is a mutable reference to the column-major AES state,
     /// which is encrypted using the provided key schedule. The encryption
     /// modifies the `state` in place, resulting in the ciphertext.
     ///
@@ -33,7 +40,38 @@ impl AesOps {
     /// The final encrypted state, or ciphertext, is stored in the `state`
     /// after the completion of this method. As the encryption is done in place,
     /// the input `state` is overwritten with the encrypted data.
-    pub fn encrypt(state: &mut [[u8; 4]; 4], keys: &KeySchedule) {
+    ///
+    /// On x86_64 CPUs with AES-NI, the rounds run in hardware. Otherwise, see
+    /// `software_encrypt`.
+    pub fn encrypt(state: &mut State, keys: &KeySchedule) {
+        #[cfg(target_arch = "x86_64")]
+        if aes_ni::is_available() {
+            // SAFETY: the CPU supports AES-NI.
+            unsafe { aes_ni::encrypt(state, keys) };
+            return;
+        }
+
+        Self::software_encrypt(state, keys);
+    }
+
+    /// Performs AES encryption on the given state without hardware support.
+    ///
+    /// With the `bitsliced` feature, the rounds run on the constant-time
+    /// bitsliced core; otherwise, with the `t-tables` feature, on the T-table
+    /// core; and otherwise on the step-by-step reference implementation.
+    pub fn software_encrypt(state: &mut State, keys: &KeySchedule) {
+        #[cfg(feature = "bitsliced")]
+        bitsliced::encrypt(state, keys);
+
+        #[cfg(all(feature = "t-tables", not(feature = "bitsliced")))]
+        t_tables::encrypt(state, keys);
+
+        #[cfg(not(any(feature = "t-tables", feature = "bitsliced")))]
+        Self::reference_encrypt(state, keys);
+    }
+
+    /// Performs AES encryption step by step, exactly as FIPS-197 describes it.
+    pub fn reference_encrypt(state: &mut State, keys: &KeySchedule) {
         let rounds = keys.rounds;
         // Add initial round key
         Self::add_round_key(state, keys.round_key(0));
@@ -52,7 +90,53 @@ impl AesOps {
         Self::add_round_key(state, keys.round_key(rounds as usize));
     }
 
-    pub fn decrypt(cipher_bytes: &mut [[u8; 4]; 4], keys: &KeySchedule) {
+    /// Encrypts a single 16-byte block, for modes that work on raw bytes
+    /// rather than states.
+    ///
+    /// # Arguments
+    /// * `block` - The plaintext block.
+    /// * `keys` - A reference to the `KeySchedule` used for the encryption.
+    ///
+    /// # Returns
+    /// The ciphertext block.
+    pub fn encrypt_block(block: &[u8; 16], keys: &KeySchedule) -> [u8; 16] {
+        let mut state = State::from_bytes(block);
+        Self::encrypt(&mut state, keys);
+
+        state.to_bytes()
+    }
+
+    /// Performs AES decryption on the given state, in place.
+    ///
+    /// # Arguments
+    /// * `cipher_bytes` - A mutable reference to the AES state to be decrypted.
+    /// * `keys` - A reference to the `KeySchedule` used for the encryption.
+    pub fn decrypt(cipher_bytes: &mut State, keys: &KeySchedule) {
+        #[cfg(target_arch = "x86_64")]
+        if aes_ni::is_available() {
+            // SAFETY: the CPU supports AES-NI.
+            unsafe { aes_ni::decrypt(cipher_bytes, keys) };
+            return;
+        }
+
+        Self::software_decrypt(cipher_bytes, keys);
+    }
+
+    /// Performs AES decryption on the given state without hardware support,
+    /// on the same core as `software_encrypt`.
+    pub fn software_decrypt(cipher_bytes: &mut State, keys: &KeySchedule) {
+        #[cfg(feature = "bitsliced")]
+        bitsliced::decrypt(cipher_bytes, keys);
+
+        #[cfg(all(feature = "t-tables", not(feature = "bitsliced")))]
+        t_tables::decrypt(cipher_bytes, keys);
+
+        #[cfg(not(any(feature = "t-tables", feature = "bitsliced")))]
+        Self::reference_decrypt(cipher_bytes, keys);
+    }
+
+    /// Performs AES decryption step by step, exactly as FIPS-197 describes it.
+    pub fn reference_decrypt(cipher_bytes: &mut State, keys: &KeySchedule) {
         let rounds = keys.rounds;
 
         Self::add_round_key(cipher_bytes, keys.round_key(rounds as usize));
@@ -75,8 +159,8 @@ impl AesOps {
     ///
     /// # Arguments
     /// * `key` - The round key to be XORed with the AES state.
-    fn add_round_key(state: &mut [[u8; 4]; 4], key: [[u8; 4]; 4]) {
-        *state = xor_matrices(*state, key);
+    fn add_round_key(state: &mut State, key: State) {
+        *state ^= key;
     }
 
     /// Performs the SubBytes or InvSubBytes transformation on the AES state.
@@ -88,39 +172,24 @@ impl AesOps {
     /// and InvSubBytes in decryption.
     ///
     /// # Arguments
-    /// * `state` - A mutable reference to the 4x4 state matrix.
+    /// * `state` - A mutable reference to the AES state.
     /// * `s_box` - The S-box used for the transformation, either standard or inverse.
-    fn sub_bytes(state: &mut [[u8; 4]; 4], s_box: [u8; 256]) {
-        // Iterate over each byte of the state matrix
-        for row in state.iter_mut() {
-            for e in row.iter_mut() {
-                // Apply the S-box transformation and store in `new_state`
-                *e = s_box[*e as usize];
-            }
+    fn sub_bytes(state: &mut State, s_box: [u8; 256]) {
+        for byte in state.bytes_mut() {
+            *byte = s_box[*byte as usize];
         }
     }
 
     /// Performs the "ShiftRows" step in the AES encryption process.
-    /// This function shifts the rows of the state matrix as per AES specification:
+    /// This function shifts the rows of the state as per AES specification:
     /// - The first row is not shifted.
     /// - Each subsequent row is shifted to the left by an offset equal to its row index.
-    /// - The state matrix is assumed to be column-major, i.e., each inner array represents a column.
     ///
     /// # Arguments
-    /// * `&mut self` - A mutable reference to the current instance of the AES struct.
-    fn shift_rows(state: &mut [[u8; 4]; 4]) {
-        // Temporary variable to hold the values for row shifting
-        let mut temp: [u8; 4] = [0; 4];
-
-        for i in 1..4 {
-            for j in 0..4 {
-                // Store the shifted row in a temporary variable
-                temp[j] = state[(j + i) % 4][i];
-            }
-            for j in 0..4 {
-                // Update the state with the shifted values
-                state[j][i] = temp[j];
-            }
+    /// * `state` - A mutable reference to the AES state.
+    fn shift_rows(state: &mut State) {
+        for r in 1..4 {
+            state.set_row(r, rotate_left(&state.row(r), r));
         }
     }
 
@@ -131,82 +200,81 @@ impl AesOps {
     /// the third row by two positions, and the fourth row by three positions.
     ///
     /// # Arguments
-    /// * `state` - A mutable reference to the 4x4 state matrix.
-    fn inv_shift_rows(state: &mut [[u8; 4]; 4]) {
-        let mut temp: [u8; 4];
-
-        // Second row: shift one position to the right
-        temp = [state[3][1], state[0][1], state[1][1], state[2][1]];
-        for j in 0..4 {
-            state[j][1] = temp[j];
-        }
-
-        // Third row: shift two positions to the right
-        temp = [state[2][2], state[3][2], state[0][2], state[1][2]];
-        for j in 0..4 {
-            state[j][2] = temp[j];
-        }
-
-        // Fourth row: shift three positions to the right
-        temp = [state[1][3], state[2][3], state[3][3], state[0][3]];
-        for j in 0..4 {
-            state[j][3] = temp[j];
+    /// * `state` - A mutable reference to the AES state.
+    fn inv_shift_rows(state: &mut State) {
+        for r in 1..4 {
+            state.set_row(r, rotate_left(&state.row(r), 4 - r));
         }
     }
 
     /// Performs the MixColumns or InvMixColumns transformation on the AES state.
     ///
     /// This function applies either the MixColumns or InvMixColumns step to each column
-    /// of the AES state matrix, depending on the provided transformation matrix.
+    /// of the AES state, depending on the provided transformation matrix.
     /// It uses Galois Field multiplication (`galois_mul`) for the transformation.
     ///
     /// The transformation matrix should be the standard matrix for MixColumns in AES encryption,
     /// or the inverse matrix for InvMixColumns in AES decryption.
     ///
     /// # Arguments
-    /// * `state` - A mutable reference to the 4x4 state matrix.
+    /// * `state` - A mutable reference to the AES state.
     /// * `transformation_matrix` - The matrix used for the transformation, either for
     ///   MixColumns or InvMixColumns.
-    fn mix_columns(state: &mut [[u8; 4]; 4], transformation_matrix: [[u8; 4]; 4]) {
-        for col in 0..4 {
+    fn mix_columns(state: &mut State, transformation_matrix: [[u8; 4]; 4]) {
+        for column in state.columns_mut().iter_mut() {
             // Temporary storage for the column being processed
             let mut temp_column = [0u8; 4];
 
             // Transform the current column using Galois Field multiplication
-            for i in 0..4 {
-                temp_column[i] = galois_mul(transformation_matrix[i][0], state[col][0])
-                    ^ galois_mul(transformation_matrix[i][1], state[col][1])
-                    ^ galois_mul(transformation_matrix[i][2], state[col][2])
-                    ^ galois_mul(transformation_matrix[i][3], state[col][3]);
+            for (i, row) in transformation_matrix.iter().enumerate() {
+                temp_column[i] = galois_mul(row[0], column[0])
+                    ^ galois_mul(row[1], column[1])
+                    ^ galois_mul(row[2], column[2])
+                    ^ galois_mul(row[3], column[3]);
             }
 
-            // Update the state matrix with the transformed column
-            for i in 0..4 {
-                state[col][i] = temp_column[i];
-            }
+            // Update the state with the transformed column
+            *column = temp_column;
         }
     }
 }
 
+/// An expanded key is all the modes of operation need to run AES.
+impl BlockCipher for KeySchedule {
+    const BLOCK_SIZE: usize = 16;
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let mut state = State::from_bytes((&*block).try_into().expect("AES blocks are 16 bytes"));
+        AesOps::encrypt(&mut state, self);
+        block.copy_from_slice(&state.to_bytes());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let mut state = State::from_bytes((&*block).try_into().expect("AES blocks are 16 bytes"));
+        AesOps::decrypt(&mut state, self);
+        block.copy_from_slice(&state.to_bytes());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn aes_ops_encrypt_decrypt_test() {
-        let mut state: [[u8; 4]; 4] = [
+        let mut state = State::from_columns([
             [0, 17, 34, 51],
             [68, 85, 102, 119],
             [136, 153, 170, 187],
             [204, 221, 238, 255],
-        ];
+        ]);
 
         let key_schedule =
             KeySchedule::new(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]).unwrap();
 
         AesOps::encrypt(&mut state, &key_schedule);
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [105, 196, 224, 216],
                 [106, 123, 4, 48],
@@ -217,7 +285,7 @@ mod tests {
 
         AesOps::decrypt(&mut state, &key_schedule);
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [0, 17, 34, 51],
                 [68, 85, 102, 119],
@@ -228,20 +296,91 @@ mod tests {
     }
 
     #[test]
-    fn one_round_encryption_test() {
-        let mut state: [[u8; 4]; 4] = [
+    fn software_core_encrypt_decrypt_test() {
+        // FIPS-197 Appendix C.1, on the core used when AES-NI is missing.
+        let plaintext = State::from_columns([
             [0, 17, 34, 51],
             [68, 85, 102, 119],
             [136, 153, 170, 187],
             [204, 221, 238, 255],
+        ]);
+        let key_schedule =
+            KeySchedule::new(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]).unwrap();
+
+        let mut state = plaintext;
+        AesOps::software_encrypt(&mut state, &key_schedule);
+        assert_eq!(
+            *state.columns(),
+            [
+                [105, 196, 224, 216],
+                [106, 123, 4, 48],
+                [216, 205, 183, 128],
+                [112, 180, 197, 90]
+            ]
+        );
+
+        AesOps::software_decrypt(&mut state, &key_schedule);
+        assert_eq!(state, plaintext);
+    }
+
+    #[test]
+    fn fips197_longer_keys() {
+        // FIPS-197 Appendix C.2 (AES-192) and C.3 (AES-256).
+        let plaintext = State::from_columns([
+            [0, 17, 34, 51],
+            [68, 85, 102, 119],
+            [136, 153, 170, 187],
+            [204, 221, 238, 255],
+        ]);
+        let vectors: [(usize, [[u8; 4]; 4]); 2] = [
+            (
+                24,
+                [
+                    [0xdd, 0xa9, 0x7c, 0xa4],
+                    [0x86, 0x4c, 0xdf, 0xe0],
+                    [0x6e, 0xaf, 0x70, 0xa0],
+                    [0xec, 0x0d, 0x71, 0x91],
+                ],
+            ),
+            (
+                32,
+                [
+                    [0x8e, 0xa2, 0xb7, 0xca],
+                    [0x51, 0x67, 0x45, 0xbf],
+                    [0xea, 0xfc, 0x49, 0x90],
+                    [0x4b, 0x49, 0x60, 0x89],
+                ],
+            ),
         ];
 
+        for (key_len, expected) in vectors {
+            let key: Vec<u8> = (0..key_len as u8).collect();
+            let key_schedule = KeySchedule::new(&key).unwrap();
+
+            let mut state = plaintext;
+            AesOps::encrypt(&mut state, &key_schedule);
+            assert_eq!(*state.columns(), expected);
+
+            AesOps::decrypt(&mut state, &key_schedule);
+            assert_eq!(state, plaintext);
+        }
+    }
+
+    #[test]
+    fn one_round_encryption_test() {
+        let mut state = State::from_columns([
+            [0, 17, 34, 51],
+            [68, 85, 102, 119],
+            [136, 153, 170, 187],
+            [204, 221, 238, 255],
+        ]);
+
         let key_schedule =
             KeySchedule::new(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]).unwrap();
 
         AesOps::add_round_key(&mut state, key_schedule.round_key(0));
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [0, 16, 32, 48],
                 [64, 80, 96, 112],
@@ -252,7 +391,7 @@ mod tests {
 
         AesOps::sub_bytes(&mut state, AES_S_BOX);
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [99, 202, 183, 4],
                 [9, 83, 208, 81],
@@ -263,7 +402,7 @@ mod tests {
 
         AesOps::shift_rows(&mut state);
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [99, 83, 224, 140],
                 [9, 96, 225, 4],
@@ -274,7 +413,7 @@ mod tests {
 
         AesOps::mix_columns(&mut state, TRANSFORMATION_MATRIX);
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [95, 114, 100, 21],
                 [87, 245, 188, 146],
@@ -289,16 +428,16 @@ mod tests {
         let key_schedule =
             KeySchedule::new(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]).unwrap();
 
-        let mut state: [[u8; 4]; 4] = [
+        let mut state = State::from_columns([
             [105, 196, 224, 216],
             [106, 123, 4, 48],
             [216, 205, 183, 128],
             [112, 180, 197, 90],
-        ];
+        ]);
 
         AesOps::add_round_key(&mut state, key_schedule.round_key(10));
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [122, 213, 253, 167],
                 [137, 239, 78, 39],
@@ -309,7 +448,7 @@ mod tests {
 
         AesOps::inv_shift_rows(&mut state);
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [122, 159, 16, 39],
                 [137, 213, 245, 11],
@@ -320,7 +459,7 @@ mod tests {
 
         AesOps::sub_bytes(&mut state, AES_INVERSE_S_BOX);
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [189, 110, 124, 61],
                 [242, 181, 119, 158],
@@ -333,7 +472,7 @@ mod tests {
 
         AesOps::mix_columns(&mut state, INVERSE_TRANSFORMATION_MATRIX);
         assert_eq!(
-            state,
+            *state.columns(),
             [
                 [189, 110, 124, 61],
                 [242, 181, 119, 158],
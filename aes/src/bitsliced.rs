@@ -0,0 +1,264 @@
+//! A constant-time AES core with no lookups indexed by secret data.
+//!
+//! SubBytes is bitsliced: the 16 state bytes are transposed into 8 bit
+//! planes, plane `i` holding bit `i` of every byte, and the S-box is
+//! evaluated as a boolean circuit on all 16 bytes at once. The circuit
+//! computes the GF(2^8) inverse as `x^254` (which maps 0 to 0, as AES
+//! requires) followed by the affine map. ShiftRows is a fixed permutation
+//! and MixColumns uses a masked `xtime`, so neither branches on nor indexes
+//! by the state. This is slower than the table cores, but its timing does
+//! not depend on the key or the data.
+use super::{key_schedule::KeySchedule, state::State};
+
+type Planes = [u16; 8];
+
+/// The constant of the forward affine map.
+const AFFINE_CONSTANT: u8 = 0x63;
+
+/// The constant of the inverse affine map.
+const INVERSE_AFFINE_CONSTANT: u8 = 0x05;
+
+/// Encrypts `state` in place; equivalent to the reference `AesOps::encrypt`.
+pub fn encrypt(state: &mut State, keys: &KeySchedule) {
+    let rounds = keys.rounds as usize;
+    let state = state.columns_mut();
+    add_round_key(state, keys.round_key(0));
+
+    for round in 1..rounds {
+        sub_bytes(state);
+        shift_rows(state);
+        mix_columns(state);
+        add_round_key(state, keys.round_key(round));
+    }
+
+    // Final round without MixColumns
+    sub_bytes(state);
+    shift_rows(state);
+    add_round_key(state, keys.round_key(rounds));
+}
+
+/// Decrypts `state` in place; equivalent to the reference `AesOps::decrypt`.
+pub fn decrypt(state: &mut State, keys: &KeySchedule) {
+    let rounds = keys.rounds as usize;
+    let state = state.columns_mut();
+    add_round_key(state, keys.round_key(rounds));
+
+    for round in (1..rounds).rev() {
+        inv_shift_rows(state);
+        inv_sub_bytes(state);
+        add_round_key(state, keys.round_key(round));
+        inv_mix_columns(state);
+    }
+
+    inv_shift_rows(state);
+    inv_sub_bytes(state);
+    add_round_key(state, keys.round_key(0));
+}
+
+fn add_round_key(state: &mut [[u8; 4]; 4], key: State) {
+    for (column, key_column) in state.iter_mut().zip(key.columns()) {
+        for (byte, key_byte) in column.iter_mut().zip(key_column) {
+            *byte ^= key_byte;
+        }
+    }
+}
+
+/// Transposes the state into bit planes; bit `4c + r` of plane `i` is bit
+/// `i` of `state[c][r]`.
+fn to_planes(state: &[[u8; 4]; 4]) -> Planes {
+    let mut planes = [0u16; 8];
+    for (k, &byte) in state.iter().flatten().enumerate() {
+        for (i, plane) in planes.iter_mut().enumerate() {
+            *plane |= (((byte >> i) & 1) as u16) << k;
+        }
+    }
+    planes
+}
+
+fn from_planes(planes: &Planes, state: &mut [[u8; 4]; 4]) {
+    for (k, byte) in state.iter_mut().flatten().enumerate() {
+        *byte = planes
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, plane)| acc | (((plane >> k) & 1) as u8) << i);
+    }
+}
+
+/// Multiplies 16 pairs of field elements at once, reducing by
+/// `x^8 + x^4 + x^3 + x + 1`.
+fn gf_mul(a: &Planes, b: &Planes) -> Planes {
+    let mut product = [0u16; 15];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            product[i + j] ^= a & b;
+        }
+    }
+
+    // x^k = x^(k-4) + x^(k-5) + x^(k-7) + x^(k-8) for k >= 8
+    for k in (8..15).rev() {
+        let high = product[k];
+        product[k - 4] ^= high;
+        product[k - 5] ^= high;
+        product[k - 7] ^= high;
+        product[k - 8] ^= high;
+    }
+
+    product[..8].try_into().expect("8 planes")
+}
+
+/// Computes `x^254`, the multiplicative inverse of every non-zero element.
+fn gf_inv(x: &Planes) -> Planes {
+    let x2 = gf_mul(x, x);
+    let x3 = gf_mul(&x2, x);
+    let x6 = gf_mul(&x3, &x3);
+    let x12 = gf_mul(&x6, &x6);
+    let x15 = gf_mul(&x12, &x3);
+    let x30 = gf_mul(&x15, &x15);
+    let x60 = gf_mul(&x30, &x30);
+    let x120 = gf_mul(&x60, &x60);
+    let x240 = gf_mul(&x120, &x120);
+    let x252 = gf_mul(&x240, &x12);
+
+    gf_mul(&x252, &x2)
+}
+
+/// XORs every byte with `constant`, by flipping plane `i` where bit `i` is set.
+fn xor_constant(planes: &mut Planes, constant: u8) {
+    for (i, plane) in planes.iter_mut().enumerate() {
+        *plane ^= 0u16.wrapping_sub(((constant >> i) & 1) as u16);
+    }
+}
+
+fn sub_bytes(state: &mut [[u8; 4]; 4]) {
+    let b = gf_inv(&to_planes(state));
+
+    let mut planes: Planes = core::array::from_fn(|i| {
+        b[i] ^ b[(i + 4) % 8] ^ b[(i + 5) % 8] ^ b[(i + 6) % 8] ^ b[(i + 7) % 8]
+    });
+    xor_constant(&mut planes, AFFINE_CONSTANT);
+
+    from_planes(&planes, state);
+}
+
+/// Applies the S-box to the four bytes of a key schedule word.
+pub fn sub_word(word: [u8; 4]) -> [u8; 4] {
+    let mut state = [word, [0; 4], [0; 4], [0; 4]];
+    sub_bytes(&mut state);
+
+    state[0]
+}
+
+fn inv_sub_bytes(state: &mut [[u8; 4]; 4]) {
+    let b = to_planes(state);
+
+    let mut planes: Planes =
+        core::array::from_fn(|i| b[(i + 2) % 8] ^ b[(i + 5) % 8] ^ b[(i + 7) % 8]);
+    xor_constant(&mut planes, INVERSE_AFFINE_CONSTANT);
+
+    from_planes(&gf_inv(&planes), state);
+}
+
+fn shift_rows(state: &mut [[u8; 4]; 4]) {
+    let old = *state;
+    for (c, column) in state.iter_mut().enumerate() {
+        for (r, byte) in column.iter_mut().enumerate() {
+            *byte = old[(c + r) % 4][r];
+        }
+    }
+}
+
+fn inv_shift_rows(state: &mut [[u8; 4]; 4]) {
+    let old = *state;
+    for (c, column) in state.iter_mut().enumerate() {
+        for (r, byte) in column.iter_mut().enumerate() {
+            *byte = old[(c + 4 - r) % 4][r];
+        }
+    }
+}
+
+/// Multiplies by x in GF(2^8) without branching on the top bit.
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ (0x1b & 0u8.wrapping_sub(b >> 7))
+}
+
+fn mix_columns(state: &mut [[u8; 4]; 4]) {
+    for column in state.iter_mut() {
+        let [a0, a1, a2, a3] = *column;
+        let all = a0 ^ a1 ^ a2 ^ a3;
+
+        column[0] ^= all ^ xtime(a0 ^ a1);
+        column[1] ^= all ^ xtime(a1 ^ a2);
+        column[2] ^= all ^ xtime(a2 ^ a3);
+        column[3] ^= all ^ xtime(a3 ^ a0);
+    }
+}
+
+fn inv_mix_columns(state: &mut [[u8; 4]; 4]) {
+    // InvMixColumns is MixColumns applied after mapping each column
+    // (a0, a1, a2, a3) to (5a0 + 4a2, 5a1 + 4a3, 5a2 + 4a0, 5a3 + 4a1).
+    for column in state.iter_mut() {
+        let u = xtime(xtime(column[0] ^ column[2]));
+        let v = xtime(xtime(column[1] ^ column[3]));
+
+        column[0] ^= u;
+        column[1] ^= v;
+        column[2] ^= u;
+        column[3] ^= v;
+    }
+
+    mix_columns(state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes_ops::AesOps;
+    use crate::constants::{AES_INVERSE_S_BOX, AES_S_BOX};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn circuit_reproduces_s_boxes() {
+        for chunk in 0..16 {
+            let bytes: [u8; 16] = core::array::from_fn(|k| (16 * chunk + k) as u8);
+            let block: [[u8; 4]; 4] =
+                core::array::from_fn(|c| core::array::from_fn(|r| bytes[4 * c + r]));
+
+            let mut state = block;
+            sub_bytes(&mut state);
+            for (k, byte) in state.iter().flatten().enumerate() {
+                assert_eq!(*byte, AES_S_BOX[bytes[k] as usize]);
+            }
+
+            let mut state = block;
+            inv_sub_bytes(&mut state);
+            for (k, byte) in state.iter().flatten().enumerate() {
+                assert_eq!(*byte, AES_INVERSE_S_BOX[bytes[k] as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_reference_core() {
+        let mut rng = StdRng::seed_from_u64(3767);
+
+        for key_len in [16, 24, 32] {
+            for _ in 0..20 {
+                let key: Vec<u8> = (0..key_len).map(|_| rng.gen()).collect();
+                let keys = KeySchedule::new(&key).unwrap();
+                let block = State::from_columns(rng.gen());
+
+                let mut expected = block;
+                AesOps::reference_encrypt(&mut expected, &keys);
+                let mut state = block;
+                encrypt(&mut state, &keys);
+                assert_eq!(state, expected);
+
+                let mut expected = block;
+                AesOps::reference_decrypt(&mut expected, &keys);
+                let mut state = block;
+                decrypt(&mut state, &keys);
+                assert_eq!(state, expected);
+            }
+        }
+    }
+}
@@ -1,31 +1,38 @@
 use rand::{rngs::OsRng, RngCore};
+use utils::mac::ct_eq;
 
 use super::{
-    aes_ops::AesOps,
-    definitions::{AesEncryptor, PaddingProcessor},
+    definitions::{AesEncryptor, BlockCipher, PaddingProcessor, StreamingEncryptor},
     error::AesError,
     key_schedule::KeySchedule,
-    pkcs_padding::PkcsPadding,
-    util::*,
 };
 
-pub struct CbcEncryptor<'k> {
+const BLOCK_SIZE: usize = 16;
+
+/// CBC mode over any `BlockCipher`, AES by default.
+pub struct CbcEncryptor<'k, C: BlockCipher = KeySchedule> {
+    /// Plaintext bytes of a streamed message still waiting for a whole block,
+    /// or `None` when no message is in progress.
     pub state: Option<Vec<u8>>,
     pub padding_processor: Box<dyn PaddingProcessor>,
-    pub iv: [[u8; 4]; 4],
-    keys: &'k KeySchedule,
+    iv: Vec<u8>,
+    /// The last ciphertext block, XORed into the next plaintext block.
+    chain: Vec<u8>,
+    /// Whether a message has already been encrypted under `iv`.
+    iv_used: bool,
+    keys: &'k C,
 }
 
-impl<'k> CbcEncryptor<'k> {
-    /// Generates a 16-byte initialization vector (IV) for AES encryption.
+impl<'k, C: BlockCipher> CbcEncryptor<'k, C> {
+    /// Generates a one-block initialization vector (IV).
     ///
     /// This function uses a cryptographically secure random number generator (OsRng)
-    /// to fill a 16-byte array with random data, which serves as the IV.
+    /// to fill a block with random data, which serves as the IV.
     ///
     /// Returns:
-    /// A 16-byte array `[u8; 16]` representing the IV.
-    fn gen_iv() -> [u8; 16] {
-        let mut iv = [0u8; 16];
+    /// A `C::BLOCK_SIZE` byte vector representing the IV.
+    fn gen_iv() -> Vec<u8> {
+        let mut iv = vec![0u8; C::BLOCK_SIZE];
         OsRng.fill_bytes(&mut iv);
 
         iv
@@ -42,55 +49,233 @@ impl<'k> CbcEncryptor<'k> {
     /// A `Result` containing the new instance or an `AesError` on failure.
     ///
     /// The function initializes the key schedule for AES based on `pk`,
-    /// sets the initial state and IV, and stores the padding processor.
+    /// sets the initial state and a random IV, and stores the padding processor.
+    /// The IV can be read back with `iv` so the ciphertext can later be decrypted.
     pub fn new<T: PaddingProcessor + 'static>(
-        keys: &'k KeySchedule,
+        keys: &'k C,
         padding_processor: T,
     ) -> Result<Self, AesError> {
+        Self::with_iv(keys, &Self::gen_iv(), padding_processor)
+    }
+
+    /// Creates a new CBC encryptor that uses the given initialization vector.
+    ///
+    /// Use this to decrypt a message, passing the IV it was encrypted with.
+    ///
+    /// Parameters:
+    /// * `keys`: The expanded key of the block cipher.
+    /// * `iv`: The initialization vector, one block long.
+    /// * `padding_processor`: An instance of a type that implements `PaddingProcessor`.
+    ///
+    /// Returns:
+    /// A `Result` containing the new instance, or `AesError::InvalidIvSize`
+    /// if `iv` is not one block long.
+    pub fn with_iv<T: PaddingProcessor + 'static>(
+        keys: &'k C,
+        iv: &[u8],
+        padding_processor: T,
+    ) -> Result<Self, AesError> {
+        if iv.len() != C::BLOCK_SIZE {
+            return Err(AesError::InvalidIvSize(iv.len()));
+        }
+
         Ok(Self {
             keys,
             state: None,
-            iv: gen_matrix(&Self::gen_iv()),
+            iv: iv.to_vec(),
+            chain: iv.to_vec(),
+            iv_used: false,
             padding_processor: Box::new(padding_processor),
         })
     }
-}
 
-impl<'k> AesEncryptor for CbcEncryptor<'k> {
-    /// Encrypts a message using AES with CBC mode and PKCS padding.
+    /// Returns the initialization vector used to chain the first block.
+    pub fn iv(&self) -> &[u8] {
+        &self.iv
+    }
+
+    /// Pads and encrypts a message in a caller-owned buffer, without allocating.
     ///
-    /// This function encrypts the given message using the AES encryption algorithm in CBC mode.
-    /// PKCS padding is applied to the message to ensure proper block sizing.
+    /// # Arguments
+    /// * `buffer` - The message followed by room for the padding; up to one
+    ///   block more than the message for the padding schemes.
+    /// * `message_len` - The length of the message at the start of `buffer`.
+    ///
+    /// # Returns
+    /// The ciphertext length; the ciphertext replaces the start of `buffer`.
+    ///
+    /// # Errors
+    /// Returns `AesError::IvReused` if a message has already been encrypted
+    /// with this encryptor, `AesError::BufferTooSmall` if the padding does
+    /// not fit, and the padding processor's errors.
+    pub fn encrypt_in_place(
+        &mut self,
+        buffer: &mut [u8],
+        message_len: usize,
+    ) -> Result<usize, AesError> {
+        self.start_message()?;
+        let padded_len = self
+            .padding_processor
+            .pad_in_place(buffer, message_len, C::BLOCK_SIZE)?;
+
+        self.encrypt_blocks(&mut buffer[..padded_len]);
+
+        Ok(padded_len)
+    }
+
+    /// Decrypts a ciphertext in a caller-owned buffer, without allocating.
     ///
     /// # Arguments
-    /// * `message` - A slice of bytes representing the plaintext message to be encrypted.
+    /// * `buffer` - The ciphertext, a whole number of blocks long.
+    ///
+    /// # Returns
+    /// The plaintext length; the plaintext, followed by its padding, replaces
+    /// the start of `buffer`.
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidCipherText` if the length of `buffer` is not a multiple of the block size,
+    /// and `AesError::InvalidPadding` if the decrypted padding is malformed.
+    pub fn decrypt_in_place(&self, buffer: &mut [u8]) -> Result<usize, AesError> {
+        if !buffer.len().is_multiple_of(C::BLOCK_SIZE) {
+            return Err(AesError::InvalidCipherText);
+        }
+
+        // Walk backwards so every block can still read the ciphertext before it.
+        let blocks = buffer.len() / C::BLOCK_SIZE;
+        for i in (0..blocks).rev() {
+            let (previous, rest) = buffer.split_at_mut(i * C::BLOCK_SIZE);
+            let block = &mut rest[..C::BLOCK_SIZE];
+            self.keys.decrypt_block(block);
+
+            let chain = match i {
+                0 => &self.iv[..],
+                _ => &previous[previous.len() - C::BLOCK_SIZE..],
+            };
+            xor_in_place(block, chain);
+        }
+
+        self.padding_processor.unpadded_len(buffer, C::BLOCK_SIZE)
+    }
+
+    /// Claims the IV for a new message; CBC must never encrypt two messages
+    /// under the same IV.
+    fn start_message(&mut self) -> Result<(), AesError> {
+        if self.iv_used {
+            return Err(AesError::IvReused);
+        }
+        self.iv_used = true;
+
+        Ok(())
+    }
+
+    /// Pads and encrypts the buffered bytes of the current message.
+    ///
+    /// # Returns
+    /// The last one or two ciphertext blocks.
+    fn finish_message(&mut self) -> Result<Vec<u8>, AesError> {
+        let mut pending = self.state.take().unwrap_or_default();
+        let message_len = pending.len();
+        pending.resize(message_len + C::BLOCK_SIZE, 0);
+        let padded_len =
+            self.padding_processor
+                .pad_in_place(&mut pending, message_len, C::BLOCK_SIZE)?;
+        pending.truncate(padded_len);
+
+        self.encrypt_blocks(&mut pending);
+
+        Ok(pending)
+    }
+
+    /// Encrypts whole blocks in place, chaining each one with the previous
+    /// ciphertext block.
+    ///
+    /// # Arguments
+    /// * `blocks` - Plaintext bytes, a whole number of blocks long.
+    fn encrypt_blocks(&mut self, blocks: &mut [u8]) {
+        for block in blocks.chunks_exact_mut(C::BLOCK_SIZE) {
+            xor_in_place(block, &self.chain);
+            self.keys.encrypt_block(block);
+            self.chain.copy_from_slice(block);
+        }
+    }
+}
+
+impl<'k, C: BlockCipher> StreamingEncryptor for CbcEncryptor<'k, C> {
+    /// Encrypts every whole block available so far and buffers the rest.
+    ///
+    /// # Arguments
+    /// * `input` - The next chunk of plaintext bytes.
     ///
     /// # Returns
-    /// A `Result` containing a vector of encrypted 4x4 byte matrices (`Vec<[[u8; 4]; 4]>`)
-    /// on success, or an `AesError` on failure.
-    fn encrypt(&mut self, message: &[u8]) -> Result<Vec<[[u8; 4]; 4]>, AesError> {
-        // Convert the message to a byte vector and apply PKCS padding
-        let mut plain_bytes = message.to_vec();
-        PkcsPadding.pad_input(&mut plain_bytes);
+    /// The ciphertext of the whole blocks.
+    ///
+    /// # Errors
+    /// Returns `AesError::IvReused` if this starts a second message.
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, AesError> {
+        let mut pending = match self.state.take() {
+            Some(pending) => pending,
+            None => {
+                self.start_message()?;
+                Vec::new()
+            }
+        };
+        pending.extend_from_slice(input);
 
-        // Chunk the padded message into 4x4 byte matrices
-        let input_blocks = chunk_bytes_into_4x4_matrices(&plain_bytes);
+        let whole = pending.len() - pending.len() % C::BLOCK_SIZE;
+        let mut cipher_bytes = pending[..whole].to_vec();
+        self.encrypt_blocks(&mut cipher_bytes);
 
-        // Initialize the working state by XORing the first block with the IV
-        let mut working_state = xor_matrices(input_blocks[0], self.iv);
+        pending.drain(..whole);
+        self.state = Some(pending);
 
-        let mut encrypted_blocks = Vec::with_capacity(input_blocks.len());
+        Ok(cipher_bytes)
+    }
 
-        for block in input_blocks {
-            AesOps::encrypt(&mut working_state, self.keys);
-            encrypted_blocks.push(working_state);
-            working_state = xor_matrices(working_state, block);
+    /// Pads and encrypts the buffered bytes.
+    ///
+    /// # Returns
+    /// The last one or two ciphertext blocks.
+    ///
+    /// # Errors
+    /// Returns `AesError::IvReused` if no message was started and the IV has
+    /// already been used.
+    fn finalize(mut self) -> Result<Vec<u8>, AesError> {
+        if self.state.is_none() {
+            self.start_message()?;
         }
+        self.finish_message()
+    }
+}
+
+impl<'k, C: BlockCipher> AesEncryptor for CbcEncryptor<'k, C> {
+    /// Encrypts a message using AES with CBC mode and padding.
+    ///
+    /// This function encrypts the given message using the AES encryption algorithm in CBC mode.
+    /// The padding processor is applied to the message to ensure proper block sizing.
+    /// Each encryptor encrypts a single message; create a new one, with a
+    /// fresh IV, for the next.
+    ///
+    /// # Arguments
+    /// * `message` - A slice of bytes representing the plaintext message to be encrypted.
+    ///
+    /// # Returns
+    /// A `Result` containing the ciphertext bytes, a whole number of blocks
+    /// long, on success, or an `AesError` on failure.
+    ///
+    /// # Errors
+    /// Returns `AesError::IvReused` if a message has already been encrypted
+    /// with this encryptor.
+    fn encrypt(&mut self, message: &[u8]) -> Result<Vec<u8>, AesError> {
+        self.start_message()?;
+        self.state = Some(Vec::new());
+
+        let mut cipher_bytes = self.update(message)?;
+        cipher_bytes.extend(self.finish_message()?);
 
-        Ok(encrypted_blocks)
+        Ok(cipher_bytes)
     }
 
-    /// Decrypts the given ciphertext using AES in CBC mode.
+    /// Decrypts the given ciphertext using AES in CBC mode and strips its padding.
     ///
     /// # Arguments
     /// * `cipher_bytes` - A slice of bytes representing the ciphertext to decrypt.
@@ -100,37 +285,359 @@ impl<'k> AesEncryptor for CbcEncryptor<'k> {
     /// or an `AesError` if the ciphertext is invalid or decryption fails.
     ///
     /// # Errors
-    /// Returns `AesError::InvalidCipherText` if the length of `cipher_bytes` is not a multiple of 16.
+    /// Returns `AesError::InvalidCipherText` if the length of `cipher_bytes` is not a multiple of the block size,
+    /// and `AesError::InvalidPadding` if the decrypted padding is malformed.
     fn decrypt(&mut self, cipher_bytes: &[u8]) -> Result<Vec<u8>, AesError> {
-        if cipher_bytes.len() % 16 != 0 {
-            return Err(AesError::InvalidCipherText);
+        let mut plain_bytes = cipher_bytes.to_vec();
+        let message_len = self.decrypt_in_place(&mut plain_bytes)?;
+        plain_bytes.truncate(message_len);
+
+        Ok(plain_bytes)
+    }
+}
+
+fn xor_in_place(block: &mut [u8], other: &[u8]) {
+    for (byte, other) in block.iter_mut().zip(other) {
+        *byte ^= other;
+    }
+}
+
+/// CTR mode over any `BlockCipher`, AES by default.
+pub struct CtrEncryptor<'k, C: BlockCipher = KeySchedule> {
+    counter: Vec<u8>,
+    /// Keystream of the current counter block and how many of its bytes are used.
+    keystream: Vec<u8>,
+    used: usize,
+    keys: &'k C,
+}
+
+impl<'k, C: BlockCipher> CtrEncryptor<'k, C> {
+    /// Creates a new encryption structure with CTR mode.
+    ///
+    /// The counter block is incremented as a big-endian integer after every
+    /// block, as in NIST SP 800-38A. A counter block must never be reused
+    /// under the same key, so the counter is never rewound: successive
+    /// messages continue the keystream where the previous one stopped.
+    /// Decrypt with a new encryptor created from the same initial counter.
+    ///
+    /// Parameters:
+    /// * `keys`: The expanded key of the block cipher.
+    /// * `initial_counter`: The first counter block.
+    ///
+    /// Returns:
+    /// The new instance, or `AesError::InvalidIvSize` if `initial_counter`
+    /// is not one block long.
+    pub fn new(keys: &'k C, initial_counter: &[u8]) -> Result<Self, AesError> {
+        if initial_counter.len() != C::BLOCK_SIZE {
+            return Err(AesError::InvalidIvSize(initial_counter.len()));
+        }
+
+        Ok(Self {
+            counter: initial_counter.to_vec(),
+            keystream: vec![0; C::BLOCK_SIZE],
+            used: C::BLOCK_SIZE,
+            keys,
+        })
+    }
+
+    /// Encrypts `buffer` in place with the next bytes of the keystream.
+    ///
+    /// # Arguments
+    /// * `buffer` - The plaintext, replaced by the ciphertext.
+    pub fn encrypt_in_place(&mut self, buffer: &mut [u8]) {
+        self.apply_keystream(buffer);
+    }
+
+    /// Decrypts `buffer` in place, which is the same operation as encryption.
+    ///
+    /// # Arguments
+    /// * `buffer` - The ciphertext, replaced by the plaintext.
+    pub fn decrypt_in_place(&mut self, buffer: &mut [u8]) {
+        self.encrypt_in_place(buffer);
+    }
+
+    /// XORs `buffer` with the next bytes of the keystream, encrypting a new
+    /// counter block whenever the current one runs out.
+    fn apply_keystream(&mut self, buffer: &mut [u8]) {
+        for byte in buffer {
+            if self.used == C::BLOCK_SIZE {
+                self.keystream.copy_from_slice(&self.counter);
+                self.keys.encrypt_block(&mut self.keystream);
+                self.used = 0;
+
+                // Increment the counter block as a big-endian integer.
+                for counter_byte in self.counter.iter_mut().rev() {
+                    *counter_byte = counter_byte.wrapping_add(1);
+                    if *counter_byte != 0 {
+                        break;
+                    }
+                }
+            }
+
+            *byte ^= self.keystream[self.used];
+            self.used += 1;
+        }
+    }
+}
+
+impl<'k, C: BlockCipher> StreamingEncryptor for CtrEncryptor<'k, C> {
+    /// Encrypts `input` right away; CTR needs no whole blocks or padding.
+    ///
+    /// # Arguments
+    /// * `input` - The next chunk of plaintext bytes.
+    ///
+    /// # Returns
+    /// The ciphertext bytes, as many as `input`.
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, AesError> {
+        let mut output = input.to_vec();
+        self.apply_keystream(&mut output);
+
+        Ok(output)
+    }
+
+    /// Completes the message.
+    ///
+    /// # Returns
+    /// An empty vector, as `update` has already produced all the ciphertext.
+    fn finalize(self) -> Result<Vec<u8>, AesError> {
+        Ok(Vec::new())
+    }
+}
+
+impl<'k, C: BlockCipher> AesEncryptor for CtrEncryptor<'k, C> {
+    /// Encrypts a message using AES in CTR mode, continuing the keystream
+    /// from the previous message.
+    ///
+    /// # Arguments
+    /// * `message` - A slice of bytes representing the plaintext message to be encrypted.
+    ///
+    /// # Returns
+    /// A `Result` containing the ciphertext bytes, as many as `message`.
+    fn encrypt(&mut self, message: &[u8]) -> Result<Vec<u8>, AesError> {
+        let mut cipher_bytes = message.to_vec();
+        self.encrypt_in_place(&mut cipher_bytes);
+
+        Ok(cipher_bytes)
+    }
+
+    /// Decrypts the given ciphertext using AES in CTR mode, which is the same
+    /// operation as encryption.
+    ///
+    /// # Arguments
+    /// * `cipher_bytes` - A slice of bytes representing the ciphertext to decrypt.
+    ///
+    /// # Returns
+    /// A `Result` containing the plaintext bytes.
+    fn decrypt(&mut self, cipher_bytes: &[u8]) -> Result<Vec<u8>, AesError> {
+        self.encrypt(cipher_bytes)
+    }
+}
+
+/// The low byte of the XTS reduction polynomial x^128 + x^7 + x^2 + x + 1.
+const XTS_POLY: u8 = 0x87;
+
+/// XTS (IEEE 1619) for encrypting data units such as disk sectors, over any
+/// 16-byte `BlockCipher`, AES by default.
+///
+/// Each block is encrypted as `E(K1, P ^ T) ^ T`, where the tweak `T` starts
+/// as `E(K2, tweak)` and is multiplied by x in GF(2^128) after every block.
+/// A final partial block is handled with ciphertext stealing, so the
+/// ciphertext is exactly as long as the plaintext; data units must hold at
+/// least one whole block.
+pub struct XtsEncryptor<'k, C: BlockCipher = KeySchedule> {
+    data_keys: &'k C,
+    tweak_keys: &'k C,
+    tweak: [u8; 16],
+}
+
+impl<'k, C: BlockCipher> XtsEncryptor<'k, C> {
+    /// Creates a new XTS encryptor with a random tweak.
+    ///
+    /// Parameters:
+    /// * `data_keys`: The expanded key `K1` that encrypts the data.
+    /// * `tweak_keys`: The expanded key `K2` that encrypts the tweak; it must
+    ///   be independent of `K1`.
+    ///
+    /// Returns:
+    /// A `Result` containing the new instance, or the errors of `with_tweak`.
+    /// The tweak can be read back with `tweak`.
+    pub fn new(data_keys: &'k C, tweak_keys: &'k C) -> Result<Self, AesError> {
+        let mut tweak = [0u8; 16];
+        OsRng.fill_bytes(&mut tweak);
+
+        Self::with_tweak(data_keys, tweak_keys, tweak)
+    }
+
+    /// Creates a new XTS encryptor for the given tweak. For disk encryption
+    /// the tweak of sector `n` is `n.to_le_bytes()`, as a `u128`.
+    ///
+    /// Parameters:
+    /// * `data_keys`: The expanded key `K1` that encrypts the data.
+    /// * `tweak_keys`: The expanded key `K2` that encrypts the tweak.
+    /// * `tweak`: The 16-byte tweak of the data unit.
+    ///
+    /// Returns:
+    /// A `Result` containing the new instance, `AesError::InvalidBlockSize`
+    /// if `C` does not have 16-byte blocks, since the tweak arithmetic is in
+    /// GF(2^128), or `AesError::IdenticalXtsKeys` if `K1` and `K2` are the
+    /// same key.
+    pub fn with_tweak(
+        data_keys: &'k C,
+        tweak_keys: &'k C,
+        tweak: [u8; 16],
+    ) -> Result<Self, AesError> {
+        if C::BLOCK_SIZE != BLOCK_SIZE {
+            return Err(AesError::InvalidBlockSize(C::BLOCK_SIZE));
+        }
+
+        // `C` exposes no key bytes, so compare the keys by what they encrypt
+        // the zero block to; distinct keys collide with probability 2^-128.
+        let (mut data_block, mut tweak_block) = ([0u8; 16], [0u8; 16]);
+        data_keys.encrypt_block(&mut data_block);
+        tweak_keys.encrypt_block(&mut tweak_block);
+        if ct_eq(&data_block, &tweak_block) {
+            return Err(AesError::IdenticalXtsKeys);
+        }
+
+        Ok(Self {
+            data_keys,
+            tweak_keys,
+            tweak,
+        })
+    }
+
+    /// Returns the tweak of the data unit.
+    pub fn tweak(&self) -> [u8; 16] {
+        self.tweak
+    }
+
+    /// Encrypts a data unit in place, without allocating.
+    ///
+    /// # Arguments
+    /// * `buffer` - The plaintext data unit, at least 16 bytes long,
+    ///   replaced by the ciphertext.
+    ///
+    /// # Errors
+    /// Returns `AesError::InputTooShort` if `buffer` is shorter than a block.
+    pub fn encrypt_in_place(&self, buffer: &mut [u8]) -> Result<(), AesError> {
+        self.process(buffer, false, C::encrypt_block)
+    }
+
+    /// Decrypts a data unit in place, without allocating.
+    ///
+    /// # Arguments
+    /// * `buffer` - The ciphertext data unit, at least 16 bytes long,
+    ///   replaced by the plaintext.
+    ///
+    /// # Errors
+    /// Returns `AesError::InputTooShort` if `buffer` is shorter than a block.
+    pub fn decrypt_in_place(&self, buffer: &mut [u8]) -> Result<(), AesError> {
+        self.process(buffer, true, C::decrypt_block)
+    }
+
+    /// Encrypts or decrypts a data unit in place with `cipher`, stealing
+    /// ciphertext for a final partial block.
+    fn process(
+        &self,
+        buffer: &mut [u8],
+        decrypting: bool,
+        cipher: fn(&C, &mut [u8]),
+    ) -> Result<(), AesError> {
+        if buffer.len() < BLOCK_SIZE {
+            return Err(AesError::InputTooShort);
         }
 
-        let input_blocks = chunk_bytes_into_4x4_matrices(&cipher_bytes.to_vec());
-        let mut decrypted_blocks: Vec<[[u8; 4]; 4]> = Vec::with_capacity(input_blocks.len());
+        let xex = |block: &mut [u8], t: &[u8; 16]| {
+            xor_in_place(block, t);
+            cipher(self.data_keys, block);
+            xor_in_place(block, t);
+        };
 
-        let mut working_block = self.iv;
+        let remainder = buffer.len() % BLOCK_SIZE;
+        let whole = buffer.len() - remainder;
+        // With a partial block, the last whole block is left to the stealing step.
+        let direct = if remainder == 0 {
+            whole
+        } else {
+            whole - BLOCK_SIZE
+        };
 
-        for block in input_blocks {
-            let mut cipher_block = block;
-            AesOps::decrypt(&mut cipher_block, self.keys);
+        let mut t = self.tweak;
+        self.tweak_keys.encrypt_block(&mut t);
+        for block in buffer[..direct].chunks_exact_mut(BLOCK_SIZE) {
+            xex(block, &t);
+            t = mul_x(&t);
+        }
+
+        if remainder != 0 {
+            // Decryption undoes the last two blocks in the opposite tweak order.
+            let next = mul_x(&t);
+            let (first, second) = if decrypting { (&next, &t) } else { (&t, &next) };
 
-            cipher_block = xor_matrices(cipher_block, working_block);
-            decrypted_blocks.push(cipher_block);
-            working_block = block;
+            let (head, tail) = buffer[direct..].split_at_mut(BLOCK_SIZE);
+            xex(head, first);
+            // Swap the partial block with the head of the block just processed,
+            // whose remaining bytes are stolen to fill it up.
+            head[..remainder].swap_with_slice(tail);
+            xex(head, second);
         }
 
-        Ok(decrypted_blocks
-            .into_iter()
-            .flat_map(|block| block.into_iter())
-            .flat_map(|row| row.into_iter())
-            .collect())
+        Ok(())
+    }
+}
+
+impl<'k, C: BlockCipher> AesEncryptor for XtsEncryptor<'k, C> {
+    /// Encrypts a data unit using XTS-AES.
+    ///
+    /// # Arguments
+    /// * `input` - The plaintext data unit, at least 16 bytes long.
+    ///
+    /// # Returns
+    /// A `Result` containing the ciphertext bytes, as many as `input`.
+    ///
+    /// # Errors
+    /// Returns `AesError::InputTooShort` if `input` is shorter than a block.
+    fn encrypt(&mut self, input: &[u8]) -> Result<Vec<u8>, AesError> {
+        let mut cipher_bytes = input.to_vec();
+        self.encrypt_in_place(&mut cipher_bytes)?;
+
+        Ok(cipher_bytes)
     }
+
+    /// Decrypts a data unit using XTS-AES.
+    ///
+    /// # Arguments
+    /// * `cipher_bytes` - The ciphertext data unit, at least 16 bytes long.
+    ///
+    /// # Returns
+    /// A `Result` containing the plaintext bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::InputTooShort` if `cipher_bytes` is shorter than a block.
+    fn decrypt(&mut self, cipher_bytes: &[u8]) -> Result<Vec<u8>, AesError> {
+        let mut plain_bytes = cipher_bytes.to_vec();
+        self.decrypt_in_place(&mut plain_bytes)?;
+
+        Ok(plain_bytes)
+    }
+}
+
+/// Multiplies a tweak by x in GF(2^128), with the little-endian byte order
+/// of IEEE 1619.
+fn mul_x(t: &[u8; 16]) -> [u8; 16] {
+    let value = u128::from_le_bytes(*t);
+    let carry = (value >> 127) as u8;
+
+    let mut doubled = (value << 1).to_le_bytes();
+    doubled[0] ^= XTS_POLY & 0u8.wrapping_sub(carry);
+    doubled
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pkcs_padding::PkcsPadding;
+    use crate::util::decode_hex;
 
     const INPUT: [u8; 16] = [
         0, 17, 34, 51, 68, 85, 102, 119, 136, 153, 170, 187, 204, 221, 238, 255,
@@ -145,26 +652,311 @@ mod tests {
         let key_schedule =
             KeySchedule::new(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]).unwrap();
 
-        let mut cbc_ops = CbcEncryptor::new(&key_schedule, PkcsPadding).unwrap();
-        cbc_ops.iv = gen_matrix(&IV);
+        let mut cbc_ops = CbcEncryptor::with_iv(&key_schedule, &IV, PkcsPadding).unwrap();
+        assert_eq!(cbc_ops.iv(), IV);
 
-        let start_cipher_bytes: Vec<[[u8; 4]; 4]> = vec![[
-            [59, 67, 136, 134],
-            [79, 78, 189, 114],
-            [137, 150, 207, 148],
-            [186, 117, 130, 178],
-        ]];
+        let start_cipher_bytes = [
+            59, 67, 136, 134, 79, 78, 189, 114, 137, 150, 207, 148, 186, 117, 130, 178,
+        ];
 
         let result = cbc_ops.encrypt(&INPUT).unwrap();
         println!("result: {:?}", result);
-        assert!(result.as_slice().starts_with(&start_cipher_bytes));
-
-        let plain_bytes = cbc_ops
-            .decrypt(&[
-                59, 67, 136, 134, 79, 78, 189, 114, 137, 150, 207, 148, 186, 117, 130, 178, 17,
-                210, 7, 174, 109, 178, 129, 201, 24, 52, 14, 108, 136, 148, 142, 63,
-            ])
-            .unwrap();
-        println!("plain_bytes: {:?}", plain_bytes);
+        assert_eq!(result.len(), 32);
+        assert!(result.starts_with(&start_cipher_bytes));
+
+        let plain_bytes = cbc_ops.decrypt(&result).unwrap();
+        assert_eq!(plain_bytes, INPUT);
+    }
+
+    #[test]
+    fn test_cbc_decrypt_rejects_bad_padding() {
+        let key_schedule = KeySchedule::new(&[0x2b; 16]).unwrap();
+        let mut cbc_ops = CbcEncryptor::with_iv(&key_schedule, &IV, PkcsPadding).unwrap();
+
+        let mut cipher_bytes = cbc_ops.encrypt(b"attacker-controlled").unwrap();
+        let last = cipher_bytes.len() - 1;
+        cipher_bytes[last] ^= 1;
+
+        assert!(matches!(
+            cbc_ops.decrypt(&cipher_bytes),
+            Err(AesError::InvalidPadding)
+        ));
+        assert!(matches!(
+            cbc_ops.decrypt(&[]),
+            Err(AesError::InvalidPadding)
+        ));
+    }
+
+    #[test]
+    fn test_cbc_roundtrip_with_explicit_iv() {
+        let key_schedule = KeySchedule::new(&[0x2b; 16]).unwrap();
+        let message: Vec<u8> = (0..50).collect();
+
+        let mut encryptor = CbcEncryptor::new(&key_schedule, PkcsPadding).unwrap();
+        let cipher_bytes = encryptor.encrypt(&message).unwrap();
+        assert_eq!(cipher_bytes.len(), 64);
+
+        let mut decryptor =
+            CbcEncryptor::with_iv(&key_schedule, encryptor.iv(), PkcsPadding).unwrap();
+        let plain_bytes = decryptor.decrypt(&cipher_bytes).unwrap();
+        assert_eq!(plain_bytes, message);
+    }
+
+    #[test]
+    fn test_cbc_streaming_matches_one_shot() {
+        let key_schedule = KeySchedule::new(&[0x2b; 16]).unwrap();
+        let message: Vec<u8> = (0..=255).cycle().take(1000).collect();
+
+        let mut encryptor = CbcEncryptor::with_iv(&key_schedule, &IV, PkcsPadding).unwrap();
+        let expected = encryptor.encrypt(&message).unwrap();
+
+        for chunk_size in [1, 7, 16, 33, 1000] {
+            let mut encryptor = CbcEncryptor::with_iv(&key_schedule, &IV, PkcsPadding).unwrap();
+            let mut cipher_bytes = Vec::new();
+            for chunk in message.chunks(chunk_size) {
+                let out = encryptor.update(chunk).unwrap();
+                assert_eq!(out.len() % 16, 0);
+                cipher_bytes.extend(out);
+            }
+            cipher_bytes.extend(encryptor.finalize().unwrap());
+
+            assert_eq!(cipher_bytes, expected);
+        }
+    }
+
+    #[test]
+    fn test_ctr_sp800_38a() {
+        // NIST SP 800-38A, F.5.1 CTR-AES128.Encrypt
+        let key = decode_hex("2b7e151628aed2a6abf7158809cf4f3c");
+        let counter = decode_hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let plaintext = decode_hex(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        );
+        let ciphertext = decode_hex(
+            "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+             5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee",
+        );
+
+        let key_schedule = KeySchedule::new(&key).unwrap();
+        let mut ctr = CtrEncryptor::new(&key_schedule, &counter).unwrap();
+        assert_eq!(ctr.encrypt(&plaintext).unwrap(), ciphertext);
+
+        let mut ctr = CtrEncryptor::new(&key_schedule, &counter).unwrap();
+        assert_eq!(ctr.decrypt(&ciphertext).unwrap(), plaintext);
+
+        let mut ctr = CtrEncryptor::new(&key_schedule, &counter).unwrap();
+        let mut streamed = Vec::new();
+        for chunk in plaintext.chunks(5) {
+            streamed.extend(ctr.update(chunk).unwrap());
+        }
+        streamed.extend(ctr.finalize().unwrap());
+        assert_eq!(streamed, ciphertext);
+    }
+
+    #[test]
+    fn test_messages_never_share_keystream() {
+        let key_schedule = KeySchedule::new(&[0x2b; 16]).unwrap();
+        let zeros = [0u8; 40];
+
+        // Encrypting zeros exposes the keystream; consecutive messages, one-shot,
+        // in place or streamed, must each get fresh counter blocks.
+        let mut ctr = CtrEncryptor::new(&key_schedule, &IV).unwrap();
+        let first = ctr.encrypt(&zeros).unwrap();
+        let second = ctr.encrypt(&zeros).unwrap();
+        let mut third = zeros;
+        ctr.encrypt_in_place(&mut third);
+        let mut fourth = ctr.update(&zeros).unwrap();
+        fourth.extend(ctr.finalize().unwrap());
+
+        let mut keystream = CtrEncryptor::new(&key_schedule, &IV).unwrap();
+        let expected = keystream.encrypt(&[0u8; 160]).unwrap();
+        assert_eq!([first, second, third.to_vec(), fourth].concat(), expected);
+
+        // CBC cannot continue safely, so a second message under the IV is refused.
+        let mut cbc = CbcEncryptor::with_iv(&key_schedule, &IV, PkcsPadding).unwrap();
+        cbc.encrypt(&zeros).unwrap();
+        assert!(matches!(cbc.encrypt(&zeros), Err(AesError::IvReused)));
+        assert!(matches!(
+            cbc.encrypt_in_place(&mut [0u8; 48], 16),
+            Err(AesError::IvReused)
+        ));
+        assert!(matches!(cbc.update(&zeros), Err(AesError::IvReused)));
+        assert!(matches!(cbc.finalize(), Err(AesError::IvReused)));
+
+        let mut cbc = CbcEncryptor::with_iv(&key_schedule, &IV, PkcsPadding).unwrap();
+        cbc.update(&zeros).unwrap();
+        assert!(matches!(cbc.encrypt(&zeros), Err(AesError::IvReused)));
+    }
+
+    #[test]
+    fn test_xts_ieee1619() {
+        // IEEE 1619-2007, vectors 2, 3 and 15 to 17. Vector 1 uses K1 == K2,
+        // which `with_tweak` rejects.
+        for (key1, key2, tweak, plaintext, ciphertext) in [
+            (
+                "11111111111111111111111111111111",
+                "22222222222222222222222222222222",
+                0x3333333333u128,
+                "4444444444444444444444444444444444444444444444444444444444444444",
+                "c454185e6a16936e39334038acef838bfb186fff7480adc4289382ecd6d394f0",
+            ),
+            (
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "22222222222222222222222222222222",
+                0x3333333333,
+                "4444444444444444444444444444444444444444444444444444444444444444",
+                "af85336b597afc1a900b2eb21ec949d292df4c047e0b21532186a5971a227a89",
+            ),
+            (
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0",
+                0x123456789a,
+                "000102030405060708090a0b0c0d0e0f10",
+                "6c1625db4671522d3d7599601de7ca09ed",
+            ),
+            (
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0",
+                0x123456789a,
+                "000102030405060708090a0b0c0d0e0f1011",
+                "d069444b7a7e0cab09e24447d24deb1fedbf",
+            ),
+            (
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0",
+                0x123456789a,
+                "000102030405060708090a0b0c0d0e0f101112",
+                "e5df1351c0544ba1350b3363cd8ef4beedbf9d",
+            ),
+        ] {
+            let data_keys = KeySchedule::new(&decode_hex(key1)).unwrap();
+            let tweak_keys = KeySchedule::new(&decode_hex(key2)).unwrap();
+            let mut xts =
+                XtsEncryptor::with_tweak(&data_keys, &tweak_keys, tweak.to_le_bytes()).unwrap();
+
+            let plaintext = decode_hex(plaintext);
+            let ciphertext = decode_hex(ciphertext);
+            assert_eq!(xts.encrypt(&plaintext).unwrap(), ciphertext);
+            assert_eq!(xts.decrypt(&ciphertext).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_xts_rejects_identical_keys() {
+        let data_keys = KeySchedule::new(&[0x2b; 16]).unwrap();
+        let same_key = KeySchedule::new(&[0x2b; 16]).unwrap();
+
+        assert!(matches!(
+            XtsEncryptor::new(&data_keys, &data_keys),
+            Err(AesError::IdenticalXtsKeys)
+        ));
+        assert!(matches!(
+            XtsEncryptor::with_tweak(&data_keys, &same_key, IV),
+            Err(AesError::IdenticalXtsKeys)
+        ));
+    }
+
+    #[test]
+    fn test_xts_stealing_roundtrip() {
+        let data_keys = KeySchedule::new(&[0x2b; 32]).unwrap();
+        let tweak_keys = KeySchedule::new(&[0x7e; 32]).unwrap();
+        let mut xts = XtsEncryptor::new(&data_keys, &tweak_keys).unwrap();
+        let message: Vec<u8> = (0..100).collect();
+
+        for len in 16..=message.len() {
+            let cipher_bytes = xts.encrypt(&message[..len]).unwrap();
+            assert_eq!(cipher_bytes.len(), len);
+            assert_eq!(xts.decrypt(&cipher_bytes).unwrap(), &message[..len]);
+        }
+
+        assert!(matches!(
+            xts.encrypt(&message[..15]),
+            Err(AesError::InputTooShort)
+        ));
+    }
+
+    #[test]
+    fn test_in_place_matches_allocating() {
+        let key_schedule = KeySchedule::new(&[0x2b; 16]).unwrap();
+        let message: Vec<u8> = (0..37).collect();
+
+        let mut cbc = CbcEncryptor::with_iv(&key_schedule, &IV, PkcsPadding).unwrap();
+        let mut buffer = [0u8; 48];
+        buffer[..message.len()].copy_from_slice(&message);
+        let cipher_len = cbc.encrypt_in_place(&mut buffer, message.len()).unwrap();
+        let mut allocating = CbcEncryptor::with_iv(&key_schedule, &IV, PkcsPadding).unwrap();
+        assert_eq!(buffer[..cipher_len], allocating.encrypt(&message).unwrap());
+
+        let plain_len = cbc.decrypt_in_place(&mut buffer[..cipher_len]).unwrap();
+        assert_eq!(buffer[..plain_len], message);
+
+        // PKCS#7 always adds at least one byte, so a 48-byte message needs 64 bytes.
+        let mut cbc = CbcEncryptor::with_iv(&key_schedule, &IV, PkcsPadding).unwrap();
+        assert!(matches!(
+            cbc.encrypt_in_place(&mut buffer, 48),
+            Err(AesError::BufferTooSmall)
+        ));
+
+        let mut ctr = CtrEncryptor::new(&key_schedule, &IV).unwrap();
+        let mut buffer = message.clone();
+        ctr.encrypt_in_place(&mut buffer);
+        let mut allocating = CtrEncryptor::new(&key_schedule, &IV).unwrap();
+        assert_eq!(buffer, allocating.encrypt(&message).unwrap());
+        let mut ctr = CtrEncryptor::new(&key_schedule, &IV).unwrap();
+        ctr.decrypt_in_place(&mut buffer);
+        assert_eq!(buffer, message);
+
+        let tweak_keys = KeySchedule::new(&[0x7e; 16]).unwrap();
+        let mut xts = XtsEncryptor::with_tweak(&key_schedule, &tweak_keys, IV).unwrap();
+        let mut buffer = message.clone();
+        xts.encrypt_in_place(&mut buffer).unwrap();
+        assert_eq!(buffer, xts.encrypt(&message).unwrap());
+        xts.decrypt_in_place(&mut buffer).unwrap();
+        assert_eq!(buffer, message);
+    }
+
+    /// A toy 8-byte block cipher: XOR with the key, then rotate the block.
+    /// It has no security, it only has a different block size.
+    struct ToyCipher([u8; 8]);
+
+    impl BlockCipher for ToyCipher {
+        const BLOCK_SIZE: usize = 8;
+
+        fn encrypt_block(&self, block: &mut [u8]) {
+            xor_in_place(block, &self.0);
+            block.rotate_left(3);
+        }
+
+        fn decrypt_block(&self, block: &mut [u8]) {
+            block.rotate_right(3);
+            xor_in_place(block, &self.0);
+        }
+    }
+
+    #[test]
+    fn test_modes_over_other_block_sizes() {
+        let cipher = ToyCipher(*b"toy key!");
+        let message = b"twenty-one byte text";
+
+        let mut cbc = CbcEncryptor::new(&cipher, PkcsPadding).unwrap();
+        assert_eq!(cbc.iv().len(), 8);
+        let cipher_bytes = cbc.encrypt(message).unwrap();
+        assert_eq!(cipher_bytes.len(), 24);
+        assert_eq!(cbc.decrypt(&cipher_bytes).unwrap(), message);
+
+        let mut ctr = CtrEncryptor::new(&cipher, &[0xff; 8]).unwrap();
+        let cipher_bytes = ctr.encrypt(message).unwrap();
+        let mut ctr = CtrEncryptor::new(&cipher, &[0xff; 8]).unwrap();
+        assert_eq!(ctr.decrypt(&cipher_bytes).unwrap(), message);
+
+        assert!(matches!(
+            CbcEncryptor::with_iv(&cipher, &IV, PkcsPadding),
+            Err(AesError::InvalidIvSize(16))
+        ));
+        assert!(matches!(
+            XtsEncryptor::with_tweak(&cipher, &cipher, IV),
+            Err(AesError::InvalidBlockSize(8))
+        ));
     }
 }
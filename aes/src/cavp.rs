@@ -0,0 +1,189 @@
+//! Runs NIST CAVP known-answer tests from the `.rsp` response files in the
+//! crate's `vectors` directory. Every file is picked up, so the full KAT
+//! files from the CAVP archive can be dropped in next to the excerpts that
+//! ship with the crate.
+//!
+//! The mode is taken from the file name prefix (`ECB`, `CBC` or `CTR`); for
+//! CTR files the `IV` field holds the initial counter block.
+//!
+//! Every case runs twice: through `AesOps`, which prefers AES-NI when the
+//! CPU has it, and directly on the software core chosen by the features.
+use std::{fs, path::Path};
+
+use super::{
+    aes_ops::AesOps,
+    block_modes::{CbcEncryptor, CtrEncryptor},
+    definitions::{AesEncryptor, BlockCipher},
+    key_schedule::KeySchedule,
+    no_padding::NoPadding,
+    state::State,
+    util::decode_hex,
+};
+
+const BLOCK_SIZE: usize = 16;
+
+/// A single `COUNT` entry of a response file.
+#[derive(Debug, Default)]
+pub struct KatCase {
+    pub count: usize,
+    /// Whether the entry is in an `[ENCRYPT]` rather than a `[DECRYPT]` section.
+    pub encrypt: bool,
+    pub key: Vec<u8>,
+    pub iv: Option<Vec<u8>>,
+    pub plaintext: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+/// Parses the entries of a CAVP response file. Comment lines start with
+/// `#`, sections are `[ENCRYPT]` and `[DECRYPT]`, and each entry starts at
+/// its `COUNT` line.
+///
+/// # Panics
+/// Panics on a malformed line, so a broken vector file fails the test run.
+pub fn parse_rsp(contents: &str) -> Vec<KatCase> {
+    let mut cases: Vec<KatCase> = Vec::new();
+    let mut encrypt = true;
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line {
+            "[ENCRYPT]" => encrypt = true,
+            "[DECRYPT]" => encrypt = false,
+            _ => {
+                let (name, value) = line
+                    .split_once('=')
+                    .unwrap_or_else(|| panic!("malformed line `{line}`"));
+                let value = value.trim();
+
+                if name.trim() == "COUNT" {
+                    cases.push(KatCase {
+                        count: value.parse().expect("COUNT is a number"),
+                        encrypt,
+                        ..Default::default()
+                    });
+                    continue;
+                }
+
+                let case = cases.last_mut().expect("field before the first COUNT");
+                match name.trim() {
+                    "KEY" => case.key = decode_hex(value),
+                    "IV" => case.iv = Some(decode_hex(value)),
+                    "PLAINTEXT" => case.plaintext = decode_hex(value),
+                    "CIPHERTEXT" => case.ciphertext = decode_hex(value),
+                    other => panic!("unknown field `{other}`"),
+                }
+            }
+        }
+    }
+
+    cases
+}
+
+/// Runs the AES rounds on the software core selected by the crate features,
+/// bypassing AES-NI, so the KATs cover that core even on CPUs where
+/// `AesOps` would dispatch to hardware.
+struct Software(KeySchedule);
+
+impl BlockCipher for Software {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let mut state = State::from_bytes((&*block).try_into().unwrap());
+        AesOps::software_encrypt(&mut state, &self.0);
+        block.copy_from_slice(&state.to_bytes());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let mut state = State::from_bytes((&*block).try_into().unwrap());
+        AesOps::software_decrypt(&mut state, &self.0);
+        block.copy_from_slice(&state.to_bytes());
+    }
+}
+
+/// Runs a case in the mode named by `mode` on `cipher`.
+///
+/// # Returns
+/// The output of the operation the case's section asks for.
+fn run<C: BlockCipher>(mode: &str, case: &KatCase, cipher: &C) -> Vec<u8> {
+    let iv = || case.iv.as_deref().expect("mode needs an IV");
+
+    match (mode, case.encrypt) {
+        ("ECB", true) => {
+            let mut output = case.plaintext.clone();
+            output
+                .chunks_exact_mut(BLOCK_SIZE)
+                .for_each(|block| cipher.encrypt_block(block));
+            output
+        }
+        ("ECB", false) => {
+            let mut output = case.ciphertext.clone();
+            output
+                .chunks_exact_mut(BLOCK_SIZE)
+                .for_each(|block| cipher.decrypt_block(block));
+            output
+        }
+        ("CBC", true) => CbcEncryptor::with_iv(cipher, iv(), NoPadding)
+            .unwrap()
+            .encrypt(&case.plaintext)
+            .unwrap(),
+        ("CBC", false) => CbcEncryptor::with_iv(cipher, iv(), NoPadding)
+            .unwrap()
+            .decrypt(&case.ciphertext)
+            .unwrap(),
+        ("CTR", true) => CtrEncryptor::new(cipher, iv())
+            .unwrap()
+            .encrypt(&case.plaintext)
+            .unwrap(),
+        ("CTR", false) => CtrEncryptor::new(cipher, iv())
+            .unwrap()
+            .decrypt(&case.ciphertext)
+            .unwrap(),
+        _ => panic!("unsupported mode `{mode}`"),
+    }
+}
+
+#[test]
+fn known_answer_tests() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("vectors");
+    let mut files = 0;
+
+    for entry in fs::read_dir(&dir).expect("vectors directory") {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "rsp") {
+            continue;
+        }
+
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let cases = parse_rsp(&fs::read_to_string(&path).unwrap());
+        assert!(!cases.is_empty(), "{name} has no test cases");
+
+        for case in &cases {
+            let expected = if case.encrypt {
+                &case.ciphertext
+            } else {
+                &case.plaintext
+            };
+            let direction = if case.encrypt { "ENCRYPT" } else { "DECRYPT" };
+            let keys = KeySchedule::new(&case.key).expect("valid key size");
+
+            assert_eq!(
+                &run(&name[..3], case, &keys),
+                expected,
+                "{name} [{direction}] COUNT = {}",
+                case.count
+            );
+            assert_eq!(
+                &run(&name[..3], case, &Software(keys)),
+                expected,
+                "{name} [{direction}] COUNT = {} on the software core",
+                case.count
+            );
+        }
+        files += 1;
+    }
+
+    assert!(files > 0, "no .rsp files in {}", dir.display());
+}
@@ -0,0 +1,299 @@
+//! This module implements the CCM authenticated encryption mode (NIST SP
+//! 800-38C), counter mode with CBC-MAC. The tag is a CBC-MAC over a first
+//! block encoding the nonce, tag size and message length, followed by the
+//! length-prefixed associated data and the message. The message is then
+//! encrypted in CTR mode from counter block 1, and the tag is encrypted with
+//! counter block 0.
+//!
+//! The nonce size `n` trades off against the message length: the counter
+//! field takes the remaining `q = 15 - n` bytes, so messages are limited to
+//! `2^(8q)` bytes.
+use utils::mac::ct_eq;
+
+use super::{
+    aes_ops::AesOps, block_modes::CtrEncryptor, definitions::AesEncryptor, error::AesError,
+    key_schedule::KeySchedule,
+};
+
+const BLOCK_SIZE: usize = 16;
+
+/// The tag sizes, in bytes, that SP 800-38C permits.
+const TAG_SIZES: [usize; 7] = [4, 6, 8, 10, 12, 14, 16];
+
+/// The smallest and largest permitted nonce sizes, in bytes.
+const MIN_NONCE_SIZE: usize = 7;
+const MAX_NONCE_SIZE: usize = 13;
+
+/// AES-CCM with a fixed tag size.
+pub struct Ccm<'k> {
+    keys: &'k KeySchedule,
+    tag_size: usize,
+}
+
+impl<'k> Ccm<'k> {
+    /// Creates a CCM instance producing tags of `tag_size` bytes.
+    ///
+    /// # Arguments
+    /// * `keys` - The expanded AES key.
+    /// * `tag_size` - The tag size in bytes: 4, 6, 8, 10, 12, 14 or 16.
+    ///
+    /// # Returns
+    /// The new instance, or `AesError::InvalidTagSize` for any other tag size.
+    pub fn new(keys: &'k KeySchedule, tag_size: usize) -> Result<Self, AesError> {
+        if !TAG_SIZES.contains(&tag_size) {
+            return Err(AesError::InvalidTagSize(tag_size));
+        }
+
+        Ok(Self { keys, tag_size })
+    }
+
+    /// Encrypts and authenticates `message`, also authenticating `associated_data`.
+    ///
+    /// # Arguments
+    /// * `nonce` - A 7 to 13 byte value unique per message under the key.
+    /// * `associated_data` - Data that is authenticated but not encrypted.
+    /// * `message` - The plaintext bytes.
+    ///
+    /// # Returns
+    /// The ciphertext followed by the tag.
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidNonceSize` for a nonce of the wrong size and
+    /// `AesError::MessageTooLong` if the message length does not fit the
+    /// counter field left by the nonce.
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        message: &[u8],
+    ) -> Result<Vec<u8>, AesError> {
+        check_nonce(nonce)?;
+        let tag = self.mac(nonce, associated_data, message)?;
+
+        // Counter block 0 encrypts the tag and the following ones the message.
+        let mut ctr = CtrEncryptor::new(self.keys, &counter_block(nonce))?;
+        let mut sealed = ctr.encrypt(&[&tag[..], message].concat())?;
+        sealed.rotate_left(BLOCK_SIZE);
+        sealed.truncate(message.len() + self.tag_size);
+
+        Ok(sealed)
+    }
+
+    /// Decrypts `sealed` and verifies its tag.
+    ///
+    /// # Arguments
+    /// * `nonce` - The nonce the message was encrypted with.
+    /// * `associated_data` - The associated data the message was encrypted with.
+    /// * `sealed` - The ciphertext followed by the tag, as returned by `encrypt`.
+    ///
+    /// # Returns
+    /// The plaintext bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidCipherText` if `sealed` is shorter than a
+    /// tag and `AesError::AuthenticationFailed` if the tag does not match,
+    /// besides the errors of `encrypt`. No plaintext is released on failure.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        sealed: &[u8],
+    ) -> Result<Vec<u8>, AesError> {
+        check_nonce(nonce)?;
+        let split = sealed
+            .len()
+            .checked_sub(self.tag_size)
+            .ok_or(AesError::InvalidCipherText)?;
+        let (cipher_bytes, received_tag) = sealed.split_at(split);
+
+        let mut padded_tag = [0u8; 16];
+        padded_tag[..self.tag_size].copy_from_slice(received_tag);
+
+        let mut ctr = CtrEncryptor::new(self.keys, &counter_block(nonce))?;
+        let mut opened = ctr.decrypt(&[&padded_tag[..], cipher_bytes].concat())?;
+        let message = opened.split_off(BLOCK_SIZE);
+
+        let tag = self.mac(nonce, associated_data, &message)?;
+        if !ct_eq(&tag[..self.tag_size], &opened[..self.tag_size]) {
+            return Err(AesError::AuthenticationFailed);
+        }
+
+        Ok(message)
+    }
+
+    /// Computes the CBC-MAC of the formatted nonce, associated data and message.
+    /// The nonce size must already be checked.
+    fn mac(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        message: &[u8],
+    ) -> Result<[u8; 16], AesError> {
+        let q = 15 - nonce.len();
+        if q < 8 && message.len() >> (8 * q) != 0 {
+            return Err(AesError::MessageTooLong);
+        }
+
+        // B0 = flags || nonce || message length in q bytes
+        let mut b0 = [0u8; 16];
+        b0[0] = ((!associated_data.is_empty() as u8) << 6)
+            | (((self.tag_size - 2) / 2) as u8) << 3
+            | (q - 1) as u8;
+        b0[1..=nonce.len()].copy_from_slice(nonce);
+        b0[16 - q..].copy_from_slice(&(message.len() as u64).to_be_bytes()[8 - q..]);
+
+        let mut y = AesOps::encrypt_block(&b0, self.keys);
+        if !associated_data.is_empty() {
+            let encoded = [&encode_length(associated_data.len())[..], associated_data].concat();
+            self.absorb(&mut y, &encoded);
+        }
+        self.absorb(&mut y, message);
+
+        Ok(y)
+    }
+
+    /// Runs the CBC-MAC over `data`, zero-padded to whole blocks.
+    fn absorb(&self, y: &mut [u8; 16], data: &[u8]) {
+        for block in data.chunks(BLOCK_SIZE) {
+            for (byte, other) in y.iter_mut().zip(block) {
+                *byte ^= other;
+            }
+            *y = AesOps::encrypt_block(y, self.keys);
+        }
+    }
+}
+
+/// Returns counter block 0, `flags || nonce || 0`. The counter occupies
+/// the remaining `15 - nonce.len()` bytes.
+fn counter_block(nonce: &[u8]) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block[0] = (14 - nonce.len()) as u8;
+    block[1..=nonce.len()].copy_from_slice(nonce);
+
+    block
+}
+
+fn check_nonce(nonce: &[u8]) -> Result<(), AesError> {
+    if !(MIN_NONCE_SIZE..=MAX_NONCE_SIZE).contains(&nonce.len()) {
+        return Err(AesError::InvalidNonceSize(nonce.len()));
+    }
+
+    Ok(())
+}
+
+/// Encodes the length of the associated data as SP 800-38C A.2.2 prescribes:
+/// two bytes below `2^16 - 2^8`, otherwise a marker followed by four or
+/// eight bytes.
+fn encode_length(len: usize) -> Vec<u8> {
+    if len < (1 << 16) - (1 << 8) {
+        (len as u16).to_be_bytes().to_vec()
+    } else if len >> 32 == 0 {
+        [&[0xff, 0xfe][..], &(len as u32).to_be_bytes()].concat()
+    } else {
+        [&[0xff, 0xff][..], &(len as u64).to_be_bytes()].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::decode_hex;
+
+    #[test]
+    fn sp800_38c_examples() {
+        // NIST SP 800-38C, appendix C.1 to C.4.
+        let keys = KeySchedule::new(&decode_hex("404142434445464748494a4b4c4d4e4f")).unwrap();
+        let long_data: Vec<u8> = (0..65536).map(|i| i as u8).collect();
+
+        for (tag_size, nonce, associated_data, message, sealed) in [
+            (
+                4,
+                "10111213141516",
+                decode_hex("0001020304050607"),
+                "20212223",
+                "7162015b4dac255d",
+            ),
+            (
+                6,
+                "1011121314151617",
+                decode_hex("000102030405060708090a0b0c0d0e0f"),
+                "202122232425262728292a2b2c2d2e2f",
+                "d2a1f0e051ea5f62081a7792073d593d1fc64fbfaccd",
+            ),
+            (
+                8,
+                "101112131415161718191a1b",
+                decode_hex("000102030405060708090a0b0c0d0e0f10111213"),
+                "202122232425262728292a2b2c2d2e2f3031323334353637",
+                "e3b201a9f5b71a7a9b1ceaeccd97e70b6176aad9a4428aa5484392fbc1b09951",
+            ),
+            (
+                14,
+                "101112131415161718191a1b1c",
+                long_data,
+                "202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f",
+                "69915dad1e84c6376a68c2967e4dab615ae0fd1faec44cc484828529463ccf72\
+                 b4ac6bec93e8598e7f0dadbcea5b",
+            ),
+        ] {
+            let ccm = Ccm::new(&keys, tag_size).unwrap();
+            let nonce = decode_hex(nonce);
+
+            let result = ccm
+                .encrypt(&nonce, &associated_data, &decode_hex(message))
+                .unwrap();
+            assert_eq!(result, decode_hex(sealed));
+
+            let opened = ccm.decrypt(&nonce, &associated_data, &result).unwrap();
+            assert_eq!(opened, decode_hex(message));
+        }
+    }
+
+    #[test]
+    fn rejects_tampering() {
+        let keys = KeySchedule::new(&[0x2b; 16]).unwrap();
+        let ccm = Ccm::new(&keys, 8).unwrap();
+        let nonce = [7u8; 11];
+        let sealed = ccm.encrypt(&nonce, b"header", b"attack at dawn").unwrap();
+
+        for i in 0..sealed.len() {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            assert!(matches!(
+                ccm.decrypt(&nonce, b"header", &tampered),
+                Err(AesError::AuthenticationFailed)
+            ));
+        }
+
+        assert!(matches!(
+            ccm.decrypt(&nonce, b"other header", &sealed),
+            Err(AesError::AuthenticationFailed)
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let keys = KeySchedule::new(&[0x2b; 16]).unwrap();
+
+        assert!(matches!(
+            Ccm::new(&keys, 5),
+            Err(AesError::InvalidTagSize(5))
+        ));
+
+        let ccm = Ccm::new(&keys, 16).unwrap();
+        assert!(matches!(
+            ccm.encrypt(&[0; 6], b"", b"message"),
+            Err(AesError::InvalidNonceSize(6))
+        ));
+        assert!(matches!(
+            ccm.encrypt(&[0; 14], b"", b"message"),
+            Err(AesError::InvalidNonceSize(14))
+        ));
+
+        // A 13-byte nonce leaves two bytes for the message length.
+        assert!(matches!(
+            ccm.encrypt(&[0; 13], b"", &vec![0; 1 << 16]),
+            Err(AesError::MessageTooLong)
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+//! The output of `AES::encrypt`: the ciphertext bytes together with the IV
+//! (the tweak for XTS) and the mode that produced them, so that `decrypt`
+//! always receives the IV it needs.
+//!
+//! With the `serde` feature the type serializes as a struct of its three
+//! fields; `to_vec` and `from_slice` provide the compact `iv || bytes` form.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::error::AesError;
+
+const IV_SIZE: usize = 16;
+
+/// The block modes a `Ciphertext` can record. Unlike `BlockMode`, it holds
+/// no key material.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CipherMode {
+    CBC,
+    XTS,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ciphertext {
+    mode: CipherMode,
+    iv: [u8; IV_SIZE],
+    bytes: Vec<u8>,
+}
+
+impl Ciphertext {
+    /// Creates a ciphertext from its parts.
+    ///
+    /// # Arguments
+    /// * `mode` - The mode the bytes were encrypted with.
+    /// * `iv` - The IV, or the tweak for XTS.
+    /// * `bytes` - The ciphertext bytes, without the IV.
+    pub fn new(mode: CipherMode, iv: [u8; IV_SIZE], bytes: Vec<u8>) -> Self {
+        Self { mode, iv, bytes }
+    }
+
+    /// Splits `data`, laid out as `iv || bytes`, into a ciphertext.
+    ///
+    /// # Arguments
+    /// * `mode` - The mode the bytes were encrypted with.
+    /// * `data` - The 16-byte IV followed by the ciphertext bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidCipherText` if `data` is shorter than an IV.
+    pub fn from_slice(mode: CipherMode, data: &[u8]) -> Result<Self, AesError> {
+        if data.len() < IV_SIZE {
+            return Err(AesError::InvalidCipherText);
+        }
+
+        let (iv, bytes) = data.split_at(IV_SIZE);
+        Ok(Self::new(mode, iv.try_into().unwrap(), bytes.to_vec()))
+    }
+
+    /// Returns the IV followed by the ciphertext bytes.
+    pub fn to_vec(&self) -> Vec<u8> {
+        [&self.iv[..], &self.bytes].concat()
+    }
+
+    pub fn mode(&self) -> CipherMode {
+        self.mode
+    }
+
+    pub fn iv(&self) -> &[u8; IV_SIZE] {
+        &self.iv
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_roundtrip() {
+        let ciphertext = Ciphertext::new(CipherMode::CBC, [7; 16], vec![1, 2, 3]);
+
+        let data = ciphertext.to_vec();
+        assert_eq!(data.len(), 19);
+        assert_eq!(
+            Ciphertext::from_slice(CipherMode::CBC, &data).unwrap(),
+            ciphertext
+        );
+
+        assert!(matches!(
+            Ciphertext::from_slice(CipherMode::CBC, &data[..15]),
+            Err(AesError::InvalidCipherText)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let ciphertext = Ciphertext::new(CipherMode::XTS, [9; 16], vec![0xab; 20]);
+
+        let json = serde_json::to_string(&ciphertext).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Ciphertext>(&json).unwrap(),
+            ciphertext
+        );
+    }
+}
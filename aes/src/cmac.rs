@@ -0,0 +1,194 @@
+//! This module implements AES-CMAC (NIST SP 800-38B). The message is
+//! CBC-encrypted under a zero IV and the last ciphertext block is the tag;
+//! before that, the final block is XORed with one of two subkeys, K1 for a
+//! complete block and K2 for a padded one, derived from the key by doubling
+//! in GF(2^128).
+use utils::mac::{Mac, MacError};
+
+use super::{aes_ops::AesOps, key_schedule::KeySchedule};
+
+const BLOCK_SIZE: usize = 16;
+
+/// The low byte of the reduction polynomial x^128 + x^7 + x^2 + x + 1.
+const R_128: u128 = 0x87;
+
+/// An incremental AES-CMAC computation.
+pub struct Cmac<'k> {
+    keys: &'k KeySchedule,
+    k1: [u8; 16],
+    k2: [u8; 16],
+    /// The CBC chaining value over the blocks processed so far.
+    state: [u8; 16],
+    /// Bytes not yet processed. The last block is held back until `finalize`,
+    /// since only then is it known to be the last one.
+    buffer: Vec<u8>,
+}
+
+impl<'k> Cmac<'k> {
+    /// Creates a CMAC instance keyed with the expanded AES key `keys`.
+    ///
+    /// # Arguments
+    /// * `keys` - The expanded AES key.
+    pub fn new(keys: &'k KeySchedule) -> Self {
+        let (k1, k2) = Self::subkeys(keys);
+
+        Self {
+            keys,
+            k1,
+            k2,
+            state: [0; 16],
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+        }
+    }
+
+    /// Derives the subkeys `K1 = 2 * E(K, 0^128)` and `K2 = 2 * K1`.
+    ///
+    /// # Arguments
+    /// * `keys` - The expanded AES key.
+    ///
+    /// # Returns
+    /// The pair `(K1, K2)`.
+    pub fn subkeys(keys: &KeySchedule) -> ([u8; 16], [u8; 16]) {
+        let l = AesOps::encrypt_block(&[0; 16], keys);
+        let k1 = double(&l);
+        let k2 = double(&k1);
+
+        (k1, k2)
+    }
+
+    /// Absorbs `data` into the MAC computation.
+    pub fn update(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+
+        // Process every block but the last, which `finalize` needs.
+        let whole = self.buffer.len().saturating_sub(1) / BLOCK_SIZE * BLOCK_SIZE;
+        for block in self.buffer[..whole].chunks(BLOCK_SIZE) {
+            xor_into(&mut self.state, block);
+            self.state = AesOps::encrypt_block(&self.state, self.keys);
+        }
+        self.buffer.drain(..whole);
+    }
+
+    /// Returns the 16-byte authentication tag.
+    pub fn finalize(mut self) -> [u8; 16] {
+        let mut last = [0u8; 16];
+        last[..self.buffer.len()].copy_from_slice(&self.buffer);
+
+        if self.buffer.len() == BLOCK_SIZE {
+            xor_into(&mut last, &self.k1);
+        } else {
+            last[self.buffer.len()] = 0x80;
+            xor_into(&mut last, &self.k2);
+        }
+
+        xor_into(&mut self.state, &last);
+        AesOps::encrypt_block(&self.state, self.keys)
+    }
+}
+
+impl Mac for Cmac<'_> {
+    fn update(&mut self, data: &[u8]) {
+        Cmac::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Cmac::finalize(self).to_vec()
+    }
+}
+
+/// Computes AES-CMAC of `message` in one shot.
+///
+/// # Arguments
+/// * `keys` - The expanded AES key.
+/// * `message` - The message to authenticate.
+///
+/// # Returns
+/// The 16-byte authentication tag.
+pub fn cmac(keys: &KeySchedule, message: &[u8]) -> [u8; 16] {
+    let mut mac = Cmac::new(keys);
+    mac.update(message);
+    mac.finalize()
+}
+
+/// Checks `tag` against the AES-CMAC of `message` in constant time.
+///
+/// # Arguments
+/// * `keys` - The expanded AES key.
+/// * `message` - The message to authenticate.
+/// * `tag` - The tag received alongside the message.
+///
+/// # Returns
+/// `Ok(())` if the tag is valid, `MacError::VerificationFailed` otherwise.
+pub fn verify(keys: &KeySchedule, message: &[u8], tag: &[u8]) -> Result<(), MacError> {
+    let mut mac = Cmac::new(keys);
+    mac.update(message);
+    Mac::verify(mac, tag)
+}
+
+/// Multiplies `block` by x in GF(2^128), without branching on the carry bit.
+fn double(block: &[u8; 16]) -> [u8; 16] {
+    let value = u128::from_be_bytes(*block);
+    let carry = 0u128.wrapping_sub(value >> 127);
+
+    ((value << 1) ^ (carry & R_128)).to_be_bytes()
+}
+
+fn xor_into(block: &mut [u8; 16], other: &[u8]) {
+    for (byte, other) in block.iter_mut().zip(other) {
+        *byte ^= other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::decode_hex;
+
+    const MESSAGE: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+                           30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+
+    #[test]
+    fn sp800_38b_aes128() {
+        // NIST SP 800-38B, D.1 (also RFC 4493, section 4).
+        let keys = KeySchedule::new(&decode_hex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap();
+
+        let (k1, k2) = Cmac::subkeys(&keys);
+        assert_eq!(k1.to_vec(), decode_hex("fbeed618357133667c85e08f7236a8de"));
+        assert_eq!(k2.to_vec(), decode_hex("f7ddac306ae266ccf90bc11ee46d513b"));
+
+        let message = decode_hex(MESSAGE);
+        for (len, tag) in [
+            (0, "bb1d6929e95937287fa37d129b756746"),
+            (16, "070a16b46b4d4144f79bdd9dd04a287c"),
+            (40, "dfa66747de9ae63030ca32611497c827"),
+            (64, "51f0bebf7e3b9d92fc49741779363cfe"),
+        ] {
+            assert_eq!(cmac(&keys, &message[..len]).to_vec(), decode_hex(tag));
+            assert_eq!(verify(&keys, &message[..len], &decode_hex(tag)), Ok(()));
+        }
+    }
+
+    #[test]
+    fn sp800_38b_aes256() {
+        // NIST SP 800-38B, D.3.
+        let keys = KeySchedule::new(&decode_hex(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+        ))
+        .unwrap();
+
+        let message = decode_hex(MESSAGE);
+        for (len, tag) in [
+            (0, "028962f61b7bf89efc6b551f4667d983"),
+            (16, "28a7023f452e8f82bd4bf28d8c37c35c"),
+            (64, "e1992190549f6ed5696a2c056c315410"),
+        ] {
+            assert_eq!(cmac(&keys, &message[..len]).to_vec(), decode_hex(tag));
+        }
+    }
+
+    #[test]
+    fn mac_trait_properties() {
+        let keys = KeySchedule::new(&[0x2b; 16]).unwrap();
+        utils::mac::check_mac(|| Cmac::new(&keys));
+    }
+}
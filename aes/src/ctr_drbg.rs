@@ -0,0 +1,362 @@
+//! This module implements CTR_DRBG (NIST SP 800-90A, section 10.2) on
+//! AES-256, without a derivation function. The internal state is an AES key
+//! and a 128-bit counter `V`; output is the encryption of successive counter
+//! values, and after every request the state is replaced by fresh keystream
+//! (the `Update` function), so a later compromise does not reveal earlier
+//! output.
+//!
+//! Without a derivation function the entropy input must be full entropy, a
+//! whole seed of `SEED_SIZE` bytes. The generator implements `RngCore` and
+//! `CryptoRng`, so it can drive RSA prime generation or ECC key generation.
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+use super::{aes_ops::AesOps, error::AesError, key_schedule::KeySchedule};
+
+const BLOCK_SIZE: usize = 16;
+const KEY_SIZE: usize = 32;
+
+/// The seed length: an AES-256 key followed by a block.
+pub const SEED_SIZE: usize = KEY_SIZE + BLOCK_SIZE;
+
+/// The most output a single request may ask for, 2^19 bits.
+pub const MAX_BYTES_PER_REQUEST: usize = 1 << 16;
+
+/// The number of requests after which a reseed is required.
+const RESEED_INTERVAL: u64 = 1 << 48;
+
+/// A CTR_DRBG instance on AES-256 without a derivation function.
+///
+/// Create one with `instantiate` from caller-supplied entropy, or with
+/// `from_entropy` from the operating system, then draw output with
+/// `generate` or through `RngCore`. After 2^48 requests `generate` fails
+/// with `AesError::ReseedRequired` until `reseed` is called. The key and `V`
+/// are wiped when the generator is dropped.
+pub struct CtrDrbg {
+    keys: KeySchedule,
+    v: [u8; 16],
+    reseed_counter: u64,
+}
+
+impl CtrDrbg {
+    /// Instantiates the generator from full-entropy input.
+    ///
+    /// # Arguments
+    /// * `entropy` - `SEED_SIZE` bytes of full entropy.
+    /// * `personalization` - An optional string of at most `SEED_SIZE` bytes
+    ///   that sets this instance apart from others, such as a device id.
+    ///
+    /// # Returns
+    /// The new generator, or `AesError::AdditionalInputTooLong` if the
+    /// personalization string is longer than `SEED_SIZE`.
+    pub fn instantiate(
+        entropy: &[u8; SEED_SIZE],
+        personalization: Option<&[u8]>,
+    ) -> Result<Self, AesError> {
+        let seed_material = seed_material(entropy, personalization)?;
+
+        let mut drbg = Self {
+            keys: KeySchedule::new(&[0; KEY_SIZE])?,
+            v: [0; 16],
+            reseed_counter: 1,
+        };
+        drbg.update(&seed_material);
+
+        Ok(drbg)
+    }
+
+    /// Instantiates the generator with entropy from the operating system.
+    ///
+    /// # Arguments
+    /// * `personalization` - An optional personalization string, see `instantiate`.
+    pub fn from_entropy(personalization: Option<&[u8]>) -> Result<Self, AesError> {
+        let mut entropy = [0u8; SEED_SIZE];
+        OsRng.fill_bytes(&mut entropy);
+
+        let drbg = Self::instantiate(&entropy, personalization);
+        entropy.zeroize();
+        drbg
+    }
+
+    /// Mixes fresh entropy into the state and resets the reseed counter.
+    ///
+    /// # Arguments
+    /// * `entropy` - `SEED_SIZE` bytes of full entropy.
+    /// * `additional_input` - Optional input of at most `SEED_SIZE` bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::AdditionalInputTooLong` if `additional_input` is
+    /// longer than `SEED_SIZE`.
+    pub fn reseed(
+        &mut self,
+        entropy: &[u8; SEED_SIZE],
+        additional_input: Option<&[u8]>,
+    ) -> Result<(), AesError> {
+        let seed_material = seed_material(entropy, additional_input)?;
+        self.update(&seed_material);
+        self.reseed_counter = 1;
+
+        Ok(())
+    }
+
+    /// Fills `output` with pseudorandom bytes.
+    ///
+    /// # Arguments
+    /// * `output` - The buffer to fill, at most `MAX_BYTES_PER_REQUEST` bytes.
+    /// * `additional_input` - Optional input of at most `SEED_SIZE` bytes,
+    ///   mixed into the state before and after generating. Any input that is
+    ///   present is mixed in, even if it is all zeros.
+    ///
+    /// # Errors
+    /// Returns `AesError::ReseedRequired` once the reseed interval is reached,
+    /// `AesError::RequestTooLarge` for an oversized request and
+    /// `AesError::AdditionalInputTooLong` for oversized additional input.
+    pub fn generate(
+        &mut self,
+        output: &mut [u8],
+        additional_input: Option<&[u8]>,
+    ) -> Result<(), AesError> {
+        if self.reseed_counter > RESEED_INTERVAL {
+            return Err(AesError::ReseedRequired);
+        }
+        if output.len() > MAX_BYTES_PER_REQUEST {
+            return Err(AesError::RequestTooLarge);
+        }
+
+        let padded_input = pad_to_seed(additional_input)?;
+        if additional_input.is_some() {
+            self.update(&padded_input);
+        }
+
+        for chunk in output.chunks_mut(BLOCK_SIZE) {
+            increment(&mut self.v);
+            let block = AesOps::encrypt_block(&self.v, &self.keys);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+
+        self.update(&padded_input);
+        self.reseed_counter += 1;
+
+        Ok(())
+    }
+
+    /// The CTR_DRBG `Update` function: replaces the key and `V` with the next
+    /// `SEED_SIZE` bytes of keystream, XORed with `provided_data`.
+    fn update(&mut self, provided_data: &[u8; SEED_SIZE]) {
+        let mut temp = [0u8; SEED_SIZE];
+        for chunk in temp.chunks_mut(BLOCK_SIZE) {
+            increment(&mut self.v);
+            chunk.copy_from_slice(&AesOps::encrypt_block(&self.v, &self.keys));
+        }
+
+        for (byte, data) in temp.iter_mut().zip(provided_data) {
+            *byte ^= data;
+        }
+
+        self.keys = KeySchedule::new(&temp[..KEY_SIZE]).expect("AES-256 key size");
+        self.v.copy_from_slice(&temp[KEY_SIZE..]);
+        temp.zeroize();
+    }
+}
+
+impl RngCore for CtrDrbg {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// # Panics
+    /// Panics once the generator needs a reseed, after 2^48 requests.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest)
+            .expect("CTR_DRBG reseed interval reached");
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        for chunk in dest.chunks_mut(MAX_BYTES_PER_REQUEST) {
+            self.generate(chunk, None).map_err(rand::Error::new)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CryptoRng for CtrDrbg {}
+
+impl Drop for CtrDrbg {
+    fn drop(&mut self) {
+        // The key schedule wipes itself.
+        self.v.zeroize();
+    }
+}
+
+/// XORs the entropy with the zero-padded `input`.
+fn seed_material(
+    entropy: &[u8; SEED_SIZE],
+    input: Option<&[u8]>,
+) -> Result<[u8; SEED_SIZE], AesError> {
+    let mut seed_material = pad_to_seed(input)?;
+    for (byte, entropy) in seed_material.iter_mut().zip(entropy) {
+        *byte ^= entropy;
+    }
+
+    Ok(seed_material)
+}
+
+/// Pads `input` with zeros to `SEED_SIZE` bytes; no input pads to all zeros.
+fn pad_to_seed(input: Option<&[u8]>) -> Result<[u8; SEED_SIZE], AesError> {
+    let input = input.unwrap_or_default();
+    if input.len() > SEED_SIZE {
+        return Err(AesError::AdditionalInputTooLong(input.len()));
+    }
+
+    let mut padded = [0u8; SEED_SIZE];
+    padded[..input.len()].copy_from_slice(input);
+    Ok(padded)
+}
+
+/// Increments `V` as a 128-bit big-endian integer.
+fn increment(v: &mut [u8; 16]) {
+    *v = u128::from_be_bytes(*v).wrapping_add(1).to_be_bytes();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        crate::util::decode_hex(s)
+    }
+
+    #[test]
+    fn nist_cavp_no_reseed() {
+        // CAVP drbgvectors_no_reseed, CTR_DRBG.rsp, [AES-256 no df], COUNT = 0:
+        // instantiate, then two 512-bit requests of which the second is returned.
+        let entropy = hex(
+            "df5d73faa468649edda33b5cca79b0b05600419ccb7a879ddfec9db32ee494e5\
+             531b51de16a30f769262474c73bec010",
+        );
+        let mut drbg = CtrDrbg::instantiate(&entropy.try_into().unwrap(), None).unwrap();
+
+        let mut output = [0u8; 64];
+        drbg.generate(&mut output, None).unwrap();
+        drbg.generate(&mut output, None).unwrap();
+
+        assert_eq!(
+            output.to_vec(),
+            hex(
+                "d1c07cd95af8a7f11012c84ce48bb8cb87189e99d40fccb1771c619bdf82ab22\
+                 80b1dc2f2581f39164f7ac0c510494b3a43c41b7db17514c87b107ae793e01c5"
+            )
+        );
+    }
+
+    #[test]
+    fn personalization_additional_input_and_reseed() {
+        // The CTR_DRBG power-on self test of BoringSSL's FIPS module, which
+        // runs AES-256 without a derivation function: a personalized
+        // instantiate, a request with additional input, then a reseed and a
+        // second request, both with additional input.
+        let additional_input = Some(&b"BCM DRBG KAT AD "[..]);
+        let mut drbg = CtrDrbg::instantiate(
+            b"BCM Known Answer Test DBRG Initial Entropy      ",
+            Some(b"BCMPersonalization"),
+        )
+        .unwrap();
+
+        let mut output = [0u8; 64];
+        drbg.generate(&mut output, additional_input).unwrap();
+        assert_eq!(
+            output.to_vec(),
+            hex(
+                "1d63df0551492246cd9bc5bbf15d44ae1378b1e47cf196333d60b629d4bb6b44\
+                 f9efd9f4a2ba48ea39755932f7312c98142b49df02b65d710950db23dbe52295"
+            )
+        );
+
+        drbg.reseed(
+            b"BCM Known Answer Test DBRG Reseed Entropy       ",
+            additional_input,
+        )
+        .unwrap();
+        drbg.generate(&mut output, additional_input).unwrap();
+        assert_eq!(
+            output.to_vec(),
+            hex(
+                "a47705db14117671425bd8d7a54f8b39f2104a505ba2c8f0bb3ea1a5907d54d9\
+                 c6b096c02b7e9bc9a1dd782ed5a86616bd183cf2aa7a2b37f9ab356415013fc4"
+            )
+        );
+    }
+
+    #[test]
+    fn inputs_change_the_output() {
+        let entropy = [0x5a; SEED_SIZE];
+        let output = |drbg: &mut CtrDrbg| {
+            let mut bytes = [0u8; 40];
+            drbg.fill_bytes(&mut bytes);
+            bytes
+        };
+
+        let mut drbg = CtrDrbg::instantiate(&entropy, Some(b"device 1")).unwrap();
+        let first = output(&mut drbg);
+        assert_eq!(
+            first,
+            output(&mut CtrDrbg::instantiate(&entropy, Some(b"device 1")).unwrap())
+        );
+        assert_ne!(
+            first,
+            output(&mut CtrDrbg::instantiate(&entropy, Some(b"device 2")).unwrap())
+        );
+
+        // Successive requests differ, and so does output after a reseed.
+        let second = output(&mut drbg);
+        assert_ne!(first, second);
+
+        let mut reseeded = CtrDrbg::instantiate(&entropy, Some(b"device 1")).unwrap();
+        output(&mut reseeded);
+        reseeded.reseed(&[0xa5; SEED_SIZE], None).unwrap();
+        assert_ne!(output(&mut reseeded), second);
+    }
+
+    #[test]
+    fn zero_additional_input_is_not_absent() {
+        let generate = |additional_input: Option<&[u8]>| {
+            let mut drbg = CtrDrbg::instantiate(&[0x5a; SEED_SIZE], None).unwrap();
+            let mut bytes = [0u8; 32];
+            drbg.generate(&mut bytes, additional_input).unwrap();
+            bytes
+        };
+
+        // Present input runs an extra `Update` before generating, even when
+        // it pads to the same all-zero block as no input.
+        assert_ne!(generate(None), generate(Some(&[0; SEED_SIZE])));
+        assert_ne!(generate(None), generate(Some(b"")));
+        assert_eq!(generate(Some(b"")), generate(Some(&[0; SEED_SIZE])));
+    }
+
+    #[test]
+    fn rejects_oversized_inputs() {
+        assert!(matches!(
+            CtrDrbg::instantiate(&[0; SEED_SIZE], Some(&[0; SEED_SIZE + 1])),
+            Err(AesError::AdditionalInputTooLong(49))
+        ));
+
+        let mut drbg = CtrDrbg::from_entropy(None).unwrap();
+        let mut output = vec![0u8; MAX_BYTES_PER_REQUEST + 1];
+        assert!(matches!(
+            drbg.generate(&mut output, None),
+            Err(AesError::RequestTooLarge)
+        ));
+
+        // `fill_bytes` splits large requests instead.
+        drbg.fill_bytes(&mut output);
+    }
+}
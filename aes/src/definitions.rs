@@ -1,31 +1,150 @@
-use super::error::AesError;
+use super::{ciphertext::CipherMode, error::AesError, key_schedule::KeySchedule};
+
+/// The AES block size, which the `Vec` based padding methods pad to.
+const BLOCK_SIZE: usize = 16;
+
+/// A block cipher keyed for use by the modes of operation in `block_modes`,
+/// which only ever see whole blocks through this trait.
+pub trait BlockCipher {
+    /// The block size in bytes.
+    const BLOCK_SIZE: usize;
+
+    /// Encrypts one block in place.
+    ///
+    /// # Arguments
+    /// * `block` - A block of exactly `BLOCK_SIZE` bytes.
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    /// Decrypts one block in place.
+    ///
+    /// # Arguments
+    /// * `block` - A block of exactly `BLOCK_SIZE` bytes.
+    fn decrypt_block(&self, block: &mut [u8]);
+}
 
 pub trait AesEncryptor {
-    fn encrypt(&mut self, input: &[u8]) -> Result<Vec<[[u8; 4]; 4]>, AesError>;
+    fn encrypt(&mut self, input: &[u8]) -> Result<Vec<u8>, AesError>;
     fn decrypt(&mut self, cipher_bytes: &[u8]) -> Result<Vec<u8>, AesError>;
 }
 
+/// Trait for encrypting a message in chunks, without holding all of it in memory.
+pub trait StreamingEncryptor {
+    /// Feeds the next chunk of the message, carrying the chaining state over
+    /// from the previous call.
+    ///
+    /// # Arguments
+    /// * `input` - The next chunk of plaintext bytes.
+    ///
+    /// # Returns
+    /// The ciphertext bytes that can already be produced, which may be fewer
+    /// than `input` when a mode has to wait for a whole block.
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, AesError>;
+
+    /// Completes the message, consuming the encryptor so that its IV or
+    /// counter cannot be used for another one.
+    ///
+    /// # Returns
+    /// The remaining ciphertext bytes.
+    fn finalize(self) -> Result<Vec<u8>, AesError>;
+}
+
 /// Trait for padding processing in cryptographic operations.
+///
+/// Implementors work on caller-owned buffers; the `Vec` based methods are
+/// provided on top of them.
 pub trait PaddingProcessor {
-    /// Adds padding to the given input buffer.
+    /// Writes the padding for the first `message_len` bytes of `buffer`
+    /// into the space after them.
+    ///
+    /// # Arguments
+    /// * `buffer` - The message followed by room for the padding.
+    /// * `message_len` - The length of the message at the start of `buffer`.
+    /// * `block_size` - The block size of the cipher in bytes.
+    ///
+    /// # Returns
+    /// The padded length, a multiple of the block size.
+    ///
+    /// # Errors
+    /// Returns `AesError::BufferTooSmall` if the padding does not fit in
+    /// `buffer`, or `AesError::InvalidPadding` if the input cannot be padded.
+    fn pad_in_place(
+        &self,
+        buffer: &mut [u8],
+        message_len: usize,
+        block_size: usize,
+    ) -> Result<usize, AesError>;
+
+    /// Validates the padding at the end of `buffer`.
+    ///
+    /// # Arguments
+    /// * `buffer` - The padded plaintext.
+    /// * `block_size` - The block size of the cipher in bytes.
+    ///
+    /// # Returns
+    /// The length of the message without its padding.
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidPadding` if the padding is malformed.
+    fn unpadded_len(&self, buffer: &[u8], block_size: usize) -> Result<usize, AesError>;
+
+    /// Adds padding for 16-byte AES blocks to the given input buffer.
     ///
     /// # Arguments
     /// * `input_buffer` - A mutable reference to a vector of bytes representing the input data.
-    fn pad_input(&self, input_buffer: &mut Vec<u8>);
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidPadding` if the input cannot be padded.
+    fn pad_input(&self, input_buffer: &mut Vec<u8>) -> Result<(), AesError> {
+        let message_len = input_buffer.len();
+        input_buffer.resize(message_len + BLOCK_SIZE, 0);
+
+        let padded_len = self.pad_in_place(input_buffer, message_len, BLOCK_SIZE)?;
+        input_buffer.truncate(padded_len);
+        Ok(())
+    }
 
-    /// Removes padding from the given output buffer.
+    /// Removes padding for 16-byte AES blocks from the given output buffer.
     ///
     /// # Arguments
     /// * `output_buffer` - A mutable reference to a vector of bytes representing the output data.
-    fn strip_output(&self, output_buffer: &mut Vec<u8>);
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidPadding` if the padding is malformed.
+    fn strip_output(&self, output_buffer: &mut Vec<u8>) -> Result<(), AesError> {
+        let message_len = self.unpadded_len(output_buffer, BLOCK_SIZE)?;
+        output_buffer.truncate(message_len);
+        Ok(())
+    }
 }
 
 /// Enum representing different padding schemes.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum PaddingScheme {
     /// Represents the PKSC padding scheme.
     PKSC,
+    /// Represents the ANSI X9.23 padding scheme.
+    X923,
+    /// Represents the ISO/IEC 7816-4 padding scheme.
+    ISO7816,
+    /// No padding; the input must already be a multiple of the block size.
+    None,
 }
 
-pub enum BlockMode {
+pub enum BlockMode<'k> {
     CBC,
+    /// XTS-AES; the cipher's own key encrypts the data and `tweak_keys`,
+    /// which must be a different key, the tweak.
+    XTS {
+        tweak_keys: &'k KeySchedule,
+    },
+}
+
+impl BlockMode<'_> {
+    /// Returns the mode without its key material, as recorded in a `Ciphertext`.
+    pub fn kind(&self) -> CipherMode {
+        match self {
+            BlockMode::CBC => CipherMode::CBC,
+            BlockMode::XTS { .. } => CipherMode::XTS,
+        }
+    }
 }
@@ -0,0 +1,169 @@
+//! This module implements the EAX authenticated encryption mode (Bellare,
+//! Rogaway and Wagner). The message is encrypted in CTR mode and
+//! authenticated with three domain-separated CMACs, written `OMAC^t`, which
+//! prefix the input with the block `t` (0 for the nonce, 1 for the header,
+//! 2 for the ciphertext):
+//!
+//! ```text
+//! N = OMAC^0(nonce)    H = OMAC^1(header)
+//! C = CTR(counter = N, message)
+//! tag = N ^ H ^ OMAC^2(C)
+//! ```
+//!
+//! The nonce may have any length but must never repeat under the same key.
+use utils::mac::ct_eq;
+
+use super::{
+    block_modes::CtrEncryptor, cmac::Cmac, definitions::AesEncryptor, error::AesError,
+    key_schedule::KeySchedule,
+};
+
+/// The length of the authentication tag appended to the ciphertext.
+pub const TAG_SIZE: usize = 16;
+
+/// Encrypts and authenticates `message`, also authenticating `header`.
+///
+/// # Arguments
+/// * `keys` - The expanded AES key.
+/// * `nonce` - A value unique per message under `keys`, of any length.
+/// * `header` - Associated data that is authenticated but not encrypted.
+/// * `message` - The plaintext bytes.
+///
+/// # Returns
+/// The ciphertext followed by the 16-byte tag.
+pub fn encrypt(
+    keys: &KeySchedule,
+    nonce: &[u8],
+    header: &[u8],
+    message: &[u8],
+) -> Result<Vec<u8>, AesError> {
+    let n = omac(keys, 0, nonce);
+    let mut sealed = CtrEncryptor::new(keys, &n)?.encrypt(message)?;
+
+    let tag = tag(keys, n, header, &sealed);
+    sealed.extend_from_slice(&tag);
+
+    Ok(sealed)
+}
+
+/// Verifies the tag of `sealed` and decrypts it.
+///
+/// # Arguments
+/// * `keys` - The expanded AES key.
+/// * `nonce` - The nonce the message was encrypted with.
+/// * `header` - The associated data the message was encrypted with.
+/// * `sealed` - The ciphertext followed by the 16-byte tag, as returned by `encrypt`.
+///
+/// # Returns
+/// The plaintext bytes.
+///
+/// # Errors
+/// Returns `AesError::InvalidCipherText` if `sealed` is shorter than a tag,
+/// and `AesError::AuthenticationFailed` if the tag does not match. No
+/// plaintext is released in either case.
+pub fn decrypt(
+    keys: &KeySchedule,
+    nonce: &[u8],
+    header: &[u8],
+    sealed: &[u8],
+) -> Result<Vec<u8>, AesError> {
+    let split = sealed
+        .len()
+        .checked_sub(TAG_SIZE)
+        .ok_or(AesError::InvalidCipherText)?;
+    let (cipher_bytes, received_tag) = sealed.split_at(split);
+
+    let n = omac(keys, 0, nonce);
+    if !ct_eq(&tag(keys, n, header, cipher_bytes), received_tag) {
+        return Err(AesError::AuthenticationFailed);
+    }
+
+    CtrEncryptor::new(keys, &n)?.decrypt(cipher_bytes)
+}
+
+/// Computes `OMAC^0(nonce) ^ OMAC^1(header) ^ OMAC^2(cipher_bytes)`, given
+/// the already computed nonce MAC `n`.
+fn tag(keys: &KeySchedule, n: [u8; 16], header: &[u8], cipher_bytes: &[u8]) -> [u8; 16] {
+    let h = omac(keys, 1, header);
+    let c = omac(keys, 2, cipher_bytes);
+
+    core::array::from_fn(|i| n[i] ^ h[i] ^ c[i])
+}
+
+/// Computes `OMAC^t(data)`, the CMAC of `data` prefixed with the block encoding `t`.
+fn omac(keys: &KeySchedule, t: u8, data: &[u8]) -> [u8; 16] {
+    let mut prefix = [0u8; 16];
+    prefix[15] = t;
+
+    let mut mac = Cmac::new(keys);
+    mac.update(&prefix);
+    mac.update(data);
+    mac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::decode_hex;
+
+    #[test]
+    fn eax_paper_vectors() {
+        // Test vectors from the appendix of the EAX paper.
+        for (message, key, nonce, header, sealed) in [
+            (
+                "",
+                "233952dee4d5ed5f9b9c6d6ff80ff478",
+                "62ec67f9c3a4a407fcb2a8c49031a8b3",
+                "6bfb914fd07eae6b",
+                "e037830e8389f27b025a2d6527e79d01",
+            ),
+            (
+                "f7fb",
+                "91945d3f4dcbee0bf45ef52255f095a4",
+                "becaf043b0a23d843194ba972c66debd",
+                "fa3bfd4806eb53fa",
+                "19dd5c4c9331049d0bdab0277408f67967e5",
+            ),
+            (
+                "1a47cb4933",
+                "01f74ad64077f2e704c0f60ada3dd523",
+                "70c3db4f0d26368400a10ed05d2bff5e",
+                "234a3463c1264ac6",
+                "d851d5bae03a59f238a23e39199dc9266626c40f80",
+            ),
+        ] {
+            let keys = KeySchedule::new(&decode_hex(key)).unwrap();
+            let (nonce, header) = (decode_hex(nonce), decode_hex(header));
+
+            let result = encrypt(&keys, &nonce, &header, &decode_hex(message)).unwrap();
+            assert_eq!(result, decode_hex(sealed));
+
+            let opened = decrypt(&keys, &nonce, &header, &result).unwrap();
+            assert_eq!(opened, decode_hex(message));
+        }
+    }
+
+    #[test]
+    fn rejects_tampering() {
+        let keys = KeySchedule::new(&[0x2b; 16]).unwrap();
+        let sealed = encrypt(&keys, b"nonce", b"header", b"attack at dawn").unwrap();
+
+        for i in 0..sealed.len() {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            assert!(matches!(
+                decrypt(&keys, b"nonce", b"header", &tampered),
+                Err(AesError::AuthenticationFailed)
+            ));
+        }
+
+        assert!(matches!(
+            decrypt(&keys, b"nonce", b"other header", &sealed),
+            Err(AesError::AuthenticationFailed)
+        ));
+        assert!(matches!(
+            decrypt(&keys, b"nonce", b"header", &sealed[..TAG_SIZE - 1]),
+            Err(AesError::InvalidCipherText)
+        ));
+    }
+}
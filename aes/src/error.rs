@@ -1,3 +1,4 @@
+use sha_256::pbkdf2::Pbkdf2Error;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,4 +20,58 @@ pub enum AesError {
 
     #[error("Invalid cipher text")]
     InvalidCipherText,
+
+    #[error("Invalid padding")]
+    InvalidPadding,
+
+    #[error("Input length is not a multiple of the block size")]
+    UnalignedInput,
+
+    #[error("Authentication tag mismatch")]
+    AuthenticationFailed,
+
+    #[error("Invalid tag size of `{0}` bytes")]
+    InvalidTagSize(usize),
+
+    #[error("Invalid nonce size of `{0}` bytes")]
+    InvalidNonceSize(usize),
+
+    #[error("Invalid IV size of `{0}` bytes")]
+    InvalidIvSize(usize),
+
+    #[error("The IV has already been used to encrypt a message")]
+    IvReused,
+
+    #[error("Block mode needs a 16-byte block cipher, got `{0}` bytes")]
+    InvalidBlockSize(usize),
+
+    #[error("XTS data and tweak keys must be different")]
+    IdenticalXtsKeys,
+
+    #[error("Message too long for the chosen nonce size")]
+    MessageTooLong,
+
+    #[error("Input is shorter than one block")]
+    InputTooShort,
+
+    #[error("Padding scheme not supported by this block mode")]
+    UnsupportedPadding,
+
+    #[error("Buffer too small for the padded message")]
+    BufferTooSmall,
+
+    #[error("Additional input of `{0}` bytes is longer than the seed")]
+    AdditionalInputTooLong(usize),
+
+    #[error("Too many bytes requested at once")]
+    RequestTooLarge,
+
+    #[error("The generator must be reseeded")]
+    ReseedRequired,
+
+    #[error("Ciphertext was produced under a different block mode")]
+    ModeMismatch,
+
+    #[error("Key derivation failed: {0}")]
+    KeyDerivation(#[from] Pbkdf2Error),
 }
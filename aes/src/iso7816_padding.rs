@@ -0,0 +1,117 @@
+use super::{definitions::PaddingProcessor, error::AesError};
+
+/// The byte marking the start of the padding.
+const MARKER: u8 = 0x80;
+
+/// ISO/IEC 7816-4 padding mode
+///
+/// This struct implements the ISO/IEC 7816-4 padding scheme: a single 0x80
+/// byte is appended to the plaintext, followed by zeros up to the block size.
+#[derive(Clone, Copy)]
+pub struct Iso7816Padding;
+
+impl PaddingProcessor for Iso7816Padding {
+    /// Writes ISO/IEC 7816-4 padding after the message.
+    ///
+    /// Between 1 and `block_size` bytes are written: 0x80, followed by zeros.
+    ///
+    /// # Arguments
+    /// * `buffer` - The message followed by room for the padding.
+    /// * `message_len` - The length of the message at the start of `buffer`.
+    /// * `block_size` - The block size of the cipher in bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::BufferTooSmall` if the padding does not fit.
+    fn pad_in_place(
+        &self,
+        buffer: &mut [u8],
+        message_len: usize,
+        block_size: usize,
+    ) -> Result<usize, AesError> {
+        let pad_size = block_size - (message_len % block_size);
+        let padding = buffer
+            .get_mut(message_len..message_len + pad_size)
+            .ok_or(AesError::BufferTooSmall)?;
+        padding.fill(0);
+        padding[0] = MARKER;
+
+        Ok(message_len + pad_size)
+    }
+
+    /// Validates ISO/IEC 7816-4 padding and returns the length without it.
+    ///
+    /// # Arguments
+    /// * `buffer` - The padded plaintext.
+    /// * `block_size` - The block size of the cipher in bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidPadding` if the buffer is empty, its length is
+    /// not a multiple of `block_size`, or the last block has no 0x80 marker
+    /// followed only by zeros.
+    fn unpadded_len(&self, buffer: &[u8], block_size: usize) -> Result<usize, AesError> {
+        if buffer.is_empty() || !buffer.len().is_multiple_of(block_size) {
+            return Err(AesError::InvalidPadding);
+        }
+
+        let last_block = &buffer[buffer.len() - block_size..];
+        let zeros = last_block
+            .iter()
+            .rev()
+            .take_while(|&&byte| byte == 0)
+            .count();
+        if zeros == block_size || last_block[block_size - 1 - zeros] != MARKER {
+            return Err(AesError::InvalidPadding);
+        }
+
+        Ok(buffer.len() - zeros - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad_input() {
+        let mut input = vec![10; 10];
+        Iso7816Padding.pad_input(&mut input).unwrap();
+
+        let mut expected = vec![10; 10];
+        expected.extend([0x80, 0, 0, 0, 0, 0]);
+        assert_eq!(input, expected);
+
+        let mut input = vec![10; 15];
+        Iso7816Padding.pad_input(&mut input).unwrap();
+        assert_eq!(input.len(), 16);
+        assert_eq!(input[15], 0x80);
+    }
+
+    #[test]
+    fn test_strip_output() {
+        for len in 0..=32 {
+            let mut input = vec![0x80; len];
+            Iso7816Padding.pad_input(&mut input).unwrap();
+            Iso7816Padding.strip_output(&mut input).unwrap();
+            assert_eq!(input, vec![0x80; len]);
+        }
+    }
+
+    #[test]
+    fn test_strip_output_rejects_malformed_padding() {
+        let mut wrong_marker = vec![0; 16];
+        wrong_marker[10] = 0x81;
+
+        for mut output in [
+            vec![],
+            vec![0x80; 15],
+            vec![0; 16],
+            vec![1; 16],
+            wrong_marker,
+        ] {
+            assert!(matches!(
+                Iso7816Padding.strip_output(&mut output),
+                Err(AesError::InvalidPadding)
+            ));
+        }
+    }
+}
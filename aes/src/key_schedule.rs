@@ -1,4 +1,10 @@
-use super::{constants::*, error::AesError, util::rotate_left};
+use std::fmt;
+
+#[cfg(feature = "bitsliced")]
+use super::bitsliced;
+use zeroize::Zeroize;
+
+use super::{constants::*, error::AesError, state::State, util::rotate_left};
 
 const AES_KEY_SIZE_128: usize = 128 / 8;
 const AES_KEY_SIZE_192: usize = 192 / 8;
@@ -8,7 +14,8 @@ const ROUNDS_128: u8 = 10;
 const ROUNDS_192: u8 = 12;
 const ROUNDS_256: u8 = 14;
 
-#[derive(Debug)]
+/// The expanded round keys. They are wiped when the schedule is dropped, and
+/// `Debug` prints only the number of rounds.
 pub struct KeySchedule {
     keys: Vec<[u8; 4]>,
     pub rounds: u8,
@@ -20,123 +27,78 @@ pub struct KeySchedule {
 /// * `pk` - A byte slice representing the key.
 ///
 /// # Returns
-/// An instance of `KeySchedule` or an error if the key size is invalid.
-///
-/// # Panics
-/// Panics if the key size is not 128, 192, or 256 bits.
+/// An instance of `KeySchedule`, or `AesError::InvalidKeySize` if the key is
+/// not 128, 192, or 256 bits.
 impl KeySchedule {
     pub fn new(pk: &[u8]) -> Result<Self, AesError> {
-        let pk: [u8; 16] = pk
-            .try_into()
-            .map_err(|_e| AesError::InvalidKeySize(pk.len()))?;
-
-        let keys = Self::key_expansion(&pk)?;
-
-        match pk.len() {
-            AES_KEY_SIZE_128 => Ok(Self {
-                keys,
-                rounds: ROUNDS_128,
-            }),
-            AES_KEY_SIZE_192 => Ok(Self {
-                keys,
-                rounds: ROUNDS_192,
-            }),
-            AES_KEY_SIZE_256 => Ok(Self {
-                keys,
-                rounds: ROUNDS_256,
-            }),
-            _ => Err(AesError::InvalidKeySize(pk.len())),
-        }
+        let rounds = match pk.len() {
+            AES_KEY_SIZE_128 => ROUNDS_128,
+            AES_KEY_SIZE_192 => ROUNDS_192,
+            AES_KEY_SIZE_256 => ROUNDS_256,
+            _ => return Err(AesError::InvalidKeySize(pk.len())),
+        };
+
+        let keys = Self::key_expansion(pk, rounds)?;
+
+        Ok(Self { keys, rounds })
     }
 
     /// Retrieves the round key for a specific AES encryption round.
-    pub fn round_key(&self, round: usize) -> [[u8; 4]; 4] {
+    pub fn round_key(&self, round: usize) -> State {
         let mut key: [[u8; 4]; 4] = [[0; 4]; 4];
         let start = round * 4;
         key.copy_from_slice(&self.keys[start..(start + 4)]);
 
-        key
+        State::from_columns(key)
     }
 
-    /// Performs key expansion for AES encryption.
+    /// Performs key expansion for AES encryption (FIPS-197 §5.2).
     ///
-    /// This function expands an initial key into a series of round keys used
-    /// in each round of AES encryption. The key expansion process transforms
-    /// the initial key into a larger key matrix suitable for the number of
-    /// encryption rounds.
+    /// This function expands an initial key of `Nk` words into the `4 * (rounds + 1)`
+    /// words of the round keys. Every word is the XOR of the word `Nk` positions
+    /// earlier and the previous word; the previous word first goes through the
+    /// g function at the start of each `Nk`-word block, and, for 256-bit keys,
+    /// through the S-Box alone in the middle of each block.
     ///
     /// Args:
-    ///     pk: The initial encryption key as a byte slice.
-    ///     n: The number of rounds for key expansion. For AES-128, this should be 10.
+    ///     pk: The initial encryption key of 16, 24 or 32 bytes.
+    ///     rounds: The number of encryption rounds for this key size.
     ///
     /// Returns:
     ///     A `Vec<[u8; 4]>` representing the expanded key if successful, or
     ///     an `AesError` in case of an error.
     ///
     /// Errors:
-    ///     Returns `AesError` if the initial key is too short or if any
-    ///     part of the key expansion process fails.
-    fn key_expansion(pk: &[u8]) -> Result<Vec<[u8; 4]>, AesError> {
-        let mut words: Vec<[u8; 4]> = vec![];
-
-        // Generate the initial words `w0-w3`
-        pk.chunks(4).for_each(|chunk| {
-            let mut array = [0u8; 4];
-            let len = chunk.len().min(4);
-            array[..len].copy_from_slice(&chunk[..len]);
-            words.push(array);
-        });
-
-        for round in 0..10 {
-            let previous_key_matrix_slice = &words[words.len().saturating_sub(4)..];
-
-            let previous_key_matrix: [[u8; 4]; 4] = match previous_key_matrix_slice {
-                [row0, row1, row2, row3] => [*row0, *row1, *row2, *row3],
-                _ => return Err(AesError::KeyMatrixConversionError),
-            };
-
-            let new_key_round =
-                Self::generate_new_round(&previous_key_matrix, ROUND_CONSTANT_128[round]);
-
-            for row in new_key_round {
-                words.push(row);
-            }
-        }
+    ///     Returns `AesError::KeyMatrixConversionError` if the key is not a
+    ///     whole number of words.
+    fn key_expansion(pk: &[u8], rounds: u8) -> Result<Vec<[u8; 4]>, AesError> {
+        let nk = pk.len() / 4;
+        let total_words = 4 * (rounds as usize + 1);
 
-        Ok(words)
-    }
+        // The initial words `w0` to `w(Nk-1)` are the key itself.
+        let mut words = pk
+            .chunks(4)
+            .map(|chunk| chunk.try_into())
+            .collect::<Result<Vec<[u8; 4]>, _>>()
+            .map_err(|_e| AesError::KeyMatrixConversionError)?;
 
-    /// Generates a new round key for AES encryption.
-    ///
-    /// This function is part of the AES key expansion algorithm for a 128-bit key.
-    /// It takes the previous round key and applies a series of transformations
-    /// to generate the new round key.
-    ///
-    /// Args:
-    ///     key_matrix: The previous round key, a 4x4 matrix of bytes.
-    ///     rc: The round constant for the current round of key expansion.
-    ///
-    /// Returns:
-    ///     A new 4x4 matrix representing the next round key.
-    fn generate_new_round(key_matrix: &[[u8; 4]; 4], rc: u8) -> [[u8; 4]; 4] {
-        let mut new_key_matrix: [[u8; 4]; 4] = [[0u8; 4]; 4];
-
-        // Apply the g_function to the last column of the previous round key
-        let mut array_rc = KeySchedule::g_function(key_matrix[key_matrix.len() - 1], rc);
-        for c in 0..4 {
-            let mut next_array_rc: [u8; 4] = [0u8; 4];
-            // XOR each column of the previous key with the transformed column
-            // to create the new round key
-            for r in 0..4 {
-                new_key_matrix[c][r] = array_rc[r] ^ key_matrix[c][r];
-                next_array_rc[r] = new_key_matrix[c][r];
+        for i in nk..total_words {
+            let mut temp = words[i - 1];
+
+            if i % nk == 0 {
+                temp = Self::g_function(temp, ROUND_CONSTANT_128[i / nk - 1]);
+            } else if nk > 6 && i % nk == 4 {
+                temp = Self::sub_word(temp);
             }
 
-            // Update array_rc for the next iteration
-            array_rc = next_array_rc;
+            let mut word = words[i - nk];
+            for (byte, t) in word.iter_mut().zip(temp) {
+                *byte ^= t;
+            }
+            words.push(word);
         }
 
-        new_key_matrix
+        Ok(words)
     }
 
     /// Performs the 'g' function of the AES key expansion.
@@ -156,21 +118,59 @@ impl KeySchedule {
         let mut new_word = rotate_left(&word, 1);
 
         // Perform byte substitution using the AES S-Box.
-        // Each byte of `new_word` is replaced with its corresponding value from the AES S-Box.
-        for byte in new_word.iter_mut() {
-            *byte = AES_S_BOX[*byte as usize];
-        }
+        new_word = Self::sub_word(new_word);
 
         // XOR the first byte of the transformed word with the round constant for the current round.
         new_word[0] ^= rc;
 
         new_word
     }
+
+    /// Replaces each byte of `word` with its value from the AES S-Box.
+    ///
+    /// With the `bitsliced` feature the S-Box is evaluated as a circuit, so
+    /// the key bytes never index a table.
+    fn sub_word(word: [u8; 4]) -> [u8; 4] {
+        #[cfg(feature = "bitsliced")]
+        return bitsliced::sub_word(word);
+
+        #[cfg(not(feature = "bitsliced"))]
+        word.map(|byte| AES_S_BOX[byte as usize])
+    }
+}
+
+impl Zeroize for KeySchedule {
+    /// Overwrites every round key with zeros.
+    fn zeroize(&mut self) {
+        for word in self.keys.iter_mut() {
+            word.zeroize();
+        }
+    }
+}
+
+impl fmt::Debug for KeySchedule {
+    /// Prints the number of rounds; the round keys, which begin with the
+    /// cipher key itself, are left out.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeySchedule")
+            .field("rounds", &self.rounds)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for KeySchedule {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use utils::wipe::{freed_with, WatchingAllocator};
+
+    #[global_allocator]
+    static ALLOCATOR: WatchingAllocator = WatchingAllocator;
 
     #[test]
     fn test_g_function() {
@@ -233,4 +233,33 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn debug_omits_round_keys() {
+        let key_schedule = KeySchedule::new(&[0x2b; 16]).unwrap();
+
+        assert_eq!(
+            format!("{key_schedule:?}"),
+            "KeySchedule { rounds: 10, .. }"
+        );
+    }
+
+    #[test]
+    fn zeroize_clears_round_keys() {
+        let mut key_schedule = KeySchedule::new(&[0x2b; 32]).unwrap();
+        assert!(key_schedule.keys.iter().any(|word| *word != [0; 4]));
+
+        key_schedule.zeroize();
+        assert_eq!(key_schedule.keys.len(), 60);
+        assert!(key_schedule.keys.iter().all(|word| *word == [0; 4]));
+    }
+
+    #[test]
+    fn drop_wipes_round_keys() {
+        // The first round keys are the cipher key itself.
+        let key: Vec<u8> = (0x40..0x60).collect();
+        let key_schedule = KeySchedule::new(&key).unwrap();
+
+        assert!(!freed_with(&key, || drop(key_schedule)));
+    }
 }
@@ -1,16 +1,44 @@
 pub mod aes_ops;
 pub mod block_modes;
+pub mod ccm;
+pub mod ciphertext;
+pub mod cmac;
+pub mod ctr_drbg;
 pub mod definitions;
+pub mod eax;
+pub mod iso7816_padding;
+pub mod no_padding;
 pub mod pkcs_padding;
+pub mod x923_padding;
 
+#[cfg(target_arch = "x86_64")]
+mod aes_ni;
+#[cfg(feature = "bitsliced")]
+mod bitsliced;
+#[cfg(test)]
+mod cavp;
 mod constants;
 mod error;
 mod key_schedule;
+pub mod state;
+#[cfg(all(feature = "t-tables", not(feature = "bitsliced")))]
+mod t_tables;
 mod util;
 
+use ciphertext::{CipherMode, Ciphertext};
 use definitions::*;
-use error::AesError;
-use key_schedule::*;
+use rand::{rngs::OsRng, RngCore};
+use sha_256::pbkdf2::pbkdf2_hmac_sha256;
+use zeroize::Zeroize;
+
+pub use error::AesError;
+pub use key_schedule::KeySchedule;
+
+/// The size of the random nonce `AES::seal` prepends to its output.
+const SEAL_NONCE_SIZE: usize = 16;
+
+/// The size of the AES-256 key `AES::from_password` derives.
+const PASSWORD_KEY_SIZE: usize = 32;
 
 #[derive(Debug)]
 pub struct AES(KeySchedule);
@@ -20,19 +48,375 @@ impl AES {
         Ok(Self(KeySchedule::new(pk)?))
     }
 
+    /// Creates an AES-256 instance keyed from a password with
+    /// PBKDF2-HMAC-SHA256.
+    ///
+    /// # Arguments
+    /// * `password` - The password bytes.
+    /// * `salt` - A random value, at least 16 bytes, stored next to the
+    ///   ciphertext so the key can be derived again.
+    /// * `iterations` - The PBKDF2 iteration count; higher values make each
+    ///   password guess more expensive.
+    ///
+    /// # Errors
+    /// Returns `AesError::KeyDerivation` if `iterations` is zero.
+    pub fn from_password(password: &[u8], salt: &[u8], iterations: u32) -> Result<Self, AesError> {
+        let mut key = [0u8; PASSWORD_KEY_SIZE];
+        pbkdf2_hmac_sha256(password, salt, iterations, &mut key)?;
+
+        let aes = Self::new(&key);
+        key.zeroize();
+        aes
+    }
+
+    /// Encrypts `input` under the given block mode and padding scheme.
+    ///
+    /// # Arguments
+    /// * `mode` - The block cipher mode of operation.
+    /// * `padding_scheme` - The scheme used to pad `input` to whole blocks.
+    /// * `input` - The plaintext bytes.
+    ///
+    /// # Returns
+    /// A `Ciphertext` holding the bytes, the randomly generated 16-byte IV
+    /// (the tweak for XTS) and the mode, or an `AesError` on failure.
+    ///
+    /// # Errors
+    /// XTS steals ciphertext instead of padding, so it returns
+    /// `AesError::UnsupportedPadding` for any scheme other than `None`.
     pub fn encrypt(
         &self,
         mode: BlockMode,
         padding_scheme: PaddingScheme,
         input: &[u8],
-    ) -> Result<Vec<[[u8; 4]; 4]>, AesError> {
-        let mut enc = match (mode, padding_scheme) {
-            (BlockMode::CBC, PaddingScheme::PKSC) => {
-                block_modes::CbcEncryptor::new(&self.0, pkcs_padding::PkcsPadding)?
+    ) -> Result<Ciphertext, AesError> {
+        match mode {
+            BlockMode::CBC => {
+                let mut enc = match padding_scheme {
+                    PaddingScheme::PKSC => {
+                        block_modes::CbcEncryptor::new(&self.0, pkcs_padding::PkcsPadding)?
+                    }
+                    PaddingScheme::X923 => {
+                        block_modes::CbcEncryptor::new(&self.0, x923_padding::X923Padding)?
+                    }
+                    PaddingScheme::ISO7816 => {
+                        block_modes::CbcEncryptor::new(&self.0, iso7816_padding::Iso7816Padding)?
+                    }
+                    PaddingScheme::None => {
+                        block_modes::CbcEncryptor::new(&self.0, no_padding::NoPadding)?
+                    }
+                };
+
+                let cipher_bytes = enc.encrypt(input)?;
+                let iv = enc.iv().try_into().unwrap();
+
+                Ok(Ciphertext::new(CipherMode::CBC, iv, cipher_bytes))
+            }
+            BlockMode::XTS { tweak_keys } => {
+                if padding_scheme != PaddingScheme::None {
+                    return Err(AesError::UnsupportedPadding);
+                }
+
+                let mut enc = block_modes::XtsEncryptor::new(&self.0, tweak_keys)?;
+                let cipher_bytes = enc.encrypt(input)?;
+
+                Ok(Ciphertext::new(CipherMode::XTS, enc.tweak(), cipher_bytes))
+            }
+        }
+    }
+
+    /// Decrypts `ciphertext` under the given block mode and strips its padding.
+    ///
+    /// # Errors
+    /// Returns `AesError::ModeMismatch` if `ciphertext` was produced under a
+    /// different mode, and `AesError::InvalidPadding` if the padding is
+    /// malformed, which includes decrypting under the wrong key.
+    ///
+    /// # Arguments
+    /// * `mode` - The block cipher mode the ciphertext was produced with.
+    /// * `padding_scheme` - The scheme that was used to pad the plaintext.
+    /// * `ciphertext` - The output of `encrypt`, carrying its IV.
+    ///
+    /// # Returns
+    /// The plaintext bytes, or an `AesError` on failure.
+    pub fn decrypt(
+        &self,
+        mode: BlockMode,
+        padding_scheme: PaddingScheme,
+        ciphertext: &Ciphertext,
+    ) -> Result<Vec<u8>, AesError> {
+        if mode.kind() != ciphertext.mode() {
+            return Err(AesError::ModeMismatch);
+        }
+
+        let iv = *ciphertext.iv();
+        let ciphertext = ciphertext.bytes();
+
+        match mode {
+            BlockMode::CBC => {
+                let mut dec = match padding_scheme {
+                    PaddingScheme::PKSC => {
+                        block_modes::CbcEncryptor::with_iv(&self.0, &iv, pkcs_padding::PkcsPadding)?
+                    }
+                    PaddingScheme::X923 => {
+                        block_modes::CbcEncryptor::with_iv(&self.0, &iv, x923_padding::X923Padding)?
+                    }
+                    PaddingScheme::ISO7816 => block_modes::CbcEncryptor::with_iv(
+                        &self.0,
+                        &iv,
+                        iso7816_padding::Iso7816Padding,
+                    )?,
+                    PaddingScheme::None => {
+                        block_modes::CbcEncryptor::with_iv(&self.0, &iv, no_padding::NoPadding)?
+                    }
+                };
+
+                dec.decrypt(ciphertext)
+            }
+            BlockMode::XTS { tweak_keys } => {
+                if padding_scheme != PaddingScheme::None {
+                    return Err(AesError::UnsupportedPadding);
+                }
+
+                block_modes::XtsEncryptor::with_tweak(&self.0, tweak_keys, iv)?.decrypt(ciphertext)
             }
+        }
+    }
+
+    /// Encrypts and authenticates `plaintext` with EAX under a fresh random
+    /// nonce, producing a self-contained blob for `open`.
+    ///
+    /// # Arguments
+    /// * `plaintext` - The bytes to encrypt.
+    /// * `aad` - Associated data that is authenticated but not encrypted;
+    ///   the same bytes must be passed to `open`.
+    ///
+    /// # Returns
+    /// The 16-byte nonce, the ciphertext and the 16-byte tag, concatenated.
+    pub fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, AesError> {
+        let mut nonce = [0u8; SEAL_NONCE_SIZE];
+        OsRng.fill_bytes(&mut nonce);
+
+        let mut blob = nonce.to_vec();
+        blob.extend_from_slice(&eax::encrypt(&self.0, &nonce, aad, plaintext)?);
+        Ok(blob)
+    }
+
+    /// Verifies and decrypts a blob produced by `seal`.
+    ///
+    /// # Arguments
+    /// * `blob` - The nonce, ciphertext and tag, as returned by `seal`.
+    /// * `aad` - The associated data the blob was sealed with.
+    ///
+    /// # Returns
+    /// The plaintext bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidCipherText` if `blob` is too short to hold a
+    /// nonce and a tag, and `AesError::AuthenticationFailed` if it was
+    /// tampered with or sealed under another key or `aad`.
+    pub fn open(&self, blob: &[u8], aad: &[u8]) -> Result<Vec<u8>, AesError> {
+        if blob.len() < SEAL_NONCE_SIZE + eax::TAG_SIZE {
+            return Err(AesError::InvalidCipherText);
+        }
+
+        let (nonce, sealed) = blob.split_at(SEAL_NONCE_SIZE);
+        eax::decrypt(&self.0, nonce, aad, sealed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_returns_bytes() {
+        let aes = AES::new(&[7u8; 16]).unwrap();
+
+        for (len, expected) in [(0, 16), (15, 16), (16, 32), (33, 48)] {
+            let ciphertext = aes
+                .encrypt(BlockMode::CBC, PaddingScheme::PKSC, &vec![0xab; len])
+                .unwrap();
+            assert_eq!(ciphertext.bytes().len(), expected);
+            assert_eq!(ciphertext.to_vec().len(), 16 + expected);
+        }
+    }
+
+    #[test]
+    fn ciphertext_carries_iv() {
+        let pk = [7u8; 16];
+        let message = b"a message spanning more than one block";
+        let output = AES::new(&pk)
+            .unwrap()
+            .encrypt(BlockMode::CBC, PaddingScheme::PKSC, message)
+            .unwrap();
+        assert_eq!(output.mode(), CipherMode::CBC);
+
+        // The IV survives the trip through the `iv || bytes` wire format.
+        let received = Ciphertext::from_slice(CipherMode::CBC, &output.to_vec()).unwrap();
+        let plain_bytes = AES::new(&pk)
+            .unwrap()
+            .decrypt(BlockMode::CBC, PaddingScheme::PKSC, &received)
+            .unwrap();
+        assert_eq!(plain_bytes, message);
+    }
+
+    #[test]
+    fn decrypt_roundtrip() {
+        let aes = AES::new(&[0x5a; 32]).unwrap();
+
+        for len in [0, 1, 15, 16, 17, 100] {
+            let message: Vec<u8> = (0..len as u8).collect();
+            let output = aes
+                .encrypt(BlockMode::CBC, PaddingScheme::PKSC, &message)
+                .unwrap();
+
+            let plain_bytes = aes
+                .decrypt(BlockMode::CBC, PaddingScheme::PKSC, &output)
+                .unwrap();
+            assert_eq!(plain_bytes, message);
+        }
+    }
+
+    #[test]
+    fn multi_block_cbc_decryption() {
+        // Every block must be chained to the preceding ciphertext block,
+        // and the first one to the IV.
+        let aes = AES::new(&[0x3c; 16]).unwrap();
+        let message: Vec<u8> = (0..64).collect();
+        let output = aes
+            .encrypt(BlockMode::CBC, PaddingScheme::None, &message)
+            .unwrap();
+
+        let mut expected = Vec::new();
+        let mut previous = *output.iv();
+        for block in output.bytes().chunks(16) {
+            let mut state = state::State::from_bytes(block.try_into().unwrap());
+            aes_ops::AesOps::decrypt(&mut state, &aes.0);
+            let plain_block = (state ^ state::State::from_bytes(&previous)).to_bytes();
+            expected.extend_from_slice(&plain_block);
+            previous = block.try_into().unwrap();
+        }
+        assert_eq!(expected, message);
+
+        assert_eq!(
+            aes.decrypt(BlockMode::CBC, PaddingScheme::None, &output)
+                .unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn decrypt_rejects_partial_blocks() {
+        let aes = AES::new(&[0x5a; 16]).unwrap();
+        let ciphertext = Ciphertext::new(CipherMode::CBC, [0; 16], vec![0; 20]);
+        assert!(matches!(
+            aes.decrypt(BlockMode::CBC, PaddingScheme::PKSC, &ciphertext),
+            Err(AesError::InvalidCipherText)
+        ));
+    }
+
+    #[test]
+    fn padding_schemes_roundtrip() {
+        let aes = AES::new(&[0x11; 16]).unwrap();
+        let message = b"seventeen bytes!!";
+
+        for scheme in [
+            PaddingScheme::PKSC,
+            PaddingScheme::X923,
+            PaddingScheme::ISO7816,
+        ] {
+            let output = aes.encrypt(BlockMode::CBC, scheme, message).unwrap();
+            assert_eq!(output.bytes().len(), 32);
+
+            let plain_bytes = aes.decrypt(BlockMode::CBC, scheme, &output).unwrap();
+            assert_eq!(plain_bytes, message);
+        }
+    }
+
+    #[test]
+    fn no_padding_requires_aligned_input() {
+        let aes = AES::new(&[0x11; 16]).unwrap();
+
+        let output = aes
+            .encrypt(BlockMode::CBC, PaddingScheme::None, &[0x42; 32])
+            .unwrap();
+        assert_eq!(output.bytes().len(), 32);
+
+        let plain_bytes = aes
+            .decrypt(BlockMode::CBC, PaddingScheme::None, &output)
+            .unwrap();
+        assert_eq!(plain_bytes, [0x42; 32]);
+
+        assert!(matches!(
+            aes.encrypt(BlockMode::CBC, PaddingScheme::None, &[0x42; 33]),
+            Err(AesError::UnalignedInput)
+        ));
+    }
+
+    #[test]
+    fn xts_roundtrip() {
+        let aes = AES::new(&[0x11; 16]).unwrap();
+        let tweak_keys = KeySchedule::new(&[0x22; 16]).unwrap();
+        let mode = || BlockMode::XTS {
+            tweak_keys: &tweak_keys,
         };
+        let message = b"an unaligned sector of data";
+
+        let output = aes.encrypt(mode(), PaddingScheme::None, message).unwrap();
+        assert_eq!(output.mode(), CipherMode::XTS);
+        assert_eq!(output.bytes().len(), message.len());
+
+        let plain_bytes = aes.decrypt(mode(), PaddingScheme::None, &output).unwrap();
+        assert_eq!(plain_bytes, message);
+
+        assert!(matches!(
+            aes.decrypt(BlockMode::CBC, PaddingScheme::None, &output),
+            Err(AesError::ModeMismatch)
+        ));
+        assert!(matches!(
+            aes.encrypt(mode(), PaddingScheme::PKSC, message),
+            Err(AesError::UnsupportedPadding)
+        ));
+    }
+
+    #[test]
+    fn seal_open_roundtrip() {
+        let aes = AES::new(&[0x11; 32]).unwrap();
+        let message = b"self-contained message";
+
+        let blob = aes.seal(message, b"v1").unwrap();
+        assert_eq!(blob.len(), 16 + message.len() + 16);
+        assert_eq!(aes.open(&blob, b"v1").unwrap(), message);
+
+        // A fresh nonce every time.
+        assert_ne!(aes.seal(message, b"v1").unwrap(), blob);
+
+        assert!(matches!(
+            aes.open(&blob, b"v2"),
+            Err(AesError::AuthenticationFailed)
+        ));
+        assert!(matches!(
+            AES::new(&[0x22; 32]).unwrap().open(&blob, b"v1"),
+            Err(AesError::AuthenticationFailed)
+        ));
+        assert!(matches!(
+            aes.open(&blob[..31], b"v1"),
+            Err(AesError::InvalidCipherText)
+        ));
+    }
+
+    #[test]
+    fn from_password_derives_pbkdf2_key() {
+        let key =
+            util::decode_hex("c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a");
+        let blob = AES::new(&key).unwrap().seal(b"file contents", b"").unwrap();
+
+        let aes = AES::from_password(b"password", b"salt", 4096).unwrap();
+        assert_eq!(aes.open(&blob, b"").unwrap(), b"file contents");
 
-        let cipher_bytes = enc.encrypt(input)?;
-        Ok(cipher_bytes)
+        assert!(matches!(
+            AES::from_password(b"password", b"salt", 0),
+            Err(AesError::KeyDerivation(_))
+        ));
     }
 }
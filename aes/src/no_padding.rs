@@ -0,0 +1,82 @@
+use super::{definitions::PaddingProcessor, error::AesError};
+
+/// No padding mode
+///
+/// This struct leaves the plaintext as it is, for callers that frame their
+/// messages themselves. The plaintext must be a multiple of the block size.
+#[derive(Clone, Copy)]
+pub struct NoPadding;
+
+impl PaddingProcessor for NoPadding {
+    /// Checks that the message is block-aligned, writing nothing.
+    ///
+    /// # Arguments
+    /// * `buffer` - The buffer holding the message.
+    /// * `message_len` - The length of the message at the start of `buffer`.
+    /// * `block_size` - The block size of the cipher in bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::UnalignedInput` if `message_len` is not a multiple
+    /// of `block_size`, and `AesError::BufferTooSmall` if it exceeds the buffer.
+    fn pad_in_place(
+        &self,
+        buffer: &mut [u8],
+        message_len: usize,
+        block_size: usize,
+    ) -> Result<usize, AesError> {
+        let message = buffer.get(..message_len).ok_or(AesError::BufferTooSmall)?;
+        check_alignment(message, block_size)?;
+
+        Ok(message_len)
+    }
+
+    /// Checks that the plaintext is block-aligned; all of it is message.
+    ///
+    /// # Arguments
+    /// * `buffer` - The plaintext.
+    /// * `block_size` - The block size of the cipher in bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::UnalignedInput` if the length of `buffer` is not a
+    /// multiple of `block_size`.
+    fn unpadded_len(&self, buffer: &[u8], block_size: usize) -> Result<usize, AesError> {
+        check_alignment(buffer, block_size)?;
+
+        Ok(buffer.len())
+    }
+}
+
+fn check_alignment(buffer: &[u8], block_size: usize) -> Result<(), AesError> {
+    if !buffer.len().is_multiple_of(block_size) {
+        return Err(AesError::UnalignedInput);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aligned_input_is_unchanged() {
+        for len in [0, 16, 48] {
+            let mut input = vec![7; len];
+            NoPadding.pad_input(&mut input).unwrap();
+            assert_eq!(input, vec![7; len]);
+
+            NoPadding.strip_output(&mut input).unwrap();
+            assert_eq!(input, vec![7; len]);
+        }
+    }
+
+    #[test]
+    fn test_unaligned_input_is_rejected() {
+        for len in [1, 15, 17] {
+            assert!(matches!(
+                NoPadding.pad_input(&mut vec![7; len]),
+                Err(AesError::UnalignedInput)
+            ));
+        }
+    }
+}
@@ -1,6 +1,4 @@
-use super::definitions::PaddingProcessor;
-
-const BLOCK_SIZE: usize = 16;
+use super::{definitions::PaddingProcessor, error::AesError};
 
 /// PKCS padding mode for ECB encryption
 ///
@@ -10,67 +8,74 @@ const BLOCK_SIZE: usize = 16;
 pub struct PkcsPadding;
 
 impl PaddingProcessor for PkcsPadding {
-    /// Adds PKCS#7 padding to the input buffer.
+    /// Writes PKCS#7 padding after the message.
     ///
-    /// This method calculates the necessary number of padding bytes and appends
-    /// them to the input buffer. Each padding byte has a value equal to the
+    /// This method calculates the necessary number of padding bytes and writes
+    /// them after the message. Each padding byte has a value equal to the
     /// number of padding bytes.
     ///
     /// # Arguments
-    /// * `input_buffer` - A mutable reference to a Vec<u8> representing the plaintext.
-    fn pad_input(&self, input_buffer: &mut Vec<u8>) {
-        let pad_size = BLOCK_SIZE - (input_buffer.len() % BLOCK_SIZE);
-        let padding: Vec<u8> = std::iter::repeat(pad_size as u8).take(pad_size).collect();
-        input_buffer.extend(padding);
+    /// * `buffer` - The message followed by room for the padding.
+    /// * `message_len` - The length of the message at the start of `buffer`.
+    /// * `block_size` - The block size of the cipher in bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::BufferTooSmall` if the padding does not fit.
+    fn pad_in_place(
+        &self,
+        buffer: &mut [u8],
+        message_len: usize,
+        block_size: usize,
+    ) -> Result<usize, AesError> {
+        let pad_size = block_size - (message_len % block_size);
+        let padding = buffer
+            .get_mut(message_len..message_len + pad_size)
+            .ok_or(AesError::BufferTooSmall)?;
+        padding.fill(pad_size as u8);
+
+        Ok(message_len + pad_size)
     }
 
-    /// Removes PKCS#7 padding from the output buffer.
+    /// Validates PKCS#7 padding and returns the length without it.
     ///
-    /// This method validates and strips the padding bytes from the output buffer.
-    /// It panics if the output buffer's length is not a multiple of BLOCK_SIZE or
-    /// if the padding is incorrect.
+    /// Every kind of malformed padding yields the same error, so callers do not
+    /// leak which check failed.
     ///
     /// # Arguments
-    /// * `output_buffer` - A mutable reference to a Vec<u8> representing the padded plaintext.
+    /// * `buffer` - The padded plaintext.
+    /// * `block_size` - The block size of the cipher in bytes.
     ///
-    /// # Panics
-    /// Panics if the length of `output_buffer` is not a multiple of `BLOCK_SIZE`,
-    /// or if the padding bytes are incorrect.
-    fn strip_output(&self, output_buffer: &mut Vec<u8>) {
-        if output_buffer.len() % BLOCK_SIZE != 0 {
-            panic!(
-                "Invalid output size: length is not a multiple of {}.",
-                BLOCK_SIZE
-            );
+    /// # Errors
+    /// Returns `AesError::InvalidPadding` if the buffer is empty, its length is
+    /// not a multiple of `block_size`, or the padding bytes are incorrect.
+    fn unpadded_len(&self, buffer: &[u8], block_size: usize) -> Result<usize, AesError> {
+        if buffer.is_empty() || !buffer.len().is_multiple_of(block_size) {
+            return Err(AesError::InvalidPadding);
+        }
+
+        let pad_size = buffer[buffer.len() - 1] as usize;
+        if pad_size > block_size || pad_size == 0 {
+            return Err(AesError::InvalidPadding);
         }
 
-        if let Some(&pad_size) = output_buffer.last() {
-            if pad_size as usize > BLOCK_SIZE || pad_size == 0 {
-                panic!("Invalid padding: incorrect padding size.");
-            }
-            let expected_padding = vec![pad_size; pad_size as usize];
-            if output_buffer.ends_with(&expected_padding) {
-                output_buffer.truncate(output_buffer.len() - pad_size as usize);
-            } else {
-                panic!("Invalid padding: incorrect padding bytes.");
-            }
-        } else {
-            panic!("Invalid padding: empty output buffer.");
+        let padding = &buffer[buffer.len() - pad_size..];
+        if padding.iter().any(|&byte| byte as usize != pad_size) {
+            return Err(AesError::InvalidPadding);
         }
+
+        Ok(buffer.len() - pad_size)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::pkcs_padding;
-
     use super::*;
 
     #[test]
     fn test_pad_input() {
         let mut input = vec![10; 10];
 
-        PkcsPadding.pad_input(&mut input);
+        PkcsPadding.pad_input(&mut input).unwrap();
 
         let mut expected = vec![10; 10];
         expected.extend(vec![6; 6]);
@@ -80,36 +85,48 @@ mod tests {
     #[test]
     fn test_strip_input() {
         let mut input = vec![10; 10];
-        PkcsPadding.pad_input(&mut input);
+        PkcsPadding.pad_input(&mut input).unwrap();
 
-        PkcsPadding.strip_output(&mut input);
+        PkcsPadding.strip_output(&mut input).unwrap();
         assert_eq!(input, vec![10; 10]);
     }
 
     #[test]
-    #[should_panic(expected = "Invalid output size: length is not a multiple of 16.")]
-    fn test_strip_output_panic_on_invalid_output_size() {
-        PkcsPadding.strip_output(&mut vec![1; 15]);
+    fn test_strip_output_rejects_invalid_output_size() {
+        assert!(matches!(
+            PkcsPadding.strip_output(&mut vec![1; 15]),
+            Err(AesError::InvalidPadding)
+        ));
     }
 
     #[test]
-    #[should_panic(expected = "Invalid padding: incorrect padding size.")]
-    fn test_strip_output_panic_on_invalid_size() {
-        PkcsPadding.strip_output(&mut vec![17; 16]);
+    fn test_strip_output_rejects_invalid_size() {
+        assert!(matches!(
+            PkcsPadding.strip_output(&mut vec![17; 16]),
+            Err(AesError::InvalidPadding)
+        ));
+        assert!(matches!(
+            PkcsPadding.strip_output(&mut vec![0; 16]),
+            Err(AesError::InvalidPadding)
+        ));
     }
 
     #[test]
-    #[should_panic(expected = "Invalid padding: incorrect padding bytes.")]
-    fn test_strip_output_panic_on_invalid_padding_bytes() {
+    fn test_strip_output_rejects_invalid_padding_bytes() {
         let mut output = vec![6; 6];
         output.extend(vec![16; 10]);
 
-        PkcsPadding.strip_output(&mut output);
+        assert!(matches!(
+            PkcsPadding.strip_output(&mut output),
+            Err(AesError::InvalidPadding)
+        ));
     }
 
     #[test]
-    #[should_panic(expected = "Invalid padding: empty output buffer.")]
-    fn test_strip_output_panic_on_empty_output() {
-        PkcsPadding.strip_output(&mut vec![]);
+    fn test_strip_output_rejects_empty_output() {
+        assert!(matches!(
+            PkcsPadding.strip_output(&mut vec![]),
+            Err(AesError::InvalidPadding)
+        ));
     }
 }
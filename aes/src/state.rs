@@ -0,0 +1,144 @@
+//! The AES state: a block held as a 4x4 matrix of bytes, column by column,
+//! with the row and column accessors the round steps need. It is the type
+//! every core in `AesOps` works on.
+use std::ops::{BitXor, BitXorAssign};
+
+/// The 4x4 byte state AES operates on.
+///
+/// The state is stored column by column, as FIPS-197 lays it out: byte
+/// `4c + r` of a block is row `r` of column `c`. Columns are what
+/// MixColumns and the round key words act on; rows are what ShiftRows
+/// rotates, so both have accessors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct State([[u8; 4]; 4]);
+
+impl State {
+    /// Loads a 16-byte block into the state, four bytes per column.
+    pub fn from_bytes(bytes: &[u8; 16]) -> Self {
+        let mut columns = [[0u8; 4]; 4];
+        for (column, chunk) in columns.iter_mut().zip(bytes.chunks_exact(4)) {
+            column.copy_from_slice(chunk);
+        }
+
+        State(columns)
+    }
+
+    /// Stores the state back into a 16-byte block; the inverse of `from_bytes`.
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        for (chunk, column) in bytes.chunks_exact_mut(4).zip(self.0) {
+            chunk.copy_from_slice(&column);
+        }
+
+        bytes
+    }
+
+    /// Builds the state from its four columns.
+    pub fn from_columns(columns: [[u8; 4]; 4]) -> Self {
+        State(columns)
+    }
+
+    /// Returns the four columns.
+    pub fn columns(&self) -> &[[u8; 4]; 4] {
+        &self.0
+    }
+
+    /// Returns the four columns for in-place updates.
+    pub fn columns_mut(&mut self) -> &mut [[u8; 4]; 4] {
+        &mut self.0
+    }
+
+    /// Returns column `c`.
+    pub fn column(&self, c: usize) -> [u8; 4] {
+        self.0[c]
+    }
+
+    /// Returns row `r`, i.e. byte `r` of every column.
+    pub fn row(&self, r: usize) -> [u8; 4] {
+        self.0.map(|column| column[r])
+    }
+
+    /// Overwrites row `r`.
+    pub fn set_row(&mut self, r: usize, row: [u8; 4]) {
+        for (column, byte) in self.0.iter_mut().zip(row) {
+            column[r] = byte;
+        }
+    }
+
+    /// Iterates over all 16 bytes for in-place updates, in block order.
+    pub fn bytes_mut(&mut self) -> impl Iterator<Item = &mut u8> {
+        self.0.iter_mut().flatten()
+    }
+}
+
+impl BitXor for State {
+    type Output = State;
+
+    fn bitxor(mut self, rhs: State) -> State {
+        self ^= rhs;
+        self
+    }
+}
+
+impl BitXorAssign for State {
+    fn bitxor_assign(&mut self, rhs: State) {
+        for (byte, other) in self.bytes_mut().zip(rhs.0.iter().flatten()) {
+            *byte ^= other;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+    #[test]
+    fn bytes_fill_columns() {
+        let state = State::from_bytes(&BLOCK);
+
+        assert_eq!(state.column(1), [4, 5, 6, 7]);
+        assert_eq!(state.row(1), [1, 5, 9, 13]);
+        assert_eq!(state.to_bytes(), BLOCK);
+    }
+
+    #[test]
+    fn set_row_changes_one_byte_per_column() {
+        let mut state = State::from_bytes(&BLOCK);
+        state.set_row(2, [20, 21, 22, 23]);
+
+        assert_eq!(state.row(2), [20, 21, 22, 23]);
+        assert_eq!(
+            state.to_bytes(),
+            [0, 1, 20, 3, 4, 5, 21, 7, 8, 9, 22, 11, 12, 13, 23, 15]
+        );
+    }
+
+    #[test]
+    fn xor() {
+        let a = State::from_columns([
+            [184, 3, 184, 3],
+            [186, 159, 186, 159],
+            [199, 73, 199, 73],
+            [73, 223, 73, 223],
+        ]);
+
+        let b = State::from_columns([
+            [144, 105, 242, 11],
+            [151, 108, 244, 15],
+            [52, 207, 87, 172],
+            [80, 250, 51, 153],
+        ]);
+
+        assert_eq!(
+            a ^ b,
+            State::from_columns([
+                [40, 106, 74, 8],
+                [45, 243, 78, 144,],
+                [243, 134, 144, 229],
+                [25, 37, 122, 70]
+            ])
+        );
+    }
+}
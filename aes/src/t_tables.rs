@@ -0,0 +1,183 @@
+//! A T-table AES core. SubBytes, ShiftRows and MixColumns of a round are
+//! folded into four lookups per column from 256-entry tables of 32-bit
+//! words, turning a round into 16 lookups and XORs instead of per-byte
+//! `galois_mul` calls. The tables are indexed by secret state bytes, so this
+//! core is fast but not resistant to cache-timing attacks.
+//!
+//! Columns are packed as big-endian words, row 0 in the top byte.
+use super::{
+    constants::{AES_INVERSE_S_BOX, AES_S_BOX},
+    key_schedule::KeySchedule,
+    state::State,
+};
+
+/// `TE[0][x]` is the column `(2·S[x], S[x], S[x], 3·S[x])`; `TE[i]` is that
+/// column rotated down by `i` rows.
+const TE: [[u32; 256]; 4] = tables(&AES_S_BOX, [2, 1, 1, 3]);
+
+/// `TD[0][x]` is the column `(e·Si[x], 9·Si[x], d·Si[x], b·Si[x])`; `TD[i]`
+/// is that column rotated down by `i` rows.
+const TD: [[u32; 256]; 4] = tables(&AES_INVERSE_S_BOX, [0x0e, 0x09, 0x0d, 0x0b]);
+
+const fn galois_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut p = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            p ^= a;
+        }
+        a = (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 };
+        b >>= 1;
+    }
+    p
+}
+
+const fn tables(s_box: &[u8; 256], column: [u8; 4]) -> [[u32; 256]; 4] {
+    let mut tables = [[0u32; 256]; 4];
+    let mut x = 0;
+    while x < 256 {
+        let s = s_box[x];
+        let word = u32::from_be_bytes([
+            galois_mul(column[0], s),
+            galois_mul(column[1], s),
+            galois_mul(column[2], s),
+            galois_mul(column[3], s),
+        ]);
+
+        let mut i = 0;
+        while i < 4 {
+            tables[i][x] = word.rotate_right(8 * i as u32);
+            i += 1;
+        }
+        x += 1;
+    }
+    tables
+}
+
+fn load(state: &State) -> [u32; 4] {
+    state.columns().map(u32::from_be_bytes)
+}
+
+fn store(words: [u32; 4], state: &mut State) {
+    *state = State::from_columns(words.map(u32::to_be_bytes));
+}
+
+fn round_key(keys: &KeySchedule, round: usize) -> [u32; 4] {
+    load(&keys.round_key(round))
+}
+
+/// Returns byte `row` of `word`, row 0 being the top byte.
+#[inline(always)]
+fn byte(word: u32, row: usize) -> usize {
+    (word >> (24 - 8 * row)) as usize & 0xff
+}
+
+/// Encrypts `state` in place; equivalent to the reference `AesOps::encrypt`.
+pub fn encrypt(state: &mut State, keys: &KeySchedule) {
+    let rounds = keys.rounds as usize;
+    let mut s = load(state);
+    for (word, rk) in s.iter_mut().zip(round_key(keys, 0)) {
+        *word ^= rk;
+    }
+
+    for round in 1..rounds {
+        let rk = round_key(keys, round);
+        s = core::array::from_fn(|c| {
+            TE[0][byte(s[c], 0)]
+                ^ TE[1][byte(s[(c + 1) % 4], 1)]
+                ^ TE[2][byte(s[(c + 2) % 4], 2)]
+                ^ TE[3][byte(s[(c + 3) % 4], 3)]
+                ^ rk[c]
+        });
+    }
+
+    // Final round without MixColumns
+    let rk = round_key(keys, rounds);
+    s = core::array::from_fn(|c| {
+        u32::from_be_bytes([
+            AES_S_BOX[byte(s[c], 0)],
+            AES_S_BOX[byte(s[(c + 1) % 4], 1)],
+            AES_S_BOX[byte(s[(c + 2) % 4], 2)],
+            AES_S_BOX[byte(s[(c + 3) % 4], 3)],
+        ]) ^ rk[c]
+    });
+
+    store(s, state);
+}
+
+/// Decrypts `state` in place with the equivalent inverse cipher (FIPS-197
+/// §5.3.5), whose middle round keys pass through InvMixColumns first.
+pub fn decrypt(state: &mut State, keys: &KeySchedule) {
+    let rounds = keys.rounds as usize;
+    let mut s = load(state);
+    for (word, rk) in s.iter_mut().zip(round_key(keys, rounds)) {
+        *word ^= rk;
+    }
+
+    for round in (1..rounds).rev() {
+        let rk = round_key(keys, round).map(inv_mix_column);
+        s = core::array::from_fn(|c| {
+            TD[0][byte(s[c], 0)]
+                ^ TD[1][byte(s[(c + 3) % 4], 1)]
+                ^ TD[2][byte(s[(c + 2) % 4], 2)]
+                ^ TD[3][byte(s[(c + 1) % 4], 3)]
+                ^ rk[c]
+        });
+    }
+
+    // Final round without InvMixColumns
+    let rk = round_key(keys, 0);
+    s = core::array::from_fn(|c| {
+        u32::from_be_bytes([
+            AES_INVERSE_S_BOX[byte(s[c], 0)],
+            AES_INVERSE_S_BOX[byte(s[(c + 3) % 4], 1)],
+            AES_INVERSE_S_BOX[byte(s[(c + 2) % 4], 2)],
+            AES_INVERSE_S_BOX[byte(s[(c + 1) % 4], 3)],
+        ]) ^ rk[c]
+    });
+
+    store(s, state);
+}
+
+/// Applies InvMixColumns to a single column, using `TD[i][S[x]]`, which
+/// cancels the inverse S-box folded into the table.
+fn inv_mix_column(word: u32) -> u32 {
+    TD[0][AES_S_BOX[byte(word, 0)] as usize]
+        ^ TD[1][AES_S_BOX[byte(word, 1)] as usize]
+        ^ TD[2][AES_S_BOX[byte(word, 2)] as usize]
+        ^ TD[3][AES_S_BOX[byte(word, 3)] as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes_ops::AesOps;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn matches_reference_core() {
+        let mut rng = StdRng::seed_from_u64(3766);
+
+        for key_len in [16, 24, 32] {
+            for _ in 0..50 {
+                let key: Vec<u8> = (0..key_len).map(|_| rng.gen()).collect();
+                let keys = KeySchedule::new(&key).unwrap();
+                let block = State::from_columns(rng.gen());
+
+                let mut expected = block;
+                AesOps::reference_encrypt(&mut expected, &keys);
+                let mut state = block;
+                encrypt(&mut state, &keys);
+                assert_eq!(state, expected);
+
+                decrypt(&mut state, &keys);
+                assert_eq!(state, block);
+
+                let mut expected = block;
+                AesOps::reference_decrypt(&mut expected, &keys);
+                let mut state = block;
+                decrypt(&mut state, &keys);
+                assert_eq!(state, expected);
+            }
+        }
+    }
+}
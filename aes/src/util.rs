@@ -1,57 +1,3 @@
-/// Generates a 4x4 matrix from an array of 16 bytes
-pub fn gen_matrix(bytes: &[u8; 16]) -> [[u8; 4]; 4] {
-    let mut matrix = [[0; 4]; 4];
-
-    for (i, chunk) in bytes.chunks(4).enumerate() {
-        for (j, &byte) in chunk.iter().enumerate() {
-            matrix[i][j] = byte;
-        }
-    }
-
-    matrix
-}
-
-/// Converts a byte vector into a vector of 4x4 byte matrices.
-///
-/// The input vector must have a length that is a multiple of 16.
-/// Panics if the length requirement is not met.
-///
-/// # Arguments
-/// * `bytes` - A reference to a vector of bytes (`Vec<u8>`).
-///
-/// # Returns
-/// A `Vec<[[u8; 4]; 4]>` where each element is a 4x4 byte matrix.
-pub fn chunk_bytes_into_4x4_matrices(bytes: &Vec<u8>) -> Vec<[[u8; 4]; 4]> {
-    if bytes.len() % 16 != 0 {
-        panic!("Input not a multiple of 16");
-    }
-
-    let mut buffer: Vec<[[u8; 4]; 4]> = Vec::with_capacity(bytes.len() / 16);
-
-    for chunk in bytes.chunks(16) {
-        let mut matrix = [[0u8; 4]; 4];
-        for (i, row) in chunk.chunks(4).enumerate() {
-            matrix[i] = row.try_into().expect("Slice with incorrect length");
-        }
-        buffer.push(matrix);
-    }
-
-    buffer
-}
-
-/// Performs element-wise XOR operation on two 4x4 state matrices.
-/// Returns a new 4x4 matrix resulting from the XOR of `a` and `b`.
-pub fn xor_matrices(a: [[u8; 4]; 4], b: [[u8; 4]; 4]) -> [[u8; 4]; 4] {
-    let mut new_state = [[0; 4]; 4];
-    for (i, (row_a, row_b)) in a.iter().zip(b.iter()).enumerate() {
-        for (j, (&val_a, &val_b)) in row_a.iter().zip(row_b.iter()).enumerate() {
-            new_state[i][j] = val_a ^ val_b;
-        }
-    }
-
-    new_state
-}
-
 #[inline]
 pub fn rotate_left(matrix: &[u8; 4], n: usize) -> [u8; 4] {
     let n = n % matrix.len(); // Skip redundant rotations.
@@ -70,7 +16,7 @@ pub fn galois_mul(mut a: u8, mut b: u8) -> u8 {
     let m: u8 = 0x1B; // The irreducible polynomial x^8 + x^4 + x^3 + x + 1, used for modular reduction.
 
     // Iterate over each bit of `b`.
-    for i in 0..8 {
+    for _ in 0..8 {
         // Check if the i-th bit of `b` is set.
         if b & 0x1 != 0 {
             // If the i-th bit of `b` is set, XOR `a` with `p`.
@@ -96,39 +42,19 @@ pub fn galois_mul(mut a: u8, mut b: u8) -> u8 {
     p
 }
 
+/// Decodes a hex string into bytes, for test vectors.
+#[cfg(test)]
+pub fn decode_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_xor_matrices() {
-        let a: [[u8; 4]; 4] = [
-            [184, 3, 184, 3],
-            [186, 159, 186, 159],
-            [199, 73, 199, 73],
-            [73, 223, 73, 223],
-        ];
-
-        let b: [[u8; 4]; 4] = [
-            [144, 105, 242, 11],
-            [151, 108, 244, 15],
-            [52, 207, 87, 172],
-            [80, 250, 51, 153],
-        ];
-
-        let result = xor_matrices(a, b);
-
-        assert_eq!(
-            result,
-            [
-                [40, 106, 74, 8],
-                [45, 243, 78, 144,],
-                [243, 134, 144, 229],
-                [25, 37, 122, 70]
-            ]
-        );
-    }
-
     #[test]
     fn test_rotate_left() {
         let result = rotate_left(&[1, 2, 3, 4], 3);
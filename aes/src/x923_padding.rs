@@ -0,0 +1,110 @@
+use super::{definitions::PaddingProcessor, error::AesError};
+
+/// ANSI X9.23 padding mode
+///
+/// This struct implements the ANSI X9.23 padding scheme: the plaintext is
+/// filled with zero bytes, and the last byte holds the number of padding bytes.
+#[derive(Clone, Copy)]
+pub struct X923Padding;
+
+impl PaddingProcessor for X923Padding {
+    /// Writes ANSI X9.23 padding after the message.
+    ///
+    /// Between 1 and `block_size` bytes are written: zeros, followed by a
+    /// byte holding the number of padding bytes.
+    ///
+    /// # Arguments
+    /// * `buffer` - The message followed by room for the padding.
+    /// * `message_len` - The length of the message at the start of `buffer`.
+    /// * `block_size` - The block size of the cipher in bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::BufferTooSmall` if the padding does not fit.
+    fn pad_in_place(
+        &self,
+        buffer: &mut [u8],
+        message_len: usize,
+        block_size: usize,
+    ) -> Result<usize, AesError> {
+        let pad_size = block_size - (message_len % block_size);
+        let padding = buffer
+            .get_mut(message_len..message_len + pad_size)
+            .ok_or(AesError::BufferTooSmall)?;
+        padding.fill(0);
+        padding[pad_size - 1] = pad_size as u8;
+
+        Ok(message_len + pad_size)
+    }
+
+    /// Validates ANSI X9.23 padding and returns the length without it.
+    ///
+    /// # Arguments
+    /// * `buffer` - The padded plaintext.
+    /// * `block_size` - The block size of the cipher in bytes.
+    ///
+    /// # Errors
+    /// Returns `AesError::InvalidPadding` if the buffer is empty, its length is
+    /// not a multiple of `block_size`, the length byte is out of range, or the
+    /// fill bytes are not zero.
+    fn unpadded_len(&self, buffer: &[u8], block_size: usize) -> Result<usize, AesError> {
+        if buffer.is_empty() || !buffer.len().is_multiple_of(block_size) {
+            return Err(AesError::InvalidPadding);
+        }
+
+        let pad_size = buffer[buffer.len() - 1] as usize;
+        if pad_size > block_size || pad_size == 0 {
+            return Err(AesError::InvalidPadding);
+        }
+
+        let fill = &buffer[buffer.len() - pad_size..buffer.len() - 1];
+        if fill.iter().any(|&byte| byte != 0) {
+            return Err(AesError::InvalidPadding);
+        }
+
+        Ok(buffer.len() - pad_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad_input() {
+        let mut input = vec![10; 10];
+        X923Padding.pad_input(&mut input).unwrap();
+
+        let mut expected = vec![10; 10];
+        expected.extend([0, 0, 0, 0, 0, 6]);
+        assert_eq!(input, expected);
+
+        let mut input = vec![10; 16];
+        X923Padding.pad_input(&mut input).unwrap();
+        assert_eq!(input.len(), 32);
+        assert_eq!(input[31], 16);
+    }
+
+    #[test]
+    fn test_strip_output() {
+        for len in 0..=32 {
+            let mut input = vec![0xab; len];
+            X923Padding.pad_input(&mut input).unwrap();
+            X923Padding.strip_output(&mut input).unwrap();
+            assert_eq!(input, vec![0xab; len]);
+        }
+    }
+
+    #[test]
+    fn test_strip_output_rejects_malformed_padding() {
+        let mut nonzero_fill = vec![0; 16];
+        nonzero_fill[13] = 1;
+        nonzero_fill[15] = 3;
+
+        for mut output in [vec![], vec![1; 15], vec![0; 16], vec![17; 16], nonzero_fill] {
+            assert!(matches!(
+                X923Padding.strip_output(&mut output),
+                Err(AesError::InvalidPadding)
+            ));
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! BLAKE2b: 64-bit words, 128-byte blocks, 12 rounds and digests of up to
+//! 64 bytes.
+use std::io::{self, Write};
+
+use utils::mac::Mac;
+
+use crate::{Blake2Error, MIX, SIGMA};
+
+const BLOCK_SIZE: usize = 128;
+
+/// The largest digest and key sizes in bytes.
+pub const MAX_OUTPUT_SIZE: usize = 64;
+pub const MAX_KEY_SIZE: usize = 64;
+
+const ROUNDS: usize = 12;
+
+/// The initialization vector, shared with SHA-512.
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// An incremental BLAKE2b computation.
+#[derive(Clone, Debug)]
+pub struct Blake2b {
+    h: [u64; 8],
+    buffer: [u8; BLOCK_SIZE],
+    buffer_len: usize,
+    /// The number of bytes compressed so far.
+    counter: u128,
+    output_size: usize,
+}
+
+impl Blake2b {
+    /// Creates an unkeyed hasher producing `output_size`-byte digests.
+    ///
+    /// # Arguments
+    /// * `output_size` - The digest length, 1 to 64 bytes.
+    pub fn new(output_size: usize) -> Result<Self, Blake2Error> {
+        Self::new_keyed(&[], output_size)
+    }
+
+    /// Creates a keyed hasher, a MAC, producing `output_size`-byte tags.
+    ///
+    /// # Arguments
+    /// * `key` - The secret key, at most 64 bytes. An empty key is unkeyed hashing.
+    /// * `output_size` - The tag length, 1 to 64 bytes.
+    ///
+    /// # Errors
+    /// Returns `Blake2Error::InvalidOutputLength` or `Blake2Error::InvalidKeyLength`
+    /// for sizes outside those ranges.
+    pub fn new_keyed(key: &[u8], output_size: usize) -> Result<Self, Blake2Error> {
+        if !(1..=MAX_OUTPUT_SIZE).contains(&output_size) {
+            return Err(Blake2Error::InvalidOutputLength(output_size));
+        }
+        if key.len() > MAX_KEY_SIZE {
+            return Err(Blake2Error::InvalidKeyLength(key.len()));
+        }
+
+        // Parameter block: digest length, key length, fanout 1 and depth 1.
+        let mut h = IV;
+        h[0] ^= 0x01010000 ^ ((key.len() as u64) << 8) ^ output_size as u64;
+
+        let mut hasher = Blake2b {
+            h,
+            buffer: [0u8; BLOCK_SIZE],
+            buffer_len: 0,
+            counter: 0,
+            output_size,
+        };
+
+        if !key.is_empty() {
+            hasher.buffer[..key.len()].copy_from_slice(key);
+            hasher.buffer_len = BLOCK_SIZE;
+        }
+
+        Ok(hasher)
+    }
+
+    /// Absorbs `data` into the hash computation.
+    ///
+    /// # Arguments
+    /// * `data` - The next chunk of the message.
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // The final block must be compressed with the last-block flag,
+            // so a full buffer waits until more data arrives.
+            if self.buffer_len == BLOCK_SIZE {
+                self.counter += BLOCK_SIZE as u128;
+                let block = self.buffer;
+                self.compress(&block, false);
+                self.buffer_len = 0;
+            }
+
+            let take = (BLOCK_SIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+        }
+    }
+
+    /// Compresses the final block and returns the digest.
+    pub fn finalize(mut self) -> Vec<u8> {
+        self.counter += self.buffer_len as u128;
+        self.buffer[self.buffer_len..].fill(0);
+        let block = self.buffer;
+        self.compress(&block, true);
+
+        self.h
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(self.output_size)
+            .collect()
+    }
+
+    fn compress(&mut self, block: &[u8; BLOCK_SIZE], last: bool) {
+        let mut m = [0u64; 16];
+        for (word, bytes) in m.iter_mut().zip(block.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.counter as u64;
+        v[13] ^= (self.counter >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        for round in 0..ROUNDS {
+            let s = &SIGMA[round % 10];
+            for (i, &[a, b, c, d]) in MIX.iter().enumerate() {
+                g(&mut v, [a, b, c, d], m[s[2 * i]], m[s[2 * i + 1]]);
+            }
+        }
+
+        for (i, h) in self.h.iter_mut().enumerate() {
+            *h ^= v[i] ^ v[i + 8];
+        }
+    }
+}
+
+/// The mixing function `G` (RFC 7693 §3.1).
+fn g(v: &mut [u64; 16], [a, b, c, d]: [usize; 4], x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+impl Mac for Blake2b {
+    fn update(&mut self, data: &[u8]) {
+        Blake2b::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Blake2b::finalize(self)
+    }
+}
+
+/// Feeding a `Blake2b` through `Write` lets `io::copy` hash a file or socket
+/// in constant memory.
+impl Write for Blake2b {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Computes the BLAKE2b digest of `message` in one shot.
+///
+/// # Arguments
+/// * `message` - The bytes to hash.
+/// * `output_size` - The digest length, 1 to 64 bytes.
+pub fn hash(message: &[u8], output_size: usize) -> Result<Vec<u8>, Blake2Error> {
+    let mut hasher = Blake2b::new(output_size)?;
+    hasher.update(message);
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selftest_seq;
+
+    #[test]
+    fn rfc7693_abc() {
+        // RFC 7693, appendix A.
+        assert_eq!(
+            hex::encode(hash(b"abc", 64).unwrap()),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+    }
+
+    #[test]
+    fn rfc7693_selftest() {
+        // RFC 7693, appendix E: hashes every combination of digest length
+        // and input length, unkeyed and keyed, into one BLAKE2b-256 digest.
+        let mut ctx = Blake2b::new(32).unwrap();
+
+        for output_size in [20, 32, 48, 64] {
+            for input_len in [0, 3, 128, 129, 255, 1024] {
+                let input = selftest_seq(input_len, input_len as u32);
+                ctx.update(&hash(&input, output_size).unwrap());
+
+                let key = selftest_seq(output_size, output_size as u32);
+                let mut keyed = Blake2b::new_keyed(&key, output_size).unwrap();
+                keyed.update(&input);
+                ctx.update(&keyed.finalize());
+            }
+        }
+
+        assert_eq!(
+            hex::encode(ctx.finalize()),
+            "c23a7800d98123bd10f506c61e29da5603d763b8bbad2e737f5e765a7bccd475"
+        );
+    }
+
+    #[test]
+    fn keyed_mode() {
+        // The first keyed answer of the BLAKE2 reference test suite: the key
+        // 00 01 .. 3f over the empty message.
+        let key: Vec<u8> = (0..64).collect();
+        assert_eq!(
+            hex::encode(Blake2b::new_keyed(&key, 64).unwrap().finalize()),
+            "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786\
+             b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568"
+        );
+
+        utils::mac::check_mac(|| Blake2b::new_keyed(&key, 32).unwrap());
+    }
+
+    #[test]
+    fn hashes_through_io_copy() {
+        let message: Vec<u8> = (0..500u32).map(|i| (i * 7) as u8).collect();
+
+        let mut hasher = Blake2b::new(64).unwrap();
+        io::copy(&mut message.as_slice(), &mut hasher).unwrap();
+        assert_eq!(hasher.finalize(), hash(&message, 64).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        assert_eq!(
+            Blake2b::new(0).unwrap_err(),
+            Blake2Error::InvalidOutputLength(0)
+        );
+        assert_eq!(
+            Blake2b::new(65).unwrap_err(),
+            Blake2Error::InvalidOutputLength(65)
+        );
+        assert_eq!(
+            Blake2b::new_keyed(&[0; 65], 64).unwrap_err(),
+            Blake2Error::InvalidKeyLength(65)
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! BLAKE2s: 32-bit words, 64-byte blocks, 10 rounds and digests of up to
+//! 32 bytes.
+use std::io::{self, Write};
+
+use utils::mac::Mac;
+
+use crate::{Blake2Error, MIX, SIGMA};
+
+const BLOCK_SIZE: usize = 64;
+
+/// The largest digest and key sizes in bytes.
+pub const MAX_OUTPUT_SIZE: usize = 32;
+pub const MAX_KEY_SIZE: usize = 32;
+
+const ROUNDS: usize = 10;
+
+/// The initialization vector, shared with SHA-256.
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// An incremental BLAKE2s computation.
+#[derive(Clone, Debug)]
+pub struct Blake2s {
+    h: [u32; 8],
+    buffer: [u8; BLOCK_SIZE],
+    buffer_len: usize,
+    /// The number of bytes compressed so far.
+    counter: u64,
+    output_size: usize,
+}
+
+impl Blake2s {
+    /// Creates an unkeyed hasher producing `output_size`-byte digests.
+    ///
+    /// # Arguments
+    /// * `output_size` - The digest length, 1 to 32 bytes.
+    pub fn new(output_size: usize) -> Result<Self, Blake2Error> {
+        Self::new_keyed(&[], output_size)
+    }
+
+    /// Creates a keyed hasher, a MAC, producing `output_size`-byte tags.
+    ///
+    /// # Arguments
+    /// * `key` - The secret key, at most 32 bytes. An empty key is unkeyed hashing.
+    /// * `output_size` - The tag length, 1 to 32 bytes.
+    ///
+    /// # Errors
+    /// Returns `Blake2Error::InvalidOutputLength` or `Blake2Error::InvalidKeyLength`
+    /// for sizes outside those ranges.
+    pub fn new_keyed(key: &[u8], output_size: usize) -> Result<Self, Blake2Error> {
+        if !(1..=MAX_OUTPUT_SIZE).contains(&output_size) {
+            return Err(Blake2Error::InvalidOutputLength(output_size));
+        }
+        if key.len() > MAX_KEY_SIZE {
+            return Err(Blake2Error::InvalidKeyLength(key.len()));
+        }
+
+        // Parameter block: digest length, key length, fanout 1 and depth 1.
+        let mut h = IV;
+        h[0] ^= 0x01010000 ^ ((key.len() as u32) << 8) ^ output_size as u32;
+
+        let mut hasher = Blake2s {
+            h,
+            buffer: [0u8; BLOCK_SIZE],
+            buffer_len: 0,
+            counter: 0,
+            output_size,
+        };
+
+        if !key.is_empty() {
+            hasher.buffer[..key.len()].copy_from_slice(key);
+            hasher.buffer_len = BLOCK_SIZE;
+        }
+
+        Ok(hasher)
+    }
+
+    /// Absorbs `data` into the hash computation.
+    ///
+    /// # Arguments
+    /// * `data` - The next chunk of the message.
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // The final block must be compressed with the last-block flag,
+            // so a full buffer waits until more data arrives.
+            if self.buffer_len == BLOCK_SIZE {
+                self.counter += BLOCK_SIZE as u64;
+                let block = self.buffer;
+                self.compress(&block, false);
+                self.buffer_len = 0;
+            }
+
+            let take = (BLOCK_SIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+        }
+    }
+
+    /// Compresses the final block and returns the digest.
+    pub fn finalize(mut self) -> Vec<u8> {
+        self.counter += self.buffer_len as u64;
+        self.buffer[self.buffer_len..].fill(0);
+        let block = self.buffer;
+        self.compress(&block, true);
+
+        self.h
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(self.output_size)
+            .collect()
+    }
+
+    fn compress(&mut self, block: &[u8; BLOCK_SIZE], last: bool) {
+        let mut m = [0u32; 16];
+        for (word, bytes) in m.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+
+        let mut v = [0u32; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.counter as u32;
+        v[13] ^= (self.counter >> 32) as u32;
+        if last {
+            v[14] = !v[14];
+        }
+
+        for s in SIGMA.iter().take(ROUNDS) {
+            for (i, &[a, b, c, d]) in MIX.iter().enumerate() {
+                g(&mut v, [a, b, c, d], m[s[2 * i]], m[s[2 * i + 1]]);
+            }
+        }
+
+        for (i, h) in self.h.iter_mut().enumerate() {
+            *h ^= v[i] ^ v[i + 8];
+        }
+    }
+}
+
+/// The mixing function `G` (RFC 7693 §3.1).
+fn g(v: &mut [u32; 16], [a, b, c, d]: [usize; 4], x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+impl Mac for Blake2s {
+    fn update(&mut self, data: &[u8]) {
+        Blake2s::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Blake2s::finalize(self)
+    }
+}
+
+/// Feeding a `Blake2s` through `Write` lets `io::copy` hash a file or socket
+/// in constant memory.
+impl Write for Blake2s {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Computes the BLAKE2s digest of `message` in one shot.
+///
+/// # Arguments
+/// * `message` - The bytes to hash.
+/// * `output_size` - The digest length, 1 to 32 bytes.
+pub fn hash(message: &[u8], output_size: usize) -> Result<Vec<u8>, Blake2Error> {
+    let mut hasher = Blake2s::new(output_size)?;
+    hasher.update(message);
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selftest_seq;
+
+    #[test]
+    fn rfc7693_abc() {
+        // RFC 7693, appendix B.
+        assert_eq!(
+            hex::encode(hash(b"abc", 32).unwrap()),
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"
+        );
+    }
+
+    #[test]
+    fn rfc7693_selftest() {
+        // RFC 7693, appendix E, with BLAKE2s's digest and block sizes.
+        let mut ctx = Blake2s::new(32).unwrap();
+
+        for output_size in [16, 20, 28, 32] {
+            for input_len in [0, 3, 64, 65, 255, 1024] {
+                let input = selftest_seq(input_len, input_len as u32);
+                ctx.update(&hash(&input, output_size).unwrap());
+
+                let key = selftest_seq(output_size, output_size as u32);
+                let mut keyed = Blake2s::new_keyed(&key, output_size).unwrap();
+                keyed.update(&input);
+                ctx.update(&keyed.finalize());
+            }
+        }
+
+        assert_eq!(
+            hex::encode(ctx.finalize()),
+            "6a411f08ce25adcdfb02aba641451cec53c598b24f4fc787fbdc88797f4c1dfe"
+        );
+    }
+
+    #[test]
+    fn keyed_mode() {
+        // The key 00 01 .. 1f over the empty message, from the BLAKE2
+        // reference test suite.
+        let key: Vec<u8> = (0..32).collect();
+        assert_eq!(
+            hex::encode(Blake2s::new_keyed(&key, 32).unwrap().finalize()),
+            "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49"
+        );
+
+        utils::mac::check_mac(|| Blake2s::new_keyed(&key, 16).unwrap());
+    }
+
+    #[test]
+    fn hashes_through_io_copy() {
+        let message: Vec<u8> = (0..500u32).map(|i| (i * 7) as u8).collect();
+
+        let mut hasher = Blake2s::new(32).unwrap();
+        io::copy(&mut message.as_slice(), &mut hasher).unwrap();
+        assert_eq!(hasher.finalize(), hash(&message, 32).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        assert_eq!(
+            Blake2s::new(33).unwrap_err(),
+            Blake2Error::InvalidOutputLength(33)
+        );
+        assert_eq!(
+            Blake2s::new_keyed(&[0; 33], 32).unwrap_err(),
+            Blake2Error::InvalidKeyLength(33)
+        );
+    }
+}
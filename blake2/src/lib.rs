@@ -0,0 +1,67 @@
+//! This crate implements the BLAKE2 hash functions (RFC 7693).
+//!
+//! BLAKE2b works on 64-bit words and produces digests of 1 to 64 bytes;
+//! BLAKE2s works on 32-bit words for smaller platforms and produces 1 to 32
+//! bytes. Both take an optional key, which turns them into a MAC without the
+//! HMAC construction: the key is padded to a full block and hashed first.
+pub mod blake2b;
+pub mod blake2s;
+
+use thiserror::Error;
+
+pub use blake2b::Blake2b;
+pub use blake2s::Blake2s;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Blake2Error {
+    #[error("Invalid digest length of `{0}` bytes")]
+    InvalidOutputLength(usize),
+
+    #[error("Invalid key length of `{0}` bytes")]
+    InvalidKeyLength(usize),
+}
+
+/// The message word permutations, one per round; BLAKE2b's rounds 10 and 11
+/// reuse the first two (RFC 7693 §2.7).
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The state words each of the eight `G` calls of a round mixes: four
+/// columns, then four diagonals.
+const MIX: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// Generates the RFC 7693 appendix E test input: `len` bytes of a
+/// Fibonacci-like sequence seeded with `seed`.
+#[cfg(test)]
+fn selftest_seq(len: usize, seed: u32) -> Vec<u8> {
+    let mut a = 0xdead4badu32.wrapping_mul(seed);
+    let mut b = 1u32;
+
+    (0..len)
+        .map(|_| {
+            let t = a.wrapping_add(b);
+            a = b;
+            b = t;
+            (t >> 24) as u8
+        })
+        .collect()
+}
@@ -0,0 +1,11 @@
+use sha_256::hkdf::HkdfError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DhError {
+    #[error("The peer's public key must lie in (1, p - 1)")]
+    InvalidPublicKey,
+
+    #[error("Key derivation failed: {0}")]
+    KeyDerivation(#[from] HkdfError),
+}
@@ -1,10 +1,21 @@
+mod error;
+
+use std::fmt;
+
 use num_bigint::{BigUint, RandBigInt};
-use num_traits::Num;
+use num_traits::{Num, One};
+use sha_256::hkdf;
+use utils::{encoding::to_bytes_be_padded, wipe::wipe_biguint};
+use zeroize::Zeroize;
+
+pub use error::DhError;
 
 // safe prime in RFC3526 https://datatracker.ietf.org/doc/rfc3526/
 const SAFE_PRIME_HEX: &str = "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF";
 
-#[derive(Debug, Clone)]
+/// A Diffie-Hellman party. The private key is wiped when it is dropped, and
+/// `Debug` redacts it.
+#[derive(Clone)]
 pub struct SimpleDiffieHellman {
     // secret private key
     pk: BigUint,
@@ -59,17 +70,87 @@ impl SimpleDiffieHellman {
     pub fn calculate_shared_secret(&self, public_key: &BigUint) -> BigUint {
         public_key.modpow(&self.pk, &self.p)
     }
+
+    /// Derives symmetric key material from the shared secret with HKDF-SHA256.
+    ///
+    /// The raw shared secret is a group element, not a uniformly random key,
+    /// so it is encoded big-endian at the byte length of `p` and run through
+    /// HKDF before use.
+    ///
+    /// The peer's public key is checked first: 0, 1 and `p - 1` (and anything
+    /// outside the group) would force the shared secret to a value an
+    /// attacker knows without the private key.
+    ///
+    /// # Arguments
+    /// * `public_key` - The other party's public key.
+    /// * `salt` - An optional non-secret salt, e.g. both parties' nonces.
+    /// * `info` - Context binding the key to its purpose, e.g. `b"session key"`.
+    /// * `out` - The buffer to fill with key material.
+    ///
+    /// # Errors
+    /// Returns `DhError::InvalidPublicKey` unless `1 < public_key < p - 1`,
+    /// and `DhError::KeyDerivation` if `out` exceeds the HKDF output limit.
+    pub fn derive_key(
+        &self,
+        public_key: &BigUint,
+        salt: &[u8],
+        info: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), DhError> {
+        if public_key <= &BigUint::one() || public_key >= &(&self.p - 1u32) {
+            return Err(DhError::InvalidPublicKey);
+        }
+
+        let mut shared = self.calculate_shared_secret(public_key);
+        let width = self.p.bits().div_ceil(8) as usize;
+        let mut ikm = to_bytes_be_padded(&shared, width).expect("shared secret is below p");
+
+        let result = hkdf::hkdf(salt, &ikm, info, out);
+        wipe_biguint(&mut shared);
+        ikm.zeroize();
+
+        Ok(result?)
+    }
+}
+
+impl Zeroize for SimpleDiffieHellman {
+    /// Wipes the private key; the group parameters are public and kept.
+    fn zeroize(&mut self) {
+        wipe_biguint(&mut self.pk);
+    }
+}
+
+impl fmt::Debug for SimpleDiffieHellman {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimpleDiffieHellman")
+            .field("pk", &"<redacted>")
+            .field("g", &self.g)
+            .field("p", &self.p)
+            .finish()
+    }
+}
+
+impl Drop for SimpleDiffieHellman {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sha_256::hkdf::HkdfError;
+    use utils::wipe::{biguint_pattern, freed_with, WatchingAllocator};
+
+    #[global_allocator]
+    static ALLOCATOR: WatchingAllocator = WatchingAllocator;
+    use num_traits::Zero;
 
     #[test]
     fn test_simple_diffie_hellman() {
         let g = BigUint::from(2u64);
 
-        let (_sophie_prime, safe_prime) =
+        let (safe_prime, _sophie_prime) =
             SimpleDiffieHellman::generate_safe_prime_and_sophie_prime();
 
         let alice = SimpleDiffieHellman::new(g, safe_prime);
@@ -86,4 +167,96 @@ mod tests {
 
         assert!(alice_version_of_shared_secret.eq(&bob_version_of_shared_secret));
     }
+
+    #[test]
+    fn derived_keys_agree() {
+        let (safe_prime, _sophie_prime) =
+            SimpleDiffieHellman::generate_safe_prime_and_sophie_prime();
+        let alice = SimpleDiffieHellman::new(BigUint::from(2u64), safe_prime.clone());
+        let bob = SimpleDiffieHellman::new(BigUint::from(2u64), safe_prime);
+
+        let mut alice_key = [0u8; 32];
+        alice
+            .derive_key(
+                &bob.gen_public_key(),
+                b"salt",
+                b"session key",
+                &mut alice_key,
+            )
+            .unwrap();
+
+        let mut bob_key = [0u8; 32];
+        bob.derive_key(
+            &alice.gen_public_key(),
+            b"salt",
+            b"session key",
+            &mut bob_key,
+        )
+        .unwrap();
+        assert_eq!(alice_key, bob_key);
+
+        // Different contexts yield unrelated keys.
+        let mut other_key = [0u8; 32];
+        bob.derive_key(&alice.gen_public_key(), b"salt", b"mac key", &mut other_key)
+            .unwrap();
+        assert_ne!(bob_key, other_key);
+
+        assert_eq!(
+            bob.derive_key(&alice.gen_public_key(), b"", b"", &mut [0u8; 255 * 32 + 1]),
+            Err(DhError::KeyDerivation(HkdfError::OutputTooLong(
+                255 * 32 + 1
+            )))
+        );
+    }
+
+    #[test]
+    fn derive_key_rejects_degenerate_public_keys() {
+        let (safe_prime, _sophie_prime) =
+            SimpleDiffieHellman::generate_safe_prime_and_sophie_prime();
+        let party = SimpleDiffieHellman::new(BigUint::from(2u64), safe_prime.clone());
+
+        for public_key in [
+            BigUint::zero(),
+            BigUint::one(),
+            &safe_prime - 1u32,
+            safe_prime.clone(),
+            &safe_prime + 1u32,
+        ] {
+            assert_eq!(
+                party.derive_key(&public_key, b"salt", b"session key", &mut [0u8; 32]),
+                Err(DhError::InvalidPublicKey)
+            );
+        }
+
+        assert!(party
+            .derive_key(
+                &BigUint::from(2u64),
+                b"salt",
+                b"session key",
+                &mut [0u8; 32]
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn zeroize_clears_private_key() {
+        let (safe_prime, _sophie_prime) =
+            SimpleDiffieHellman::generate_safe_prime_and_sophie_prime();
+        let mut party = SimpleDiffieHellman::new(BigUint::from(2u64), safe_prime);
+        assert!(!party.pk.is_zero());
+
+        party.zeroize();
+        assert!(party.pk.is_zero());
+    }
+
+    #[test]
+    fn drop_wipes_private_key() {
+        let (safe_prime, _sophie_prime) =
+            SimpleDiffieHellman::generate_safe_prime_and_sophie_prime();
+        let party = SimpleDiffieHellman::new(BigUint::from(2u64), safe_prime);
+        let pattern = biguint_pattern(&party.pk);
+
+        assert!(!format!("{party:?}").contains(&party.pk.to_string()));
+        assert!(!freed_with(&pattern, || drop(party)));
+    }
 }
@@ -1,11 +1,12 @@
 use num_bigint::BigInt;
+use num_traits::{One, Zero};
 
 // A tuple struct representing a point with two BigUint coordinates (x, y).
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub struct Point(pub BigInt, pub BigInt);
 
 /// Represents a point on an elliptic curve.
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub enum EccPoint {
     // A point with finite coordinates represented by a `Point` tuple struct.
     Finite(Point),
@@ -13,10 +14,52 @@ pub enum EccPoint {
     Infinity,
 }
 
+/// A point in Jacobian projective coordinates: `(X, Y, Z)` stands for the
+/// affine point `(X / Z^2, Y / Z^3)`, and any point with `Z = 0` is the point
+/// at infinity. Adding and doubling in this form need no field inversion, so
+/// a scalar multiplication pays for a single inversion when converting back.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct JacobianPoint {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub z: BigInt,
+}
+
+impl JacobianPoint {
+    /// Returns the point at infinity, `(1, 1, 0)`.
+    pub fn infinity() -> Self {
+        Self {
+            x: BigInt::one(),
+            y: BigInt::one(),
+            z: BigInt::zero(),
+        }
+    }
+
+    /// Returns `true` if the point is the point at infinity.
+    pub fn is_infinity(&self) -> bool {
+        self.z.is_zero()
+    }
+}
+
+impl From<&EccPoint> for JacobianPoint {
+    /// Lifts an affine point to Jacobian coordinates with `Z = 1`.
+    fn from(ecc_point: &EccPoint) -> Self {
+        match ecc_point {
+            EccPoint::Finite(point) => Self {
+                x: point.0.clone(),
+                y: point.1.clone(),
+                z: BigInt::one(),
+            },
+            EccPoint::Infinity => Self::infinity(),
+        }
+    }
+}
+
 /// Represents the supported elliptic curves.
 ///
 /// # Variants
 /// * `Secp256k1` - Represents the secp256k1 curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Curve {
     Secp256k1,
 }
@@ -28,4 +71,16 @@ pub trait EllipticCurve {
 
     // Doubles a point on the elliptic curve.
     fn double_point(&self, a: &EccPoint) -> EccPoint;
+
+    // Checks whether a point satisfies the curve equation; the point at infinity always does.
+    fn is_on_curve(&self, p: &EccPoint) -> bool;
+
+    // Adds two points given in Jacobian coordinates, without a field inversion.
+    fn add_jacobian(&self, a: &JacobianPoint, b: &JacobianPoint) -> JacobianPoint;
+
+    // Doubles a point given in Jacobian coordinates, without a field inversion.
+    fn double_jacobian(&self, a: &JacobianPoint) -> JacobianPoint;
+
+    // Converts a Jacobian point back to affine coordinates reduced modulo the field prime.
+    fn to_affine(&self, a: &JacobianPoint) -> EccPoint;
 }
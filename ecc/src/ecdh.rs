@@ -0,0 +1,108 @@
+//! This module implements elliptic curve Diffie-Hellman key agreement
+//! (SEC 1 §3.3.1): both parties multiply the peer's public key by their own
+//! secret key and arrive at the same point, whose x-coordinate is the shared secret.
+use num_bigint::{BigInt, BigUint};
+use num_traits::Zero;
+
+use crate::definitions::{Curve, EccPoint, EllipticCurve, Point};
+use crate::error::EccError;
+use crate::secp256k1::SECP256K1;
+use crate::util::{reduce, scalar_mul};
+
+/// Computes the ECDH shared secret between `secret_key` and `peer_public_key`.
+///
+/// The peer's key is validated first, since multiplying a point that is not
+/// on the curve can leak the secret key (invalid-curve attack).
+///
+/// # Arguments
+/// * `curve` - The elliptic curve of both keys.
+/// * `secret_key` - Our private key, in [1, n) for the group order `n`.
+/// * `peer_public_key` - The other party's public key.
+///
+/// # Returns
+/// The 32-byte big-endian x-coordinate of the shared point,
+/// `EccError::InvalidSecretKey`, or `EccError::PointNotOnCurve`.
+pub fn shared_secret(
+    curve: Curve,
+    secret_key: &BigUint,
+    peer_public_key: &Point,
+) -> Result<[u8; 32], EccError> {
+    let ecc_curve = match curve {
+        Curve::Secp256k1 => SECP256K1::default(),
+    };
+
+    if secret_key.is_zero() || BigInt::from(secret_key.clone()) >= ecc_curve.n {
+        return Err(EccError::InvalidSecretKey);
+    }
+
+    let peer = EccPoint::Finite(peer_public_key.clone());
+    if !ecc_curve.is_on_curve(&peer) {
+        return Err(EccError::PointNotOnCurve);
+    }
+
+    // The coordinates are reduced, so that the result is a canonical point.
+    let peer = Point(
+        reduce(&peer_public_key.0, &ecc_curve.p),
+        reduce(&peer_public_key.1, &ecc_curve.p),
+    );
+
+    match scalar_mul(secret_key, &peer, &ecc_curve) {
+        EccPoint::Finite(point) => {
+            let mut shared = [0u8; 32];
+            shared.copy_from_slice(&point.to_uncompressed()[1..33]);
+            Ok(shared)
+        }
+        // The group has prime order and cofactor 1, so every multiple of a
+        // curve point by a scalar in [1, n) is finite.
+        EccPoint::Infinity => unreachable!("non-zero multiple of a point of prime order"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::secp256k1::{ecdh::shared_secret_point, PublicKey, SecretKey};
+
+    fn public_key(secret_key: &BigUint) -> Point {
+        match SECP256K1::default().mul_g(secret_key) {
+            EccPoint::Finite(point) => point,
+            EccPoint::Infinity => panic!("the secret key is a multiple of the order"),
+        }
+    }
+
+    #[test]
+    fn agrees_with_peer_and_reference() {
+        let (alice, bob) = (BigUint::from(0xa11ceu32), BigUint::from(0xb0bu32) << 200u32);
+        let (alice_public, bob_public) = (public_key(&alice), public_key(&bob));
+
+        let shared = shared_secret(Curve::Secp256k1, &alice, &bob_public).unwrap();
+        assert_eq!(
+            shared_secret(Curve::Secp256k1, &bob, &alice_public),
+            Ok(shared)
+        );
+
+        let mut alice_bytes = [0u8; 32];
+        alice_bytes[29..].copy_from_slice(&alice.to_bytes_be());
+        let reference = shared_secret_point(
+            &PublicKey::from_slice(&bob_public.to_uncompressed()).unwrap(),
+            &SecretKey::from_slice(&alice_bytes).unwrap(),
+        );
+        assert_eq!(shared, reference[..32]);
+    }
+
+    #[test]
+    fn rejects_off_curve_peer() {
+        let peer = public_key(&BigUint::from(7u32));
+        let off_curve = Point(peer.0.clone(), &peer.1 + 1);
+
+        assert_eq!(
+            shared_secret(Curve::Secp256k1, &BigUint::from(3u32), &off_curve),
+            Err(EccError::PointNotOnCurve)
+        );
+        assert_eq!(
+            shared_secret(Curve::Secp256k1, &BigUint::zero(), &peer),
+            Err(EccError::InvalidSecretKey)
+        );
+    }
+}
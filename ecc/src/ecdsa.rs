@@ -0,0 +1,598 @@
+//! This module implements ECDSA signatures (SEC 1 §4.1) over secp256k1, with
+//! the nonce derived from the secret key and the message digest as specified
+//! by RFC 6979 instead of being drawn from an RNG.
+use num_bigint::{BigInt, BigUint};
+use num_traits::Zero;
+use sha_256::drbg::HmacDrbg;
+
+use crate::definitions::{Curve, EccPoint, EllipticCurve, JacobianPoint, Point};
+use crate::error::EccError;
+use crate::secp256k1::SECP256K1;
+use crate::util::wnaf_mul;
+
+/// An ECDSA signature `(r, s)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+/// Derives the signing nonce `k` for `digest` under `secret_key` as specified
+/// by RFC 6979 §3.2, with HMAC-SHA256.
+///
+/// The same key and digest always yield the same nonce, so signatures do not
+/// depend on the quality of an RNG, while the nonce stays unpredictable to
+/// anyone without the key.
+///
+/// # Arguments
+/// * `secret_key` - The private key `x`, in [1, order).
+/// * `digest` - The message hash `H(m)`.
+/// * `order` - The group order `q`.
+///
+/// # Returns
+/// The first nonce candidate in [1, order).
+pub fn deterministic_k(secret_key: &BigUint, digest: &[u8], order: &BigUint) -> BigUint {
+    NonceGenerator::new(secret_key, digest, order).next_k()
+}
+
+/// Signs `digest` with `secret_key`, using the RFC 6979 nonce.
+///
+/// Signing the same digest twice yields the same signature.
+///
+/// # Arguments
+/// * `curve` - The elliptic curve of the key.
+/// * `secret_key` - The private key, in [1, n) for the group order `n`.
+/// * `digest` - The message hash; hashes longer than the order are truncated
+///   to their leftmost bits.
+///
+/// # Returns
+/// The signature, or `EccError::InvalidSecretKey` if the key is out of range.
+pub fn sign_deterministic(
+    curve: Curve,
+    secret_key: &BigUint,
+    digest: &[u8],
+) -> Result<Signature, EccError> {
+    sign_recoverable(curve, secret_key, digest).map(|(signature, _)| signature)
+}
+
+/// Signs `digest` like `sign_deterministic` and also returns the recovery id
+/// that lets `recover` rebuild the public key from the signature.
+///
+/// # Arguments
+/// * `curve` - The elliptic curve of the key.
+/// * `secret_key` - The private key, in [1, n) for the group order `n`.
+/// * `digest` - The message hash.
+///
+/// # Returns
+/// The signature and its recovery id in [0, 3]: bit 0 is the parity of the
+/// `y`-coordinate of `R = k·G`, and bit 1 is set if its `x`-coordinate was
+/// at least `n`, so that `r = x - n`.
+pub fn sign_recoverable(
+    curve: Curve,
+    secret_key: &BigUint,
+    digest: &[u8],
+) -> Result<(Signature, u8), EccError> {
+    let (ecc_curve, order) = curve_and_order(curve);
+
+    if secret_key.is_zero() || secret_key >= &order {
+        return Err(EccError::InvalidSecretKey);
+    }
+
+    let e = bits2int(digest, order.bits());
+    let mut nonces = NonceGenerator::new(secret_key, digest, &order);
+
+    // RFC 6979 §3.4: a nonce yielding r = 0 or s = 0 is replaced by the next candidate.
+    loop {
+        let k = nonces.next_k();
+
+        let (r, recovery_id) = match ecc_curve.mul_g(&k) {
+            EccPoint::Finite(point) => {
+                let x = to_scalar(&point.0);
+                let overflow = x >= order;
+                (x % &order, (overflow as u8) << 1 | point.1.bit(0) as u8)
+            }
+            EccPoint::Infinity => continue,
+        };
+
+        let s = (inverse(&k, &order) * (&e + secret_key * &r)) % &order;
+
+        if !r.is_zero() && !s.is_zero() {
+            return Ok((Signature { r, s }, recovery_id));
+        }
+    }
+}
+
+/// Recovers the public key that produced `signature` over `digest`.
+///
+/// The recovery id selects `R` among the up to four points with
+/// `x ≡ r (mod n)`; the key is then `Q = r^-1·(s·R - e·G)`.
+///
+/// # Arguments
+/// * `curve` - The elliptic curve of the key.
+/// * `digest` - The message hash the signature was created over.
+/// * `signature` - The signature.
+/// * `recovery_id` - The id returned by `sign_recoverable`, in [0, 3].
+///
+/// # Returns
+/// The public key; `EccError::InvalidRecoveryId` for an id above 3; or
+/// `EccError::InvalidSignature` if `r` or `s` is out of range or no key
+/// matches.
+pub fn recover(
+    curve: Curve,
+    digest: &[u8],
+    signature: &Signature,
+    recovery_id: u8,
+) -> Result<Point, EccError> {
+    let (ecc_curve, order) = curve_and_order(curve);
+
+    if recovery_id > 3 {
+        return Err(EccError::InvalidRecoveryId);
+    }
+
+    let Signature { r, s } = signature;
+    if r.is_zero() || r >= &order || s.is_zero() || s >= &order {
+        return Err(EccError::InvalidSignature);
+    }
+
+    // x = r + n for ids 2 and 3; it must still be an element of the field.
+    let x = if recovery_id & 2 != 0 {
+        r + &order
+    } else {
+        r.clone()
+    };
+    if BigInt::from(x.clone()) >= ecc_curve.p {
+        return Err(EccError::InvalidSignature);
+    }
+
+    let mut compressed = [0u8; 33];
+    compressed[0] = 0x02 | (recovery_id & 1);
+    let x_bytes = x.to_bytes_be();
+    compressed[33 - x_bytes.len()..].copy_from_slice(&x_bytes);
+    let r_point =
+        Point::from_compressed(&compressed, curve).map_err(|_| EccError::InvalidSignature)?;
+
+    // Q = u_1·G + u_2·R with u_1 = -e·r^-1 and u_2 = s·r^-1. All values are
+    // public, so the variable-time methods are safe here.
+    let r_inv = inverse(r, &order);
+    let e = bits2int(digest, order.bits()) % &order;
+    let u_1 = ((&order - e) * &r_inv) % &order;
+    let u_2 = (s * r_inv) % &order;
+
+    let q = ecc_curve.add_jacobian(
+        &JacobianPoint::from(&ecc_curve.mul_g_fast(&u_1)),
+        &JacobianPoint::from(&wnaf_mul(&u_2, &r_point, &ecc_curve)),
+    );
+
+    match ecc_curve.to_affine(&q) {
+        EccPoint::Finite(point) => Ok(point),
+        EccPoint::Infinity => Err(EccError::InvalidSignature),
+    }
+}
+
+/// Verifies an ECDSA signature over `digest`.
+///
+/// # Arguments
+/// * `curve` - The elliptic curve of the key.
+/// * `public_key` - The signer's public key point.
+/// * `digest` - The message hash the signature was created over.
+/// * `signature` - The signature to check.
+///
+/// # Returns
+/// `true` if the signature is valid; `false` also for a public key that does
+/// not lie on the curve.
+pub fn verify(curve: Curve, public_key: &Point, digest: &[u8], signature: &Signature) -> bool {
+    let (ecc_curve, order) = curve_and_order(curve);
+
+    if !ecc_curve.is_on_curve(&EccPoint::Finite(public_key.clone())) {
+        return false;
+    }
+
+    let Signature { r, s } = signature;
+    if r.is_zero() || r >= &order || s.is_zero() || s >= &order {
+        return false;
+    }
+
+    let w = inverse(s, &order);
+    let u_1 = (bits2int(digest, order.bits()) * &w) % &order;
+    let u_2 = (r * &w) % &order;
+
+    // Both scalars are public, so the variable-time methods are safe here.
+    let a = ecc_curve.mul_g_fast(&u_1);
+    let b = wnaf_mul(&u_2, public_key, &ecc_curve);
+
+    match ecc_curve.add_points(&a, &b) {
+        EccPoint::Finite(point) => &(to_scalar(&point.0) % &order) == r,
+        EccPoint::Infinity => false,
+    }
+}
+
+/// The nonce candidates of RFC 6979 §3.2.
+///
+/// Instantiating HMAC_DRBG with `int2octets(x)` as entropy and
+/// `bits2octets(h1)` as nonce performs steps d to g; each `generate` call,
+/// including the state update that ends it, is one iteration of step h.
+struct NonceGenerator<'a> {
+    drbg: HmacDrbg,
+    order: &'a BigUint,
+    candidate: Vec<u8>,
+}
+
+impl<'a> NonceGenerator<'a> {
+    fn new(secret_key: &BigUint, digest: &[u8], order: &'a BigUint) -> Self {
+        let rlen = order.bits().div_ceil(8) as usize;
+
+        NonceGenerator {
+            drbg: HmacDrbg::new(
+                &int2octets(secret_key, rlen),
+                &bits2octets(digest, order, rlen),
+                &[],
+            ),
+            order,
+            candidate: vec![0u8; rlen],
+        }
+    }
+
+    fn next_k(&mut self) -> BigUint {
+        loop {
+            self.drbg
+                .generate(&mut self.candidate, None)
+                .expect("a nonce search never reaches the reseed interval");
+
+            let k = bits2int(&self.candidate, self.order.bits());
+            if !k.is_zero() && &k < self.order {
+                return k;
+            }
+        }
+    }
+}
+
+/// bits2int (RFC 6979 §2.3.2): the leftmost `qlen` bits of `bits` as an integer.
+fn bits2int(bits: &[u8], qlen: u64) -> BigUint {
+    let value = BigUint::from_bytes_be(bits);
+    let blen = bits.len() as u64 * 8;
+
+    if blen > qlen {
+        value >> (blen - qlen)
+    } else {
+        value
+    }
+}
+
+/// int2octets (RFC 6979 §2.3.3): `x` as exactly `rlen` big-endian bytes.
+fn int2octets(x: &BigUint, rlen: usize) -> Vec<u8> {
+    let bytes = x.to_bytes_be();
+    let mut octets = vec![0u8; rlen.saturating_sub(bytes.len())];
+    octets.extend_from_slice(&bytes[bytes.len().saturating_sub(rlen)..]);
+    octets
+}
+
+/// bits2octets (RFC 6979 §2.3.4): `bits2int(bits) mod q` as `rlen` bytes.
+fn bits2octets(bits: &[u8], order: &BigUint, rlen: usize) -> Vec<u8> {
+    let z = bits2int(bits, order.bits()) % order;
+    int2octets(&z, rlen)
+}
+
+/// Returns the curve parameters together with the group order as a `BigUint`.
+fn curve_and_order(curve: Curve) -> (SECP256K1, BigUint) {
+    let ecc_curve = match curve {
+        Curve::Secp256k1 => SECP256K1::default(),
+    };
+    let order = to_scalar(&ecc_curve.n);
+
+    (ecc_curve, order)
+}
+
+/// Computes `k^-1 mod order` by Fermat's little theorem; `order` is prime.
+fn inverse(k: &BigUint, order: &BigUint) -> BigUint {
+    k.modpow(&(order - 2u32), order)
+}
+
+/// Converts a canonical, hence non-negative, coordinate to a `BigUint`.
+fn to_scalar(coordinate: &BigInt) -> BigUint {
+    coordinate
+        .to_biguint()
+        .expect("point coordinates are non-negative")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::secp256k1::{Message, Secp256k1, SecretKey};
+    use num_traits::Num;
+    use sha_256::Sha256;
+
+    // The private key of the RFC 6979 §A.2.5 (P-256) examples.
+    const RFC6979_KEY: &str = "C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721";
+
+    fn hex(s: &str) -> BigUint {
+        BigUint::from_str_radix(s, 16).unwrap()
+    }
+
+    fn sha256(msg: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(msg);
+        hasher.finalize()
+    }
+
+    fn public_key(secret_key: &BigUint) -> Point {
+        match SECP256K1::default().mul_g(secret_key) {
+            EccPoint::Finite(point) => point,
+            EccPoint::Infinity => panic!("the secret key is a multiple of the order"),
+        }
+    }
+
+    #[test]
+    fn rfc6979_nonces() {
+        // RFC 6979 §A.2.5, ECDSA with P-256 and SHA-256. The nonce only
+        // depends on the key, the digest and the group order.
+        let order = hex("FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551");
+
+        assert_eq!(
+            deterministic_k(&hex(RFC6979_KEY), &sha256(b"sample"), &order),
+            hex("A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60")
+        );
+        assert_eq!(
+            deterministic_k(&hex(RFC6979_KEY), &sha256(b"test"), &order),
+            hex("D16B6AE827F17175E040871A1C7EC3500192C4C92677336EC2537ACAEE0008E0")
+        );
+    }
+
+    #[test]
+    fn rfc6979_secp256k1_signatures() {
+        // RFC 6979 has no secp256k1 examples. Both orders are 256-bit and
+        // the first candidates lie below both, so these signatures use the
+        // §A.2.5 nonces above; r and s match an independent implementation.
+        let x = hex(RFC6979_KEY);
+        let order = curve_and_order(Curve::Secp256k1).1;
+
+        for (msg, k, r, s) in [
+            (
+                &b"sample"[..],
+                "A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60",
+                "432310E32CB80EB6503A26CE83CC165C783B870845FB8AAD6D970889FCD7A6C8",
+                "530128B6B81C548874A6305D93ED071CA6E05074D85863D4056CE89B02BFAB69",
+            ),
+            (
+                &b"test"[..],
+                "D16B6AE827F17175E040871A1C7EC3500192C4C92677336EC2537ACAEE0008E0",
+                "F2ADCEA7139057BE6409855EE96D008E0E5B5F532333EC17448E26A36F47BCB2",
+                "570C9D342779B40F513C0D75CBF93E3F3DE7B01F6593F17BFC2EE87151414D64",
+            ),
+        ] {
+            let digest = sha256(msg);
+            assert_eq!(deterministic_k(&x, &digest, &order), hex(k));
+
+            let signature = sign_deterministic(Curve::Secp256k1, &x, &digest).unwrap();
+            assert_eq!(
+                signature,
+                Signature {
+                    r: hex(r),
+                    s: hex(s)
+                }
+            );
+            assert_eq!(
+                sign_deterministic(Curve::Secp256k1, &x, &digest),
+                Ok(signature.clone())
+            );
+            assert!(verify(
+                Curve::Secp256k1,
+                &public_key(&x),
+                &digest,
+                &signature
+            ));
+        }
+    }
+
+    #[test]
+    fn matches_libsecp256k1() {
+        // libsecp256k1 also derives its nonces per RFC 6979 with HMAC-SHA256,
+        // but normalizes s to the lower half of the range.
+        let secp256k1_extern = Secp256k1::signing_only();
+        let order = curve_and_order(Curve::Secp256k1).1;
+
+        for (secret_key, msg) in [
+            (BigUint::from(1u32), &b"Satoshi Nakamoto"[..]),
+            (hex(RFC6979_KEY), b"sample"),
+            (&order - 1u32, b"All those moments will be lost in time."),
+        ] {
+            let digest = sha256(msg);
+            let mut signature = sign_deterministic(Curve::Secp256k1, &secret_key, &digest).unwrap();
+            if signature.s > &order >> 1 {
+                signature.s = &order - &signature.s;
+            }
+
+            let extern_key = SecretKey::from_slice(&int2octets(&secret_key, 32)).unwrap();
+            let expected = secp256k1_extern
+                .sign_ecdsa(&Message::from_digest_slice(&digest).unwrap(), &extern_key)
+                .serialize_compact();
+
+            assert_eq!(
+                [int2octets(&signature.r, 32), int2octets(&signature.s, 32)].concat(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn recovers_signing_key() {
+        let mut secret_key = BigUint::from(7u32);
+
+        for i in 0..8u32 {
+            let digest = sha256(&i.to_be_bytes());
+            let (signature, recovery_id) =
+                sign_recoverable(Curve::Secp256k1, &secret_key, &digest).unwrap();
+
+            assert_eq!(
+                signature,
+                sign_deterministic(Curve::Secp256k1, &secret_key, &digest).unwrap()
+            );
+            assert!(recovery_id < 2, "x >= n has probability about 2^-128");
+            assert_eq!(
+                recover(Curve::Secp256k1, &digest, &signature, recovery_id),
+                Ok(public_key(&secret_key))
+            );
+
+            // The other parity gives a different, wrong key.
+            assert_ne!(
+                recover(Curve::Secp256k1, &digest, &signature, recovery_id ^ 1),
+                Ok(public_key(&secret_key))
+            );
+
+            secret_key = (&secret_key * &secret_key + 1u32) % curve_and_order(Curve::Secp256k1).1;
+        }
+    }
+
+    #[test]
+    fn recovers_libsecp256k1_fixtures() {
+        // (digest, r || s, recovery id, uncompressed public key), produced by
+        // libsecp256k1's sign_ecdsa_recoverable for the keys
+        // 4c0883a6...3f362318 and 1.
+        let fixtures = [
+            (
+                "1113f0a94f45c4b068876e9175f687b08b2229c50182a579027424158ff392f9",
+                "543f803cfe5236c0b882b2ab77a256b93e1704fb0f7a25e413024078fef505fb\
+                 2e097b21458fcb19059c37e028643959c78042fe5c558aaa1585217d597057da",
+                1,
+                "044e3b81af9c2234cad09d679ce6035ed1392347ce64ce405f5dcd36228a25de6e\
+                 47fd35c4215d1edf53e6f83de344615ce719bdb0fd878f6ed76f06dd277956de",
+            ),
+            (
+                "2ae2f0decb81fc1ec7b1dcbc19912b19605e312b725268f01de9d6614d2d8926",
+                "9b293d6dd80f9774ffe319d22fb3055789c64d57358d1d4fab30bfbf8ec09f5c\
+                 0ee4e98480732f2723f36252b65b09842440405ac1c81adf397d1c1d21b0d269",
+                1,
+                "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+                 483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+            ),
+        ];
+
+        for (digest, signature, recovery_id, public_key) in fixtures {
+            let signature = Signature {
+                r: hex(&signature[..64]),
+                s: hex(&signature[64..]),
+            };
+            let digest = ::hex::decode(digest).unwrap();
+
+            let recovered = recover(Curve::Secp256k1, &digest, &signature, recovery_id).unwrap();
+            assert_eq!(::hex::encode(recovered.to_uncompressed()), public_key);
+        }
+
+        // And live, over signatures with both recovery ids.
+        let secp256k1_extern = Secp256k1::new();
+        let extern_key = SecretKey::from_slice(&int2octets(&hex(RFC6979_KEY), 32)).unwrap();
+        let mut seen_ids = [false; 2];
+
+        for i in 0..16u32 {
+            let digest = sha256(&i.to_le_bytes());
+            let (id, compact) = secp256k1_extern
+                .sign_ecdsa_recoverable(&Message::from_digest_slice(&digest).unwrap(), &extern_key)
+                .serialize_compact();
+            let signature = Signature {
+                r: BigUint::from_bytes_be(&compact[..32]),
+                s: BigUint::from_bytes_be(&compact[32..]),
+            };
+            seen_ids[id.to_i32() as usize] = true;
+
+            assert_eq!(
+                recover(Curve::Secp256k1, &digest, &signature, id.to_i32() as u8),
+                Ok(public_key(&hex(RFC6979_KEY)))
+            );
+        }
+        assert_eq!(seen_ids, [true, true]);
+    }
+
+    #[test]
+    fn recovery_rejects_bad_input() {
+        let digest = sha256(b"sample");
+        let (signature, recovery_id) =
+            sign_recoverable(Curve::Secp256k1, &BigUint::from(3u32), &digest).unwrap();
+
+        assert_eq!(
+            recover(Curve::Secp256k1, &digest, &signature, 4),
+            Err(EccError::InvalidRecoveryId)
+        );
+
+        // r + n exceeds p unless r < p - n, which is below 2^129.
+        assert_eq!(
+            recover(Curve::Secp256k1, &digest, &signature, recovery_id | 2),
+            Err(EccError::InvalidSignature)
+        );
+
+        let zero_r = Signature {
+            r: BigUint::zero(),
+            s: signature.s.clone(),
+        };
+        assert_eq!(
+            recover(Curve::Secp256k1, &digest, &zero_r, recovery_id),
+            Err(EccError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_keys_and_signatures() {
+        let digest = sha256(b"sample");
+        let order = curve_and_order(Curve::Secp256k1).1;
+
+        for secret_key in [BigUint::zero(), order.clone()] {
+            assert_eq!(
+                sign_deterministic(Curve::Secp256k1, &secret_key, &digest),
+                Err(EccError::InvalidSecretKey)
+            );
+        }
+
+        let x = hex(RFC6979_KEY);
+        let public_key = public_key(&x);
+        let signature = sign_deterministic(Curve::Secp256k1, &x, &digest).unwrap();
+
+        assert!(!verify(
+            Curve::Secp256k1,
+            &public_key,
+            &sha256(b"samplf"),
+            &signature
+        ));
+        assert!(!verify(
+            Curve::Secp256k1,
+            &Point(public_key.0.clone(), &public_key.1 + 1),
+            &digest,
+            &signature
+        ));
+        for tampered in [
+            Signature {
+                r: &signature.r + 1u32,
+                ..signature.clone()
+            },
+            Signature {
+                s: &signature.s + 1u32,
+                ..signature.clone()
+            },
+            Signature {
+                r: BigUint::zero(),
+                ..signature.clone()
+            },
+            Signature {
+                s: order.clone(),
+                ..signature.clone()
+            },
+        ] {
+            assert!(!verify(Curve::Secp256k1, &public_key, &digest, &tampered));
+        }
+    }
+
+    #[test]
+    fn rfc6979_conversions() {
+        // A 163-bit order with a 256-bit hash, as in RFC 6979 §A.1.
+        let order = hex("4000000000000000000020108A2E0CC0D99F8A5EF");
+        let h1 = sha256(b"sample");
+
+        assert_eq!(
+            bits2int(&h1, order.bits()),
+            BigUint::from_bytes_be(&h1) >> (256 - 163)
+        );
+        assert_eq!(
+            bits2octets(&h1, &order, 21),
+            int2octets(&((BigUint::from_bytes_be(&h1) >> 93u32) % &order), 21)
+        );
+        assert_eq!(int2octets(&BigUint::from(0x0102u32), 4), [0, 0, 1, 2]);
+    }
+}
@@ -0,0 +1,277 @@
+//! This module implements ECIES hybrid encryption over secp256k1.
+//!
+//! The sender draws an ephemeral key pair and runs ECDH against the
+//! recipient's public key. HKDF-SHA256 turns the shared secret into an
+//! AES-256 key and an HMAC-SHA256 key. The message is encrypted with AES-256
+//! in CBC mode with PKCS#7 padding, and the IV and ciphertext are then
+//! authenticated (encrypt-then-MAC). The output is
+//! `ephemeral_pub (65) || IV (16) || ciphertext || tag (32)`.
+use aes::{
+    block_modes::CbcEncryptor, definitions::AesEncryptor, pkcs_padding::PkcsPadding, KeySchedule,
+};
+use rand::{CryptoRng, RngCore};
+use sha_256::{hkdf, HmacSha256};
+use utils::mac::Mac;
+
+use crate::definitions::{Curve, EccPoint, Point};
+use crate::ecdh::shared_secret;
+use crate::error::EccError;
+use crate::secp256k1::SECP256K1;
+use crate::secret_key::SecretKey;
+
+// Lengths in bytes of the fixed-size parts of a ciphertext.
+const PUBLIC_KEY_LEN: usize = 65;
+const IV_LEN: usize = 16;
+const TAG_LEN: usize = 32;
+const BLOCK_SIZE: usize = 16;
+
+// Binds the derived keys to this construction.
+const HKDF_INFO: &[u8] = b"ecies-secp256k1-aes256cbc-hmacsha256";
+
+/// Encrypts `plaintext` for the holder of the secret key of `recipient`.
+///
+/// # Arguments
+/// * `recipient` - The recipient's public key.
+/// * `plaintext` - The message to encrypt.
+/// * `rng` - The source of the ephemeral key and the IV.
+///
+/// # Returns
+/// `ephemeral_pub || IV || ciphertext || tag`, or
+/// `EccError::PointNotOnCurve` if `recipient` is not a curve point.
+pub fn encrypt<R: RngCore + CryptoRng + ?Sized>(
+    recipient: &Point,
+    plaintext: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>, EccError> {
+    let ephemeral_key = SecretKey::generate(Curve::Secp256k1, rng);
+    let ephemeral_pub = match SECP256K1::default().mul_g(ephemeral_key.value()) {
+        EccPoint::Finite(point) => point.to_uncompressed(),
+        EccPoint::Infinity => unreachable!("non-zero multiple of a point of prime order"),
+    };
+
+    let shared = shared_secret(Curve::Secp256k1, ephemeral_key.value(), recipient)?;
+    let (enc_key, mac_key) = derive_keys(&ephemeral_pub, &shared);
+
+    let mut iv = [0u8; IV_LEN];
+    rng.fill_bytes(&mut iv);
+
+    let mut output = ephemeral_pub.to_vec();
+    output.extend_from_slice(&iv);
+    output.extend(cbc_encrypt(&enc_key, &iv, plaintext));
+
+    let mut mac = HmacSha256::new(&mac_key);
+    mac.update(&output[PUBLIC_KEY_LEN..]);
+    output.extend_from_slice(&mac.finalize());
+
+    Ok(output)
+}
+
+/// Decrypts a ciphertext produced by `encrypt`.
+///
+/// The tag is checked, in constant time, before anything is decrypted.
+///
+/// # Arguments
+/// * `secret_key` - The recipient's secret key.
+/// * `bytes` - `ephemeral_pub || IV || ciphertext || tag`.
+///
+/// # Returns
+/// The plaintext; `EccError::InvalidPointEncoding` or
+/// `EccError::PointNotOnCurve` for a bad ephemeral key; and
+/// `EccError::DecryptionFailed` for a malformed ciphertext or a wrong tag.
+pub fn decrypt(secret_key: &SecretKey, bytes: &[u8]) -> Result<Vec<u8>, EccError> {
+    let body_len = bytes
+        .len()
+        .checked_sub(PUBLIC_KEY_LEN + IV_LEN + TAG_LEN)
+        .ok_or(EccError::DecryptionFailed)?;
+    if body_len == 0 || body_len % BLOCK_SIZE != 0 {
+        return Err(EccError::DecryptionFailed);
+    }
+
+    let (ephemeral_pub, rest) = bytes.split_at(PUBLIC_KEY_LEN);
+    let (authenticated, tag) = rest.split_at(rest.len() - TAG_LEN);
+    let (iv, ciphertext) = authenticated.split_at(IV_LEN);
+
+    let ephemeral_pub: &[u8; PUBLIC_KEY_LEN] = ephemeral_pub.try_into().expect("split at 65");
+    let peer = Point::from_uncompressed(ephemeral_pub, Curve::Secp256k1)?;
+
+    let shared = shared_secret(Curve::Secp256k1, secret_key.value(), &peer)?;
+    let (enc_key, mac_key) = derive_keys(ephemeral_pub, &shared);
+
+    let mut mac = HmacSha256::new(&mac_key);
+    mac.update(authenticated);
+    Mac::verify(mac, tag).map_err(|_e| EccError::DecryptionFailed)?;
+
+    cbc_decrypt(&enc_key, iv.try_into().expect("split at 16"), ciphertext)
+        .ok_or(EccError::DecryptionFailed)
+}
+
+/// Derives the AES-256 key and the HMAC key from the ECDH shared secret, with
+/// the ephemeral public key as the HKDF salt.
+fn derive_keys(ephemeral_pub: &[u8], shared: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut okm = [0u8; 64];
+    hkdf::hkdf(ephemeral_pub, shared, HKDF_INFO, &mut okm).expect("64 bytes is within the limit");
+
+    let mut enc_key = [0u8; 32];
+    let mut mac_key = [0u8; 32];
+    enc_key.copy_from_slice(&okm[..32]);
+    mac_key.copy_from_slice(&okm[32..]);
+
+    (enc_key, mac_key)
+}
+
+/// Encrypts with AES-256-CBC after PKCS#7 padding.
+fn cbc_encrypt(key: &[u8; 32], iv: &[u8; IV_LEN], plaintext: &[u8]) -> Vec<u8> {
+    let keys = KeySchedule::new(key).expect("32-byte AES key");
+
+    CbcEncryptor::with_iv(&keys, iv, PkcsPadding)
+        .and_then(|mut cbc| cbc.encrypt(plaintext))
+        .expect("PKCS#7 padding accepts any input")
+}
+
+/// Decrypts AES-256-CBC and strips the PKCS#7 padding, returning `None` if
+/// the padding is malformed.
+fn cbc_decrypt(key: &[u8; 32], iv: &[u8; IV_LEN], ciphertext: &[u8]) -> Option<Vec<u8>> {
+    let keys = KeySchedule::new(key).expect("32-byte AES key");
+
+    CbcEncryptor::with_iv(&keys, iv, PkcsPadding)
+        .and_then(|mut cbc| cbc.decrypt(ciphertext))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::BigInt;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn key_pair(rng: &mut StdRng) -> (SecretKey, Point) {
+        let secret_key = SecretKey::generate(Curve::Secp256k1, rng);
+        match SECP256K1::default().mul_g(secret_key.value()) {
+            EccPoint::Finite(point) => (secret_key, point),
+            EccPoint::Infinity => unreachable!("non-zero multiple of a point of prime order"),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = StdRng::seed_from_u64(3395);
+        let (secret_key, public_key) = key_pair(&mut rng);
+
+        for len in [0, 1, 15, 16, 17, 100] {
+            let plaintext: Vec<u8> = (0..len as u8).collect();
+            let ciphertext = encrypt(&public_key, &plaintext, &mut rng).unwrap();
+
+            let padded_len = (len / BLOCK_SIZE + 1) * BLOCK_SIZE;
+            assert_eq!(
+                ciphertext.len(),
+                PUBLIC_KEY_LEN + IV_LEN + padded_len + TAG_LEN
+            );
+            assert_eq!(decrypt(&secret_key, &ciphertext), Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn cbc_matches_known_answer() {
+        // NIST SP 800-38A F.2.5, the first two blocks of CBC-AES256.Encrypt.
+        let key: [u8; 32] =
+            hex::decode("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let iv: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let plaintext =
+            hex::decode("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")
+                .unwrap();
+
+        let ciphertext = cbc_encrypt(&key, &iv, &plaintext);
+        assert_eq!(
+            hex::encode(&ciphertext[..32]),
+            "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d"
+        );
+        assert_eq!(cbc_decrypt(&key, &iv, &ciphertext), Some(plaintext));
+    }
+
+    #[test]
+    fn wrong_key_fails() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let (_, public_key) = key_pair(&mut rng);
+        let (other_key, _) = key_pair(&mut rng);
+
+        let ciphertext = encrypt(&public_key, b"attack at dawn", &mut rng).unwrap();
+        assert_eq!(
+            decrypt(&other_key, &ciphertext),
+            Err(EccError::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn bit_flips_fail() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let (secret_key, public_key) = key_pair(&mut rng);
+        let ciphertext = encrypt(&public_key, &[0x5a; 40], &mut rng).unwrap();
+
+        // One position in the ephemeral key, the IV, each ciphertext block and the tag.
+        let body = PUBLIC_KEY_LEN + IV_LEN;
+        for position in [
+            0,
+            1,
+            64,
+            PUBLIC_KEY_LEN,
+            body,
+            body + 16,
+            body + 47,
+            body + 48,
+        ] {
+            let mut tampered = ciphertext.clone();
+            tampered[position] ^= 0x01;
+            assert!(
+                decrypt(&secret_key, &tampered).is_err(),
+                "byte {}",
+                position
+            );
+        }
+
+        let last = ciphertext.len() - 1;
+        let mut tampered = ciphertext.clone();
+        tampered[last] ^= 0x80;
+        assert_eq!(
+            decrypt(&secret_key, &tampered),
+            Err(EccError::DecryptionFailed)
+        );
+
+        for len in [0, PUBLIC_KEY_LEN + IV_LEN + TAG_LEN, ciphertext.len() - 1] {
+            assert_eq!(
+                decrypt(&secret_key, &ciphertext[..len]),
+                Err(EccError::DecryptionFailed)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_off_curve_keys() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let (secret_key, public_key) = key_pair(&mut rng);
+        let mut ciphertext = encrypt(&public_key, b"payload", &mut rng).unwrap();
+
+        // Move the ephemeral key off the curve: (x, y + 1).
+        let mut point = Point::from_uncompressed(
+            ciphertext[..PUBLIC_KEY_LEN].try_into().unwrap(),
+            Curve::Secp256k1,
+        )
+        .unwrap();
+        point.1 = (&point.1 + 1) % &SECP256K1::default().p;
+        ciphertext[..PUBLIC_KEY_LEN].copy_from_slice(&point.to_uncompressed());
+
+        assert_eq!(
+            decrypt(&secret_key, &ciphertext),
+            Err(EccError::PointNotOnCurve)
+        );
+
+        let off_curve = Point(public_key.0.clone(), &public_key.1 + BigInt::from(1));
+        assert_eq!(
+            encrypt(&off_curve, b"payload", &mut rng),
+            Err(EccError::PointNotOnCurve)
+        );
+    }
+}
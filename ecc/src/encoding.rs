@@ -0,0 +1,304 @@
+//! This module converts curve points to and from the SEC 1 §2.3.3 octet
+//! strings: `0x04 || x || y` uncompressed, or `0x02`/`0x03 || x` compressed,
+//! where the prefix carries the parity of `y`.
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
+use sha_256::{base58check, ripemd160::hash160};
+
+use crate::definitions::{Curve, EccPoint, EllipticCurve, Point};
+use crate::error::EccError;
+use crate::secp256k1::SECP256K1;
+
+// Length in bytes of a coordinate of a secp256k1 point.
+const COORDINATE_LEN: usize = 32;
+
+// Base58Check version byte of Bitcoin mainnet P2PKH addresses.
+const P2PKH_VERSION: u8 = 0x00;
+
+impl Point {
+    /// Encodes the point as `0x02` (even `y`) or `0x03` (odd `y`) followed by
+    /// the 32-byte big-endian `x`.
+    pub fn to_compressed(&self) -> [u8; 33] {
+        let mut bytes = [0u8; 33];
+        bytes[0] = if self.1.bit(0) { 0x03 } else { 0x02 };
+        write_coordinate(&self.0, &mut bytes[1..]);
+        bytes
+    }
+
+    /// Encodes the point as `0x04` followed by the 32-byte big-endian `x`
+    /// and `y`.
+    pub fn to_uncompressed(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[0] = 0x04;
+        write_coordinate(&self.0, &mut bytes[1..33]);
+        write_coordinate(&self.1, &mut bytes[33..]);
+        bytes
+    }
+
+    /// Encodes the point in the SEC 1 format.
+    ///
+    /// # Arguments
+    /// * `compressed` - Whether to emit the 33-byte compressed form rather
+    ///   than the 65-byte uncompressed one.
+    pub fn to_sec1_bytes(&self, compressed: bool) -> Vec<u8> {
+        if compressed {
+            self.to_compressed().to_vec()
+        } else {
+            self.to_uncompressed().to_vec()
+        }
+    }
+
+    /// Returns the uncompressed SEC 1 encoding as 130 lowercase hex characters.
+    pub fn to_hex_string(&self) -> String {
+        hex::encode(self.to_sec1_bytes(false))
+    }
+
+    /// Returns the compressed SEC 1 encoding as 66 lowercase hex characters.
+    pub fn to_compressed_hex(&self) -> String {
+        hex::encode(self.to_sec1_bytes(true))
+    }
+
+    /// Derives the Bitcoin mainnet P2PKH address of the point: the
+    /// Base58Check encoding, under version `0x00`, of `hash160` of its SEC 1
+    /// encoding.
+    ///
+    /// # Arguments
+    /// * `compressed` - Whether to hash the compressed encoding. The two
+    ///   encodings give different addresses for the same key; wallets have
+    ///   used the compressed one since 2012.
+    pub fn to_p2pkh_address(&self, compressed: bool) -> String {
+        base58check::encode(P2PKH_VERSION, &hash160(&self.to_sec1_bytes(compressed)))
+    }
+
+    /// Decodes a compressed point, recovering `y` from the curve equation.
+    ///
+    /// # Arguments
+    /// * `bytes` - The prefix `0x02` or `0x03` followed by the big-endian `x`.
+    /// * `curve` - The curve the point lies on.
+    ///
+    /// # Returns
+    /// The point, `EccError::InvalidPointEncoding` for a bad prefix or an `x`
+    /// outside the field, or `EccError::PointNotOnCurve` if no point has this `x`.
+    pub fn from_compressed(bytes: &[u8; 33], curve: Curve) -> Result<Point, EccError> {
+        let ecc_curve = match curve {
+            Curve::Secp256k1 => SECP256K1::default(),
+        };
+
+        let odd = match bytes[0] {
+            0x02 => false,
+            0x03 => true,
+            _ => return Err(EccError::InvalidPointEncoding),
+        };
+        let x = read_coordinate(&bytes[1..], &ecc_curve)?;
+
+        let rhs = curve_rhs(&x, &ecc_curve);
+        let y = sqrt_mod(&rhs, &ecc_curve.p).ok_or(EccError::PointNotOnCurve)?;
+
+        // Of the two roots y and p - y, exactly one has the requested parity
+        // (y = 0 would need x^3 + 7 = 0, which has no solution in the field).
+        let y = if y.bit(0) == odd { y } else { &ecc_curve.p - y };
+
+        Ok(Point(x, y))
+    }
+
+    /// Decodes an uncompressed point and checks that it lies on the curve.
+    ///
+    /// # Arguments
+    /// * `bytes` - The prefix `0x04` followed by the big-endian `x` and `y`.
+    /// * `curve` - The curve the point lies on.
+    ///
+    /// # Returns
+    /// The point, `EccError::InvalidPointEncoding` for a bad prefix or a
+    /// coordinate outside the field, or `EccError::PointNotOnCurve`.
+    pub fn from_uncompressed(bytes: &[u8; 65], curve: Curve) -> Result<Point, EccError> {
+        let ecc_curve = match curve {
+            Curve::Secp256k1 => SECP256K1::default(),
+        };
+
+        if bytes[0] != 0x04 {
+            return Err(EccError::InvalidPointEncoding);
+        }
+        let x = read_coordinate(&bytes[1..33], &ecc_curve)?;
+        let y = read_coordinate(&bytes[33..], &ecc_curve)?;
+
+        let point = Point(x, y);
+        if !ecc_curve.is_on_curve(&EccPoint::Finite(point.clone())) {
+            return Err(EccError::PointNotOnCurve);
+        }
+
+        Ok(point)
+    }
+}
+
+/// Writes a non-negative coordinate as exactly `out.len()` big-endian bytes.
+fn write_coordinate(coordinate: &BigInt, out: &mut [u8]) {
+    let (_, bytes) = coordinate.to_bytes_be();
+    out[COORDINATE_LEN - bytes.len()..].copy_from_slice(&bytes);
+}
+
+/// Reads a big-endian coordinate, which must be an element of the field.
+fn read_coordinate(bytes: &[u8], ecc_curve: &SECP256K1) -> Result<BigInt, EccError> {
+    let coordinate = BigInt::from_bytes_be(Sign::Plus, bytes);
+    if coordinate >= ecc_curve.p {
+        return Err(EccError::InvalidPointEncoding);
+    }
+
+    Ok(coordinate)
+}
+
+/// Evaluates the right-hand side of y^2 = x^3 + ax + b modulo the field prime.
+fn curve_rhs(x: &BigInt, ecc_curve: &SECP256K1) -> BigInt {
+    (x.pow(3) + &ecc_curve.a * x + &ecc_curve.b) % &ecc_curve.p
+}
+
+/// Computes a square root of `a` modulo a prime `p ≡ 3 (mod 4)` as
+/// `a^((p + 1) / 4)`, which is a root exactly when `a` is a quadratic residue.
+fn sqrt_mod(a: &BigInt, p: &BigInt) -> Option<BigInt> {
+    let root = a.modpow(&((p + BigInt::one()) >> 2u32), p);
+
+    if (&root * &root - a) % p == BigInt::zero() {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::secp256k1::{PublicKey, Secp256k1, SecretKey};
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    fn reference_keys() -> Vec<PublicKey> {
+        let secp256k1_extern = Secp256k1::signing_only();
+        let mut rng = StdRng::seed_from_u64(3380);
+
+        (0..16)
+            .map(|_| {
+                let mut secret = [0u8; 32];
+                rng.fill_bytes(&mut secret);
+                let secret_key = SecretKey::from_slice(&secret).expect("within curve order");
+                PublicKey::from_secret_key(&secp256k1_extern, &secret_key)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_reference_keys() {
+        let mut parities = [false; 2];
+
+        for key in reference_keys() {
+            let compressed = key.serialize();
+            let uncompressed = key.serialize_uncompressed();
+            parities[(compressed[0] - 0x02) as usize] = true;
+
+            let point = Point::from_compressed(&compressed, Curve::Secp256k1).unwrap();
+            assert_eq!(
+                Point::from_uncompressed(&uncompressed, Curve::Secp256k1),
+                Ok(point.clone())
+            );
+
+            assert_eq!(point.to_compressed(), compressed);
+            assert_eq!(point.to_uncompressed(), uncompressed);
+        }
+
+        assert_eq!(parities, [true, true]);
+    }
+
+    #[test]
+    fn hex_encodings_match_reference() {
+        for key in reference_keys().into_iter().take(4) {
+            let point = Point::from_compressed(&key.serialize(), Curve::Secp256k1).unwrap();
+
+            assert_eq!(point.to_sec1_bytes(true), key.serialize());
+            assert_eq!(point.to_sec1_bytes(false), key.serialize_uncompressed());
+            assert_eq!(point.to_compressed_hex(), hex::encode(key.serialize()));
+            assert_eq!(
+                point.to_hex_string(),
+                hex::encode(key.serialize_uncompressed())
+            );
+        }
+    }
+
+    #[test]
+    fn p2pkh_addresses_of_generator() {
+        // The generator is the public key of the private key 1.
+        let g = SECP256K1::default().g;
+
+        assert_eq!(
+            g.to_p2pkh_address(true),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+        assert_eq!(
+            g.to_p2pkh_address(false),
+            "1EHNa6Q4Jz2uvNExL497mE43ikXhwF6kZm"
+        );
+    }
+
+    #[test]
+    fn decompresses_generator() {
+        let g = SECP256K1::default().g;
+        let compressed = g.to_compressed();
+
+        assert_eq!(compressed[0], 0x02);
+        assert_eq!(Point::from_compressed(&compressed, Curve::Secp256k1), Ok(g));
+    }
+
+    #[test]
+    fn rejects_x_without_square_root() {
+        // x^3 + 7 is a quadratic non-residue modulo p for these x.
+        for x in [0u8, 5, 7, 9, 10, 11] {
+            let mut compressed = [0u8; 33];
+            compressed[0] = 0x02;
+            compressed[32] = x;
+
+            assert_eq!(
+                Point::from_compressed(&compressed, Curve::Secp256k1),
+                Err(EccError::PointNotOnCurve)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_encodings() {
+        let key = reference_keys().remove(0);
+        let compressed = key.serialize();
+        let uncompressed = key.serialize_uncompressed();
+
+        for prefix in [0x00, 0x04, 0x05] {
+            let mut bytes = compressed;
+            bytes[0] = prefix;
+            assert_eq!(
+                Point::from_compressed(&bytes, Curve::Secp256k1),
+                Err(EccError::InvalidPointEncoding)
+            );
+        }
+
+        let mut bytes = uncompressed;
+        bytes[0] = 0x02;
+        assert_eq!(
+            Point::from_uncompressed(&bytes, Curve::Secp256k1),
+            Err(EccError::InvalidPointEncoding)
+        );
+
+        // A y that does not match x.
+        let mut bytes = uncompressed;
+        bytes[64] ^= 0x01;
+        assert_eq!(
+            Point::from_uncompressed(&bytes, Curve::Secp256k1),
+            Err(EccError::PointNotOnCurve)
+        );
+
+        // x = p lies outside the field.
+        let mut bytes = [0xff; 33];
+        bytes[0] = 0x02;
+        bytes[28] = 0xfe;
+        bytes[31] = 0xfc;
+        bytes[32] = 0x2f;
+        assert_eq!(
+            Point::from_compressed(&bytes, Curve::Secp256k1),
+            Err(EccError::InvalidPointEncoding)
+        );
+    }
+}
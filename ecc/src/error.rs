@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EccError {
+    #[error("The secret key must lie in [1, n), where n is the group order")]
+    InvalidSecretKey,
+
+    #[error("Invalid point encoding")]
+    InvalidPointEncoding,
+
+    #[error("The point does not lie on the curve")]
+    PointNotOnCurve,
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("The recovery id must lie in [0, 3]")]
+    InvalidRecoveryId,
+
+    #[error("Decryption failed")]
+    DecryptionFailed,
+}
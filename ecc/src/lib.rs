@@ -1,12 +1,21 @@
 pub mod definitions;
+pub mod ecdh;
+pub mod ecdsa;
+pub mod ecies;
+mod encoding;
+mod error;
+pub mod schnorr;
 mod secp256k1;
+mod secret_key;
 pub mod util;
 
-use rand::{rngs::OsRng, RngCore};
+pub use error::EccError;
+pub use secp256k1::SECP256K1;
+pub use secret_key::SecretKey;
+
+use rand::rngs::OsRng;
 
-use crate::secp256k1::SECP256K1;
 use definitions::{Curve, EccPoint};
-use util::{bytes_to_binary, scalar_mul};
 
 /// Generates a key pair (private and public) for a given elliptic curve.
 ///
@@ -14,31 +23,29 @@ use util::{bytes_to_binary, scalar_mul};
 ///   * `curve`: The elliptic curve to generate keys for.
 ///
 /// Returns:
-///   * A tuple of (private_key, public_key) represented as hexadecimal strings.
+///   * A tuple of (private_key, public_key) represented as hexadecimal strings. The private key
+///     is 32 bytes in [1, n - 1]; the public key is the 65-byte uncompressed encoding
+///     `04 || x || y`.
 pub fn generate_key_pair(curve: Curve) -> (String, String) {
-    let (hex_pk, ecc_point) = match curve {
-        Curve::Secp256k1 => {
-            let mut secret_key = [0u8; 32];
-            OsRng.fill_bytes(&mut secret_key);
-
-            let mut bytes_key: Vec<u8> = Vec::with_capacity(32);
-            bytes_to_binary(&secret_key, &mut bytes_key);
-
-            let secp256k1 = SECP256K1::default();
-            (
-                hex::encode(secret_key),
-                scalar_mul(&bytes_key, &secp256k1.g, &secp256k1),
-            )
-        }
+    let secret_key = SecretKey::generate(curve, &mut OsRng);
+
+    key_pair_from_secret(curve, &secret_key)
+}
+
+/// Derives the hex-encoded key pair for `secret_key`.
+fn key_pair_from_secret(curve: Curve, secret_key: &SecretKey) -> (String, String) {
+    // The scalar is secret, so use the constant-time ladder rather than the
+    // table-driven `mul_g_fast`, whose running time depends on `k`.
+    let ecc_point = match curve {
+        Curve::Secp256k1 => SECP256K1::default().mul_g(secret_key.value()),
     };
 
-    // Convert the resulting EccPoint to a hexadecimal string for the uncompressed public key.
     let uncompressed_pub_key = match ecc_point {
-        EccPoint::Finite(p) => format!("{}{}", p.0.to_str_radix(16), p.1.to_str_radix(16)),
+        EccPoint::Finite(p) => p.to_hex_string(),
         _ => panic!("Failed to generate public key"),
     };
 
-    (hex_pk, uncompressed_pub_key)
+    (hex::encode(secret_key.to_bytes()), uncompressed_pub_key)
 }
 
 #[cfg(test)]
@@ -61,6 +68,65 @@ mod tests {
 
         let secp256k1_extern_uncompressed_pub_key = hex::encode(pub_key.serialize_uncompressed());
 
-        assert!(format!("04{}", uncompressed_pub_key) == secp256k1_extern_uncompressed_pub_key);
+        assert!(uncompressed_pub_key == secp256k1_extern_uncompressed_pub_key);
+    }
+
+    #[test]
+    fn scalars_with_leading_zero_bits() {
+        let secp256k1_extern = Secp256k1::signing_only();
+
+        let mut short = [0u8; 32];
+        short[31] = 0x05;
+        let mut alternating = [0u8; 32];
+        alternating[1..].fill(0x01);
+
+        for secret in [[0x01; 32], [0x0f; 32], short, alternating] {
+            let secret_key = SecretKey::from_slice(&secret).unwrap();
+            let pub_key = PublicKey::from_secret_key(&secp256k1_extern, &secret_key);
+
+            assert_eq!(
+                key_pair_from_secret(
+                    Curve::Secp256k1,
+                    &crate::SecretKey::from_bytes(&secret, Curve::Secp256k1).unwrap(),
+                )
+                .1,
+                hex::encode(pub_key.serialize_uncompressed())
+            );
+        }
+    }
+
+    #[test]
+    fn encodes_short_coordinates_at_full_width() {
+        let secp256k1_extern = Secp256k1::signing_only();
+
+        // Roughly one key in 128 has a coordinate with a leading zero byte.
+        let (secret, pub_key) = (1u32..)
+            .map(|i| {
+                let mut secret = [0u8; 32];
+                secret[28..].copy_from_slice(&i.to_be_bytes());
+                let secret_key = SecretKey::from_slice(&secret).unwrap();
+                (
+                    secret,
+                    PublicKey::from_secret_key(&secp256k1_extern, &secret_key),
+                )
+            })
+            .find(|(_, pub_key)| {
+                let bytes = pub_key.serialize_uncompressed();
+                bytes[1] == 0 || bytes[33] == 0
+            })
+            .unwrap();
+
+        let (priv_key, uncompressed_pub_key) = key_pair_from_secret(
+            Curve::Secp256k1,
+            &crate::SecretKey::from_bytes(&secret, Curve::Secp256k1).unwrap(),
+        );
+
+        assert_eq!(priv_key.len(), 64);
+        assert_eq!(uncompressed_pub_key.len(), 130);
+        assert_eq!(
+            uncompressed_pub_key,
+            hex::encode(pub_key.serialize_uncompressed())
+        );
+        assert!(PublicKey::from_str(&uncompressed_pub_key).is_ok());
     }
 }
@@ -0,0 +1,357 @@
+//! This module implements BIP-340 Schnorr signatures over secp256k1.
+//!
+//! Public keys are 32-byte x-coordinates standing for the curve point with
+//! that `x` and an even `y`; a secret key whose point has an odd `y` is
+//! negated before use. All hashes are tagged: `SHA256(SHA256(tag) ||
+//! SHA256(tag) || data)`, so a hash computed for one purpose is never valid
+//! for another.
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::Zero;
+use rand::{rngs::OsRng, RngCore};
+use sha_256::Sha256;
+
+use crate::definitions::{Curve, EccPoint, EllipticCurve, JacobianPoint, Point};
+use crate::secp256k1::SECP256K1;
+use crate::secret_key::SecretKey;
+use crate::util::wnaf_mul;
+
+// Tags of the three hashes of BIP-340.
+const TAG_AUX: &str = "BIP0340/aux";
+const TAG_NONCE: &str = "BIP0340/nonce";
+const TAG_CHALLENGE: &str = "BIP0340/challenge";
+
+/// Returns the x-only public key of `secret_key`.
+pub fn x_only_public_key(secret_key: &SecretKey) -> [u8; 32] {
+    x_bytes(&public_point(secret_key))
+}
+
+/// Signs a 32-byte message as specified by BIP-340.
+///
+/// # Arguments
+/// * `secret_key` - The signer's private key.
+/// * `msg32` - The message, usually a hash.
+/// * `aux` - 32 bytes of fresh auxiliary randomness, masked into the nonce
+///   derivation to protect against side channels; all zeros is still safe.
+///
+/// # Returns
+/// The 64-byte signature `bytes(R) || bytes(s)`.
+pub fn sign(secret_key: &SecretKey, msg32: &[u8; 32], aux: &[u8; 32]) -> [u8; 64] {
+    let ecc_curve = SECP256K1::default();
+    let order = order(&ecc_curve);
+
+    let p = public_point(secret_key);
+    let d = if has_even_y(&p) {
+        secret_key.value().clone()
+    } else {
+        &order - secret_key.value()
+    };
+    let p_bytes = x_bytes(&p);
+
+    let mut t = scalar_bytes(&d);
+    for (t, mask) in t.iter_mut().zip(tagged_hash(TAG_AUX, &[aux])) {
+        *t ^= mask;
+    }
+
+    let k = BigUint::from_bytes_be(&tagged_hash(TAG_NONCE, &[&t, &p_bytes, msg32])) % &order;
+    assert!(
+        !k.is_zero(),
+        "the nonce hash is a multiple of the group order"
+    );
+
+    let r = finite(ecc_curve.mul_g(&k));
+    let k = if has_even_y(&r) { k } else { &order - k };
+    let r_bytes = x_bytes(&r);
+
+    let e = challenge(&r_bytes, &p_bytes, msg32, &order);
+    let s = (k + e * d) % &order;
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&r_bytes);
+    signature[32..].copy_from_slice(&scalar_bytes(&s));
+
+    debug_assert!(verify(&p_bytes, msg32, &signature));
+    signature
+}
+
+/// Verifies a BIP-340 signature.
+///
+/// # Arguments
+/// * `xonly_pub` - The signer's x-only public key.
+/// * `msg32` - The signed message.
+/// * `signature` - The 64-byte signature.
+///
+/// # Returns
+/// `true` if the signature is valid; `false` also if the public key is not
+/// the x-coordinate of a curve point.
+pub fn verify(xonly_pub: &[u8; 32], msg32: &[u8; 32], signature: &[u8; 64]) -> bool {
+    let ecc_curve = SECP256K1::default();
+    let order = order(&ecc_curve);
+
+    let Some(p) = lift_x(xonly_pub) else {
+        return false;
+    };
+    let Some((r, s)) = split_signature(signature, &ecc_curve) else {
+        return false;
+    };
+
+    // R = s·G - e·P = s·G + (n - e)·P
+    let e = challenge(&signature[..32], xonly_pub, msg32, &order);
+    let r_point = ecc_curve.add_jacobian(
+        &JacobianPoint::from(&ecc_curve.mul_g_fast(&s)),
+        &JacobianPoint::from(&wnaf_mul(&(&order - e), &p, &ecc_curve)),
+    );
+
+    match ecc_curve.to_affine(&r_point) {
+        EccPoint::Finite(r_point) => has_even_y(&r_point) && r_point.0 == r,
+        EccPoint::Infinity => false,
+    }
+}
+
+/// Verifies several BIP-340 signatures at once.
+///
+/// With random coefficients `a_1 = 1, a_2, ..., a_u`, checks the single
+/// equation `(Σ a_i·s_i)·G = Σ a_i·R_i + Σ (a_i·e_i)·P_i`, which holds for
+/// all valid batches and fails, except with negligible probability, if any
+/// signature is invalid.
+///
+/// # Arguments
+/// * `batch` - The `(xonly_pub, msg32, signature)` triples to check.
+///
+/// # Returns
+/// `true` if every signature is valid; an empty batch is valid.
+pub fn verify_batch(batch: &[(&[u8; 32], &[u8; 32], &[u8; 64])]) -> bool {
+    let ecc_curve = SECP256K1::default();
+    let order = order(&ecc_curve);
+
+    let mut s_sum = BigUint::zero();
+    let mut rhs = JacobianPoint::infinity();
+
+    for (i, (xonly_pub, msg32, signature)) in batch.iter().enumerate() {
+        let Some(p) = lift_x(xonly_pub) else {
+            return false;
+        };
+        let Some((r, s)) = split_signature(signature, &ecc_curve) else {
+            return false;
+        };
+        let Some(r_point) = lift_x(&signature[..32].try_into().expect("32-byte half")) else {
+            return false;
+        };
+        debug_assert_eq!(r_point.0, r);
+
+        let a = if i == 0 {
+            BigUint::from(1u32)
+        } else {
+            random_coefficient(&order)
+        };
+        let e = challenge(&signature[..32], &xonly_pub[..], &msg32[..], &order);
+
+        s_sum = (s_sum + &a * s) % &order;
+        for term in [
+            wnaf_mul(&a, &r_point, &ecc_curve),
+            wnaf_mul(&(a * e % &order), &p, &ecc_curve),
+        ] {
+            rhs = ecc_curve.add_jacobian(&rhs, &JacobianPoint::from(&term));
+        }
+    }
+
+    ecc_curve.mul_g_fast(&s_sum) == ecc_curve.to_affine(&rhs)
+}
+
+/// Computes the tagged hash `SHA256(SHA256(tag) || SHA256(tag) || data)`.
+///
+/// # Arguments
+/// * `tag` - The tag naming the purpose of the hash.
+/// * `data` - The parts of the message, hashed as their concatenation.
+pub fn tagged_hash(tag: &str, data: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new_tagged(tag);
+    for part in data {
+        hasher.update(part);
+    }
+
+    hasher.finalize()
+}
+
+/// Returns the point with x-coordinate `x` and an even `y`, if there is one.
+fn lift_x(x: &[u8; 32]) -> Option<Point> {
+    let mut compressed = [0x02; 33];
+    compressed[1..].copy_from_slice(x);
+
+    Point::from_compressed(&compressed, Curve::Secp256k1).ok()
+}
+
+/// Parses `bytes(r) || bytes(s)`, requiring `r < p` and `s < n`.
+fn split_signature(signature: &[u8; 64], ecc_curve: &SECP256K1) -> Option<(BigInt, BigUint)> {
+    let r = BigInt::from_bytes_be(Sign::Plus, &signature[..32]);
+    let s = BigUint::from_bytes_be(&signature[32..]);
+
+    if r >= ecc_curve.p || BigInt::from(s.clone()) >= ecc_curve.n {
+        return None;
+    }
+
+    Some((r, s))
+}
+
+/// Computes the challenge `e = int(hash_challenge(bytes(R) || bytes(P) || m)) mod n`.
+fn challenge(r: &[u8], p: &[u8], msg32: &[u8], order: &BigUint) -> BigUint {
+    BigUint::from_bytes_be(&tagged_hash(TAG_CHALLENGE, &[r, p, msg32])) % order
+}
+
+/// Draws a uniformly random coefficient in [1, n - 1] for batch verification.
+fn random_coefficient(order: &BigUint) -> BigUint {
+    let mut bytes = [0u8; 32];
+
+    loop {
+        OsRng.fill_bytes(&mut bytes);
+        let a = BigUint::from_bytes_be(&bytes);
+
+        if !a.is_zero() && &a < order {
+            return a;
+        }
+    }
+}
+
+fn public_point(secret_key: &SecretKey) -> Point {
+    finite(SECP256K1::default().mul_g(secret_key.value()))
+}
+
+fn finite(ecc_point: EccPoint) -> Point {
+    match ecc_point {
+        EccPoint::Finite(point) => point,
+        // The group has prime order, so multiples by scalars in [1, n) are finite.
+        EccPoint::Infinity => unreachable!("non-zero multiple of a point of prime order"),
+    }
+}
+
+fn has_even_y(point: &Point) -> bool {
+    !point.1.bit(0)
+}
+
+fn x_bytes(point: &Point) -> [u8; 32] {
+    let mut x = [0u8; 32];
+    x.copy_from_slice(&point.to_compressed()[1..]);
+    x
+}
+
+fn scalar_bytes(scalar: &BigUint) -> [u8; 32] {
+    let bytes = scalar.to_bytes_be();
+    let mut encoded = [0u8; 32];
+    encoded[32 - bytes.len()..].copy_from_slice(&bytes);
+    encoded
+}
+
+fn order(ecc_curve: &SECP256K1) -> BigUint {
+    ecc_curve
+        .n
+        .to_biguint()
+        .expect("the group order is positive")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Rows 0 to 14 of the BIP-340 test vectors (test-vectors.csv): index,
+    // secret key, public key, aux_rand, message, signature, verification result.
+    const TEST_VECTORS: &str = "\
+0,0000000000000000000000000000000000000000000000000000000000000003,F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9,0000000000000000000000000000000000000000000000000000000000000000,0000000000000000000000000000000000000000000000000000000000000000,E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0,TRUE
+1,B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF,DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659,0000000000000000000000000000000000000000000000000000000000000001,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A,TRUE
+2,C90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B14E5C9,DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8,C87AA53824B4D7AE2EB035A2B5BBBCCC080E76CDC6D1692C4B0B62D798E6D906,7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C,5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7,TRUE
+3,0B432B2677937381AEF05BB02A66ECD012773062CF3FA2549E44F58ED2401710,25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517,FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF,FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF,7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3,TRUE
+4,,D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9,,4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703,00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4,TRUE
+5,,EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B,FALSE
+6,,DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A14602975563CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2,FALSE
+7,,DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD,FALSE
+8,,DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6,FALSE
+9,,DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,0000000000000000000000000000000000000000000000000000000000000000123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051,FALSE
+10,,DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,00000000000000000000000000000000000000000000000000000000000000017615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197,FALSE
+11,,DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B,FALSE
+12,,DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B,FALSE
+13,,DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141,FALSE
+14,,FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30,,243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89,6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B,FALSE";
+
+    fn decode<const N: usize>(field: &str) -> [u8; N] {
+        hex::decode(field)
+            .unwrap()
+            .try_into()
+            .expect("field of the expected length")
+    }
+
+    #[test]
+    fn bip340_test_vectors() {
+        for row in TEST_VECTORS.lines() {
+            let fields: Vec<&str> = row.split(',').collect();
+            let public_key = decode::<32>(fields[2]);
+            let msg = decode::<32>(fields[4]);
+            let signature = decode::<64>(fields[5]);
+            let valid = fields[6] == "TRUE";
+
+            if !fields[1].is_empty() {
+                let secret_key =
+                    SecretKey::from_bytes(&decode(fields[1]), Curve::Secp256k1).unwrap();
+
+                assert_eq!(
+                    x_only_public_key(&secret_key),
+                    public_key,
+                    "vector {}",
+                    fields[0]
+                );
+                assert_eq!(
+                    sign(&secret_key, &msg, &decode(fields[3])),
+                    signature,
+                    "vector {}",
+                    fields[0]
+                );
+            }
+
+            assert_eq!(
+                verify(&public_key, &msg, &signature),
+                valid,
+                "vector {}",
+                fields[0]
+            );
+            assert_eq!(
+                verify_batch(&[(&public_key, &msg, &signature)]),
+                valid,
+                "vector {}",
+                fields[0]
+            );
+        }
+    }
+
+    #[test]
+    fn batch_verification() {
+        let mut rng = StdRng::seed_from_u64(340);
+
+        let items: Vec<([u8; 32], [u8; 32], [u8; 64])> = (0..4)
+            .map(|_| {
+                let secret_key = SecretKey::generate(Curve::Secp256k1, &mut rng);
+                let mut msg = [0u8; 32];
+                rng.fill_bytes(&mut msg);
+
+                (
+                    x_only_public_key(&secret_key),
+                    msg,
+                    sign(&secret_key, &msg, &[0u8; 32]),
+                )
+            })
+            .collect();
+        let batch: Vec<_> = items.iter().map(|(p, m, s)| (p, m, s)).collect();
+
+        assert!(verify_batch(&batch));
+        assert!(verify_batch(&[]));
+
+        // One bad signature spoils the batch.
+        let mut tampered = items[2].2;
+        tampered[63] ^= 1;
+        let mut bad_batch = batch.clone();
+        bad_batch[2].2 = &tampered;
+        assert!(!verify_batch(&bad_batch));
+
+        // So does a signature moved to another message.
+        let mut swapped = batch;
+        swapped[0].1 = &items[1].1;
+        assert!(!verify_batch(&swapped));
+    }
+}
@@ -1,4 +1,6 @@
-use num_bigint::BigInt;
+use std::sync::OnceLock;
+
+use num_bigint::{BigInt, BigUint};
 use num_traits::{Num, Zero};
 
 use super::{definitions::*, util::*};
@@ -6,16 +8,54 @@ use super::{definitions::*, util::*};
 // Secp256k1 domain parameters
 pub const X: &str = "79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798";
 pub const Y: &str = "483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8";
-pub const N: &str = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F";
+pub const P: &str = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F";
 pub const A: &str = "0000000000000000000000000000000000000000000000000000000000000000";
 pub const B: &str = "0000000000000000000000000000000000000000000000000000000000000007";
+pub const N: &str = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141";
+pub const H: u32 = 1;
+
+// Width in bits of the scalar windows of the fixed-base generator table.
+const G_TABLE_WINDOW: u32 = 4;
+
+// Multiples of the secp256k1 generator, built on first use by `mul_g_fast`.
+static G_TABLE: OnceLock<GeneratorTable> = OnceLock::new();
+
+/// The fixed-base table for the secp256k1 generator: `windows[i][j]` is
+/// `(j + 1)·2^(4i)·G`, so `k·G` is the sum of one entry per 4-bit window of `k`.
+struct GeneratorTable {
+    windows: Vec<Vec<JacobianPoint>>,
+}
+
+impl GeneratorTable {
+    fn build() -> Self {
+        let curve = SECP256K1::default();
+        let entries = (1 << G_TABLE_WINDOW) - 1;
+
+        let mut base = JacobianPoint::from(&curve.g_point());
+        let mut windows = Vec::new();
+        for _ in 0..N.len() as u32 * 4 / G_TABLE_WINDOW {
+            let mut row = vec![base.clone()];
+            for j in 1..entries {
+                let next = curve.add_jacobian(&row[j - 1], &base);
+                row.push(next);
+            }
+
+            base = curve.add_jacobian(&row[entries - 1], &base);
+            windows.push(row);
+        }
+
+        Self { windows }
+    }
+}
 
 #[derive(PartialEq)]
 pub struct SECP256K1 {
     pub g: Point,
-    pub n: BigInt,
+    pub p: BigInt, // The prime of the field the coordinates live in.
     pub a: BigInt,
     pub b: BigInt,
+    pub n: BigInt, // The order of `g`.
+    pub h: BigInt, // The cofactor, the number of curve points divided by `n`.
 }
 
 impl Default for SECP256K1 {
@@ -26,18 +66,77 @@ impl Default for SECP256K1 {
         let y: BigInt =
             BigInt::from_str_radix(Y, 16).expect("Failed to parse Secp256k1-generator-y");
 
-        let n: BigInt =
-            BigInt::from_str_radix(N, 16).expect("Failed to parse Secp256k1-group-order");
+        let p: BigInt =
+            BigInt::from_str_radix(P, 16).expect("Failed to parse Secp256k1-field-prime");
 
         let a: BigInt = BigInt::from_str_radix(A, 16).expect("Failed to parse Secp256k1-a");
 
         let b: BigInt = BigInt::from_str_radix(B, 16).expect("Failed to parse Secp256k1-b");
 
+        let n: BigInt =
+            BigInt::from_str_radix(N, 16).expect("Failed to parse Secp256k1-group-order");
+
         Self {
             g: Point(x, y),
-            n,
+            p,
             a,
             b,
+            n,
+            h: BigInt::from(H),
+        }
+    }
+}
+
+impl SECP256K1 {
+    /// Returns the generator `G` as an `EccPoint`.
+    pub fn g_point(&self) -> EccPoint {
+        EccPoint::Finite(self.g.clone())
+    }
+
+    /// Computes `k·G`.
+    pub fn mul_g(&self, k: &BigUint) -> EccPoint {
+        scalar_mul(k, &self.g, self)
+    }
+
+    /// Computes `k·G` from a precomputed table of multiples of the secp256k1
+    /// generator: one addition per non-zero 4-bit window of `k` and no doublings.
+    ///
+    /// The table is built once per process, on first use. For a curve with
+    /// another generator this falls back to `wnaf_mul`. The running time
+    /// depends on `k`.
+    ///
+    /// # Arguments
+    /// * `k` - The scalar; it is reduced modulo `n` first.
+    ///
+    /// # Returns
+    /// `k·G` with coordinates in `[0, p)`.
+    pub fn mul_g_fast(&self, k: &BigUint) -> EccPoint {
+        let standard = SECP256K1::default();
+        if self.g != standard.g || self.p != standard.p || self.a != standard.a {
+            return wnaf_mul(k, &self.g, self);
+        }
+
+        let table = G_TABLE.get_or_init(GeneratorTable::build);
+        let k = k % self.n.magnitude();
+
+        let mut r = JacobianPoint::infinity();
+        for (i, window) in k.to_radix_le(1 << G_TABLE_WINDOW).into_iter().enumerate() {
+            if window != 0 {
+                r = self.add_jacobian(&r, &table.windows[i][window as usize - 1]);
+            }
+        }
+
+        self.to_affine(&r)
+    }
+
+    /// Negates a point: `-(x, y) = (x, -y mod p)`, and `-∞ = ∞`.
+    pub fn neg(&self, ecc_point: &EccPoint) -> EccPoint {
+        match ecc_point {
+            EccPoint::Finite(point) => EccPoint::Finite(Point(
+                reduce(&point.0, &self.p),
+                reduce(&-&point.1, &self.p),
+            )),
+            EccPoint::Infinity => EccPoint::Infinity,
         }
     }
 }
@@ -51,7 +150,7 @@ impl EllipticCurve for SECP256K1 {
     ///
     /// # Arguments
     /// * `ecc_point` - A reference to `EccPoint`, which can either be a finite point
-    ///                 on the curve or the point at infinity.
+    ///   on the curve or the point at infinity.
     ///
     /// # Returns
     /// Returns `EccPoint`, which is either:
@@ -62,19 +161,20 @@ impl EllipticCurve for SECP256K1 {
     fn double_point(&self, ecc_point: &EccPoint) -> EccPoint {
         match ecc_point {
             EccPoint::Finite(point) => {
-                if point.1.is_zero() {
+                let x = reduce(&point.0, &self.p);
+                let y = reduce(&point.1, &self.p);
+
+                if y.is_zero() {
                     return EccPoint::Infinity;
                 }
 
-                let numerator = (BigInt::from(3u32) * (point.0).pow(2) + &self.a) % &self.n;
-
-                let denominator = BigInt::from(2u32) * &point.1;
+                let numerator = reduce(&(BigInt::from(3u32) * x.pow(2) + &self.a), &self.p);
+                let denominator = reduce(&(BigInt::from(2u32) * &y), &self.p);
 
                 // Slope
-                let lambda = (numerator * mod_inv(&denominator, &self.n)) % &self.n;
+                let lambda = reduce(&(numerator * mod_inv(&denominator, &self.p)), &self.p);
 
-                let (x3, y3) =
-                    derive_new_point_coordinates(&lambda, &point.0, &point.0, &point.1, &self.n);
+                let (x3, y3) = derive_new_point_coordinates(&lambda, &x, &x, &y, &self.p);
 
                 EccPoint::Finite(Point(x3, y3))
             }
@@ -83,10 +183,151 @@ impl EllipticCurve for SECP256K1 {
         }
     }
 
+    /// Checks whether a point lies on the curve, i.e. y^2 ≡ x^3 + a·x + b (mod p).
+    ///
+    /// Points from outside the library must be checked before any arithmetic:
+    /// the formulas never use `b`, so on an off-curve point they silently compute
+    /// on a different, possibly weak, curve (invalid-curve attacks).
+    ///
+    /// # Arguments
+    /// * `ecc_point` - The point to check; its coordinates are reduced modulo `p` first.
+    ///
+    /// # Returns
+    /// `true` for the point at infinity and for finite points satisfying the equation.
+    fn is_on_curve(&self, ecc_point: &EccPoint) -> bool {
+        match ecc_point {
+            EccPoint::Finite(point) => {
+                let x = reduce(&point.0, &self.p);
+                let y = reduce(&point.1, &self.p);
+
+                reduce(&(&y * &y - x.pow(3) - &self.a * &x - &self.b), &self.p).is_zero()
+            }
+            EccPoint::Infinity => true,
+        }
+    }
+
+    /// Adds two points in Jacobian coordinates (the "add-1998-cmo-2" formulas).
+    ///
+    /// Unlike `add_points`, this handles `p1 == p2` by doubling, so it is a
+    /// complete addition on the group. The point at infinity is handled by
+    /// computing the generic sum and selecting the result without branching,
+    /// since in the ladder an operand is infinity exactly while the leading
+    /// zero bits of the secret scalar are processed. The only branch is on
+    /// equal `x`, which needs operands that are equal or inverses. The ladder
+    /// keeps its operands `P` apart, so they are never equal, but they are
+    /// inverses when the bits processed so far form `(n - 1)/2`. For a scalar
+    /// below `n` that happens only in the last step of `k = n - 1`, where the
+    /// branch returns infinity and the timing differs.
+    ///
+    /// # Arguments
+    /// * `p1` - The first point as `JacobianPoint`.
+    /// * `p2` - The second point as `JacobianPoint`.
+    ///
+    /// # Returns
+    /// The sum as `JacobianPoint`, with coordinates reduced modulo `p`.
+    fn add_jacobian(&self, p1: &JacobianPoint, p2: &JacobianPoint) -> JacobianPoint {
+        let p1_infinity = p1.is_infinity();
+        let p2_infinity = p2.is_infinity();
+
+        let z1z1 = &p1.z * &p1.z % &self.p;
+        let z2z2 = &p2.z * &p2.z % &self.p;
+        let u1 = &p1.x * &z2z2 % &self.p;
+        let u2 = &p2.x * &z1z1 % &self.p;
+        let s1 = &p1.y * &p2.z * &z2z2 % &self.p;
+        let s2 = &p2.y * &p1.z * &z1z1 % &self.p;
+
+        let h = reduce(&(u2 - &u1), &self.p);
+        let r = reduce(&(s2 - &s1), &self.p);
+
+        // Same affine x: the points are either equal or inverses of each other.
+        if h.is_zero() & !p1_infinity & !p2_infinity {
+            return if r.is_zero() {
+                self.double_jacobian(p1)
+            } else {
+                JacobianPoint::infinity()
+            };
+        }
+
+        let hh = &h * &h % &self.p;
+        let hhh = &h * &hh % &self.p;
+        let v = u1 * hh % &self.p;
+
+        let x3 = reduce(&(&r * &r - &hhh - BigInt::from(2u32) * &v), &self.p);
+        let y3 = reduce(&(r * (v - &x3) - s1 * hhh), &self.p);
+        let z3 = &p1.z * &p2.z * h % &self.p;
+
+        let sum = JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        };
+
+        let sum = conditional_select(p2_infinity, p1, &sum);
+        conditional_select(p1_infinity, p2, &sum)
+    }
+
+    /// Doubles a point in Jacobian coordinates for a curve with any `a`.
+    ///
+    /// There is no early return: the point at infinity and points with `y = 0`
+    /// come out with `Z3 = 2·Y·Z = 0`, i.e. as the point at infinity.
+    ///
+    /// # Arguments
+    /// * `ecc_point` - The point to double as `JacobianPoint`.
+    ///
+    /// # Returns
+    /// `2·ecc_point` as `JacobianPoint`, which has `Z = 0` when the input
+    /// is infinity or has `y = 0`.
+    fn double_jacobian(&self, ecc_point: &JacobianPoint) -> JacobianPoint {
+        let y = reduce(&ecc_point.y, &self.p);
+
+        let xx = &ecc_point.x * &ecc_point.x % &self.p;
+        let yy = &y * &y % &self.p;
+        let zz = &ecc_point.z * &ecc_point.z % &self.p;
+
+        // S = 4·X·Y^2 and M = 3·X^2 + a·Z^4.
+        let s = BigInt::from(4u32) * &ecc_point.x * &yy % &self.p;
+        let m = (BigInt::from(3u32) * xx + &self.a * &zz * &zz) % &self.p;
+
+        let x3 = reduce(&(&m * &m - BigInt::from(2u32) * &s), &self.p);
+        let y3 = reduce(&(m * (s - &x3) - BigInt::from(8u32) * &yy * &yy), &self.p);
+        let z3 = reduce(&(BigInt::from(2u32) * y * &ecc_point.z), &self.p);
+
+        JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Converts a Jacobian point to affine coordinates with one field inversion.
+    ///
+    /// # Arguments
+    /// * `ecc_point` - The point to convert.
+    ///
+    /// # Returns
+    /// `EccPoint::Finite((X / Z^2, Y / Z^3))` reduced into `[0, p)`, or
+    /// `EccPoint::Infinity` when `Z = 0`.
+    fn to_affine(&self, ecc_point: &JacobianPoint) -> EccPoint {
+        let z = reduce(&ecc_point.z, &self.p);
+        if z.is_zero() {
+            return EccPoint::Infinity;
+        }
+
+        let z_inv = mod_inv(&z, &self.p);
+        let z_inv2 = &z_inv * &z_inv % &self.p;
+        let z_inv3 = &z_inv2 * &z_inv % &self.p;
+
+        EccPoint::Finite(Point(
+            reduce(&(&ecc_point.x * z_inv2), &self.p),
+            reduce(&(&ecc_point.y * z_inv3), &self.p),
+        ))
+    }
+
     /// Adds two points on an elliptic curve.
     ///
     /// Handles the addition of finite points and points at infinity. If the points are inverses,
-    /// returns the point at infinity.
+    /// returns the point at infinity; if they are equal, doubles the point. Inputs may have
+    /// coordinates outside [0, p), including negative ones; the result is always reduced.
     ///
     /// # Arguments
     /// * `p1` - The first point as `EccPoint`.
@@ -97,33 +338,44 @@ impl EllipticCurve for SECP256K1 {
     fn add_points(&self, p1: &EccPoint, p2: &EccPoint) -> EccPoint {
         match (p1, p2) {
             (EccPoint::Finite(p1), EccPoint::Finite(p2)) => {
-                // If `p1` and `p2` are inverse or symmetric over the x-axis,
-                // then adding both points will result in the point at infinity.
-                // Also, if `x1 == x2`, then it means that the line intersecting the two points is vertical.
-                // For elliptic curves, this means that the points `P` and `Q`` add up to the point at infinity,
-                // as there is no third intersection point with the curve.
-                if points_inverse(p1, p2) || p2.0 == p1.0 {
-                    return EccPoint::Infinity;
+                // Coordinates are reduced first, so that equal points compare equal
+                // and every intermediate value below lies in [0, p).
+                let (x1, y1) = (reduce(&p1.0, &self.p), reduce(&p1.1, &self.p));
+                let (x2, y2) = (reduce(&p2.0, &self.p), reduce(&p2.1, &self.p));
+
+                // If `x1 == x2`, the points are either equal or symmetric over the x-axis.
+                // Equal points are doubled. For inverse points the line through them is
+                // vertical, with no third intersection point with the curve, so they add
+                // up to the point at infinity.
+                if x1 == x2 {
+                    return if y1 == y2 {
+                        self.double_point(&EccPoint::Finite(Point(x1, y1)))
+                    } else {
+                        EccPoint::Infinity
+                    };
                 }
 
-                let numerator = (&p2.1 - &p1.1) % &self.n;
-                let denominator = &p2.0 - &p1.0;
-                let lambda = (numerator * mod_inv(&denominator, &self.n)) % &self.n;
+                let numerator = reduce(&(&y2 - &y1), &self.p);
+                let denominator = reduce(&(&x2 - &x1), &self.p);
+                let lambda = reduce(&(numerator * mod_inv(&denominator, &self.p)), &self.p);
 
-                let (x3, y3) = derive_new_point_coordinates(&lambda, &p1.0, &p2.0, &p1.1, &self.n);
+                let (x3, y3) = derive_new_point_coordinates(&lambda, &x1, &x2, &y1, &self.p);
 
                 EccPoint::Finite(Point(x3, y3))
             }
-            (EccPoint::Finite(p1), EccPoint::Infinity) => EccPoint::Finite(p1.clone()),
-            (EccPoint::Infinity, EccPoint::Finite(p2)) => EccPoint::Finite(p2.clone()),
-            _ => EccPoint::Infinity,
+            (finite, EccPoint::Infinity) | (EccPoint::Infinity, finite) => finite.clone(),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Barrier;
+    use std::thread;
+
+    use ::secp256k1::{PublicKey, Secp256k1, SecretKey};
     use lazy_static::lazy_static;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
 
     use super::*;
 
@@ -131,9 +383,11 @@ mod tests {
         static ref SECP256K1_CURVE: SECP256K1 = SECP256K1::default();
         static ref MOCK_SECP256K1_CURVE: SECP256K1 = SECP256K1 {
             g: Point(BigInt::from(5i32), BigInt::from(1i32),),
-            n: BigInt::from(17i32),
+            p: BigInt::from(17i32),
             a: BigInt::from(2i32),
-            b: BigInt::from(2i32)
+            b: BigInt::from(2i32),
+            n: BigInt::from(19i32),
+            h: BigInt::from(1i32),
         };
     }
 
@@ -173,10 +427,90 @@ mod tests {
         assert!(new_point == EccPoint::Infinity);
     }
 
+    #[test]
+    fn add_points_reduces_intermediates() {
+        let curve = &*MOCK_SECP256K1_CURVE;
+        let multiples: Vec<EccPoint> = (0..19u32)
+            .map(|k| scalar_mul_affine(&BigUint::from(k), &curve.g, curve))
+            .collect();
+
+        // Every pair of multiples of G, which includes pairs with x2 < x1 and
+        // y2 < y1, equal points and inverse points.
+        for i in 1..19 {
+            for j in 1..19 {
+                assert_eq!(
+                    curve.add_points(&multiples[i], &multiples[j]),
+                    multiples[(i + j) % 19],
+                    "{}·G + {}·G",
+                    i,
+                    j
+                );
+            }
+        }
+
+        // Non-canonical and negative coordinates give the same, canonical, result.
+        let p = &curve.p;
+        for (i, j) in [(3, 5), (7, 2), (4, 4)] {
+            let (EccPoint::Finite(a), EccPoint::Finite(b)) = (&multiples[i], &multiples[j]) else {
+                unreachable!()
+            };
+            let shifted_a = EccPoint::Finite(Point(&a.0 - p, &a.1 + p));
+            let shifted_b = EccPoint::Finite(Point(&b.0 - 2 * p, &b.1 - 3 * p));
+
+            assert_eq!(
+                curve.add_points(&shifted_a, &shifted_b),
+                multiples[(i + j) % 19]
+            );
+            assert_eq!(curve.double_point(&shifted_a), multiples[2 * i % 19]);
+        }
+    }
+
+    #[test]
+    fn add_points_matches_reference_crate() {
+        let curve = &*SECP256K1_CURVE;
+        let secp256k1_extern = Secp256k1::signing_only();
+        let mut rng = StdRng::seed_from_u64(3397);
+
+        let keys: Vec<(PublicKey, Point)> = (0..8)
+            .map(|_| {
+                let mut secret = [0u8; 32];
+                rng.fill_bytes(&mut secret);
+                let secret_key = SecretKey::from_slice(&secret).expect("within curve order");
+                let public_key = PublicKey::from_secret_key(&secp256k1_extern, &secret_key);
+                let point = Point::from_uncompressed(
+                    &public_key.serialize_uncompressed(),
+                    Curve::Secp256k1,
+                )
+                .unwrap();
+                (public_key, point)
+            })
+            .collect();
+
+        let mut both_decreasing = false;
+        for (key_1, p1) in &keys {
+            for (key_2, p2) in &keys {
+                both_decreasing |= p2.0 < p1.0 && p2.1 < p1.1;
+
+                let sum =
+                    curve.add_points(&EccPoint::Finite(p1.clone()), &EccPoint::Finite(p2.clone()));
+                let EccPoint::Finite(sum) = sum else {
+                    panic!("random keys summed to infinity")
+                };
+
+                assert_eq!(
+                    sum.to_uncompressed(),
+                    key_1.combine(key_2).unwrap().serialize_uncompressed()
+                );
+            }
+        }
+
+        assert!(both_decreasing);
+    }
+
     #[test]
     fn scalar_mul_test() {
         let mut new_point = scalar_mul(
-            &[1, 1, 1, 1],
+            &BigUint::from(0b1111u32),
             &Point(BigInt::from(5i32), BigInt::from(1i32)),
             &*MOCK_SECP256K1_CURVE,
         );
@@ -184,11 +518,161 @@ mod tests {
         assert!(new_point == EccPoint::Finite(Point(BigInt::from(3i32), BigInt::from(16i32))));
 
         new_point = scalar_mul(
-            &[1, 0, 0, 1, 1],
+            &BigUint::from(0b10011u32),
             &Point(BigInt::from(5i32), BigInt::from(1i32)),
             &*MOCK_SECP256K1_CURVE,
         );
 
         assert!(new_point == EccPoint::Infinity);
     }
+
+    #[test]
+    fn is_on_curve_test() {
+        let g = SECP256K1_CURVE.g.clone();
+        assert!(SECP256K1_CURVE.is_on_curve(&EccPoint::Finite(g.clone())));
+        assert!(SECP256K1_CURVE.is_on_curve(&EccPoint::Infinity));
+
+        let off_curve = Point(g.0.clone(), &g.1 + 1);
+        assert!(!SECP256K1_CURVE.is_on_curve(&EccPoint::Finite(off_curve)));
+
+        // Coordinates are compared modulo p.
+        let shifted = Point(&g.0 + &SECP256K1_CURVE.p, &g.1 - &SECP256K1_CURVE.p);
+        assert!(SECP256K1_CURVE.is_on_curve(&EccPoint::Finite(shifted)));
+
+        assert!(MOCK_SECP256K1_CURVE.is_on_curve(&EccPoint::Finite(Point(
+            BigInt::from(5i32),
+            BigInt::from(1i32)
+        ))));
+        assert!(!MOCK_SECP256K1_CURVE.is_on_curve(&EccPoint::Finite(Point(
+            BigInt::from(5i32),
+            BigInt::from(2i32)
+        ))));
+    }
+
+    #[test]
+    fn point_helpers() {
+        let curve = &*SECP256K1_CURVE;
+        let g = curve.g_point();
+        let two_g = curve.mul_g(&BigUint::from(2u32));
+
+        // 2·P and P + Q.
+        assert_eq!(curve.double_point(&g), two_g);
+        assert_eq!(
+            curve.add_points(&g, &two_g),
+            curve.mul_g(&BigUint::from(3u32))
+        );
+
+        // P + (-P) = ∞, and n·G = ∞.
+        assert_eq!(
+            curve.add_points(&two_g, &curve.neg(&two_g)),
+            EccPoint::Infinity
+        );
+        assert!(curve.is_on_curve(&curve.neg(&two_g)));
+        assert_eq!(curve.neg(&EccPoint::Infinity), EccPoint::Infinity);
+        assert_eq!(
+            curve.mul_g(&curve.n.to_biguint().unwrap()),
+            EccPoint::Infinity
+        );
+        assert_eq!(
+            curve.mul_g(&(curve.n.to_biguint().unwrap() - 1u32)),
+            curve.neg(&g)
+        );
+    }
+
+    fn random_scalar(rng: &mut StdRng, n: &BigUint) -> BigUint {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        BigUint::from_bytes_be(&bytes) % n
+    }
+
+    #[test]
+    fn mul_g_fast_matches_ladder() {
+        let curve = &*SECP256K1_CURVE;
+        let n = curve.n.to_biguint().unwrap();
+
+        let mut scalars: Vec<BigUint> = [0u32, 1, 2, 15, 16, 17]
+            .into_iter()
+            .map(BigUint::from)
+            .collect();
+        scalars.extend([&n - 2u32, &n - 1u32, n.clone(), &n + 1u32]);
+
+        let mut rng = StdRng::seed_from_u64(3390);
+        scalars.extend((0..64).map(|_| random_scalar(&mut rng, &n)));
+
+        for k in &scalars {
+            let expected = curve.mul_g(k);
+            assert_eq!(curve.mul_g_fast(k), expected);
+            assert_eq!(wnaf_mul(k, &curve.g, curve), expected);
+        }
+
+        // The fast paths against each other, over many more scalars.
+        for _ in 0..1000 {
+            let k = random_scalar(&mut rng, &n);
+            assert_eq!(curve.mul_g_fast(&k), wnaf_mul(&k, &curve.g, curve));
+        }
+
+        // On the mock curve `mul_g_fast` cannot use the table.
+        for k in 0u32..64 {
+            let k = BigUint::from(k);
+            assert_eq!(
+                MOCK_SECP256K1_CURVE.mul_g_fast(&k),
+                MOCK_SECP256K1_CURVE.mul_g(&k)
+            );
+        }
+    }
+
+    #[test]
+    fn generator_table_is_built_once() {
+        let barrier = Barrier::new(8);
+
+        let tables: Vec<&GeneratorTable> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..8u32)
+                .map(|i| {
+                    let barrier = &barrier;
+                    scope.spawn(move || {
+                        barrier.wait();
+                        let k = BigUint::from(i + 1);
+                        assert_eq!(SECP256K1_CURVE.mul_g_fast(&k), SECP256K1_CURVE.mul_g(&k));
+                        G_TABLE.get().expect("table built by mul_g_fast")
+                    })
+                })
+                .collect();
+
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        // Every thread sees the one table that won the race to be built.
+        assert!(tables.iter().all(|table| std::ptr::eq(*table, tables[0])));
+    }
+
+    #[test]
+    fn ladder_handles_inverse_operands() {
+        // For k = n - 1 the last ladder step adds ((n - 1)/2)·G and
+        // ((n + 1)/2)·G, which are inverses; for k = n the sum is the result.
+        let curve = &*SECP256K1_CURVE;
+        let n = curve.n.to_biguint().unwrap();
+
+        assert_eq!(curve.mul_g(&(&n - 1u32)), curve.neg(&curve.g_point()));
+        assert_eq!(curve.mul_g(&n), EccPoint::Infinity);
+    }
+
+    #[test]
+    fn generator_has_group_order() {
+        let n = SECP256K1_CURVE.n.to_biguint().unwrap();
+        let g = &SECP256K1_CURVE.g;
+
+        assert_eq!(scalar_mul(&n, g, &*SECP256K1_CURVE), EccPoint::Infinity);
+        assert_eq!(
+            scalar_mul(&(&n + 1u32), g, &*SECP256K1_CURVE),
+            EccPoint::Finite(g.clone())
+        );
+        assert_eq!(SECP256K1_CURVE.h, BigInt::from(1u32));
+
+        // The mock curve has 19 points, all multiples of its generator.
+        let mock_n = MOCK_SECP256K1_CURVE.n.to_biguint().unwrap();
+        assert_eq!(
+            scalar_mul(&mock_n, &MOCK_SECP256K1_CURVE.g, &*MOCK_SECP256K1_CURVE),
+            EccPoint::Infinity
+        );
+    }
 }
@@ -0,0 +1,204 @@
+//! This module defines private keys, which must lie in [1, n - 1] for the
+//! group order `n`.
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+use rand::{CryptoRng, RngCore};
+use utils::{mac::ct_eq, wipe::wipe_biguint};
+
+use crate::definitions::Curve;
+use crate::error::EccError;
+use crate::secp256k1::SECP256K1;
+
+/// A private key, guaranteed to lie in [1, n - 1].
+///
+/// The key is wiped when it is dropped. `Debug` redacts it and `==` compares
+/// the encodings in constant time.
+pub struct SecretKey(BigUint);
+
+impl SecretKey {
+    /// Parses a private key from its 32-byte big-endian encoding.
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded key.
+    /// * `curve` - The curve the key belongs to.
+    ///
+    /// # Returns
+    /// The key, or `EccError::InvalidSecretKey` if it is 0 or at least the group order.
+    pub fn from_bytes(bytes: &[u8; 32], curve: Curve) -> Result<Self, EccError> {
+        let order = match curve {
+            Curve::Secp256k1 => SECP256K1::default().n,
+        };
+
+        let value = BigUint::from_bytes_be(bytes);
+        if value.is_zero() || &value >= order.magnitude() {
+            return Err(EccError::InvalidSecretKey);
+        }
+
+        Ok(SecretKey(value))
+    }
+
+    /// Draws a uniformly random private key by rejection sampling: 32-byte
+    /// candidates are drawn until one lies in [1, n - 1].
+    ///
+    /// For secp256k1 a candidate is rejected with probability below 2^-127.
+    ///
+    /// # Arguments
+    /// * `curve` - The curve the key belongs to.
+    /// * `rng` - The source of randomness.
+    pub fn generate<R: RngCore + CryptoRng + ?Sized>(curve: Curve, rng: &mut R) -> Self {
+        let mut bytes = [0u8; 32];
+
+        loop {
+            rng.fill_bytes(&mut bytes);
+
+            if let Ok(secret_key) = SecretKey::from_bytes(&bytes, curve) {
+                return secret_key;
+            }
+        }
+    }
+
+    /// Returns the key as an integer.
+    pub fn value(&self) -> &BigUint {
+        &self.0
+    }
+
+    /// Returns the 32-byte big-endian encoding of the key.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let bytes = self.0.to_bytes_be();
+        let mut encoded = [0u8; 32];
+        encoded[32 - bytes.len()..].copy_from_slice(&bytes);
+        encoded
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.to_bytes(), &other.to_bytes())
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(<redacted>)")
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        wipe_biguint(&mut self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::wipe::{biguint_pattern, freed_with, WatchingAllocator};
+
+    #[global_allocator]
+    static ALLOCATOR: WatchingAllocator = WatchingAllocator;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn order_minus(offset: u32) -> BigUint {
+        SECP256K1::default().n.to_biguint().unwrap() - offset
+    }
+
+    fn encode(value: BigUint) -> [u8; 32] {
+        value.to_bytes_be().try_into().unwrap()
+    }
+
+    #[test]
+    fn rejects_out_of_range_keys() {
+        for bytes in [
+            [0u8; 32],
+            encode(order_minus(0)),
+            encode(order_minus(0) + 5u32),
+            [0xff; 32],
+        ] {
+            assert_eq!(
+                SecretKey::from_bytes(&bytes, Curve::Secp256k1),
+                Err(EccError::InvalidSecretKey)
+            );
+        }
+
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        for bytes in [one, encode(order_minus(1))] {
+            assert_eq!(
+                SecretKey::from_bytes(&bytes, Curve::Secp256k1).map(|key| key.to_bytes()),
+                Ok(bytes)
+            );
+        }
+    }
+
+    #[test]
+    fn generation_skips_out_of_range_draws() {
+        // Hands out the all-zero and all-one candidates first, then a seeded stream.
+        struct Rigged(Vec<[u8; 32]>, StdRng);
+
+        impl RngCore for Rigged {
+            fn next_u32(&mut self) -> u32 {
+                self.1.next_u32()
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                self.1.next_u64()
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                match self.0.pop() {
+                    Some(candidate) => dest.copy_from_slice(&candidate),
+                    None => self.1.fill_bytes(dest),
+                }
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+
+        impl CryptoRng for Rigged {}
+
+        let mut rng = Rigged(
+            vec![encode(order_minus(0) + 5u32), [0u8; 32]],
+            StdRng::seed_from_u64(3386),
+        );
+        let secret_key = SecretKey::generate(Curve::Secp256k1, &mut rng);
+
+        let mut expected = [0u8; 32];
+        StdRng::seed_from_u64(3386).fill_bytes(&mut expected);
+
+        assert!(rng.0.is_empty());
+        assert_eq!(secret_key.to_bytes(), expected);
+    }
+
+    #[test]
+    fn generates_from_ctr_drbg() {
+        use aes::ctr_drbg::{CtrDrbg, SEED_SIZE};
+
+        let drbg = || CtrDrbg::instantiate(&[0x42; SEED_SIZE], Some(b"ecc keygen")).unwrap();
+        let first = SecretKey::generate(Curve::Secp256k1, &mut drbg());
+        let second = SecretKey::generate(Curve::Secp256k1, &mut drbg());
+
+        assert_eq!(first.to_bytes(), second.to_bytes());
+    }
+
+    #[test]
+    fn redacts_and_wipes_the_key() {
+        let key = || SecretKey::from_bytes(&[0x5c; 32], Curve::Secp256k1).unwrap();
+        let secret_key = key();
+
+        assert_eq!(format!("{secret_key:?}"), "SecretKey(<redacted>)");
+        assert_eq!(secret_key, key());
+        assert_ne!(
+            secret_key,
+            SecretKey::from_bytes(&[0x5d; 32], Curve::Secp256k1).unwrap()
+        );
+
+        let pattern = biguint_pattern(secret_key.value());
+        assert!(!freed_with(&pattern, || drop(secret_key)));
+    }
+}
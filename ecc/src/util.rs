@@ -1,15 +1,34 @@
 use std::ops::Add;
 
-use num_bigint::BigInt;
+use num_bigint::{BigInt, BigUint};
 use num_traits::Zero;
 
-use crate::definitions::{EccPoint, EllipticCurve};
+use crate::definitions::{EccPoint, EllipticCurve, JacobianPoint};
 
 use super::definitions::Point;
 
+/// Minimum number of ladder steps in `scalar_mul`, the bit length of the secp256k1 group order.
+/// Shorter scalars are padded with leading zero bits so that their length does not show.
+pub const LADDER_BITS: u64 = 256;
+
+/// Width of the non-adjacent form used by `wnaf_mul`.
+pub const WNAF_WIDTH: u32 = 5;
+
 /// Calculates the modular inverse of `a` modulo `m` using a modified version of Fermat's theorem.
+///
+/// `a` may be negative or at least `m`; it is reduced into [0, m) first.
 pub fn mod_inv(a: &BigInt, m: &BigInt) -> BigInt {
-    a.modpow(&(m - BigInt::from(2i32)), m)
+    reduce(a, m).modpow(&(m - BigInt::from(2i32)), m)
+}
+
+/// Reduces `a` into the range [0, m).
+pub fn reduce(a: &BigInt, m: &BigInt) -> BigInt {
+    let r = a % m;
+    if r < BigInt::zero() {
+        r + m
+    } else {
+        r
+    }
 }
 
 /// Checks if two points on an elliptic curve are inverses of each other.
@@ -24,7 +43,7 @@ pub fn points_inverse(a: &Point, b: &Point) -> bool {
 /// * `p1_x` - The x-coordinate of the first point.
 /// * `p2_x` - The x-coordinate of the second point or same as `p1_x` for doubling.
 /// * `p1_y` - The y-coordinate of the first point.
-/// * `n` - The modulus for the finite field.
+/// * `p` - The prime of the finite field.
 ///
 /// # Returns
 /// A tuple `(x3, y3)` representing the new point coordinates.
@@ -33,48 +52,110 @@ pub fn derive_new_point_coordinates(
     p1_x: &BigInt,
     p2_x: &BigInt,
     p1_y: &BigInt,
-    n: &BigInt,
+    p: &BigInt,
 ) -> (BigInt, BigInt) {
-    let mut x3 = (slope.pow(2) - (p1_x + p2_x)) % n;
+    let mut x3 = (slope.pow(2) - (p1_x + p2_x)) % p;
     if x3 < BigInt::zero() {
-        x3 += n;
+        x3 += p;
     }
 
-    let mut y3 = (slope * (p1_x - &x3) - p1_y) % n;
+    let mut y3 = (slope * (p1_x - &x3) - p1_y) % p;
     if y3 < BigInt::zero() {
-        y3 += n;
+        y3 += p;
     }
 
     (x3, y3)
 }
 
 /// Performs scalar multiplication on an elliptic curve using the Montgomery Ladder algorithm.
-/// This method is preferred for its resistance to side-channel attacks, providing consistent
-/// execution time and memory access patterns to protect against certain types of attacks.
+/// Every step performs one addition and one doubling, whatever the bit of `k`, and the number
+/// of steps is `LADDER_BITS` for any scalar of at most that many bits.
+///
+/// Instead of branching on each bit, the two ladder registers are exchanged with
+/// `conditional_swap` before and after the step, and the Jacobian formulas handle the point at
+/// infinity without early returns. This makes the sequence of curve operations independent of
+/// the scalar. `BigInt` arithmetic itself is not constant-time, so timing still leaks through
+/// the sizes of intermediate values; this removes the structural leaks only.
+///
+/// The ladder runs in Jacobian coordinates, so the only field inversion is the conversion of
+/// the result back to affine coordinates. `scalar_mul_affine` is the affine reference version.
 ///
 /// Arguments:
-///   * `k`: A reference to a vector of bytes representing the scalar value to multiply the point by.
-///          Each byte represents a part of the scalar, typically in big-endian order. This vector
-///          effectively represents the private key or scalar multiplier in binary form.
+///   * `k`: The scalar value to multiply the point by, typically the private key. Its bits are
+///     processed from the most significant one down.
 ///   * `p`: A reference to the point on the elliptic curve to be multiplied. This point should be
-///          a valid point on the provided curve.
+///     a valid point on the provided curve.
 ///   * `ecc_curve`: A reference to the elliptic curve being used, which must implement the
-///                  `EllipticCurve` trait.
+///     `EllipticCurve` trait.
 ///
 /// Returns:
 ///   * An `EccPoint` representing the result of scalar multiplication of `p` by `k` on the elliptic curve.
-///     The result is another point on the curve.
+///     The result is another point on the curve, with coordinates reduced modulo the field prime.
 ///
-/// Note: This function assumes that `k` is provided in a big-endian byte order and the most significant
-///        bit  is the leftmost bit of the first byte in the vector. Ensure that `k` and `p`
-///        are valid and that `p` is indeed a point on the provided elliptic curve.  Improper inputs
+/// Note: Ensure that `p` is indeed a point on the provided elliptic curve. Improper inputs
 ///        could lead to incorrect results or errors.
-pub fn scalar_mul(k: &[u8], p: &Point, ecc_curve: &impl EllipticCurve) -> EccPoint {
+pub fn scalar_mul(k: &BigUint, p: &Point, ecc_curve: &impl EllipticCurve) -> EccPoint {
+    let mut r_0 = JacobianPoint::infinity();
+    let mut r_1 = JacobianPoint::from(&EccPoint::Finite(p.clone()));
+
+    for i in (0..k.bits().max(LADDER_BITS)).rev() {
+        let bit = k.bit(i);
+
+        // With the registers swapped, the step computes (2·R1, R0 + R1) instead of
+        // (2·R0, R0 + R1); the second swap puts them back in place.
+        conditional_swap(bit, &mut r_0, &mut r_1);
+        r_1 = ecc_curve.add_jacobian(&r_0, &r_1);
+        r_0 = ecc_curve.double_jacobian(&r_0);
+        conditional_swap(bit, &mut r_0, &mut r_1);
+    }
+
+    ecc_curve.to_affine(&r_0)
+}
+
+/// Exchanges `a` and `b` if `choice` is set, with the same arithmetic either way.
+///
+/// Each coordinate pair is updated as `a += c·(b - a)` and `b -= c·(b - a)` for `c` in {0, 1}.
+pub fn conditional_swap(choice: bool, a: &mut JacobianPoint, b: &mut JacobianPoint) {
+    let c = BigInt::from(choice as u8);
+
+    for (a, b) in [
+        (&mut a.x, &mut b.x),
+        (&mut a.y, &mut b.y),
+        (&mut a.z, &mut b.z),
+    ] {
+        let delta = &c * (&*b - &*a);
+        *a += &delta;
+        *b -= &delta;
+    }
+}
+
+/// Returns a copy of `a` if `choice` is set and of `b` otherwise, without branching on `choice`.
+pub fn conditional_select(choice: bool, a: &JacobianPoint, b: &JacobianPoint) -> JacobianPoint {
+    let mut selected = b.clone();
+    let mut other = a.clone();
+    conditional_swap(choice, &mut selected, &mut other);
+
+    selected
+}
+
+/// Performs the same Montgomery Ladder as `scalar_mul` directly in affine coordinates.
+///
+/// Every step pays for a field inversion, which makes this version much slower; it is kept as
+/// the reference implementation the Jacobian arithmetic is checked against.
+///
+/// Arguments:
+///   * `k`: The scalar value to multiply the point by.
+///   * `p`: A reference to the point on the elliptic curve to be multiplied.
+///   * `ecc_curve`: A reference to the elliptic curve being used.
+///
+/// Returns:
+///   * An `EccPoint` representing `k·p`.
+pub fn scalar_mul_affine(k: &BigUint, p: &Point, ecc_curve: &impl EllipticCurve) -> EccPoint {
     let mut r_0 = EccPoint::Infinity;
     let mut r_1 = EccPoint::Finite(p.clone());
 
-    for &bit in k.iter() {
-        if bit == 0 {
+    for i in (0..k.bits()).rev() {
+        if !k.bit(i) {
             r_1 = ecc_curve.add_points(&r_0, &r_1);
             r_0 = ecc_curve.double_point(&r_0);
         } else {
@@ -86,22 +167,92 @@ pub fn scalar_mul(k: &[u8], p: &Point, ecc_curve: &impl EllipticCurve) -> EccPoi
     r_0
 }
 
-pub fn bytes_to_binary(i: &[u8; 32], r: &mut Vec<u8>) {
-    for m in i.iter() {
-        format!("{:8b}", m).chars().for_each(|b| {
-            if b == '1' {
-                r.push(1);
-            } else {
-                r.push(0)
+/// Performs scalar multiplication with the width-`WNAF_WIDTH` non-adjacent form of `k`.
+///
+/// The odd multiples `p, 3p, ..., (2^(w-1) - 1)p` are precomputed, after which every non-zero
+/// digit costs one addition; on average only one in `w + 1` digits is non-zero, against one
+/// addition per bit for the ladder. The running time depends on `k`, so this is meant for
+/// public scalars such as those of signature verification.
+///
+/// Arguments:
+///   * `k`: The scalar value to multiply the point by.
+///   * `p`: A reference to the point on the elliptic curve to be multiplied.
+///   * `ecc_curve`: A reference to the elliptic curve being used.
+///
+/// Returns:
+///   * An `EccPoint` representing `k·p`, with coordinates reduced modulo the field prime.
+pub fn wnaf_mul(k: &BigUint, p: &Point, ecc_curve: &impl EllipticCurve) -> EccPoint {
+    let base = JacobianPoint::from(&EccPoint::Finite(p.clone()));
+    let twice = ecc_curve.double_jacobian(&base);
+
+    // odd_multiples[i] = (2i + 1)·p
+    let mut odd_multiples = vec![base];
+    for i in 1..1 << (WNAF_WIDTH - 2) {
+        let next = ecc_curve.add_jacobian(&odd_multiples[i - 1], &twice);
+        odd_multiples.push(next);
+    }
+
+    let mut r = JacobianPoint::infinity();
+    for digit in wnaf_digits(k, WNAF_WIDTH).into_iter().rev() {
+        r = ecc_curve.double_jacobian(&r);
+
+        if digit > 0 {
+            r = ecc_curve.add_jacobian(&r, &odd_multiples[(digit / 2) as usize]);
+        } else if digit < 0 {
+            let multiple = &odd_multiples[(-digit / 2) as usize];
+            let negated = JacobianPoint {
+                x: multiple.x.clone(),
+                y: -&multiple.y,
+                z: multiple.z.clone(),
+            };
+            r = ecc_curve.add_jacobian(&r, &negated);
+        }
+    }
+
+    ecc_curve.to_affine(&r)
+}
+
+/// Computes the width-`w` non-adjacent form of `k`, least significant digit first.
+///
+/// Every digit is zero or odd with absolute value below `2^(w-1)`, and any `w` consecutive
+/// digits contain at most one non-zero one.
+pub fn wnaf_digits(k: &BigUint, w: u32) -> Vec<i8> {
+    let window = BigInt::from(1u32 << w);
+    let half = BigInt::from(1u32 << (w - 1));
+
+    let mut k = BigInt::from(k.clone());
+    let mut digits = Vec::with_capacity(k.bits() as usize + 1);
+
+    while !k.is_zero() {
+        let digit = if k.bit(0) {
+            let mut d = &k % &window;
+            if d >= half {
+                d -= &window;
             }
-        });
+            k -= &d;
+            i8::try_from(d).expect("digits are below 2^(w-1) in absolute value")
+        } else {
+            0
+        };
+
+        digits.push(digit);
+        k >>= 1;
     }
+
+    digits
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::cell::Cell;
+
+    use ::secp256k1::{PublicKey, Secp256k1, SecretKey};
+    use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+
+    use crate::secp256k1::SECP256K1;
+
     #[test]
     fn mod_inv_test() {
         let result = mod_inv(&BigInt::from(3i32), &BigInt::from(11i32));
@@ -122,4 +273,169 @@ mod tests {
 
         assert!(!is_inverse)
     }
+
+    #[test]
+    fn jacobian_matches_affine_on_small_curve() {
+        // y^2 = x^3 + 2x + 2 over F_17, a curve of order 19 with a != 0.
+        let curve = SECP256K1 {
+            g: Point(BigInt::from(5i32), BigInt::from(1i32)),
+            p: BigInt::from(17i32),
+            a: BigInt::from(2i32),
+            b: BigInt::from(2i32),
+            n: BigInt::from(19i32),
+            h: BigInt::from(1i32),
+        };
+
+        for k in 0u32..64 {
+            let k = BigUint::from(k);
+            assert_eq!(
+                scalar_mul(&k, &curve.g, &curve),
+                scalar_mul_affine(&k, &curve.g, &curve)
+            );
+        }
+    }
+
+    #[test]
+    fn jacobian_matches_affine_on_secp256k1() {
+        let curve = SECP256K1::default();
+        let mut rng = StdRng::seed_from_u64(3389);
+
+        // A random base point, so the check is not specific to G.
+        let base = match curve.mul_g(&BigUint::from(rng.gen::<u64>())) {
+            EccPoint::Finite(point) => point,
+            EccPoint::Infinity => unreachable!("a small non-zero multiple of G is finite"),
+        };
+
+        for _ in 0..24 {
+            let mut k = [0u8; 32];
+            rng.fill_bytes(&mut k);
+            let k = BigUint::from_bytes_be(&k);
+
+            assert_eq!(
+                scalar_mul(&k, &base, &curve),
+                scalar_mul_affine(&k, &base, &curve)
+            );
+        }
+    }
+
+    #[test]
+    fn jacobian_matches_reference_crate() {
+        let curve = SECP256K1::default();
+        let secp256k1_extern = Secp256k1::signing_only();
+        let mut rng = StdRng::seed_from_u64(1998);
+
+        for _ in 0..16 {
+            let mut secret = [0u8; 32];
+            rng.fill_bytes(&mut secret);
+            let secret_key = SecretKey::from_slice(&secret).expect("within curve order");
+            let public_key = PublicKey::from_secret_key(&secp256k1_extern, &secret_key);
+
+            let point = match scalar_mul(&BigUint::from_bytes_be(&secret), &curve.g, &curve) {
+                EccPoint::Finite(point) => point,
+                EccPoint::Infinity => panic!("non-zero secret key gave the point at infinity"),
+            };
+
+            assert_eq!(point.to_uncompressed(), public_key.serialize_uncompressed());
+        }
+    }
+
+    #[test]
+    fn wnaf_digits_test() {
+        // 7 = 8 - 1 in width 3: digits -1, 0, 0, 1.
+        assert_eq!(wnaf_digits(&BigUint::from(7u32), 3), vec![-1, 0, 0, 1]);
+        assert!(wnaf_digits(&BigUint::zero(), WNAF_WIDTH).is_empty());
+
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..256 {
+            let k = BigUint::from(rng.gen::<u128>());
+            let digits = wnaf_digits(&k, WNAF_WIDTH);
+
+            let value = digits
+                .iter()
+                .rev()
+                .fold(BigInt::zero(), |acc, &d| acc * 2 + d);
+            assert_eq!(value, BigInt::from(k));
+
+            for (i, &d) in digits.iter().enumerate() {
+                if d != 0 {
+                    assert!(d % 2 != 0 && d.abs() < 16);
+                    let next = &digits[i + 1..digits.len().min(i + WNAF_WIDTH as usize)];
+                    assert!(next.iter().all(|&d| d == 0));
+                }
+            }
+        }
+    }
+
+    // Counts the Jacobian operations performed on secp256k1.
+    #[derive(Default)]
+    struct CountingCurve {
+        curve: SECP256K1,
+        additions: Cell<usize>,
+        doublings: Cell<usize>,
+    }
+
+    impl EllipticCurve for CountingCurve {
+        fn add_points(&self, a: &EccPoint, b: &EccPoint) -> EccPoint {
+            self.curve.add_points(a, b)
+        }
+
+        fn double_point(&self, a: &EccPoint) -> EccPoint {
+            self.curve.double_point(a)
+        }
+
+        fn is_on_curve(&self, p: &EccPoint) -> bool {
+            self.curve.is_on_curve(p)
+        }
+
+        fn add_jacobian(&self, a: &JacobianPoint, b: &JacobianPoint) -> JacobianPoint {
+            self.additions.set(self.additions.get() + 1);
+            self.curve.add_jacobian(a, b)
+        }
+
+        fn double_jacobian(&self, a: &JacobianPoint) -> JacobianPoint {
+            self.doublings.set(self.doublings.get() + 1);
+            self.curve.double_jacobian(a)
+        }
+
+        fn to_affine(&self, a: &JacobianPoint) -> EccPoint {
+            self.curve.to_affine(a)
+        }
+    }
+
+    #[test]
+    fn ladder_operation_count_is_fixed() {
+        let n = SECP256K1::default().n.to_biguint().unwrap();
+        let scalars = [
+            BigUint::from(1u32),
+            BigUint::from(2u32),
+            BigUint::from(1u32) << 64,
+            BigUint::from(1u32) << 255,
+            &n - 1u32,
+        ];
+
+        for k in &scalars {
+            let counting_curve = CountingCurve::default();
+            let result = scalar_mul(k, &counting_curve.curve.g, &counting_curve);
+
+            assert_eq!(counting_curve.additions.get(), LADDER_BITS as usize);
+            assert_eq!(counting_curve.doublings.get(), LADDER_BITS as usize);
+            assert_eq!(result, counting_curve.curve.mul_g_fast(k));
+        }
+    }
+
+    #[test]
+    fn conditional_swap_test() {
+        let a = JacobianPoint::from(&SECP256K1::default().g_point());
+        let b = JacobianPoint::infinity();
+
+        let (mut x, mut y) = (a.clone(), b.clone());
+        conditional_swap(false, &mut x, &mut y);
+        assert_eq!((&x, &y), (&a, &b));
+
+        conditional_swap(true, &mut x, &mut y);
+        assert_eq!((&x, &y), (&b, &a));
+
+        assert_eq!(conditional_select(true, &a, &b), a);
+        assert_eq!(conditional_select(false, &a, &b), b);
+    }
 }
@@ -0,0 +1,252 @@
+//! This crate implements MD5 (RFC 1321) as a demonstration of a broken hash.
+//!
+//! MD5 is not collision resistant: distinct messages with equal digests can
+//! be found in seconds, so it must not be used for signatures, certificates
+//! or integrity checks. The tests include the colliding message pair published
+//! by Wang and Yu, "How to Break MD5 and Other Hash Functions" (2005).
+//!
+//! Like SHA-1 and SHA-256, MD5 is a Merkle–Damgård hash over 512-bit blocks,
+//! but its words and length trailer are little-endian.
+use std::io::{self, Write};
+
+use utils::block_buffer::{BlockBuffer, LengthEncoding, BLOCK_SIZE};
+
+/// The initial hash value (RFC 1321 §3.3).
+const H: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// The round constants, `floor(abs(sin(i + 1)) * 2^32)` (RFC 1321 §3.4).
+const K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// The left rotation amounts, four per round.
+const S: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+/// Computes the MD5 digest of `message`.
+///
+/// **Not secure**: MD5 is vulnerable to practical collision attacks.
+///
+/// # Arguments
+/// * `message` - The bytes to hash.
+///
+/// # Returns
+/// The 16-byte digest.
+pub fn hash_bytes(message: &[u8]) -> [u8; 16] {
+    let mut hasher = Md5::new();
+    hasher.update(message);
+    hasher.finalize()
+}
+
+/// An incremental MD5 hasher.
+#[derive(Clone, Debug)]
+pub struct Md5 {
+    state: [u32; 4],
+    buffer: BlockBuffer,
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Md5::new()
+    }
+}
+
+impl Md5 {
+    /// Creates a hasher initialised with the MD5 initial hash value.
+    pub fn new() -> Self {
+        Md5 {
+            state: H,
+            buffer: BlockBuffer::new(),
+        }
+    }
+
+    /// Absorbs `data` into the hash computation.
+    ///
+    /// # Arguments
+    /// * `data` - The next chunk of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.buffer
+            .update(data, |block| compress_block(&mut self.state, block));
+    }
+
+    /// Applies the final padding and returns the 16-byte digest.
+    pub fn finalize(mut self) -> [u8; 16] {
+        self.buffer.finalize(LengthEncoding::LittleEndian, |block| {
+            compress_block(&mut self.state, block)
+        });
+
+        let mut digest = [0u8; 16];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+}
+
+impl utils::digest::HashFunction for Md5 {
+    const BLOCK_SIZE: usize = 64;
+
+    fn new() -> Self {
+        Md5::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Md5::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Md5::finalize(self).to_vec()
+    }
+
+    fn output_size(&self) -> usize {
+        16
+    }
+}
+
+/// Feeding an `Md5` through `Write` lets `io::copy` hash a file or socket in
+/// constant memory.
+impl Write for Md5 {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs the four rounds of 16 steps over one block and adds the result into `state`.
+fn compress_block(state: &mut [u32; 4], block: &[u8; BLOCK_SIZE]) {
+    let mut m = [0u32; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let [mut a, mut b, mut c, mut d] = *state;
+
+    for (i, &k) in K.iter().enumerate() {
+        // F, G, H and I, each with its own order of message words.
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+
+        let rotated = a
+            .wrapping_add(f)
+            .wrapping_add(k)
+            .wrapping_add(m[g])
+            .rotate_left(S[i / 16][i % 4]);
+
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(rotated);
+    }
+
+    for (h, v) in state.iter_mut().zip([a, b, c, d]) {
+        *h = h.wrapping_add(v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc1321_test_suite() {
+        // RFC 1321, appendix A.5.
+        let vectors: [(&[u8], &str); 7] = [
+            (b"", "d41d8cd98f00b204e9800998ecf8427e"),
+            (b"a", "0cc175b9c0f1b6a831c399e269772661"),
+            (b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+            (b"message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                b"abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+
+        for (message, expected) in vectors {
+            assert_eq!(hex::encode(hash_bytes(message)), expected);
+        }
+    }
+
+    #[test]
+    fn wang_yu_collision() {
+        // Two 128-byte messages differing in six bytes with the same MD5 digest.
+        let first = hex::decode(
+            "d131dd02c5e6eec4693d9a0698aff95c2fcab58712467eab4004583eb8fb7f89\
+             55ad340609f4b30283e488832571415a085125e8f7cdc99fd91dbdf280373c5b\
+             d8823e3156348f5bae6dacd436c919c6dd53e2b487da03fd02396306d248cda0\
+             e99f33420f577ee8ce54b67080a80d1ec69821bcb6a8839396f9652b6ff72a70",
+        )
+        .unwrap();
+        let second = hex::decode(
+            "d131dd02c5e6eec4693d9a0698aff95c2fcab50712467eab4004583eb8fb7f89\
+             55ad340609f4b30283e4888325f1415a085125e8f7cdc99fd91dbd7280373c5b\
+             d8823e3156348f5bae6dacd436c919c6dd53e23487da03fd02396306d248cda0\
+             e99f33420f577ee8ce54b67080280d1ec69821bcb6a8839396f965ab6ff72a70",
+        )
+        .unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(hash_bytes(&first), hash_bytes(&second));
+        assert_eq!(
+            hex::encode(hash_bytes(&first)),
+            "79054025255fb1a26e4bc422aef54eb4"
+        );
+
+        // MD5 is Merkle–Damgård, so the collision survives any common suffix.
+        let suffix = b"appended to both messages";
+        assert_eq!(
+            hash_bytes(&[&first[..], suffix].concat()),
+            hash_bytes(&[&second[..], suffix].concat())
+        );
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let message: Vec<u8> = (0..300u32).map(|i| (i * 7) as u8).collect();
+
+        for len in [0, 55, 56, 63, 64, 65, 300] {
+            let mut hasher = Md5::new();
+            for chunk in message[..len].chunks(13) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize(), hash_bytes(&message[..len]));
+        }
+
+        utils::digest::check_digest::<Md5>();
+    }
+
+    #[test]
+    fn hashes_through_io_copy() {
+        let message: Vec<u8> = (0..300u32).map(|i| (i * 7) as u8).collect();
+
+        let mut hasher = Md5::new();
+        io::copy(&mut message.as_slice(), &mut hasher).unwrap();
+        assert_eq!(hasher.finalize(), hash_bytes(&message));
+    }
+}
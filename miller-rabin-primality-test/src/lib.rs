@@ -1,26 +1,106 @@
+mod prime_gen;
+
 use std::ops::Div;
 
-use num_bigint::{BigInt, BigUint, ToBigInt};
-use num_traits::{Pow, Zero};
+use num_bigint::{BigInt, BigUint, RandBigInt, ToBigInt};
+use num_traits::{Pow, ToPrimitive, Zero};
+use rand::RngCore;
+
+pub use prime_gen::{
+    gen_prime, gen_prime_batched, gen_prime_with_rounds, has_small_factor, DEFAULT_BATCH_SIZE,
+    SMALL_PRIMES,
+};
+
+/// Default number of Miller-Rabin rounds for `MRPT::is_probable_prime`,
+/// bounding the chance of accepting a composite by 4^-63.
+pub const DEFAULT_ROUNDS: usize = 64;
 
 pub struct MRPT;
 
 impl MRPT {
+    /// Runs a single round of the Miller-Rabin test with base 2.
     pub fn is_prime(p: &BigUint) -> bool {
+        // step 2: select `a`
+        // we choose any value of a in the range 1 < a < p - 1.
+        MRPT::passes_round(p, BigUint::from(2u32))
+    }
+
+    /// Runs the Miller-Rabin test with the first `rounds` primes as bases.
+    ///
+    /// Values below 2000 are decided exactly by trial division instead.
+    ///
+    /// # Arguments
+    /// * `p` - The number to test.
+    /// * `rounds` - The number of bases to test; at most `SMALL_PRIMES.len() + 1`.
+    ///
+    /// # Returns
+    /// `false` if `p` is certainly composite, and `true` if it is probably prime.
+    pub fn is_prime_with_rounds(p: &BigUint, rounds: usize) -> bool {
+        if let Some(is_prime) = MRPT::decide_small(p) {
+            return is_prime;
+        }
+
+        std::iter::once(2)
+            .chain(SMALL_PRIMES)
+            .take(rounds)
+            .all(|a| MRPT::passes_round(p, BigUint::from(a)))
+    }
+
+    /// Runs the Miller-Rabin test with base 2 followed by `rounds - 1`
+    /// uniformly random bases drawn from `rng`.
+    ///
+    /// Testing base 2 first rejects almost every composite without touching
+    /// `rng`; the random bases then catch strong pseudoprimes to base 2.
+    /// Values below 2000 are decided exactly by trial division instead.
+    ///
+    /// # Arguments
+    /// * `p` - The number to test.
+    /// * `rounds` - The total number of bases to test; at least 1.
+    /// * `rng` - The source of the random bases.
+    ///
+    /// # Returns
+    /// `false` if `p` is certainly composite, and `true` if it is probably
+    /// prime, with an error probability of at most 4^-(rounds - 1): the
+    /// fixed base 2 gives no bound against an adversarially chosen `p`, so
+    /// only the random bases count.
+    pub fn is_probable_prime<R: RngCore + ?Sized>(p: &BigUint, rounds: usize, rng: &mut R) -> bool {
+        if let Some(is_prime) = MRPT::decide_small(p) {
+            return is_prime;
+        }
+
+        if !MRPT::passes_round(p, BigUint::from(2u32)) {
+            return false;
+        }
+
+        // Bases lie in [3, p - 2]; 1 and p - 1 are useless witnesses.
+        let (low, high) = (BigUint::from(3u32), p - 1u32);
+        (1..rounds).all(|_| MRPT::passes_round(p, rng.gen_biguint_range(&low, &high)))
+    }
+
+    /// Decides primality outright for values below 2000 and even values.
+    fn decide_small(p: &BigUint) -> Option<bool> {
+        if let Some(small) = p.to_u32().filter(|&n| n < 2000) {
+            return Some(small == 2 || SMALL_PRIMES.contains(&small));
+        }
+
+        if !p.bit(0) {
+            return Some(false);
+        }
+
+        None
+    }
+
+    /// Runs one Miller-Rabin round on the odd number `p` with base `a`.
+    fn passes_round(p: &BigUint, a: BigUint) -> bool {
         let one_biguint: BigUint = BigUint::from(1u32);
         let one_bigint: BigInt = BigInt::from(1u32);
         let negative_one_bigint: BigInt = BigInt::from(-1i32);
-        let two_biguint: BigUint = BigUint::from(2u32);
 
         //Step 1: derive m and k
         let (k, m) = MRPT::derive_k_and_m(p);
 
-        // step 2: select `a`
-        // we choose any value of a in the range 1 < a < p - 1.
-        let a = two_biguint;
-
         // step 3: derive b
-        let (n, itr) = MRPT::derive_b(a, &m, &k, &p);
+        let (n, itr) = MRPT::derive_b(a, &m, &k, p);
 
         // If `i` == 1, then `n` can be either -1 or 1,
         // and this means `p` is a probably a prime number.
@@ -46,8 +126,8 @@ impl MRPT {
     /// * `k` - the calculated value of k
     /// * `m` - the calculated value of m
     fn derive_k_and_m(p: &BigUint) -> (BigUint, BigUint) {
-        let mut k: BigUint = Zero::zero();
-        let mut m: BigUint = Zero::zero();
+        let k: BigUint;
+        let m: BigUint;
 
         let mut temp_k: BigUint = Zero::zero();
         let mut temp_m: BigUint = Zero::zero();
@@ -101,7 +181,7 @@ impl MRPT {
         let p_bigint = p.to_bigint().unwrap();
         while itr.lt(k) {
             // For the first iteration (itr = 0), calculate b using: a^m mod p.
-            // If a^m congruent to -1 mod p or = mod p, then `p` is prime.
+            // If a^m congruent to -1 mod p or 1 mod p, then `p` is prime.
             if itr.is_zero() {
                 b = a.modpow(m, p);
 
@@ -111,7 +191,7 @@ impl MRPT {
                 let congruent_to_one =
                     MRPT::is_congruent(&p_bigint, b.to_bigint().unwrap(), BigInt::from(1i32));
 
-                if congruent_to_negative_one && congruent_to_one {
+                if congruent_to_negative_one || congruent_to_one {
                     // Return either 1 or -1; since in the first iteration
                     // 1 or -1 means `p` is prime. Caller should use the 2nd
                     // element in the tuple `i` to deduce if prime or not.
@@ -177,7 +257,7 @@ mod tests {
     fn derive_b() {
         let p = BigUint::from(53u32);
         let (k, m) = MRPT::derive_k_and_m(&p);
-        let (n, i) = MRPT::derive_b(BigUint::from(2u32), &m, &k, &p);
+        let (n, _i) = MRPT::derive_b(BigUint::from(2u32), &m, &k, &p);
 
         assert_eq!(n, BigInt::from(-1i32));
     }
@@ -198,6 +278,62 @@ mod tests {
         assert!(!is_prime);
     }
 
+    #[test]
+    fn accepts_primes_congruent_to_three_mod_four() {
+        // 2^((p - 1) / 2) ≡ -1 (mod p) for both, so the first square is already -1.
+        for p in ["3", "11", "19", "2147483647"] {
+            assert!(MRPT::is_prime(&BigUint::from_str(p).unwrap()), "{}", p);
+        }
+
+        // 2^127 - 1 and 2^521 - 1, with 2^m ≡ 1 (mod p).
+        let mersenne_127 = (BigUint::from(1u32) << 127u32) - 1u32;
+        let mersenne_521 = (BigUint::from(1u32) << 521u32) - 1u32;
+        assert!(MRPT::is_prime(&mersenne_127));
+        assert!(MRPT::is_prime(&mersenne_521));
+    }
+
+    #[test]
+    fn rounds_reject_base_two_pseudoprimes() {
+        // 2047 = 23 · 89 and 3215031751 = 151 · 751 · 28351 are strong
+        // pseudoprimes to base 2 (the latter also to bases 3, 5 and 7).
+        for n in [2047u64, 3215031751] {
+            let n = BigUint::from(n);
+            assert!(MRPT::is_prime(&n));
+            assert!(!MRPT::is_prime_with_rounds(&n, 5));
+        }
+
+        for p in [2u32, 3, 1999, 2003, 1_000_000_007] {
+            assert!(MRPT::is_prime_with_rounds(&BigUint::from(p), 20), "{}", p);
+        }
+        for n in [0u32, 1, 4, 1995, 2001, 1_000_000_008] {
+            assert!(!MRPT::is_prime_with_rounds(&BigUint::from(n), 20), "{}", n);
+        }
+    }
+
+    #[test]
+    fn random_bases_reject_base_two_pseudoprimes() {
+        use rand::SeedableRng;
+
+        // 13747361 = 2141 · 6421 is a strong pseudoprime to base 2 with no
+        // factor below 2000, so neither the sieve nor base 2 alone catch it.
+        let pseudoprime = BigUint::from(13747361u32);
+        assert!(MRPT::is_prime(&pseudoprime));
+
+        for seed in 0..32 {
+            let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(seed);
+            assert!(!MRPT::is_probable_prime(
+                &pseudoprime,
+                DEFAULT_ROUNDS,
+                &mut rng
+            ));
+            assert!(MRPT::is_probable_prime(
+                &BigUint::from(1_000_000_007u32),
+                DEFAULT_ROUNDS,
+                &mut rng
+            ));
+        }
+    }
+
     #[test]
     fn is_congruent() {
         let is_congruent = MRPT::is_congruent(
@@ -0,0 +1,352 @@
+//! This module generates random primes of a given size, filtering candidates
+//! by trial division with the small primes before running Miller-Rabin.
+use num_bigint::BigUint;
+use num_traits::Zero;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
+
+use crate::{DEFAULT_ROUNDS, MRPT};
+
+// Candidates are trial-divided by every prime below this bound.
+const SIEVE_LIMIT: usize = 2000;
+
+/// Number of consecutive odd candidates tested in parallel by `gen_prime`.
+pub const DEFAULT_BATCH_SIZE: usize = 64;
+
+// Number of primes below `SIEVE_LIMIT`.
+const SMALL_PRIME_COUNT: usize = 303;
+
+/// The odd primes below 2000, computed at compile time with the sieve of
+/// Eratosthenes. 2 is omitted since every candidate is odd.
+pub const SMALL_PRIMES: [u32; SMALL_PRIME_COUNT - 1] = small_primes();
+
+const fn small_primes() -> [u32; SMALL_PRIME_COUNT - 1] {
+    let mut composite = [false; SIEVE_LIMIT];
+    let mut i = 2;
+    while i * i < SIEVE_LIMIT {
+        if !composite[i] {
+            let mut multiple = i * i;
+            while multiple < SIEVE_LIMIT {
+                composite[multiple] = true;
+                multiple += i;
+            }
+        }
+        i += 1;
+    }
+
+    let mut primes = [0u32; SMALL_PRIME_COUNT - 1];
+    let mut count = 0;
+    let mut n = 3;
+    while n < SIEVE_LIMIT {
+        if !composite[n] {
+            primes[count] = n as u32;
+            count += 1;
+        }
+        n += 1;
+    }
+
+    // Fails the build if `SMALL_PRIME_COUNT` is out of date.
+    assert!(count == SMALL_PRIME_COUNT - 1);
+    primes
+}
+
+/// Generates a random prime of exactly `bits` bits with its two most
+/// significant bits set, using `DEFAULT_ROUNDS` Miller-Rabin rounds.
+///
+/// See `gen_prime_batched`.
+pub fn gen_prime<R: RngCore + ?Sized>(bits: usize, rng: &mut R) -> BigUint {
+    gen_prime_with_rounds(bits, DEFAULT_ROUNDS, rng)
+}
+
+/// Generates a random prime of exactly `bits` bits with its two most
+/// significant bits set, testing `DEFAULT_BATCH_SIZE` candidates at a time.
+///
+/// See `gen_prime_batched`.
+pub fn gen_prime_with_rounds<R: RngCore + ?Sized>(
+    bits: usize,
+    rounds: usize,
+    rng: &mut R,
+) -> BigUint {
+    gen_prime_batched(bits, rounds, DEFAULT_BATCH_SIZE, rng)
+}
+
+/// Generates a random prime of exactly `bits` bits with its two most
+/// significant bits set.
+///
+/// Each round draws a random odd starting point and a seed from `rng`, then
+/// tests the window of `batch_size` consecutive odd numbers from that point
+/// in parallel. The lowest prime in the window is returned, so the result
+/// only depends on `rng`, not on thread scheduling; each candidate draws its
+/// Miller-Rabin bases from its own ChaCha20 stream of the seed. A batch size
+/// of 0 or 1 tests one independent random candidate at a time on the
+/// calling thread.
+///
+/// Every candidate is first trial-divided by `SMALL_PRIMES`, which rejects
+/// most composites with cheap single-limb remainders; only the survivors go
+/// through the Miller-Rabin test.
+///
+/// # Arguments
+/// * `bits` - The size of the prime; at least 2.
+/// * `rounds` - The number of Miller-Rabin rounds; see `MRPT::is_probable_prime`.
+/// * `batch_size` - The number of candidates tested per window.
+/// * `rng` - The source of randomness for the candidates and the bases.
+///
+/// # Returns
+/// A probable prime `p` with `p.bits() == bits`.
+pub fn gen_prime_batched<R: RngCore + ?Sized>(
+    bits: usize,
+    rounds: usize,
+    batch_size: usize,
+    rng: &mut R,
+) -> BigUint {
+    assert!(bits >= 2, "A prime has at least 2 bits");
+
+    let mut bytes = vec![0u8; bits.div_ceil(8)];
+
+    loop {
+        rng.fill_bytes(&mut bytes);
+        shape_candidate(&mut bytes, bits);
+        let start = BigUint::from_bytes_be(&bytes);
+
+        if batch_size <= 1 {
+            if !has_small_factor(&start) && MRPT::is_probable_prime(&start, rounds, rng) {
+                return start;
+            }
+            continue;
+        }
+
+        let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+        rng.fill_bytes(&mut seed);
+
+        let found = (0..batch_size as u64)
+            .into_par_iter()
+            .map(|offset| (offset, &start + 2 * offset))
+            .filter(|(_, candidate)| candidate.bits() == bits as u64)
+            .filter(|(_, candidate)| !has_small_factor(candidate))
+            .find_first(|(offset, candidate)| {
+                let mut bases = ChaCha20Rng::from_seed(seed);
+                bases.set_stream(*offset);
+                MRPT::is_probable_prime(candidate, rounds, &mut bases)
+            });
+
+        if let Some((_, prime)) = found {
+            return prime;
+        }
+    }
+}
+
+/// Checks whether `n` is divisible by one of `SMALL_PRIMES` other than itself.
+pub fn has_small_factor(n: &BigUint) -> bool {
+    SMALL_PRIMES
+        .iter()
+        .any(|&prime| (n % prime).is_zero() && *n != BigUint::from(prime))
+}
+
+/// Turns random big-endian `bytes` into an odd `bits`-bit candidate whose
+/// two most significant bits are set.
+///
+/// With the top two bits set, each prime is at least 1.5·2^(bits-1), so the
+/// product of two such primes always has exactly `2·bits` bits.
+///
+/// # Arguments
+/// * `bytes` - Random bytes; must hold exactly `bits.div_ceil(8)` bytes.
+/// * `bits` - The size of the candidate, at least 2.
+pub fn shape_candidate(bytes: &mut [u8], bits: usize) {
+    let len = bytes.len();
+    debug_assert!(bits >= 2 && len == bits.div_ceil(8));
+
+    // Clear the excess high bits when `bits` is not a whole number of bytes.
+    bytes[0] &= 0xff >> (len * 8 - bits);
+
+    // Set bits `bits - 1` and `bits - 2`, counted from the least significant
+    // bit; they straddle two bytes when `bits` is one more than a multiple of 8.
+    for bit in [bits - 1, bits - 2] {
+        bytes[len - 1 - bit / 8] |= 1 << (bit % 8);
+    }
+
+    // Set the least significant bit to 1 to ensure the number is odd.
+    bytes[len - 1] |= 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn small_prime_table() {
+        assert_eq!(SMALL_PRIMES[..5], [3, 5, 7, 11, 13]);
+        assert_eq!(SMALL_PRIMES[SMALL_PRIMES.len() - 1], 1999);
+        assert!(SMALL_PRIMES
+            .iter()
+            .all(|&p| (2..p).take_while(|d| d * d <= p).all(|d| p % d != 0)));
+    }
+
+    #[test]
+    fn trial_division() {
+        let big_prime = (BigUint::from(1u32) << 127u32) - 1u32;
+
+        assert!(!has_small_factor(&big_prime));
+        assert!(!has_small_factor(&BigUint::from(1999u32)));
+        assert!(has_small_factor(&(&big_prime * 1999u32)));
+        assert!(has_small_factor(&BigUint::from(3u32 * 1999)));
+    }
+
+    #[test]
+    fn matches_unsieved_search() {
+        // The search without trial division, as it was before the sieve.
+        fn gen_prime_unsieved(bits: usize, rng: &mut StdRng) -> BigUint {
+            let mut bytes = vec![0u8; bits.div_ceil(8)];
+            loop {
+                rng.fill_bytes(&mut bytes);
+                shape_candidate(&mut bytes, bits);
+                let candidate = BigUint::from_bytes_be(&bytes);
+                if MRPT::is_probable_prime(&candidate, DEFAULT_ROUNDS, rng) {
+                    return candidate;
+                }
+            }
+        }
+
+        for seed in 0..4 {
+            let sieved =
+                gen_prime_batched(512, DEFAULT_ROUNDS, 1, &mut StdRng::seed_from_u64(seed));
+            let unsieved = gen_prime_unsieved(512, &mut StdRng::seed_from_u64(seed));
+
+            assert_eq!(sieved, unsieved);
+            assert_eq!(sieved.bits(), 512);
+        }
+    }
+
+    #[test]
+    fn batched_search_is_deterministic() {
+        let search = |seed, threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| gen_prime(512, &mut StdRng::seed_from_u64(seed)))
+        };
+
+        for seed in 0..4 {
+            let prime = search(seed, 1);
+
+            assert_eq!(search(seed, 4), prime);
+            assert_eq!(prime.bits(), 512);
+            assert!(MRPT::is_prime_with_rounds(&prime, 20));
+        }
+        assert_ne!(search(0, 4), search(1, 4));
+    }
+
+    #[test]
+    fn batched_search_returns_first_prime_in_window() {
+        // 2^127 - 1 is prime, and the window from 2^127 - 23 reaches it at
+        // offset 11; the odd numbers in between are all composite.
+        struct StartAt(Vec<u8>, StdRng);
+
+        impl RngCore for StartAt {
+            fn next_u32(&mut self) -> u32 {
+                self.1.next_u32()
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                self.1.next_u64()
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                match std::mem::take(&mut self.0) {
+                    start if !start.is_empty() => dest.copy_from_slice(&start),
+                    _ => self.1.fill_bytes(dest),
+                }
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+
+        let mersenne = (BigUint::from(1u32) << 127u32) - 1u32;
+        let start = &mersenne - 22u32;
+        let mut rng = StartAt(start.to_bytes_be(), StdRng::seed_from_u64(0));
+
+        assert_eq!(gen_prime_batched(127, 20, 64, &mut rng), mersenne);
+    }
+
+    #[test]
+    fn never_returns_base_two_pseudoprimes() {
+        // Hands out `script` to the first draws, then falls back to a
+        // seeded stream.
+        struct ScriptedRng {
+            script: Vec<Vec<u8>>,
+            inner: StdRng,
+        }
+
+        impl RngCore for ScriptedRng {
+            fn next_u32(&mut self) -> u32 {
+                self.inner.next_u32()
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                self.inner.next_u64()
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                match self.script.pop() {
+                    Some(bytes) => dest.copy_from_slice(&bytes),
+                    None => self.inner.fill_bytes(dest),
+                }
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+                self.inner.try_fill_bytes(dest)
+            }
+        }
+
+        // 13747361 = 0xd1c4a1 = 2141 · 6421 survives the sieve and passes
+        // the base-2 round, so only the random bases can reject it.
+        let pseudoprime = BigUint::from(13747361u32);
+        assert!(!has_small_factor(&pseudoprime) && MRPT::is_prime(&pseudoprime));
+
+        for seed in 0..8 {
+            let mut rng = ScriptedRng {
+                script: vec![vec![0xd1, 0xc4, 0xa1]],
+                inner: StdRng::seed_from_u64(seed),
+            };
+
+            let p = gen_prime(24, &mut rng);
+            assert!(rng.script.is_empty());
+            assert_ne!(p, pseudoprime);
+            // The first 20 prime bases decide primality exactly below 2^64.
+            assert!(MRPT::is_prime_with_rounds(&p, 20));
+        }
+    }
+
+    #[test]
+    fn shapes_candidates() {
+        let mut bytes = [0u8; 128];
+        shape_candidate(&mut bytes, 1024);
+        assert_eq!(bytes[0], 0xc0);
+        assert_eq!(bytes[127], 0x01);
+        assert!(bytes[1..127].iter().all(|&byte| byte == 0));
+
+        let mut bytes = [0xffu8; 128];
+        shape_candidate(&mut bytes, 1024);
+        assert!(bytes.iter().all(|&byte| byte == 0xff));
+
+        // 1020 bits: the top four bits of the leading byte are cleared.
+        let mut bytes = [0xffu8; 128];
+        shape_candidate(&mut bytes, 1020);
+        assert_eq!(bytes[0], 0x0f);
+
+        let mut bytes = [0u8; 128];
+        shape_candidate(&mut bytes, 1020);
+        assert_eq!(bytes[0], 0x0c);
+
+        // 1017 bits: the top bit is alone in the leading byte.
+        let mut bytes = [0u8; 128];
+        shape_candidate(&mut bytes, 1017);
+        assert_eq!(&bytes[..2], &[0x01, 0x80]);
+        assert_eq!(BigUint::from_bytes_be(&bytes).bits(), 1017);
+    }
+}
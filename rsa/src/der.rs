@@ -0,0 +1,253 @@
+//! This module implements the small subset of ASN.1 DER and PEM needed to
+//! exchange RSA keys: INTEGER, NULL, BIT STRING, OCTET STRING, OBJECT
+//! IDENTIFIER and SEQUENCE, with definite lengths only.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use num_bigint::BigUint;
+
+use crate::error::RsaError;
+
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_BIT_STRING: u8 = 0x03;
+pub const TAG_OCTET_STRING: u8 = 0x04;
+pub const TAG_NULL: u8 = 0x05;
+pub const TAG_OID: u8 = 0x06;
+pub const TAG_SEQUENCE: u8 = 0x30;
+
+// Base64 characters per PEM line, as written by OpenSSL (RFC 7468).
+const PEM_LINE_LENGTH: usize = 64;
+
+fn invalid(reason: &str) -> RsaError {
+    RsaError::InvalidEncoding(reason.to_string())
+}
+
+/// Appends a tag-length-value triple to `out`.
+///
+/// # Arguments
+/// * `tag` - The identifier octet.
+/// * `content` - The encoded contents.
+/// * `out` - The buffer to write to.
+pub fn write_tlv(tag: u8, content: &[u8], out: &mut Vec<u8>) {
+    out.push(tag);
+
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        // Long form: 0x80 | number of length bytes, then the length big-endian.
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&byte| byte == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+
+    out.extend_from_slice(content);
+}
+
+/// Appends a non-negative INTEGER to `out`.
+///
+/// A zero byte is prepended when the most significant bit is set, since DER
+/// integers are two's complement.
+pub fn write_integer(n: &BigUint, out: &mut Vec<u8>) {
+    let mut bytes = n.to_bytes_be();
+    if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0);
+    }
+
+    write_tlv(TAG_INTEGER, &bytes, out);
+}
+
+/// A cursor over DER-encoded data.
+pub struct DerReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        DerReader { data }
+    }
+
+    /// Reads the next element, which must carry `tag`, and returns its contents.
+    pub fn read_tlv(&mut self, tag: u8) -> Result<&'a [u8], RsaError> {
+        let (&actual, rest) = self
+            .data
+            .split_first()
+            .ok_or_else(|| invalid("unexpected end of data"))?;
+        if actual != tag {
+            return Err(invalid(&format!(
+                "expected tag {:#04x}, found {:#04x}",
+                tag, actual
+            )));
+        }
+
+        let (&first, mut rest) = rest
+            .split_first()
+            .ok_or_else(|| invalid("missing length"))?;
+
+        let len = if first < 0x80 {
+            first as usize
+        } else {
+            let count = (first & 0x7f) as usize;
+            if count == 0 || count > 4 || count > rest.len() {
+                return Err(invalid("unsupported length encoding"));
+            }
+
+            let (len_bytes, tail) = rest.split_at(count);
+            rest = tail;
+
+            let len = len_bytes
+                .iter()
+                .fold(0usize, |acc, &byte| (acc << 8) | byte as usize);
+
+            // DER requires the shortest length encoding.
+            if len_bytes[0] == 0 || len < 0x80 {
+                return Err(invalid("non-minimal length encoding"));
+            }
+            len
+        };
+
+        if len > rest.len() {
+            return Err(invalid("length exceeds the available data"));
+        }
+
+        let (content, rest) = rest.split_at(len);
+        self.data = rest;
+        Ok(content)
+    }
+
+    /// Reads a non-negative INTEGER.
+    pub fn read_integer(&mut self) -> Result<BigUint, RsaError> {
+        let content = self.read_tlv(TAG_INTEGER)?;
+
+        match content {
+            [] => Err(invalid("empty integer")),
+            [first, ..] if first & 0x80 != 0 => Err(invalid("negative integer")),
+            [0, second, ..] if second & 0x80 == 0 => Err(invalid("non-minimal integer")),
+            _ => Ok(BigUint::from_bytes_be(content)),
+        }
+    }
+
+    /// Reads a SEQUENCE and returns a reader over its contents.
+    pub fn read_sequence(&mut self) -> Result<DerReader<'a>, RsaError> {
+        Ok(DerReader::new(self.read_tlv(TAG_SEQUENCE)?))
+    }
+
+    /// Fails unless all data has been consumed.
+    pub fn finish(&self) -> Result<(), RsaError> {
+        if !self.data.is_empty() {
+            return Err(invalid("trailing data"));
+        }
+        Ok(())
+    }
+}
+
+/// Wraps `der` in a PEM envelope with the given `label`.
+pub fn to_pem(label: &str, der: &[u8]) -> String {
+    let encoded = STANDARD.encode(der);
+
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.as_bytes().chunks(PEM_LINE_LENGTH) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+
+    pem
+}
+
+/// Extracts the DER payload of a PEM envelope with the given `label`.
+pub fn from_pem(label: &str, pem: &str) -> Result<Vec<u8>, RsaError> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+
+    let body = pem
+        .trim()
+        .strip_prefix(&begin)
+        .and_then(|rest| rest.strip_suffix(&end))
+        .ok_or_else(|| invalid(&format!("expected a PEM block labelled {}", label)))?;
+
+    let encoded: String = body.split_whitespace().collect();
+    STANDARD
+        .decode(encoded)
+        .map_err(|err| invalid(&err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_encodings() {
+        for (len, header) in [
+            (0, vec![0x04, 0x00]),
+            (0x7f, vec![0x04, 0x7f]),
+            (0x80, vec![0x04, 0x81, 0x80]),
+            (0x100, vec![0x04, 0x82, 0x01, 0x00]),
+        ] {
+            let content = vec![0xab; len];
+            let mut out = Vec::new();
+            write_tlv(TAG_OCTET_STRING, &content, &mut out);
+
+            assert_eq!(out[..header.len()], header);
+
+            let mut reader = DerReader::new(&out);
+            assert_eq!(reader.read_tlv(TAG_OCTET_STRING).unwrap(), content);
+            assert!(reader.finish().is_ok());
+        }
+    }
+
+    #[test]
+    fn integer_encodings() {
+        for (n, encoded) in [
+            (0u32, vec![0x02, 0x01, 0x00]),
+            (0x7f, vec![0x02, 0x01, 0x7f]),
+            (0x80, vec![0x02, 0x02, 0x00, 0x80]),
+            (0x010001, vec![0x02, 0x03, 0x01, 0x00, 0x01]),
+        ] {
+            let mut out = Vec::new();
+            write_integer(&BigUint::from(n), &mut out);
+            assert_eq!(out, encoded);
+
+            assert_eq!(
+                DerReader::new(&encoded).read_integer().unwrap(),
+                BigUint::from(n)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let integers: [&[u8]; 4] = [
+            &[0x02, 0x01, 0x80],       // negative
+            &[0x02, 0x02, 0x00, 0x7f], // non-minimal integer
+            &[0x02, 0x00],             // empty integer
+            &[0x03, 0x01, 0x00],       // wrong tag
+        ];
+        for case in integers {
+            assert!(DerReader::new(case).read_integer().is_err());
+        }
+
+        let strings: [&[u8]; 4] = [
+            &[0x04, 0x81, 0x05, 0, 0, 0, 0, 0], // non-minimal length
+            &[0x04, 0x82, 0x00, 0x80],          // leading zero length byte
+            &[0x04, 0x80],                      // indefinite length
+            &[0x04, 0x05, 0x00],                // truncated
+        ];
+        for case in strings {
+            assert!(DerReader::new(case).read_tlv(TAG_OCTET_STRING).is_err());
+        }
+
+        let mut reader = DerReader::new(&[0x05, 0x00, 0x00]);
+        reader.read_tlv(TAG_NULL).unwrap();
+        assert!(reader.finish().is_err());
+    }
+
+    #[test]
+    fn pem_round_trip() {
+        let der: Vec<u8> = (0..=255).collect();
+        let pem = to_pem("TEST", &der);
+
+        assert!(pem.lines().all(|line| line.len() <= PEM_LINE_LENGTH));
+        assert_eq!(from_pem("TEST", &pem).unwrap(), der);
+        assert!(from_pem("OTHER", &pem).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RsaError {
+    #[error("Invalid key size of `{0}` bits")]
+    InvalidKeySize(usize),
+
+    #[error("The public exponent is not co-prime to the totient")]
+    NotCoprime,
+
+    #[error("The public exponent has no inverse modulo the totient")]
+    InverseNotFound,
+
+    #[error("The public exponent must be odd and lie in (1, n)")]
+    InvalidPublicExponent,
+
+    #[error("The private exponent must lie in (1, n)")]
+    InvalidPrivateExponent,
+
+    #[error("The primes must be distinct and greater than 2")]
+    InvalidPrimes,
+
+    #[error("A prime factor of the key is composite")]
+    CompositeFactor,
+
+    #[error("The modulus is not the product of the primes")]
+    ModulusMismatch,
+
+    #[error("The CRT exponents or coefficient do not match the key")]
+    InvalidCrtParameters,
+
+    #[error("Invalid key encoding: {0}")]
+    InvalidEncoding(String),
+
+    #[error("The key does not carry its prime factors")]
+    MissingPrimes,
+
+    #[error("The message is too long for the key size")]
+    MessageTooLong,
+
+    #[error("Refusing to encrypt a message below 2^64 without padding")]
+    TextbookMessage,
+
+    #[error("The ciphertext must lie in [0, n)")]
+    CiphertextOutOfRange,
+
+    #[error("Expected a ciphertext of `{expected}` bytes, got `{actual}`")]
+    InvalidCiphertextLength { expected: usize, actual: usize },
+
+    #[error("Integer too large for the requested length")]
+    IntegerTooLarge,
+
+    #[error("Decryption error")]
+    DecryptionError,
+
+    #[error("The modulus is too small for the requested operation")]
+    KeyTooSmall,
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+}
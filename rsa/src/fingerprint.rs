@@ -0,0 +1,74 @@
+//! This module derives stable identifiers for public keys.
+use sha_256::Sha256;
+
+use crate::key::RsaPublicKey;
+
+impl RsaPublicKey {
+    /// Computes the SHA-256 fingerprint of the key.
+    ///
+    /// The hash covers the DER `SubjectPublicKeyInfo` encoding, so it equals
+    /// `openssl pkey -pubin -outform DER | sha256sum` for the same key.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.to_public_key_der());
+        hasher.finalize()
+    }
+
+    /// Returns `fingerprint` as a lowercase hex string.
+    pub fn fingerprint_hex(&self) -> String {
+        self.fingerprint()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::RsaPrivateKey;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const PKCS8_PEM: &str = include_str!("../fixtures/openssl_2048_pkcs8.pem");
+    const SPKI_PEM: &str = include_str!("../fixtures/openssl_2048_spki.pem");
+
+    #[test]
+    fn matches_openssl() {
+        // openssl pkey -pubin -in openssl_2048_spki.pem -outform DER | sha256sum
+        let expected = "3b142f287b500a223f14567a61f6e0024b1943a8ec7ec07efabd00eb88debaae";
+
+        let public_key = RsaPublicKey::from_public_key_pem(SPKI_PEM).unwrap();
+        assert_eq!(public_key.fingerprint_hex(), expected);
+    }
+
+    #[test]
+    fn invariant_under_reimport() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+        let fingerprint = key.public_key().fingerprint();
+
+        let reimported = [
+            RsaPublicKey::from_pkcs1_der(&key.public_key().to_pkcs1_der()).unwrap(),
+            RsaPublicKey::from_public_key_pem(&key.public_key().to_public_key_pem()).unwrap(),
+            RsaPrivateKey::from_pkcs1_der(&key.to_pkcs1_der().unwrap())
+                .unwrap()
+                .public_key()
+                .clone(),
+        ];
+        for public_key in reimported {
+            assert_eq!(public_key.fingerprint(), fingerprint);
+        }
+    }
+
+    #[test]
+    fn differs_between_keys() {
+        let mut rng = ChaCha20Rng::seed_from_u64(3370);
+        let first = crate::RSA::generate(&mut rng, 512).unwrap();
+        let second = crate::RSA::generate(&mut rng, 512).unwrap();
+
+        assert_ne!(
+            first.public_key().fingerprint(),
+            second.public_key().fingerprint()
+        );
+    }
+}
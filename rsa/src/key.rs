@@ -0,0 +1,891 @@
+use std::fmt;
+
+use miller_rabin_primality_test::MRPT;
+use num_bigint::{BigInt, BigUint, ToBigInt};
+use num_traits::One;
+use utils::{mac::ct_eq, modular_inverse, relative_prime, wipe::wipe_biguint};
+use zeroize::Zeroize;
+
+use crate::error::RsaError;
+use crate::primitives::{i2osp, os2ip};
+
+// Miller-Rabin bases tried by `RsaPrivateKey::validate` on each prime.
+const VALIDATION_MR_ROUNDS: usize = 20;
+
+// Unpadded messages of at most this many bits are rejected by
+// `RsaPublicKey::encrypt_raw` unless textbook use is allowed: m^e < n for
+// such messages whenever e·64 < log2(n), and small message spaces can be
+// searched exhaustively, so the ciphertext hides nothing.
+const TEXTBOOK_MESSAGE_BITS: u64 = 64;
+
+/// Options for unpadded (textbook) RSA encryption.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RawOptions {
+    /// Allows messages below 2^64, e.g. for textbook examples and test vectors.
+    pub allow_textbook: bool,
+}
+
+/// An RSA public key `(n, e)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RsaPublicKey {
+    n: BigUint, // The modulus.
+    e: BigUint, // The public exponent.
+}
+
+/// An RSA private key.
+///
+/// The private exponent and the CRT parameters are wiped when the key is
+/// dropped. The key is deliberately not `Clone`, so no copy escapes the wipe;
+/// `Debug` redacts the secret components and `==` compares them in constant
+/// time.
+///
+/// The primes are only known for keys that were generated or imported from
+/// their components; keys imported from `(n, e, d)` alone do not carry them
+/// and fall back to the slower non-CRT private operation.
+pub struct RsaPrivateKey {
+    public_key: RsaPublicKey,
+    d: BigUint,             // The private exponent.
+    crt: Option<CrtParams>, // The primes and CRT exponents, when known.
+}
+
+/// The Chinese Remainder Theorem representation of the private key (RFC 8017 §3.2).
+struct CrtParams {
+    p: BigUint,
+    q: BigUint,
+    d_p: BigUint,   // d mod (p - 1)
+    d_q: BigUint,   // d mod (q - 1)
+    q_inv: BigUint, // q^-1 mod p
+}
+
+impl CrtParams {
+    fn new(p: BigUint, q: BigUint, d: &BigUint) -> Result<Self, RsaError> {
+        let q_inv =
+            modular_inverse::try_mod_inverse(q.to_bigint().unwrap(), p.to_bigint().unwrap())
+                .and_then(|q_inv| q_inv.to_biguint())
+                .ok_or(RsaError::InvalidPrimes)?;
+
+        Ok(CrtParams {
+            d_p: d % (&p - 1u32),
+            d_q: d % (&q - 1u32),
+            q_inv,
+            p,
+            q,
+        })
+    }
+}
+
+/// Computes Carmichael's function λ(n) = lcm(p - 1, q - 1) for `n = p·q`.
+fn carmichael_lambda(p: &BigUint, q: &BigUint) -> BigInt {
+    relative_prime::lcm(
+        &(p - 1u32).to_bigint().unwrap(),
+        &(q - 1u32).to_bigint().unwrap(),
+    )
+}
+
+impl RsaPublicKey {
+    /// Creates a public key from its modulus and exponent.
+    ///
+    /// # Arguments
+    /// * `n` - The modulus.
+    /// * `e` - The public exponent; must be odd and satisfy 1 < e < n.
+    ///
+    /// # Returns
+    /// The public key, or `RsaError::InvalidPublicExponent` if `e` is out of range.
+    pub fn new(n: BigUint, e: BigUint) -> Result<Self, RsaError> {
+        if e <= BigUint::one() || e >= n || !e.bit(0) {
+            return Err(RsaError::InvalidPublicExponent);
+        }
+
+        Ok(RsaPublicKey { n, e })
+    }
+
+    /// Returns the modulus `n`.
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    /// Returns the public exponent `e`.
+    pub fn e(&self) -> &BigUint {
+        &self.e
+    }
+
+    /// Returns the modulus length `k` in bytes.
+    pub fn size(&self) -> usize {
+        self.n.bits().div_ceil(8) as usize
+    }
+
+    /// Computes `msg^e mod n` without padding (textbook RSA).
+    ///
+    /// Unpadded RSA is deterministic and malleable; use `encrypt_oaep` to
+    /// encrypt data. Messages below 2^64 are rejected, see
+    /// `encrypt_raw_with_options`.
+    ///
+    /// # Returns
+    /// The ciphertext, `RsaError::TextbookMessage` if `msg < 2^64`, or
+    /// `RsaError::MessageTooLong` if `msg >= n`.
+    pub fn encrypt_raw(&self, msg: &BigUint) -> Result<BigUint, RsaError> {
+        self.encrypt_raw_with_options(msg, &RawOptions::default())
+    }
+
+    /// Computes `msg^e mod n` without padding (textbook RSA).
+    ///
+    /// # Arguments
+    /// * `msg` - The message.
+    /// * `options` - Set `allow_textbook` to accept messages below 2^64.
+    ///
+    /// # Returns
+    /// The ciphertext, `RsaError::TextbookMessage` if `msg < 2^64` and
+    /// textbook use is not allowed, or `RsaError::MessageTooLong` if `msg >= n`.
+    pub fn encrypt_raw_with_options(
+        &self,
+        msg: &BigUint,
+        options: &RawOptions,
+    ) -> Result<BigUint, RsaError> {
+        if !options.allow_textbook && msg.bits() <= TEXTBOOK_MESSAGE_BITS {
+            return Err(RsaError::TextbookMessage);
+        }
+
+        self.rsaep(msg)
+    }
+
+    /// Computes `msg^e mod n`.
+    #[deprecated(note = "use `encrypt_raw`, or `encrypt_oaep` to encrypt data")]
+    pub fn encrypt(&self, msg: &BigUint) -> Result<BigUint, RsaError> {
+        self.rsaep(msg)
+    }
+
+    /// The RSAEP primitive (RFC 8017 §5.1.1) underlying every encryption
+    /// and signature verification.
+    pub(crate) fn rsaep(&self, msg: &BigUint) -> Result<BigUint, RsaError> {
+        if msg >= &self.n {
+            return Err(RsaError::MessageTooLong);
+        }
+
+        Ok(msg.modpow(&self.e, &self.n))
+    }
+
+    /// Encrypts the big-endian integer `msg` without padding (textbook RSA).
+    ///
+    /// # Arguments
+    /// * `msg` - The message bytes, interpreted as a big-endian integer.
+    ///
+    /// # Returns
+    /// The ciphertext as exactly `k` bytes, or the errors of `encrypt_raw`
+    /// for the integer form of `msg`.
+    pub fn encrypt_raw_bytes(&self, msg: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let c = self.encrypt_raw(&os2ip(msg))?;
+
+        Ok(i2osp(&c, self.size()).expect("c < n fits in k bytes"))
+    }
+}
+
+impl RsaPrivateKey {
+    /// Derives a private key from the primes `p`, `q` and the public exponent `e`.
+    ///
+    /// # Arguments
+    /// * `p` - The first prime factor.
+    /// * `q` - The second prime factor.
+    /// * `e` - The public exponent.
+    ///
+    /// # Returns
+    /// The private key, with `n = p·q` and `d = e⁻¹ mod λ(n)`.
+    pub fn from_components(p: BigUint, q: BigUint, e: BigUint) -> Result<Self, RsaError> {
+        let three = BigUint::from(3u32);
+        if p == q || p < three || q < three {
+            return Err(RsaError::InvalidPrimes);
+        }
+
+        let public_key = RsaPublicKey::new(&p * &q, e)?;
+
+        let lambda_n = carmichael_lambda(&p, &q);
+        let e = public_key.e.to_bigint().unwrap();
+
+        // e must be co-prime to lambda_n for the private exponent to exist.
+        if !relative_prime::is_co_prime(&lambda_n, &e) {
+            return Err(RsaError::NotCoprime);
+        }
+
+        // Calculate the private exponent d, the modular inverse of e mod lambda_n.
+        let d = modular_inverse::try_mod_inverse(e, lambda_n)
+            .and_then(|d| d.to_biguint())
+            .ok_or(RsaError::InverseNotFound)?;
+
+        Ok(RsaPrivateKey {
+            crt: Some(CrtParams::new(p, q, &d)?),
+            public_key,
+            d,
+        })
+    }
+
+    /// Creates a private key from `(n, e, d)` when the primes are not available.
+    ///
+    /// # Arguments
+    /// * `n` - The modulus.
+    /// * `e` - The public exponent.
+    /// * `d` - The private exponent; must satisfy 1 < d < n.
+    pub fn from_raw(n: BigUint, e: BigUint, d: BigUint) -> Result<Self, RsaError> {
+        let public_key = RsaPublicKey::new(n, e)?;
+
+        if d <= BigUint::one() || d >= public_key.n {
+            return Err(RsaError::InvalidPrivateExponent);
+        }
+
+        Ok(RsaPrivateKey {
+            public_key,
+            d,
+            crt: None,
+        })
+    }
+
+    /// Assembles a private key from all of its components, checking that they
+    /// fit together, e.g. when decoding a key that already carries `d`.
+    pub(crate) fn from_parts(
+        n: BigUint,
+        e: BigUint,
+        d: BigUint,
+        p: BigUint,
+        q: BigUint,
+    ) -> Result<Self, RsaError> {
+        if p == q || &p * &q != n {
+            return Err(RsaError::InvalidPrimes);
+        }
+
+        // d may be reduced modulo λ(n) or φ(n); either way e·d ≡ 1 (mod λ(n)).
+        let lambda_n = carmichael_lambda(&p, &q).to_biguint().unwrap();
+        if (&e * &d) % lambda_n != BigUint::one() {
+            return Err(RsaError::InvalidPrivateExponent);
+        }
+
+        let mut key = Self::from_raw(n, e, d)?;
+        key.crt = Some(CrtParams::new(p, q, &key.d)?);
+
+        Ok(key)
+    }
+
+    /// Replaces the CRT exponents and coefficient derived by `from_parts` with
+    /// stored ones, e.g. those carried by an encoded key, so that `validate`
+    /// can check them.
+    pub(crate) fn set_crt_values(&mut self, d_p: BigUint, d_q: BigUint, q_inv: BigUint) {
+        if let Some(crt) = &mut self.crt {
+            for (slot, value) in [
+                (&mut crt.d_p, d_p),
+                (&mut crt.d_q, d_q),
+                (&mut crt.q_inv, q_inv),
+            ] {
+                wipe_biguint(slot);
+                *slot = value;
+            }
+        }
+    }
+
+    /// Checks that the key is internally consistent (RFC 8017 §3.2).
+    ///
+    /// Keys without their primes only get the range checks on `e` and `d`,
+    /// since the remaining checks need the factorization of `n`.
+    ///
+    /// # Returns
+    /// `Ok(())`, or the error naming the first check that failed:
+    /// * `InvalidPublicExponent` - `e` is even or outside (1, n).
+    /// * `InvalidPrivateExponent` - `d` is outside (1, n) or e·d ≢ 1 (mod λ(n)).
+    /// * `InvalidPrimes` - `p == q` or a prime is smaller than 3.
+    /// * `CompositeFactor` - `p` or `q` fails the Miller-Rabin test.
+    /// * `ModulusMismatch` - `n ≠ p·q`.
+    /// * `InvalidCrtParameters` - `d_p ≠ d mod (p - 1)`, `d_q ≠ d mod (q - 1)`
+    ///   or `q_inv ≠ q⁻¹ mod p`.
+    pub fn validate(&self) -> Result<(), RsaError> {
+        let RsaPublicKey { n, e } = &self.public_key;
+        let d = &self.d;
+        let one = BigUint::one();
+
+        if e <= &one || e >= n || !e.bit(0) {
+            return Err(RsaError::InvalidPublicExponent);
+        }
+
+        if d <= &one || d >= n {
+            return Err(RsaError::InvalidPrivateExponent);
+        }
+
+        let Some(crt) = &self.crt else {
+            return Ok(());
+        };
+        let CrtParams {
+            p,
+            q,
+            d_p,
+            d_q,
+            q_inv,
+        } = crt;
+
+        let three = BigUint::from(3u32);
+        if p == q || p < &three || q < &three {
+            return Err(RsaError::InvalidPrimes);
+        }
+
+        if !MRPT::is_prime_with_rounds(p, VALIDATION_MR_ROUNDS)
+            || !MRPT::is_prime_with_rounds(q, VALIDATION_MR_ROUNDS)
+        {
+            return Err(RsaError::CompositeFactor);
+        }
+
+        if &(p * q) != n {
+            return Err(RsaError::ModulusMismatch);
+        }
+
+        let lambda_n = carmichael_lambda(p, q).to_biguint().unwrap();
+        if (e * d) % lambda_n != one {
+            return Err(RsaError::InvalidPrivateExponent);
+        }
+
+        // q_inv is the inverse of q modulo p only if it is also reduced.
+        if d_p != &(d % (p - 1u32))
+            || d_q != &(d % (q - 1u32))
+            || q_inv >= p
+            || (q * q_inv) % p != one
+        {
+            return Err(RsaError::InvalidCrtParameters);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the corresponding public key.
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+
+    /// Returns an owned copy of the public key, which can be handed out
+    /// without exposing `d` or the primes.
+    pub fn to_public(&self) -> RsaPublicKey {
+        self.public_key.clone()
+    }
+
+    /// Returns the modulus `n`.
+    pub fn n(&self) -> &BigUint {
+        &self.public_key.n
+    }
+
+    /// Returns the public exponent `e`.
+    pub fn e(&self) -> &BigUint {
+        &self.public_key.e
+    }
+
+    /// Returns the private exponent `d`.
+    pub fn d(&self) -> &BigUint {
+        &self.d
+    }
+
+    /// Returns the prime `p`, if known.
+    pub fn p(&self) -> Option<&BigUint> {
+        self.crt.as_ref().map(|crt| &crt.p)
+    }
+
+    /// Returns the prime `q`, if known.
+    pub fn q(&self) -> Option<&BigUint> {
+        self.crt.as_ref().map(|crt| &crt.q)
+    }
+
+    /// Returns `d mod (p - 1)`, if the primes are known.
+    pub fn d_p(&self) -> Option<&BigUint> {
+        self.crt.as_ref().map(|crt| &crt.d_p)
+    }
+
+    /// Returns `d mod (q - 1)`, if the primes are known.
+    pub fn d_q(&self) -> Option<&BigUint> {
+        self.crt.as_ref().map(|crt| &crt.d_q)
+    }
+
+    /// Returns `q^-1 mod p`, if the primes are known.
+    pub fn q_inv(&self) -> Option<&BigUint> {
+        self.crt.as_ref().map(|crt| &crt.q_inv)
+    }
+
+    /// Computes `c^d mod n` without padding (textbook RSA).
+    ///
+    /// When the primes are known this uses the CRT: two half-size
+    /// exponentiations recombined with Garner's formula, roughly 3-4 times
+    /// faster than a full exponentiation modulo `n`.
+    ///
+    /// # Returns
+    /// The message, or `RsaError::CiphertextOutOfRange` if `c >= n`.
+    pub fn decrypt_raw(&self, c: &BigUint) -> Result<BigUint, RsaError> {
+        if c >= &self.public_key.n {
+            return Err(RsaError::CiphertextOutOfRange);
+        }
+
+        Ok(match &self.crt {
+            Some(crt) => {
+                let m_1 = c.modpow(&crt.d_p, &crt.p);
+                let m_2 = c.modpow(&crt.d_q, &crt.q);
+
+                // h = q_inv * (m_1 - m_2) mod p, kept non-negative.
+                let h = (&crt.q_inv * (&crt.p + m_1 - (&m_2 % &crt.p))) % &crt.p;
+
+                m_2 + h * &crt.q
+            }
+            None => c.modpow(&self.d, &self.public_key.n),
+        })
+    }
+
+    /// Computes `c^d mod n`.
+    #[deprecated(note = "use `decrypt_raw`, or `decrypt_oaep` to decrypt data")]
+    pub fn decrypt(&self, c: &BigUint) -> Result<BigUint, RsaError> {
+        self.decrypt_raw(c)
+    }
+
+    /// Decrypts a ciphertext produced by `RsaPublicKey::encrypt_raw_bytes`.
+    ///
+    /// # Returns
+    /// The message as exactly `k` bytes, so leading zero bytes of a `k`-byte
+    /// message are preserved; `RsaError::InvalidCiphertextLength` unless the
+    /// ciphertext is exactly `k` bytes; or `RsaError::CiphertextOutOfRange`.
+    pub fn decrypt_raw_bytes(&self, ciphertext: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let k = self.public_key.size();
+        if ciphertext.len() != k {
+            return Err(RsaError::InvalidCiphertextLength {
+                expected: k,
+                actual: ciphertext.len(),
+            });
+        }
+
+        let m = self.decrypt_raw(&os2ip(ciphertext))?;
+        Ok(i2osp(&m, k).expect("m < n fits in k bytes"))
+    }
+}
+
+impl RsaPrivateKey {
+    /// Concatenates the secret components, each padded to the modulus width,
+    /// for constant-time comparison.
+    fn secret_bytes(&self) -> Vec<u8> {
+        let k = self.public_key.size();
+        let mut out = Vec::new();
+        let mut push = |value: &BigUint| {
+            let mut bytes = value.to_bytes_le();
+            bytes.resize(k.max(bytes.len()), 0);
+            out.extend_from_slice(&bytes);
+            bytes.zeroize();
+        };
+
+        push(&self.d);
+        if let Some(crt) = &self.crt {
+            for value in [&crt.p, &crt.q, &crt.d_p, &crt.d_q, &crt.q_inv] {
+                push(value);
+            }
+        }
+        out
+    }
+}
+
+impl PartialEq for RsaPrivateKey {
+    /// Compares the public keys and whether the primes are known directly,
+    /// and the secret components in constant time.
+    fn eq(&self, other: &Self) -> bool {
+        if self.public_key != other.public_key || self.crt.is_some() != other.crt.is_some() {
+            return false;
+        }
+
+        let (mut a, mut b) = (self.secret_bytes(), other.secret_bytes());
+        let equal = ct_eq(&a, &b);
+        a.zeroize();
+        b.zeroize();
+        equal
+    }
+}
+
+impl fmt::Debug for RsaPrivateKey {
+    /// Prints the public key; the private exponent and CRT parameters are redacted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RsaPrivateKey")
+            .field("public_key", &self.public_key)
+            .field("d", &"<redacted>")
+            .field("crt", &self.crt.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl Zeroize for CrtParams {
+    fn zeroize(&mut self) {
+        for value in [
+            &mut self.p,
+            &mut self.q,
+            &mut self.d_p,
+            &mut self.d_q,
+            &mut self.q_inv,
+        ] {
+            wipe_biguint(value);
+        }
+    }
+}
+
+impl Zeroize for RsaPrivateKey {
+    /// Wipes the private exponent and the CRT parameters; the public key is kept.
+    fn zeroize(&mut self) {
+        wipe_biguint(&mut self.d);
+        if let Some(crt) = self.crt.as_mut() {
+            crt.zeroize();
+        }
+    }
+}
+
+impl Drop for RsaPrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::RandBigInt;
+    use num_traits::Zero;
+    use utils::wipe::{biguint_pattern, freed_with, WatchingAllocator};
+
+    #[global_allocator]
+    static ALLOCATOR: WatchingAllocator = WatchingAllocator;
+
+    fn hex(s: &str) -> BigUint {
+        BigUint::parse_bytes(s.as_bytes(), 16).unwrap()
+    }
+
+    // Components of a 2048-bit key generated with `openssl genrsa 2048`.
+    const OPENSSL_N: &str = "b5c0159f2c840262342feff505fc6105d43d126187b873a16ee8697924e975d59dfc8cd7635242110c646ef26d943cdc5dc1d92cec98e6e5336c068e14532bf8cea6c80c55b2e4be57d142026c2df5504e7be898ca984711a5ea535266c4955b7f7a391cd25a7902be568bd5814f2c60416540d79306ac6db37e1e8111694ff3270098702759529e0d296a9feb9ce6ffd269803a0f4d202c027191b5f42565b98e576a34a690025a38c049c9e53f6e9c0da4e95f9a34c1b37c1ee2d66dfffe9a1c09b69c1daebd2b0ac93fd173a91aeaed69422c14c6fcd652e607f34b52ea0826815ec2ac3e0b74963941d20641fba21e94629ed88fc17b7907f3d8874e2f4d";
+    const OPENSSL_D: &str = "4127efa4995999d80339adc2ef0bf196412f9f200f5a65b5105a1bfede9ea407a675e54f7b20b7156c6568b7a3ff7c5fbf3b5372af7c0d5393bf1e55e59d54a44c92ba089078167d7d27e297de066f9df7e05aba4a39326ee3e4cd03fe8b2e01aa4b2356c4727ab62c1f4dbb2586b9f6d8ab9be1635fc854b4aac31956ba4f4a4fb6b401e7576381893897fbbf440fdc62c0a810f2e4a50e56fd30ac5a534b72d4de961d51d6f31690938bf83a9f48d46f3c2e8a8112f8cb8b4316b522b26f6944303ed224568c3de031beadbb37516622147bfcddc775e70fcbff5ba599f62d26ea1c97470ae6b84fbb3c53d7c22b8c7c9de587859e1b7e9fb9f676e3e0257";
+    const OPENSSL_P: &str = "eb1caaf48c87a74d0a06be973b4c2f966c63cf7cfa2f8f2ac1c1d0abb211db9c2d634db93bab50b2f3635fcce4bc959f13bbd9d47f733cacd50bb4cd168ef0cdaf42ad6f02d6758e1304f1474416608114a15c44a844d78b61c81f9ba630800dd4c56b469fffd8e8f41b8feaf8beb82b3652eb6e7923a299234d65acfac13da7";
+    const OPENSSL_Q: &str = "c5e5c58a48867a35908bf4950918ecd228ff9dad3a16cf2c8a44d58541344e6743006b60afa3dd75d5c358bf6f28c9f45bb2008e66801e3837548f72ad3ef444727191975148f675c49a3dbbf7cdeeae3db4fc9b77b3b2584658ff35a3fe7be1d1aa58a0a1c08c650259893de86ab53e573df9e6917671bf4eb7301ae1e991eb";
+
+    #[test]
+    fn textbook_key_from_components() {
+        let key = RsaPrivateKey::from_components(
+            BigUint::from(61u32),
+            BigUint::from(53u32),
+            BigUint::from(17u32),
+        )
+        .unwrap();
+
+        // λ(3233) = lcm(60, 52) = 780, and 17 · 413 = 9 · 780 + 1.
+        assert_eq!(key.n(), &BigUint::from(3233u32));
+        assert_eq!(key.d(), &BigUint::from(413u32));
+        assert_eq!(key.p(), Some(&BigUint::from(61u32)));
+        assert_eq!(key.q(), Some(&BigUint::from(53u32)));
+
+        let c = BigUint::from(2790u32);
+        let textbook = RawOptions {
+            allow_textbook: true,
+        };
+        assert_eq!(key.decrypt_raw(&c), Ok(BigUint::from(65u32)));
+        assert_eq!(
+            key.public_key()
+                .encrypt_raw_with_options(&BigUint::from(65u32), &textbook),
+            Ok(c)
+        );
+        assert_eq!(
+            key.public_key().encrypt_raw(&BigUint::from(65u32)),
+            Err(RsaError::TextbookMessage)
+        );
+    }
+
+    #[test]
+    fn openssl_key_round_trip() {
+        let e = BigUint::from(65537u32);
+        let msg = hex("0123456789abcdef0123456789abcdef");
+
+        let raw = RsaPrivateKey::from_raw(hex(OPENSSL_N), e.clone(), hex(OPENSSL_D)).unwrap();
+        assert_eq!(raw.p(), None);
+        assert_eq!(
+            raw.decrypt_raw(&raw.public_key().encrypt_raw(&msg).unwrap()),
+            Ok(msg.clone())
+        );
+
+        let derived = RsaPrivateKey::from_components(hex(OPENSSL_P), hex(OPENSSL_Q), e).unwrap();
+        assert_eq!(derived.n(), raw.n());
+        assert_eq!(
+            derived.decrypt_raw(&raw.public_key().encrypt_raw(&msg).unwrap()),
+            Ok(msg.clone())
+        );
+        assert_eq!(
+            raw.decrypt_raw(&derived.public_key().encrypt_raw(&msg).unwrap()),
+            Ok(msg)
+        );
+    }
+
+    #[test]
+    fn derives_openssl_private_exponent() {
+        let key =
+            RsaPrivateKey::from_components(hex(OPENSSL_P), hex(OPENSSL_Q), BigUint::from(65537u32))
+                .unwrap();
+
+        assert_eq!(key.n(), &hex(OPENSSL_N));
+        assert_eq!(key.d(), &hex(OPENSSL_D));
+    }
+
+    #[test]
+    fn checks_private_exponent_against_lambda() {
+        let from_parts = |d: u32| {
+            RsaPrivateKey::from_parts(
+                BigUint::from(3233u32),
+                BigUint::from(17u32),
+                BigUint::from(d),
+                BigUint::from(61u32),
+                BigUint::from(53u32),
+            )
+        };
+
+        // Both the λ(n)- and the φ(n)-based exponent are accepted.
+        assert!(from_parts(413).is_ok());
+        assert!(from_parts(2753).is_ok());
+        assert_eq!(from_parts(414), Err(RsaError::InvalidPrivateExponent));
+    }
+
+    #[test]
+    fn validate_pinpoints_corrupted_components() {
+        let key =
+            RsaPrivateKey::from_components(hex(OPENSSL_P), hex(OPENSSL_Q), BigUint::from(65537u32))
+                .unwrap();
+        assert_eq!(key.validate(), Ok(()));
+
+        let corrupt = |f: &dyn Fn(&mut RsaPrivateKey)| {
+            let mut corrupted = RsaPrivateKey::from_components(
+                hex(OPENSSL_P),
+                hex(OPENSSL_Q),
+                BigUint::from(65537u32),
+            )
+            .unwrap();
+            f(&mut corrupted);
+            corrupted.validate()
+        };
+        fn crt(key: &mut RsaPrivateKey) -> &mut CrtParams {
+            key.crt.as_mut().unwrap()
+        }
+
+        assert_eq!(
+            corrupt(&|key| key.public_key.e = BigUint::from(65536u32)),
+            Err(RsaError::InvalidPublicExponent)
+        );
+        assert_eq!(
+            corrupt(&|key| key.d = key.public_key.n.clone()),
+            Err(RsaError::InvalidPrivateExponent)
+        );
+        assert_eq!(
+            corrupt(&|key| key.d += 2u32),
+            Err(RsaError::InvalidPrivateExponent)
+        );
+        assert_eq!(
+            corrupt(&|key| crt(key).q = hex(OPENSSL_P)),
+            Err(RsaError::InvalidPrimes)
+        );
+        assert_eq!(
+            corrupt(&|key| crt(key).p *= 3u32),
+            Err(RsaError::CompositeFactor)
+        );
+        assert_eq!(
+            corrupt(&|key| key.public_key.n += 2u32),
+            Err(RsaError::ModulusMismatch)
+        );
+        assert_eq!(
+            corrupt(&|key| crt(key).d_p += 1u32),
+            Err(RsaError::InvalidCrtParameters)
+        );
+        assert_eq!(
+            corrupt(&|key| crt(key).d_q -= 1u32),
+            Err(RsaError::InvalidCrtParameters)
+        );
+        assert_eq!(
+            corrupt(&|key| crt(key).q_inv += 1u32),
+            Err(RsaError::InvalidCrtParameters)
+        );
+
+        // Without the primes only the exponent ranges can be checked.
+        let raw = RsaPrivateKey::from_raw(hex(OPENSSL_N), 65537u32.into(), hex(OPENSSL_D)).unwrap();
+        assert_eq!(raw.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_invalid_components() {
+        let n = BigUint::from(3233u32);
+
+        for e in [0u32, 1, 16, 3233, 4000] {
+            assert_eq!(
+                RsaPublicKey::new(n.clone(), BigUint::from(e)),
+                Err(RsaError::InvalidPublicExponent)
+            );
+        }
+
+        for d in [0u32, 1, 3233] {
+            assert_eq!(
+                RsaPrivateKey::from_raw(n.clone(), BigUint::from(17u32), BigUint::from(d)),
+                Err(RsaError::InvalidPrivateExponent)
+            );
+        }
+
+        let from_components = |p: u32, q: u32, e: u32| {
+            RsaPrivateKey::from_components(BigUint::from(p), BigUint::from(q), BigUint::from(e))
+        };
+        assert_eq!(from_components(61, 61, 17), Err(RsaError::InvalidPrimes));
+        assert_eq!(from_components(2, 61, 17), Err(RsaError::InvalidPrimes));
+        assert_eq!(from_components(61, 53, 5), Err(RsaError::NotCoprime));
+    }
+
+    #[test]
+    fn encrypt_bounds() {
+        let key = RsaPrivateKey::from_pkcs8_pem(include_str!("../fixtures/openssl_2048_pkcs8.pem"))
+            .unwrap();
+        let public_key = key.public_key();
+        let n = public_key.n();
+
+        let c = public_key.encrypt_raw(&(n - 1u32)).unwrap();
+        assert_eq!(key.decrypt_raw(&c), Ok(n - 1u32));
+
+        for msg in [n.clone(), n + 1u32] {
+            assert_eq!(public_key.encrypt_raw(&msg), Err(RsaError::MessageTooLong));
+            assert_eq!(
+                public_key.encrypt_raw_bytes(&msg.to_bytes_be()),
+                Err(RsaError::MessageTooLong)
+            );
+        }
+    }
+
+    #[test]
+    fn decrypt_bounds() {
+        let key = RsaPrivateKey::from_pkcs8_pem(include_str!("../fixtures/openssl_2048_pkcs8.pem"))
+            .unwrap();
+        let raw =
+            RsaPrivateKey::from_raw(key.n().clone(), key.e().clone(), key.d().clone()).unwrap();
+        let n = key.n();
+        let k = key.public_key().size();
+
+        for key in [&key, &raw] {
+            for c in [n.clone(), n + 1u32] {
+                assert_eq!(key.decrypt_raw(&c), Err(RsaError::CiphertextOutOfRange));
+            }
+
+            assert_eq!(
+                key.decrypt_raw_bytes(&vec![0xff; k]),
+                Err(RsaError::CiphertextOutOfRange)
+            );
+            for len in [k - 1, k + 1] {
+                assert_eq!(
+                    key.decrypt_raw_bytes(&vec![0; len]),
+                    Err(RsaError::InvalidCiphertextLength {
+                        expected: k,
+                        actual: len
+                    })
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn raw_bytes_preserve_leading_zeros() {
+        let key = RsaPrivateKey::from_pkcs8_pem(include_str!("../fixtures/openssl_2048_pkcs8.pem"))
+            .unwrap();
+        let k = key.public_key().size();
+
+        let mut msg = vec![0u8; k];
+        msg[3..]
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, byte)| *byte = i as u8);
+
+        let ciphertext = key.public_key().encrypt_raw_bytes(&msg).unwrap();
+        assert_eq!(ciphertext.len(), k);
+        assert_eq!(key.decrypt_raw_bytes(&ciphertext), Ok(msg));
+
+        assert_eq!(
+            key.public_key().encrypt_raw_bytes(&[0, 0, 7]),
+            Err(RsaError::TextbookMessage)
+        );
+    }
+
+    #[test]
+    fn crt_matches_full_exponentiation() {
+        let key = RsaPrivateKey::from_pkcs8_pem(include_str!("../fixtures/openssl_2048_pkcs8.pem"))
+            .unwrap();
+        let raw =
+            RsaPrivateKey::from_raw(key.n().clone(), key.e().clone(), key.d().clone()).unwrap();
+        assert!(key.crt.is_some() && raw.crt.is_none());
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..32 {
+            let c = rng.gen_biguint_below(key.n());
+            assert_eq!(key.decrypt_raw(&c), raw.decrypt_raw(&c));
+        }
+
+        for c in [BigUint::from(0u32), BigUint::from(1u32), key.n() - 1u32] {
+            assert_eq!(key.decrypt_raw(&c), raw.decrypt_raw(&c));
+        }
+    }
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let key =
+            RsaPrivateKey::from_components(hex(OPENSSL_P), hex(OPENSSL_Q), BigUint::from(65537u32))
+                .unwrap();
+        let debug = format!("{key:?}");
+
+        assert!(debug.contains(&key.n().to_string()));
+        for secret in [key.d(), key.p().unwrap(), key.q().unwrap()] {
+            assert!(!debug.contains(&secret.to_string()));
+        }
+        assert!(debug.contains("<redacted>"));
+    }
+
+    #[test]
+    fn equality_covers_secret_components() {
+        let key = || {
+            RsaPrivateKey::from_components(hex(OPENSSL_P), hex(OPENSSL_Q), BigUint::from(65537u32))
+                .unwrap()
+        };
+        assert_eq!(key(), key());
+
+        let mut other = key();
+        other.d += 2u32;
+        assert_ne!(key(), other);
+
+        let mut other = key();
+        other.crt.as_mut().unwrap().q_inv += 1u32;
+        assert_ne!(key(), other);
+
+        let raw = RsaPrivateKey::from_raw(hex(OPENSSL_N), 65537u32.into(), hex(OPENSSL_D)).unwrap();
+        assert_ne!(key(), raw);
+    }
+
+    #[test]
+    fn zeroize_clears_private_components() {
+        let mut key =
+            RsaPrivateKey::from_pkcs8_pem(include_str!("../fixtures/openssl_2048_pkcs8.pem"))
+                .unwrap();
+        let n = key.n().clone();
+
+        key.zeroize();
+
+        assert!(key.d.is_zero());
+        let crt = key.crt.as_ref().unwrap();
+        for value in [&crt.p, &crt.q, &crt.d_p, &crt.d_q, &crt.q_inv] {
+            assert!(value.is_zero());
+        }
+        assert_eq!(key.n(), &n);
+    }
+
+    #[test]
+    fn drop_wipes_private_components() {
+        let load = || {
+            RsaPrivateKey::from_pkcs8_pem(include_str!("../fixtures/openssl_2048_pkcs8.pem"))
+                .unwrap()
+        };
+        let key = load();
+        let secrets: [fn(&RsaPrivateKey) -> &BigUint; 3] = [
+            |key| key.d(),
+            |key| key.p().unwrap(),
+            |key| key.q().unwrap(),
+        ];
+
+        // An unwiped copy is caught, so the watch itself works.
+        let copy = key.d().clone();
+        assert!(freed_with(&biguint_pattern(key.d()), || drop(copy)));
+
+        for secret in secrets {
+            let key = load();
+            let pattern = biguint_pattern(secret(&key));
+            assert!(!freed_with(&pattern, || drop(key)));
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! This module converts public keys to and from the OpenSSH `authorized_keys`
+//! format, whose base64 payload is the SSH wire encoding of RFC 4253 §6.6.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use num_bigint::BigUint;
+
+use crate::error::RsaError;
+use crate::key::RsaPublicKey;
+
+// Key type name that prefixes both the line and the wire encoding.
+const SSH_RSA: &str = "ssh-rsa";
+
+fn invalid(reason: &str) -> RsaError {
+    RsaError::InvalidEncoding(reason.to_string())
+}
+
+impl RsaPublicKey {
+    /// Encodes the key as an `authorized_keys` line, `ssh-rsa <base64> comment`.
+    ///
+    /// # Arguments
+    /// * `comment` - Appended after the key; omitted when empty.
+    pub fn to_openssh(&self, comment: &str) -> String {
+        let mut blob = Vec::new();
+        write_string(SSH_RSA.as_bytes(), &mut blob);
+        write_mpint(self.e(), &mut blob);
+        write_mpint(self.n(), &mut blob);
+
+        let line = format!("{} {}", SSH_RSA, STANDARD.encode(blob));
+        if comment.is_empty() {
+            line
+        } else {
+            format!("{} {}", line, comment)
+        }
+    }
+
+    /// Parses an `authorized_keys` line produced by `to_openssh` or `ssh-keygen`.
+    ///
+    /// Any comment after the key is ignored.
+    pub fn from_openssh(line: &str) -> Result<Self, RsaError> {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some(SSH_RSA) {
+            return Err(invalid("expected an ssh-rsa key"));
+        }
+
+        let blob = fields
+            .next()
+            .ok_or_else(|| invalid("missing key data"))
+            .and_then(|encoded| {
+                STANDARD
+                    .decode(encoded)
+                    .map_err(|err| invalid(&err.to_string()))
+            })?;
+
+        let mut reader = WireReader { data: &blob };
+        if reader.read_string()? != SSH_RSA.as_bytes() {
+            return Err(invalid("key type does not match the line prefix"));
+        }
+        let e = reader.read_mpint()?;
+        let n = reader.read_mpint()?;
+        if !reader.data.is_empty() {
+            return Err(invalid("trailing data"));
+        }
+
+        RsaPublicKey::new(n, e)
+    }
+}
+
+/// Appends a `uint32` length-prefixed byte string.
+fn write_string(data: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(data);
+}
+
+/// Appends a non-negative `mpint`: a zero byte is prepended when the most
+/// significant bit is set, since mpints are two's complement.
+fn write_mpint(n: &BigUint, out: &mut Vec<u8>) {
+    let mut bytes = n.to_bytes_be();
+    if bytes == [0] {
+        bytes.clear();
+    } else if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0);
+    }
+
+    write_string(&bytes, out);
+}
+
+/// A cursor over SSH wire-encoded data.
+struct WireReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    fn read_string(&mut self) -> Result<&'a [u8], RsaError> {
+        if self.data.len() < 4 {
+            return Err(invalid("unexpected end of data"));
+        }
+
+        let (len, rest) = self.data.split_at(4);
+        let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+        if len > rest.len() {
+            return Err(invalid("length exceeds the available data"));
+        }
+
+        let (content, rest) = rest.split_at(len);
+        self.data = rest;
+        Ok(content)
+    }
+
+    fn read_mpint(&mut self) -> Result<BigUint, RsaError> {
+        match self.read_string()? {
+            [first, ..] if first & 0x80 != 0 => Err(invalid("negative mpint")),
+            [0] | [0, 0..=0x7f, ..] => Err(invalid("non-minimal mpint")),
+            content => Ok(BigUint::from_bytes_be(content)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::RsaPrivateKey;
+
+    const PKCS8_PEM: &str = include_str!("../fixtures/openssl_2048_pkcs8.pem");
+
+    // `ssh-keygen -y -f` on the fixture key, and `ssh-keygen -e -m RFC4716`
+    // on that output.
+    const OPENSSH_PUB: &str = include_str!("../fixtures/openssl_2048.pub");
+    const RFC4716_PUB: &str = include_str!("../fixtures/openssl_2048_rfc4716.pub");
+
+    #[test]
+    fn matches_ssh_keygen() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+        let line = key.public_key().to_openssh("");
+
+        assert_eq!(line, OPENSSH_PUB.trim_end());
+
+        // The RFC 4716 file wraps the same wire encoding between its headers.
+        let rfc4716_body: String = RFC4716_PUB
+            .lines()
+            .filter(|line| !line.starts_with("----") && !line.starts_with("Comment:"))
+            .collect();
+        assert_eq!(line.split(' ').nth(1), Some(rfc4716_body.as_str()));
+    }
+
+    #[test]
+    fn round_trips() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+        let public_key = key.public_key();
+
+        let line = public_key.to_openssh("user@example");
+        assert!(line.starts_with("ssh-rsa AAAAB3NzaC1yc2E"));
+        assert!(line.ends_with(" user@example"));
+
+        assert_eq!(RsaPublicKey::from_openssh(&line).as_ref(), Ok(public_key));
+        assert_eq!(
+            RsaPublicKey::from_openssh(OPENSSH_PUB).as_ref(),
+            Ok(public_key)
+        );
+    }
+
+    #[test]
+    fn mpint_encoding() {
+        for (n, encoded) in [
+            (0u32, vec![0, 0, 0, 0]),
+            (0x7f, vec![0, 0, 0, 1, 0x7f]),
+            (0x80, vec![0, 0, 0, 2, 0x00, 0x80]),
+            (0x010001, vec![0, 0, 0, 3, 0x01, 0x00, 0x01]),
+        ] {
+            let mut out = Vec::new();
+            write_mpint(&BigUint::from(n), &mut out);
+            assert_eq!(out, encoded);
+            assert_eq!(
+                WireReader { data: &encoded }.read_mpint(),
+                Ok(BigUint::from(n))
+            );
+        }
+
+        for encoded in [
+            &[0, 0, 0, 1, 0x80][..],
+            &[0, 0, 0, 2, 0x00, 0x7f],
+            &[0, 0, 0, 1, 0x00],
+            &[0, 0, 0, 5, 0x01],
+        ] {
+            assert!(WireReader { data: encoded }.read_mpint().is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let line = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM)
+            .unwrap()
+            .public_key()
+            .to_openssh("");
+        let blob = line.split(' ').nth(1).unwrap();
+
+        for line in [
+            String::new(),
+            format!("ssh-ed25519 {}", blob),
+            "ssh-rsa".to_string(),
+            "ssh-rsa not-base64!".to_string(),
+            format!("ssh-rsa {}", &blob[..blob.len() - 8]),
+            format!("ssh-rsa {}AAAA", blob),
+        ] {
+            assert!(RsaPublicKey::from_openssh(&line).is_err(), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! This module serializes RSA keys in the standard interchange formats:
+//! PKCS#1 `RSAPrivateKey`/`RSAPublicKey` (RFC 8017 Appendix A.1), PKCS#8
+//! `PrivateKeyInfo` (RFC 5208) and X.509 `SubjectPublicKeyInfo` (RFC 5280),
+//! as DER or PEM.
+use num_bigint::BigUint;
+
+use crate::der::{self, DerReader};
+use crate::error::RsaError;
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+
+// DER encoding of the rsaEncryption OID, 1.2.840.113549.1.1.1.
+const RSA_ENCRYPTION_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+const PKCS1_PRIVATE_LABEL: &str = "RSA PRIVATE KEY";
+const PKCS1_PUBLIC_LABEL: &str = "RSA PUBLIC KEY";
+const PKCS8_LABEL: &str = "PRIVATE KEY";
+const SPKI_LABEL: &str = "PUBLIC KEY";
+
+impl RsaPublicKey {
+    /// Encodes the key as a PKCS#1 `RSAPublicKey` structure.
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        let mut content = Vec::new();
+        der::write_integer(self.n(), &mut content);
+        der::write_integer(self.e(), &mut content);
+
+        let mut out = Vec::new();
+        der::write_tlv(der::TAG_SEQUENCE, &content, &mut out);
+        out
+    }
+
+    /// Decodes a PKCS#1 `RSAPublicKey` structure.
+    pub fn from_pkcs1_der(bytes: &[u8]) -> Result<Self, RsaError> {
+        let mut outer = DerReader::new(bytes);
+        let mut seq = outer.read_sequence()?;
+        outer.finish()?;
+
+        let n = seq.read_integer()?;
+        let e = seq.read_integer()?;
+        seq.finish()?;
+
+        RsaPublicKey::new(n, e)
+    }
+
+    /// Encodes the key as PEM with the `RSA PUBLIC KEY` label.
+    pub fn to_pkcs1_pem(&self) -> String {
+        der::to_pem(PKCS1_PUBLIC_LABEL, &self.to_pkcs1_der())
+    }
+
+    /// Decodes a PEM `RSA PUBLIC KEY` block.
+    pub fn from_pkcs1_pem(pem: &str) -> Result<Self, RsaError> {
+        Self::from_pkcs1_der(&der::from_pem(PKCS1_PUBLIC_LABEL, pem)?)
+    }
+
+    /// Encodes the key as an X.509 `SubjectPublicKeyInfo` structure.
+    pub fn to_public_key_der(&self) -> Vec<u8> {
+        // The BIT STRING holding the key starts with a zero unused-bits octet.
+        let mut bit_string = vec![0u8];
+        bit_string.extend_from_slice(&self.to_pkcs1_der());
+
+        let mut content = algorithm_identifier();
+        der::write_tlv(der::TAG_BIT_STRING, &bit_string, &mut content);
+
+        let mut out = Vec::new();
+        der::write_tlv(der::TAG_SEQUENCE, &content, &mut out);
+        out
+    }
+
+    /// Decodes an X.509 `SubjectPublicKeyInfo` structure holding an RSA key.
+    pub fn from_public_key_der(bytes: &[u8]) -> Result<Self, RsaError> {
+        let mut outer = DerReader::new(bytes);
+        let mut seq = outer.read_sequence()?;
+        outer.finish()?;
+
+        read_algorithm_identifier(&mut seq)?;
+        let bit_string = seq.read_tlv(der::TAG_BIT_STRING)?;
+        seq.finish()?;
+
+        match bit_string.split_first() {
+            Some((0, key)) => Self::from_pkcs1_der(key),
+            _ => Err(RsaError::InvalidEncoding(
+                "unexpected unused bits in the key".to_string(),
+            )),
+        }
+    }
+
+    /// Encodes the key as PEM with the `PUBLIC KEY` label.
+    pub fn to_public_key_pem(&self) -> String {
+        der::to_pem(SPKI_LABEL, &self.to_public_key_der())
+    }
+
+    /// Decodes a PEM `PUBLIC KEY` block.
+    pub fn from_public_key_pem(pem: &str) -> Result<Self, RsaError> {
+        Self::from_public_key_der(&der::from_pem(SPKI_LABEL, pem)?)
+    }
+}
+
+impl RsaPrivateKey {
+    /// Encodes the key as a PKCS#1 `RSAPrivateKey` structure.
+    ///
+    /// # Returns
+    /// The DER bytes, or `RsaError::MissingPrimes` for keys imported without
+    /// their primes, since the structure requires them.
+    pub fn to_pkcs1_der(&self) -> Result<Vec<u8>, RsaError> {
+        let (p, q, d_p, d_q, q_inv) =
+            match (self.p(), self.q(), self.d_p(), self.d_q(), self.q_inv()) {
+                (Some(p), Some(q), Some(d_p), Some(d_q), Some(q_inv)) => (p, q, d_p, d_q, q_inv),
+                _ => return Err(RsaError::MissingPrimes),
+            };
+
+        let mut content = Vec::new();
+        let version = BigUint::from(0u32);
+        for value in [
+            &version,
+            self.n(),
+            self.e(),
+            self.d(),
+            p,
+            q,
+            d_p,
+            d_q,
+            q_inv,
+        ] {
+            der::write_integer(value, &mut content);
+        }
+
+        let mut out = Vec::new();
+        der::write_tlv(der::TAG_SEQUENCE, &content, &mut out);
+        Ok(out)
+    }
+
+    /// Decodes a PKCS#1 `RSAPrivateKey` structure.
+    ///
+    /// Only two-prime keys (version 0) are supported. The stored CRT
+    /// parameters are kept as they are; `validate` checks them against `d`,
+    /// `p` and `q`.
+    pub fn from_pkcs1_der(bytes: &[u8]) -> Result<Self, RsaError> {
+        let mut outer = DerReader::new(bytes);
+        let mut seq = outer.read_sequence()?;
+        outer.finish()?;
+
+        if seq.read_integer()? != BigUint::from(0u32) {
+            return Err(RsaError::InvalidEncoding(
+                "unsupported RSAPrivateKey version".to_string(),
+            ));
+        }
+
+        let n = seq.read_integer()?;
+        let e = seq.read_integer()?;
+        let d = seq.read_integer()?;
+        let p = seq.read_integer()?;
+        let q = seq.read_integer()?;
+        let d_p = seq.read_integer()?;
+        let d_q = seq.read_integer()?;
+        let q_inv = seq.read_integer()?;
+        seq.finish()?;
+
+        let mut key = RsaPrivateKey::from_parts(n, e, d, p, q)?;
+        key.set_crt_values(d_p, d_q, q_inv);
+
+        Ok(key)
+    }
+
+    /// Encodes the key as PEM with the `RSA PRIVATE KEY` label.
+    pub fn to_pkcs1_pem(&self) -> Result<String, RsaError> {
+        Ok(der::to_pem(PKCS1_PRIVATE_LABEL, &self.to_pkcs1_der()?))
+    }
+
+    /// Decodes a PEM `RSA PRIVATE KEY` block.
+    pub fn from_pkcs1_pem(pem: &str) -> Result<Self, RsaError> {
+        Self::from_pkcs1_der(&der::from_pem(PKCS1_PRIVATE_LABEL, pem)?)
+    }
+
+    /// Encodes the key as an unencrypted PKCS#8 `PrivateKeyInfo` structure.
+    pub fn to_pkcs8_der(&self) -> Result<Vec<u8>, RsaError> {
+        let mut content = Vec::new();
+        der::write_integer(&BigUint::from(0u32), &mut content);
+        content.extend_from_slice(&algorithm_identifier());
+        der::write_tlv(der::TAG_OCTET_STRING, &self.to_pkcs1_der()?, &mut content);
+
+        let mut out = Vec::new();
+        der::write_tlv(der::TAG_SEQUENCE, &content, &mut out);
+        Ok(out)
+    }
+
+    /// Decodes an unencrypted PKCS#8 `PrivateKeyInfo` structure holding an RSA key.
+    pub fn from_pkcs8_der(bytes: &[u8]) -> Result<Self, RsaError> {
+        let mut outer = DerReader::new(bytes);
+        let mut seq = outer.read_sequence()?;
+        outer.finish()?;
+
+        if seq.read_integer()? != BigUint::from(0u32) {
+            return Err(RsaError::InvalidEncoding(
+                "unsupported PrivateKeyInfo version".to_string(),
+            ));
+        }
+
+        read_algorithm_identifier(&mut seq)?;
+        let key = seq.read_tlv(der::TAG_OCTET_STRING)?;
+        seq.finish()?;
+
+        Self::from_pkcs1_der(key)
+    }
+
+    /// Encodes the key as PEM with the `PRIVATE KEY` label.
+    pub fn to_pkcs8_pem(&self) -> Result<String, RsaError> {
+        Ok(der::to_pem(PKCS8_LABEL, &self.to_pkcs8_der()?))
+    }
+
+    /// Decodes a PEM `PRIVATE KEY` block.
+    pub fn from_pkcs8_pem(pem: &str) -> Result<Self, RsaError> {
+        Self::from_pkcs8_der(&der::from_pem(PKCS8_LABEL, pem)?)
+    }
+}
+
+/// Encodes the `AlgorithmIdentifier` for rsaEncryption with NULL parameters.
+fn algorithm_identifier() -> Vec<u8> {
+    let mut content = Vec::new();
+    der::write_tlv(der::TAG_OID, RSA_ENCRYPTION_OID, &mut content);
+    der::write_tlv(der::TAG_NULL, &[], &mut content);
+
+    let mut out = Vec::new();
+    der::write_tlv(der::TAG_SEQUENCE, &content, &mut out);
+    out
+}
+
+/// Reads an `AlgorithmIdentifier` and checks that it names rsaEncryption.
+fn read_algorithm_identifier(reader: &mut DerReader) -> Result<(), RsaError> {
+    let mut seq = reader.read_sequence()?;
+
+    if seq.read_tlv(der::TAG_OID)? != RSA_ENCRYPTION_OID {
+        return Err(RsaError::InvalidEncoding(
+            "the key algorithm is not rsaEncryption".to_string(),
+        ));
+    }
+    if !seq.read_tlv(der::TAG_NULL)?.is_empty() {
+        return Err(RsaError::InvalidEncoding(
+            "rsaEncryption parameters must be NULL".to_string(),
+        ));
+    }
+
+    seq.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A key generated with `openssl genrsa 2048` (PKCS#8) and converted with
+    // `openssl rsa -traditional`, `-pubout` and `-RSAPublicKey_out`.
+    const PKCS8_PEM: &str = include_str!("../fixtures/openssl_2048_pkcs8.pem");
+    const PKCS1_PEM: &str = include_str!("../fixtures/openssl_2048_pkcs1.pem");
+    const SPKI_PEM: &str = include_str!("../fixtures/openssl_2048_spki.pem");
+    const PKCS1_PUBLIC_PEM: &str = include_str!("../fixtures/openssl_2048_pkcs1_pub.pem");
+
+    #[test]
+    fn openssl_private_key_round_trip() {
+        let pkcs1_der = der::from_pem(PKCS1_PRIVATE_LABEL, PKCS1_PEM).unwrap();
+        let pkcs8_der = der::from_pem(PKCS8_LABEL, PKCS8_PEM).unwrap();
+
+        let key = RsaPrivateKey::from_pkcs1_der(&pkcs1_der).unwrap();
+        assert_eq!(RsaPrivateKey::from_pkcs8_der(&pkcs8_der).unwrap(), key);
+        assert_eq!(key.n().bits(), 2048);
+
+        assert_eq!(key.to_pkcs1_der().unwrap(), pkcs1_der);
+        assert_eq!(key.to_pkcs8_der().unwrap(), pkcs8_der);
+        assert_eq!(key.to_pkcs1_pem().unwrap(), PKCS1_PEM);
+        assert_eq!(key.to_pkcs8_pem().unwrap(), PKCS8_PEM);
+    }
+
+    #[test]
+    fn keeps_stored_crt_parameters() {
+        let key = RsaPrivateKey::from_pkcs1_pem(PKCS1_PEM).unwrap();
+        let p = key.p().unwrap();
+        let components = [
+            BigUint::from(0u32),
+            key.n().clone(),
+            key.e().clone(),
+            key.d().clone(),
+            p.clone(),
+            key.q().unwrap().clone(),
+            key.d_p().unwrap().clone(),
+            key.d_q().unwrap().clone(),
+            key.q_inv().unwrap().clone(),
+        ];
+
+        // dP, dQ and qInv, each off by one, and qInv off by p.
+        for (index, offset) in [
+            (6, BigUint::from(1u32)),
+            (7, 1u32.into()),
+            (8, 1u32.into()),
+            (8, p.clone()),
+        ] {
+            let mut corrupted = components.clone();
+            corrupted[index] += offset;
+
+            let mut content = Vec::new();
+            for value in &corrupted {
+                der::write_integer(value, &mut content);
+            }
+            let mut encoded = Vec::new();
+            der::write_tlv(der::TAG_SEQUENCE, &content, &mut encoded);
+
+            let decoded = RsaPrivateKey::from_pkcs1_der(&encoded).unwrap();
+            assert_eq!(decoded.to_pkcs1_der().unwrap(), encoded, "index {index}");
+            assert_eq!(
+                decoded.validate(),
+                Err(RsaError::InvalidCrtParameters),
+                "index {index}"
+            );
+        }
+
+        assert_eq!(key.validate(), Ok(()));
+    }
+
+    #[test]
+    fn openssl_public_key_round_trip() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+
+        let spki = RsaPublicKey::from_public_key_pem(SPKI_PEM).unwrap();
+        let pkcs1 = RsaPublicKey::from_pkcs1_pem(PKCS1_PUBLIC_PEM).unwrap();
+        assert_eq!(&spki, key.public_key());
+        assert_eq!(&pkcs1, key.public_key());
+
+        assert_eq!(spki.to_public_key_pem(), SPKI_PEM);
+        assert_eq!(spki.to_pkcs1_pem(), PKCS1_PUBLIC_PEM);
+    }
+
+    #[test]
+    fn generated_key_structure() {
+        // Our output is accepted by `openssl rsa -check -in key.pem` and
+        // `openssl pkey -pubin -in pub.pem -text`; structurally, the modulus
+        // has its high bit set and therefore needs the leading zero byte.
+        let key = RsaPrivateKey::from_components(
+            BigUint::from(0xfffffffbu32),
+            BigUint::from(0xffffffefu32),
+            BigUint::from(65537u32),
+        )
+        .unwrap();
+
+        let der = key.to_pkcs1_der().unwrap();
+        let mut outer = DerReader::new(&der);
+        let mut seq = outer.read_sequence().unwrap();
+        assert!(outer.finish().is_ok());
+
+        assert_eq!(seq.read_integer().unwrap(), BigUint::from(0u32));
+        assert_eq!(&seq.read_integer().unwrap(), key.n());
+        assert_eq!(der[5..8], [der::TAG_INTEGER, 9, 0x00]);
+
+        assert_eq!(RsaPrivateKey::from_pkcs1_der(&der).unwrap(), key);
+        assert_eq!(
+            RsaPrivateKey::from_pkcs8_pem(&key.to_pkcs8_pem().unwrap()).unwrap(),
+            key
+        );
+    }
+
+    #[test]
+    fn rejects_trailing_garbage_and_missing_primes() {
+        let mut der = der::from_pem(PKCS1_PRIVATE_LABEL, PKCS1_PEM).unwrap();
+        der.push(0);
+        assert!(matches!(
+            RsaPrivateKey::from_pkcs1_der(&der),
+            Err(RsaError::InvalidEncoding(_))
+        ));
+
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+        let raw =
+            RsaPrivateKey::from_raw(key.n().clone(), key.e().clone(), key.d().clone()).unwrap();
+        assert_eq!(raw.to_pkcs1_der(), Err(RsaError::MissingPrimes));
+    }
+}
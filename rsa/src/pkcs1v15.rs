@@ -0,0 +1,368 @@
+//! This module implements RSASSA-PKCS1-v1_5 signatures (RFC 8017 §8.2) with
+//! SHA-256 and RSAES-PKCS1-v1_5 encryption (RFC 8017 §7.2).
+//!
+//! The encryption scheme is provided for interoperability with legacy
+//! systems only. Its padding check is a padding oracle (Bleichenbacher,
+//! CRYPTO '98) whenever a caller can tell a failed decryption apart from a
+//! successful one; new designs should use RSAES-OAEP.
+use rand::{thread_rng, CryptoRng, RngCore};
+use sha_256::Sha256;
+
+use crate::error::RsaError;
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::oaep::ct_is_zero;
+use crate::primitives::{i2osp, os2ip};
+
+// DER encoding of the DigestInfo header for SHA-256 (RFC 8017 §9.2, note 1).
+const SHA256_DIGEST_INFO_PREFIX: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+// Both encodings need at least 8 bytes of padding plus 3 framing bytes.
+const MIN_PADDING_LEN: usize = 11;
+
+// The minimum length of the nonzero random padding string of EME-PKCS1-v1_5.
+const MIN_PS_LEN: usize = 8;
+
+impl RsaPrivateKey {
+    /// Signs `msg` with RSASSA-PKCS1-v1_5 over SHA-256.
+    ///
+    /// # Arguments
+    /// * `msg` - The message to sign.
+    ///
+    /// # Returns
+    /// The `k`-byte signature, or `RsaError::KeyTooSmall` if the modulus
+    /// cannot hold the encoded digest.
+    pub fn sign_pkcs1_v15_sha256(&self, msg: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let k = self.public_key().size();
+        let em = emsa_pkcs1_v15_sha256(msg, k)?;
+
+        let s = self.decrypt_raw(&os2ip(&em))?;
+        Ok(i2osp(&s, k).expect("s < n fits in k bytes"))
+    }
+
+    /// Decrypts an RSAES-PKCS1-v1_5 ciphertext.
+    ///
+    /// Every malformed ciphertext yields the same `RsaError::DecryptionError`
+    /// and the padding is checked without branching on its contents, but the
+    /// scheme stays vulnerable to Bleichenbacher's attack if the caller's
+    /// behaviour reveals whether decryption failed.
+    ///
+    /// # Arguments
+    /// * `ciphertext` - The `k`-byte ciphertext.
+    ///
+    /// # Returns
+    /// The message, or `RsaError::DecryptionError`.
+    pub fn decrypt_pkcs1_v15(&self, ciphertext: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let k = self.public_key().size();
+        if ciphertext.len() != k || k < MIN_PADDING_LEN {
+            return Err(RsaError::DecryptionError);
+        }
+
+        let em = self
+            .decrypt_raw(&os2ip(ciphertext))
+            .and_then(|m| i2osp(&m, k))
+            .map_err(|_| RsaError::DecryptionError)?;
+
+        // EM = 0x00 || 0x02 || PS || 0x00 || M, with every failure condition
+        // accumulated into `bad`.
+        let mut bad = em[0] | (em[1] ^ 0x02);
+
+        // Find the first zero byte after PS.
+        let mut found = 0u8;
+        let mut separator = 0usize;
+        for (i, &byte) in em[2..].iter().enumerate() {
+            let first_zero = ct_is_zero(byte) & !found & 1;
+
+            separator |= i & (0usize.wrapping_sub(first_zero as usize));
+            found |= first_zero;
+        }
+        bad |= !found & 1;
+        bad |= (separator < MIN_PS_LEN) as u8;
+
+        if bad != 0 {
+            return Err(RsaError::DecryptionError);
+        }
+
+        Ok(em[2 + separator + 1..].to_vec())
+    }
+}
+
+impl RsaPublicKey {
+    /// Verifies an RSASSA-PKCS1-v1_5 signature over SHA-256.
+    ///
+    /// The expected encoding is rebuilt from `msg` and compared with the
+    /// recovered one as a whole, instead of parsing the recovered encoding,
+    /// which avoids the classic signature forgeries against lenient parsers.
+    ///
+    /// # Arguments
+    /// * `msg` - The signed message.
+    /// * `signature` - The `k`-byte signature.
+    ///
+    /// # Returns
+    /// `Ok(())` for a valid signature, `RsaError::InvalidSignature` otherwise.
+    pub fn verify_pkcs1_v15_sha256(&self, msg: &[u8], signature: &[u8]) -> Result<(), RsaError> {
+        let k = self.size();
+        if signature.len() != k {
+            return Err(RsaError::InvalidSignature);
+        }
+
+        let s = os2ip(signature);
+        if &s >= self.n() {
+            return Err(RsaError::InvalidSignature);
+        }
+
+        let em = i2osp(&self.rsaep(&s)?, k).map_err(|_| RsaError::InvalidSignature)?;
+        let expected = emsa_pkcs1_v15_sha256(msg, k)?;
+
+        if !utils::mac::ct_eq(&em, &expected) {
+            return Err(RsaError::InvalidSignature);
+        }
+
+        Ok(())
+    }
+
+    /// Encrypts `msg` with RSAES-PKCS1-v1_5 using fresh random padding.
+    ///
+    /// Prefer `encrypt_oaep`; this scheme exists for legacy peers.
+    ///
+    /// # Arguments
+    /// * `msg` - The message; at most `k - 11` bytes for a `k`-byte modulus.
+    ///
+    /// # Returns
+    /// The `k`-byte ciphertext, or `RsaError::MessageTooLong`.
+    pub fn encrypt_pkcs1_v15(&self, msg: &[u8]) -> Result<Vec<u8>, RsaError> {
+        self.encrypt_pkcs1_v15_with_rng(&mut thread_rng(), msg)
+    }
+
+    /// Encrypts `msg` with RSAES-PKCS1-v1_5, drawing the padding from `rng`.
+    pub fn encrypt_pkcs1_v15_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+        msg: &[u8],
+    ) -> Result<Vec<u8>, RsaError> {
+        let k = self.size();
+        let em = eme_pkcs1_v15(rng, msg, k)?;
+
+        let c = self.rsaep(&os2ip(&em))?;
+        Ok(i2osp(&c, k).expect("c < n fits in k bytes"))
+    }
+}
+
+/// EME-PKCS1-v1_5 encoding: `0x00 || 0x02 || PS || 0x00 || M`, where PS is
+/// at least 8 nonzero random bytes.
+fn eme_pkcs1_v15<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    msg: &[u8],
+    em_len: usize,
+) -> Result<Vec<u8>, RsaError> {
+    if em_len < MIN_PADDING_LEN || msg.len() > em_len - MIN_PADDING_LEN {
+        return Err(RsaError::MessageTooLong);
+    }
+
+    let ps_len = em_len - msg.len() - 3;
+    let mut em = vec![0u8; em_len];
+    em[1] = 0x02;
+
+    let ps = &mut em[2..2 + ps_len];
+    rng.fill_bytes(ps);
+    for byte in ps.iter_mut() {
+        while *byte == 0 {
+            *byte = rng.next_u32() as u8;
+        }
+    }
+
+    em[em_len - msg.len()..].copy_from_slice(msg);
+    Ok(em)
+}
+
+/// EMSA-PKCS1-v1_5 encoding: `0x00 || 0x01 || 0xff.. || 0x00 || DigestInfo`.
+fn emsa_pkcs1_v15_sha256(msg: &[u8], em_len: usize) -> Result<Vec<u8>, RsaError> {
+    let mut hasher = Sha256::new();
+    hasher.update(msg);
+    let digest = hasher.finalize();
+
+    let t_len = SHA256_DIGEST_INFO_PREFIX.len() + digest.len();
+    if em_len < t_len + MIN_PADDING_LEN {
+        return Err(RsaError::KeyTooSmall);
+    }
+
+    let mut em = vec![0xff; em_len];
+    em[0] = 0x00;
+    em[1] = 0x01;
+    em[em_len - t_len - 1] = 0x00;
+    em[em_len - t_len..em_len - digest.len()].copy_from_slice(&SHA256_DIGEST_INFO_PREFIX);
+    em[em_len - digest.len()..].copy_from_slice(&digest);
+
+    Ok(em)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PKCS8_PEM: &str = include_str!("../fixtures/openssl_2048_pkcs8.pem");
+
+    // Signed with `openssl dgst -sha256 -sign openssl_2048_pkcs8.pem message.txt`.
+    const MESSAGE: &[u8] = include_bytes!("../fixtures/message.txt");
+    const SIGNATURE: &[u8] = include_bytes!("../fixtures/message.txt.sha256.sig");
+
+    // Encrypted with `openssl pkeyutl -encrypt -pkeyopt rsa_padding_mode:pkcs1`
+    // under the public half of the same key.
+    const CIPHERTEXT: &[u8] = include_bytes!("../fixtures/message.pkcs1v15.enc");
+
+    #[test]
+    fn openssl_interop() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+
+        assert_eq!(
+            key.public_key().verify_pkcs1_v15_sha256(MESSAGE, SIGNATURE),
+            Ok(())
+        );
+
+        // The scheme is deterministic, so our signature is byte-identical to
+        // OpenSSL's and therefore passes `openssl dgst -sha256 -verify`.
+        assert_eq!(key.sign_pkcs1_v15_sha256(MESSAGE).unwrap(), SIGNATURE);
+    }
+
+    #[test]
+    fn rejects_tampering() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+        let public_key = key.public_key();
+
+        let mut message = MESSAGE.to_vec();
+        message[4] ^= 0x20;
+        assert_eq!(
+            public_key.verify_pkcs1_v15_sha256(&message, SIGNATURE),
+            Err(RsaError::InvalidSignature)
+        );
+
+        let mut signature = SIGNATURE.to_vec();
+        signature[100] ^= 0x01;
+        assert_eq!(
+            public_key.verify_pkcs1_v15_sha256(MESSAGE, &signature),
+            Err(RsaError::InvalidSignature)
+        );
+
+        assert_eq!(
+            public_key.verify_pkcs1_v15_sha256(MESSAGE, &SIGNATURE[1..]),
+            Err(RsaError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn signatures_keep_leading_zeros() {
+        use rand::SeedableRng;
+
+        let rsa =
+            crate::RSA::generate(&mut rand_chacha::ChaCha20Rng::seed_from_u64(3365), 512).unwrap();
+        let key = rsa.private_key();
+
+        // Roughly one signature in 256 starts with a zero byte.
+        let (msg, signature) = (0u32..4096)
+            .map(|i| {
+                let msg = i.to_be_bytes();
+                (msg, key.sign_pkcs1_v15_sha256(&msg).unwrap())
+            })
+            .find(|(_, signature)| signature[0] == 0)
+            .unwrap();
+
+        assert_eq!(signature.len(), key.public_key().size());
+        assert_eq!(
+            key.public_key().verify_pkcs1_v15_sha256(&msg, &signature),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn decrypts_openssl_ciphertext() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+
+        assert_eq!(
+            key.decrypt_pkcs1_v15(CIPHERTEXT),
+            Ok(b"legacy interop".to_vec())
+        );
+    }
+
+    #[test]
+    fn encryption_round_trip() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+        let public_key = key.public_key();
+        let k = public_key.size();
+
+        for len in [0, 1, k / 2, k - MIN_PADDING_LEN] {
+            let msg = vec![0xa5; len];
+            let ciphertext = public_key.encrypt_pkcs1_v15(&msg).unwrap();
+            assert_eq!(key.decrypt_pkcs1_v15(&ciphertext), Ok(msg));
+        }
+
+        // The padding is random, so encryption is not deterministic.
+        assert_ne!(
+            public_key.encrypt_pkcs1_v15(b"abc").unwrap(),
+            public_key.encrypt_pkcs1_v15(b"abc").unwrap()
+        );
+
+        assert_eq!(
+            public_key.encrypt_pkcs1_v15(&vec![0; k - MIN_PADDING_LEN + 1]),
+            Err(RsaError::MessageTooLong)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_encryption_padding() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+        let k = key.public_key().size();
+
+        // Encrypt hand-built encodings with the raw RSA primitive.
+        let encrypt = |em: &[u8]| {
+            let c = key.public_key().rsaep(&os2ip(em)).unwrap();
+            i2osp(&c, k).unwrap()
+        };
+        let valid = |ps_len: usize| {
+            let mut em = vec![0x5a; k];
+            em[0] = 0x00;
+            em[1] = 0x02;
+            em[2 + ps_len] = 0x00;
+            em
+        };
+
+        assert_eq!(
+            key.decrypt_pkcs1_v15(&encrypt(&valid(8))).unwrap().len(),
+            k - 11
+        );
+
+        let mut wrong_block_type = valid(8);
+        wrong_block_type[1] = 0x01;
+        let mut no_separator = valid(8);
+        no_separator[10] = 0x5a;
+
+        for em in [valid(7), valid(0), wrong_block_type, no_separator] {
+            assert_eq!(
+                key.decrypt_pkcs1_v15(&encrypt(&em)),
+                Err(RsaError::DecryptionError)
+            );
+        }
+
+        assert_eq!(
+            key.decrypt_pkcs1_v15(&CIPHERTEXT[1..]),
+            Err(RsaError::DecryptionError)
+        );
+    }
+
+    #[test]
+    fn encoding_layout() {
+        let em = emsa_pkcs1_v15_sha256(b"abc", 64).unwrap();
+
+        assert_eq!(em[..2], [0x00, 0x01]);
+        // 64 - 3 framing bytes - 19 prefix bytes - 32 digest bytes of padding.
+        assert!(em[2..12].iter().all(|&byte| byte == 0xff));
+        assert_eq!(em[12], 0x00);
+        assert_eq!(em[13..32], SHA256_DIGEST_INFO_PREFIX);
+
+        assert_eq!(
+            emsa_pkcs1_v15_sha256(b"abc", 61),
+            Err(RsaError::KeyTooSmall)
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! This module implements the data conversion primitives of RFC 8017 §4,
+//! which fix the width of every integer written by the padded operations.
+use num_bigint::BigUint;
+use utils::encoding;
+
+use crate::error::RsaError;
+
+/// I2OSP: encodes `x` as a big-endian octet string of exactly `len` bytes.
+///
+/// # Arguments
+/// * `x` - The non-negative integer to encode.
+/// * `len` - The intended length of the output.
+///
+/// # Returns
+/// The left-zero-padded encoding, or `RsaError::IntegerTooLarge` if `x ≥ 256^len`.
+pub fn i2osp(x: &BigUint, len: usize) -> Result<Vec<u8>, RsaError> {
+    encoding::to_bytes_be_padded(x, len).map_err(|_| RsaError::IntegerTooLarge)
+}
+
+/// OS2IP: decodes a big-endian octet string into an integer.
+pub fn os2ip(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_with_leading_zeros() {
+        assert_eq!(i2osp(&BigUint::from(0u32), 4), Ok(vec![0, 0, 0, 0]));
+        assert_eq!(i2osp(&BigUint::from(0x0102u32), 4), Ok(vec![0, 0, 1, 2]));
+        assert_eq!(i2osp(&BigUint::from(0u32), 0), Ok(vec![]));
+    }
+
+    #[test]
+    fn keeps_high_bit() {
+        assert_eq!(i2osp(&BigUint::from(0x80u32), 1), Ok(vec![0x80]));
+        assert_eq!(
+            i2osp(&BigUint::from(0xff00ffu32), 3),
+            Ok(vec![0xff, 0x00, 0xff])
+        );
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert_eq!(
+            i2osp(&BigUint::from(256u32), 1),
+            Err(RsaError::IntegerTooLarge)
+        );
+        assert_eq!(
+            i2osp(&BigUint::from(1u32), 0),
+            Err(RsaError::IntegerTooLarge)
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        for bytes in [vec![], vec![0, 0, 1], vec![0x80, 0, 0, 0], vec![0xff; 33]] {
+            assert_eq!(i2osp(&os2ip(&bytes), bytes.len()).unwrap(), bytes);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! This module implements `Serialize` and `Deserialize` for `RsaPublicKey`.
+//!
+//! `n` and `e` are written as unpadded base64url strings of their big-endian
+//! bytes, as in a JSON Web Key (RFC 7518 §6.3.1), so the format does not
+//! depend on num-bigint's internal representation:
+//!
+//! ```json
+//! {"n": "DKE", "e": "EQ"}
+//! ```
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use num_bigint::BigUint;
+use num_traits::Zero;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::key::RsaPublicKey;
+
+#[derive(Serialize, Deserialize)]
+struct PublicKeyRepr {
+    n: String,
+    e: String,
+}
+
+fn encode(x: &BigUint) -> String {
+    URL_SAFE_NO_PAD.encode(x.to_bytes_be())
+}
+
+fn decode<E: Error>(field: &str, encoded: &str) -> Result<BigUint, E> {
+    URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map(|bytes| BigUint::from_bytes_be(&bytes))
+        .map_err(|err| E::custom(format!("invalid `{}`: {}", field, err)))
+}
+
+impl Serialize for RsaPublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PublicKeyRepr {
+            n: encode(self.n()),
+            e: encode(self.e()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RsaPublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PublicKeyRepr::deserialize(deserializer)?;
+
+        let n = decode("n", &repr.n)?;
+        if n.is_zero() {
+            return Err(D::Error::custom("the modulus must not be zero"));
+        }
+
+        RsaPublicKey::new(n, decode("e", &repr.e)?).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::RsaPrivateKey;
+
+    const PKCS8_PEM: &str = include_str!("../fixtures/openssl_2048_pkcs8.pem");
+
+    #[test]
+    fn json_round_trip() {
+        let key = RsaPrivateKey::from_pkcs8_pem(PKCS8_PEM).unwrap();
+        let public_key = key.public_key();
+
+        let json = serde_json::to_string(public_key).unwrap();
+        assert!(json.ends_with(r#""e":"AQAB"}"#));
+        assert_eq!(
+            serde_json::from_str::<RsaPublicKey>(&json).unwrap(),
+            *public_key
+        );
+    }
+
+    #[test]
+    fn reads_hand_written_json() {
+        // n = 3233 = 0x0ca1 and e = 17 = 0x11.
+        let json = r#"{ "n": "DKE", "e": "EQ" }"#;
+        let key: RsaPublicKey = serde_json::from_str(json).unwrap();
+
+        assert_eq!(key.n(), &BigUint::from(3233u32));
+        assert_eq!(key.e(), &BigUint::from(17u32));
+        assert_eq!(
+            serde_json::to_string(&key).unwrap(),
+            r#"{"n":"DKE","e":"EQ"}"#
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        for json in [
+            r#"{"n":"","e":"AQAB"}"#,    // zero modulus
+            r#"{"n":"AAA","e":"AQAB"}"#, // zero modulus with leading zeros
+            r#"{"n":"DKE","e":"EA"}"#,   // even exponent
+            r#"{"n":"DK+","e":"EQ"}"#,   // not base64url
+            r#"{"n":"DKE"}"#,            // missing exponent
+        ] {
+            assert!(
+                serde_json::from_str::<RsaPublicKey>(json).is_err(),
+                "{}",
+                json
+            );
+        }
+    }
+}
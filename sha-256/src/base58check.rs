@@ -0,0 +1,174 @@
+//! This module implements Base58Check, the Bitcoin address and WIF encoding.
+//! A version byte is prepended to the payload, the first 4 bytes of
+//! `sha256d(version || payload)` are appended as a checksum, and the result is
+//! written in base 58, with every leading zero byte encoded as a '1'.
+use thiserror::Error;
+
+use crate::sha256d;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+const CHECKSUM_SIZE: usize = 4;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Base58Error {
+    #[error("Invalid base58 character `{0}`")]
+    InvalidCharacter(char),
+
+    #[error("Decoded data of `{0}` bytes is too short to hold a version and checksum")]
+    TooShort(usize),
+
+    #[error("Checksum mismatch")]
+    InvalidChecksum,
+}
+
+/// Encodes `payload` under `version` as a Base58Check string.
+///
+/// # Arguments
+/// * `version` - The version byte, e.g. `0x00` for P2PKH addresses or `0x80` for WIF keys.
+/// * `payload` - The data to encode.
+///
+/// # Returns
+/// The Base58Check string.
+pub fn encode(version: u8, payload: &[u8]) -> String {
+    let mut data = Vec::with_capacity(1 + payload.len() + CHECKSUM_SIZE);
+    data.push(version);
+    data.extend_from_slice(payload);
+
+    let checksum = sha256d(&data);
+    data.extend_from_slice(&checksum[..CHECKSUM_SIZE]);
+
+    encode_base58(&data)
+}
+
+/// Decodes a Base58Check string and validates its checksum.
+///
+/// # Arguments
+/// * `encoded` - The Base58Check string.
+///
+/// # Returns
+/// The version byte and the payload, or a `Base58Error` if the string contains
+/// characters outside the alphabet or the checksum does not match.
+pub fn decode(encoded: &str) -> Result<(u8, Vec<u8>), Base58Error> {
+    let data = decode_base58(encoded)?;
+
+    if data.len() < 1 + CHECKSUM_SIZE {
+        return Err(Base58Error::TooShort(data.len()));
+    }
+
+    let (body, checksum) = data.split_at(data.len() - CHECKSUM_SIZE);
+    if sha256d(body)[..CHECKSUM_SIZE] != *checksum {
+        return Err(Base58Error::InvalidChecksum);
+    }
+
+    Ok((body[0], body[1..].to_vec()))
+}
+
+/// Writes `data` in base 58 by repeated division of its big-endian value.
+fn encode_base58(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&byte| byte == 0).count();
+
+    // Little-endian base-58 digits of the value.
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&digit| ALPHABET[digit as usize] as char),
+        )
+        .collect()
+}
+
+/// Parses a base-58 string back into bytes, restoring leading zero bytes.
+fn decode_base58(encoded: &str) -> Result<Vec<u8>, Base58Error> {
+    let zeros = encoded.chars().take_while(|&c| c == '1').count();
+
+    // Little-endian base-256 bytes of the value.
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in encoded.chars().skip(zeros) {
+        let mut carry = ALPHABET
+            .iter()
+            .position(|&symbol| symbol as char == c)
+            .ok_or(Base58Error::InvalidCharacter(c))? as u32;
+
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    Ok(std::iter::repeat_n(0, zeros)
+        .chain(bytes.into_iter().rev())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::decode_hex;
+
+    #[test]
+    fn known_vectors() {
+        let vectors = [
+            (
+                0x00,
+                "0000000000000000000000000000000000000000",
+                "1111111111111111111114oLvT2",
+            ),
+            (
+                0x00,
+                "010966776006953d5567439e5e39f86a0d273bee",
+                "16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvM",
+            ),
+            (
+                0x80,
+                "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d",
+                "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ",
+            ),
+        ];
+
+        for (version, payload, expected) in vectors {
+            let payload = decode_hex(payload).unwrap();
+
+            assert_eq!(encode(version, &payload), expected);
+            assert_eq!(decode(expected).unwrap(), (version, payload));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        let mut encoded = encode(0x00, &[0x42; 20]).into_bytes();
+        let last = encoded.len() - 1;
+        encoded[last] = if encoded[last] == b'2' { b'3' } else { b'2' };
+
+        assert_eq!(
+            decode(std::str::from_utf8(&encoded).unwrap()),
+            Err(Base58Error::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(decode("1O1"), Err(Base58Error::InvalidCharacter('O')));
+        assert_eq!(decode("11"), Err(Base58Error::TooShort(2)));
+    }
+}
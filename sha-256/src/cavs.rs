@@ -0,0 +1,152 @@
+//! Runs NIST CAVS SHA-256 tests from the `.rsp` response files in the
+//! crate's `vectors` directory. Every file is picked up, so the full
+//! `SHA256ShortMsg`, `SHA256LongMsg` and `SHA256Monte` files from the CAVP
+//! archive can be dropped in next to the excerpts that ship with the crate.
+//!
+//! Message files list `Len`, `Msg` and `MD` entries, with `Len` in bits; a
+//! Monte Carlo file has a single `Seed` followed by `COUNT` and `MD`
+//! checkpoints.
+use std::{fs, path::Path};
+
+use super::{digest::decode_hex, hash_bits, Sha256};
+
+/// The digest size in bytes, which every file announces as `[L = 32]`.
+const DIGEST_SIZE: usize = 32;
+
+/// The number of chained hashes between two Monte Carlo checkpoints.
+const MONTE_ITERATIONS: usize = 1000;
+
+/// A single message or checkpoint entry of a response file.
+#[derive(Debug, Default)]
+pub struct KatCase {
+    /// The message length in bits, for message entries.
+    pub len: Option<usize>,
+    pub msg: Vec<u8>,
+    /// The checkpoint index, for Monte Carlo entries.
+    pub count: Option<usize>,
+    pub md: Vec<u8>,
+}
+
+/// The contents of a response file.
+#[derive(Debug, Default)]
+pub struct RspFile {
+    /// The Monte Carlo seed, present only in Monte files.
+    pub seed: Option<Vec<u8>>,
+    pub cases: Vec<KatCase>,
+}
+
+/// Parses a CAVS SHA response file. Comment lines start with `#`, and each
+/// entry starts at its `Len` or `COUNT` line.
+///
+/// # Panics
+/// Panics on a malformed line, so a broken vector file fails the test run.
+pub fn parse_rsp(contents: &str) -> RspFile {
+    let mut file = RspFile::default();
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let (_, size) = header
+                .split_once('=')
+                .unwrap_or_else(|| panic!("malformed header `{line}`"));
+            assert_eq!(
+                size.trim().parse::<usize>(),
+                Ok(DIGEST_SIZE),
+                "not a SHA-256 file"
+            );
+            continue;
+        }
+
+        let (name, value) = line
+            .split_once('=')
+            .unwrap_or_else(|| panic!("malformed line `{line}`"));
+        let value = value.trim();
+
+        match name.trim() {
+            "Seed" => file.seed = Some(decode_hex(value).unwrap()),
+            "Len" => file.cases.push(KatCase {
+                len: Some(value.parse().expect("Len is a number")),
+                ..Default::default()
+            }),
+            "COUNT" => file.cases.push(KatCase {
+                count: Some(value.parse().expect("COUNT is a number")),
+                ..Default::default()
+            }),
+            field => {
+                let case = file.cases.last_mut().expect("field before the first entry");
+                match field {
+                    "Msg" => case.msg = decode_hex(value).unwrap(),
+                    "MD" => case.md = decode_hex(value).unwrap(),
+                    other => panic!("unknown field `{other}`"),
+                }
+            }
+        }
+    }
+
+    file
+}
+
+/// Runs the SHA-2 Monte Carlo test: each checkpoint is the last of 1000
+/// hashes, each of the previous three digests concatenated, and seeds the
+/// next round.
+///
+/// # Returns
+/// The first `checkpoints` checkpoint digests.
+fn monte(seed: &[u8], checkpoints: usize) -> Vec<[u8; DIGEST_SIZE]> {
+    let mut seed: [u8; DIGEST_SIZE] = seed.try_into().expect("seed is one digest");
+
+    (0..checkpoints)
+        .map(|_| {
+            let mut md = [seed; 3];
+            for _ in 0..MONTE_ITERATIONS {
+                let mut hasher = Sha256::new();
+                for digest in &md {
+                    hasher.update(digest);
+                }
+                md = [md[1], md[2], hasher.finalize()];
+            }
+
+            seed = md[2];
+            seed
+        })
+        .collect()
+}
+
+#[test]
+fn known_answer_tests() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("vectors");
+    let mut files = 0;
+
+    for entry in fs::read_dir(&dir).expect("vectors directory") {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "rsp") {
+            continue;
+        }
+
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let file = parse_rsp(&fs::read_to_string(&path).unwrap());
+        assert!(!file.cases.is_empty(), "{name} has no test cases");
+
+        match &file.seed {
+            Some(seed) => {
+                let checkpoints = monte(seed, file.cases.len());
+                for (case, checkpoint) in file.cases.iter().zip(checkpoints) {
+                    let count = case.count.expect("Monte entries have a COUNT");
+                    assert_eq!(case.md, checkpoint, "{name} COUNT = {count}");
+                }
+            }
+            None => {
+                for case in &file.cases {
+                    let len = case.len.expect("message entries have a Len");
+                    assert_eq!(case.md, hash_bits(&case.msg, len), "{name} Len = {len}");
+                }
+            }
+        }
+        files += 1;
+    }
+
+    assert!(files > 0, "no .rsp files in {}", dir.display());
+}
@@ -0,0 +1,177 @@
+use std::fmt;
+
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ParseError {
+    #[error("Hex digest has an odd length of `{0}`")]
+    OddLength(usize),
+
+    #[error("Invalid digest size. Expected 32 bytes got `{0}`")]
+    InvalidLength(usize),
+
+    #[error("Invalid hex character `{0}`")]
+    InvalidHexCharacter(char),
+}
+
+/// A 32-byte SHA-256 digest.
+///
+/// Equality is checked in constant time, so a `Digest` can safely be compared
+/// against an expected MAC or checksum.
+#[derive(Clone, Copy, Debug, Eq)]
+pub struct Digest(pub [u8; 32]);
+
+impl Digest {
+    /// Compares two digests without exiting early on the first differing byte.
+    ///
+    /// The XOR of every byte pair is accumulated so the running time does not
+    /// depend on where (or whether) the digests differ.
+    pub fn ct_eq(&self, other: &Digest) -> bool {
+        let diff = self
+            .0
+            .iter()
+            .zip(other.0.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+
+        diff == 0
+    }
+
+    /// Parses a digest from its hexadecimal representation.
+    ///
+    /// Both upper and lower case hex digits are accepted.
+    ///
+    /// # Arguments
+    /// * `hex` - A 64 character hexadecimal string.
+    ///
+    /// # Returns
+    /// The parsed `Digest` or a `ParseError` describing why the input was rejected.
+    pub fn from_hex(hex: &str) -> Result<Digest, ParseError> {
+        let bytes = decode_hex(hex)?;
+
+        bytes
+            .try_into()
+            .map(Digest)
+            .map_err(|bytes: Vec<u8>| ParseError::InvalidLength(bytes.len()))
+    }
+
+    /// Returns the digest bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl PartialEq for Digest {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other)
+    }
+}
+
+impl AsRef<[u8]> for Digest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for Digest {
+    fn from(bytes: [u8; 32]) -> Self {
+        Digest(bytes)
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes a hexadecimal string into bytes.
+///
+/// Upper and lower case digits are accepted, as is an optional `0x` prefix.
+///
+/// # Arguments
+/// * `hex` - The hexadecimal string to decode.
+///
+/// # Returns
+/// The decoded bytes, or a `ParseError` if the input is not valid hex.
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, ParseError> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+
+    if !hex.len().is_multiple_of(2) {
+        return Err(ParseError::OddLength(hex.len()));
+    }
+
+    let mut chars = hex.chars();
+    let mut bytes = Vec::with_capacity(hex.len() / 2);
+
+    while let Some(high) = chars.next() {
+        let low = chars.next().unwrap_or_default();
+        bytes.push((hex_value(high)? << 4) | hex_value(low)?);
+    }
+
+    Ok(bytes)
+}
+
+fn hex_value(c: char) -> Result<u8, ParseError> {
+    c.to_digit(16)
+        .map(|digit| digit as u8)
+        .ok_or(ParseError::InvalidHexCharacter(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEX: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn hex_round_trip() {
+        let digest = Digest::from_hex(HEX).unwrap();
+
+        assert_eq!(digest.to_string(), HEX);
+        assert_eq!(digest.as_ref()[0], 0xba);
+    }
+
+    #[test]
+    fn parse_is_case_insensitive() {
+        let lower = Digest::from_hex(HEX).unwrap();
+        let upper = Digest::from_hex(&HEX.to_uppercase()).unwrap();
+
+        assert_eq!(lower, upper);
+        assert_eq!(upper.to_string(), HEX);
+    }
+
+    #[test]
+    fn rejects_invalid_hex() {
+        assert_eq!(
+            Digest::from_hex(&HEX[1..]).unwrap_err(),
+            ParseError::OddLength(63)
+        );
+        assert_eq!(
+            Digest::from_hex(&HEX[2..]).unwrap_err(),
+            ParseError::InvalidLength(31)
+        );
+
+        let invalid = format!("{}zz", &HEX[2..]);
+        assert_eq!(
+            Digest::from_hex(&invalid).unwrap_err(),
+            ParseError::InvalidHexCharacter('z')
+        );
+    }
+
+    #[test]
+    fn ct_eq_detects_any_difference() {
+        let digest = Digest::from_hex(HEX).unwrap();
+
+        for i in 0..32 {
+            let mut other = digest;
+            other.0[i] ^= 0x80;
+            assert!(!digest.ct_eq(&other));
+        }
+
+        assert!(digest.ct_eq(&Digest(digest.0)));
+    }
+}
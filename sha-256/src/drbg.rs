@@ -0,0 +1,202 @@
+//! This module implements the NIST SP 800-90A HMAC_DRBG deterministic random
+//! bit generator instantiated with HMAC-SHA256, without prediction resistance.
+//!
+//! The same construction underlies RFC 6979 deterministic nonces: instantiating
+//! with the private key as entropy and the message hash as nonce and then
+//! calling `generate` reproduces the nonce candidates of RFC 6979 §3.2.
+use thiserror::Error;
+
+use crate::hmac::HmacSha256;
+
+/// The maximum number of `generate` calls between reseeds (SP 800-90A, Table 2).
+pub const RESEED_INTERVAL: u64 = 1 << 48;
+
+/// The maximum number of bytes returned by a single `generate` call (2^19 bits).
+pub const MAX_BYTES_PER_REQUEST: usize = 1 << 16;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DrbgError {
+    #[error("The reseed counter limit was reached, the generator must be reseeded")]
+    ReseedRequired,
+
+    #[error("Requested `{0}` bytes, which exceeds the per-request limit")]
+    RequestTooLarge(usize),
+}
+
+pub struct HmacDrbg {
+    k: [u8; 32],
+    v: [u8; 32],
+    reseed_counter: u64,
+}
+
+impl HmacDrbg {
+    /// Instantiates the generator (HMAC_DRBG_Instantiate_algorithm).
+    ///
+    /// # Arguments
+    /// * `entropy` - The entropy input.
+    /// * `nonce` - The nonce.
+    /// * `personalization` - An optional personalization string; may be empty.
+    pub fn new(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> Self {
+        let mut drbg = HmacDrbg {
+            k: [0x00; 32],
+            v: [0x01; 32],
+            reseed_counter: 1,
+        };
+
+        drbg.update(&[entropy, nonce, personalization]);
+        drbg
+    }
+
+    /// Reseeds the generator with fresh entropy (HMAC_DRBG_Reseed_algorithm).
+    ///
+    /// # Arguments
+    /// * `entropy` - The new entropy input.
+    /// * `additional` - Optional additional input; may be empty.
+    pub fn reseed(&mut self, entropy: &[u8], additional: &[u8]) {
+        self.update(&[entropy, additional]);
+        self.reseed_counter = 1;
+    }
+
+    /// Fills `out` with pseudorandom bytes (HMAC_DRBG_Generate_algorithm).
+    ///
+    /// # Arguments
+    /// * `out` - The buffer to fill.
+    /// * `additional` - Optional additional input mixed into the state.
+    ///
+    /// # Returns
+    /// `DrbgError::ReseedRequired` once the reseed interval has elapsed, or
+    /// `DrbgError::RequestTooLarge` if `out` exceeds `MAX_BYTES_PER_REQUEST`.
+    pub fn generate(&mut self, out: &mut [u8], additional: Option<&[u8]>) -> Result<(), DrbgError> {
+        if self.reseed_counter > RESEED_INTERVAL {
+            return Err(DrbgError::ReseedRequired);
+        }
+
+        if out.len() > MAX_BYTES_PER_REQUEST {
+            return Err(DrbgError::RequestTooLarge(out.len()));
+        }
+
+        let additional = additional.unwrap_or_default();
+        if !additional.is_empty() {
+            self.update(&[additional]);
+        }
+
+        for chunk in out.chunks_mut(32) {
+            self.v = self.hmac(&[&self.v]);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+
+        self.update(&[additional]);
+        self.reseed_counter += 1;
+
+        Ok(())
+    }
+
+    /// HMAC_DRBG_Update: mixes `provided` (the concatenation of its parts) into K and V.
+    fn update(&mut self, provided: &[&[u8]]) {
+        self.k = self.hmac(&[&[&self.v, &[0x00][..]], provided].concat());
+        self.v = self.hmac(&[&self.v]);
+
+        if provided.iter().all(|part| part.is_empty()) {
+            return;
+        }
+
+        self.k = self.hmac(&[&[&self.v, &[0x01][..]], provided].concat());
+        self.v = self.hmac(&[&self.v]);
+    }
+
+    fn hmac(&self, parts: &[&[u8]]) -> [u8; 32] {
+        let mut mac = HmacSha256::new(&self.k);
+        for part in parts {
+            mac.update(part);
+        }
+        mac.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::decode_hex;
+
+    #[test]
+    fn cavp_no_reseed_vector() {
+        // HMAC_DRBG.rsp, [SHA-256] [PredictionResistance = False], COUNT = 0.
+        let entropy =
+            decode_hex("ca851911349384bffe89de1cbdc46e6831e44d34a4fb935ee285dd14b71a7488").unwrap();
+        let nonce = decode_hex("659ba96c601dc69fc902940805ec0ca8").unwrap();
+
+        let mut drbg = HmacDrbg::new(&entropy, &nonce, &[]);
+
+        let mut out = [0u8; 128];
+        drbg.generate(&mut out, None).unwrap();
+        drbg.generate(&mut out, None).unwrap();
+
+        assert_eq!(
+            out.to_vec(),
+            decode_hex(
+                "e528e9abf2dece54d47c7e75e5fe302149f817ea9fb4bee6f4199697d04d5b89\
+                 d54fbb978a15b5c443c9ec21036d2460b6f73ebad0dc2aba6e624abf07745bc1\
+                 07694bb7547bb0995f70de25d6b29e2d3011bb19d27676c07162c8b5ccde0668\
+                 961df86803482cb37ed6d5c0bb8d50cf1f50d476aa0458bdaba806f48be9dcb8"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn personalization_additional_input_and_reseed() {
+        // Cross-checked against an independent HMAC_DRBG implementation.
+        let entropy: Vec<u8> = (0..32).collect();
+        let nonce: Vec<u8> = (32..48).collect();
+        let mut drbg = HmacDrbg::new(&entropy, &nonce, b"personalization");
+
+        let mut out = [0u8; 64];
+        drbg.generate(&mut out, Some(b"additional 1")).unwrap();
+        assert_eq!(
+            out.to_vec(),
+            decode_hex(
+                "8646fd01500d97e40d157b06571b5a0be204f4add23292d6c5d927e72835ffe3\
+                 638711cc858c88d539c6984be4f73331b0c969c9dfa0e66cd8c1df2579c1ac6a"
+            )
+            .unwrap()
+        );
+
+        let reseed_entropy: Vec<u8> = (48..80).collect();
+        drbg.reseed(&reseed_entropy, b"reseed");
+        drbg.generate(&mut out, Some(b"additional 2")).unwrap();
+        assert_eq!(
+            out.to_vec(),
+            decode_hex(
+                "d744f533543a304eb2f031e515f4daae5effd980eb5f5719948d412a66aed4be\
+                 93f7835363c0c03ed37225f57f4b058c11c9609e9676e798b6ea6c6288f14339"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn reseed_counter_limit() {
+        let mut drbg = HmacDrbg::new(&[1; 32], &[2; 16], &[]);
+        drbg.reseed_counter = RESEED_INTERVAL + 1;
+
+        let mut out = [0u8; 16];
+        assert_eq!(
+            drbg.generate(&mut out, None),
+            Err(DrbgError::ReseedRequired)
+        );
+
+        drbg.reseed(&[3; 32], &[]);
+        assert!(drbg.generate(&mut out, None).is_ok());
+    }
+
+    #[test]
+    fn request_size_limit() {
+        let mut drbg = HmacDrbg::new(&[1; 32], &[2; 16], &[]);
+        let mut out = vec![0u8; MAX_BYTES_PER_REQUEST + 1];
+
+        assert_eq!(
+            drbg.generate(&mut out, None),
+            Err(DrbgError::RequestTooLarge(MAX_BYTES_PER_REQUEST + 1))
+        );
+    }
+}
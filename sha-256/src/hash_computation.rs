@@ -5,8 +5,11 @@ pub mod message_schedule {
 
     #[derive(Debug)]
     pub struct MessageSchedule {
-        pub w: Vec<[[u8; 4]; 64]>,
-        pub working_vars: [[u8; 4]; 8],
+        /// The sixteen big-endian words of each 512-bit block. Blocks are
+        /// expanded to their 64-word schedule one at a time during
+        /// compression, so the schedule never holds more than one of them.
+        pub blocks: Vec<[u32; 16]>,
+        pub working_vars: [u32; 8],
     }
 
     impl MessageSchedule {
@@ -15,70 +18,61 @@ pub mod message_schedule {
         /// The function divides the preprocessed message into blocks of 512 bits
         /// (64 bytes). Each of these blocks is further divided into sixteen 32-bit
         /// (4 bytes) words to create the message schedule required for SHA-256.
+        /// This is the point where the big-endian message bytes are converted
+        /// into the `u32` words used by the rest of the computation.
         ///
         /// # Arguments
         /// * `preprocess_data` - Contains the preprocessed message.
         ///
         /// # Returns
-        /// A new `MessageSchedule` instance.
+        /// A new `MessageSchedule` instance holding the words of each block.
         pub fn new(preprocess_result: PreprocessResult) -> Self {
-            let n = preprocess_result.0.len();
-            let mut schedule: Vec<[[u8; 4]; 64]> = vec![];
-
-            for idx in 0..n {
-                let mut block: [[u8; 4]; 64] = [[0; 4]; 64];
-
-                for t in 0..=63 {
-                    block[t] = match t {
-                        // W0 - W15 is same as M0_n - M15_n
-                        0..=15 => (preprocess_result.0)[idx][t],
-
-                        16..=63 => {
-                            let ssig1 = MessageSchedule::ssig1(block[t - 2]);
-                            let ssig0 = MessageSchedule::ssig0(block[t - 15]);
-
-                            let w_1 = block[t - 7];
-                            let w_2 = block[t - 16];
-
-                            let mut w = utilities::add_mod_2(ssig1, ssig0);
-
-                            w = utilities::add_mod_2_32(w, w_1);
-                            utilities::add_mod_2_32(w, w_2)
-                        }
-
-                        _ => panic!("Unexpected value for t"),
-                    };
-
-                    schedule.push(block);
-                }
-            }
+            let blocks = preprocess_result
+                .0
+                .iter()
+                .map(|block| block.map(u32::from_be_bytes))
+                .collect();
 
             MessageSchedule {
-                w: schedule,
+                blocks,
                 working_vars: MessageSchedule::init_working_vars(),
             }
         }
 
-        pub fn init_working_vars() -> [[u8; 4]; 8] {
-            let mut result: [[u8; 4]; 8] = Default::default();
-
-            for (i, &h) in constants::H.iter().enumerate() {
-                result[i] = hex_to_byte_array(h);
+        /// Expands a 16-word message block into the 64-word message schedule.
+        ///
+        /// W0 - W15 are the words of the block itself, while W16 - W63 are
+        /// derived from earlier words using the small sigma functions.
+        pub fn expand(block: &[u32; 16]) -> [u32; 64] {
+            let mut w = [0u32; 64];
+            w[..16].copy_from_slice(block);
+
+            for t in 16..64 {
+                let ssig1 = MessageSchedule::ssig1(w[t - 2]);
+                let ssig0 = MessageSchedule::ssig0(w[t - 15]);
+
+                let s = utilities::add_mod_2_32(ssig1, w[t - 7]);
+                let s = utilities::add_mod_2_32(s, ssig0);
+                w[t] = utilities::add_mod_2_32(s, w[t - 16]);
             }
 
-            result
+            w
+        }
+
+        pub fn init_working_vars() -> [u32; 8] {
+            constants::H
         }
 
         /// Compute the small sigma 1 function, as part of some cryptographic operation.
         /// This involves bitwise rotations and shifts.
-        pub fn ssig1(x: [u8; 4]) -> [u8; 4] {
+        pub fn ssig1(x: u32) -> u32 {
             let result = utilities::add_mod_2(utilities::rotr(x, 17), utilities::rotr(x, 19));
             utilities::add_mod_2(result, utilities::shr(x, 10))
         }
 
         /// Compute the small sigma 0 function, as part of some cryptographic operation.
         /// This involves bitwise rotations and shifts.
-        pub fn ssig0(x: [u8; 4]) -> [u8; 4] {
+        pub fn ssig0(x: u32) -> u32 {
             let result = utilities::add_mod_2(utilities::rotr(x, 7), utilities::rotr(x, 18));
             utilities::add_mod_2(result, utilities::shr(x, 3))
         }
@@ -88,106 +82,84 @@ pub mod message_schedule {
 pub mod compression {
     use super::message_schedule::MessageSchedule;
 
-    use crate::constants::{H, K};
-    use crate::preprocess::hex_to_byte_array;
+    use crate::constants::K;
     use crate::utilities::{add_mod_2_32, and, not, rotr, xor};
 
     /// Performs the SHA-256 compression on a given message schedule.
     ///
-    /// This function modifies the working variables using the SHA-256 algorithm.
+    /// Each block of the schedule is compressed into the running intermediate
+    /// hash value, starting from the initial hash value held in the schedule's
+    /// working variables.
     ///
     /// # Arguments
     /// * `msg_schedule` - The message schedule containing the working variables and data to be compressed.
     ///
     /// # Returns
-    /// * An array of the compressed working variables `a` through `h`.
-    pub fn compress(msg_schedule: MessageSchedule) -> [[u8; 4]; 8] {
-        // Temporary variables for intermediate results
-        let mut t_1: [u8; 4];
-        let mut t_2: [u8; 4];
-
-        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = msg_schedule.working_vars;
-
-        // Iterate through each block in the message schedule
-        for n in 0..msg_schedule.w.len() {
-            // Process each of the 64 rounds
-            for idx in 0..=63 {
-                t_1 = compute_t_1(
-                    e,
-                    f,
-                    g,
-                    h,
-                    hex_to_byte_array(K[idx]),
-                    msg_schedule.w[n][idx],
-                );
-
-                t_2 = compute_t_2(
-                    msg_schedule.working_vars[0],
-                    msg_schedule.working_vars[1],
-                    msg_schedule.working_vars[2],
-                );
-
-                // Update the working variables according to the SHA-256 specifications
-                h = g;
-                g = f;
-                f = e;
-                e = add_mod_2_32(d, t_1);
-                d = c;
-                c = b;
-                b = a;
-                a = add_mod_2_32(t_1, t_2);
-            }
+    /// * The final intermediate hash value `H(N)`.
+    pub fn compress(msg_schedule: MessageSchedule) -> [u32; 8] {
+        let mut state = msg_schedule.working_vars;
+
+        // Expand and compress each block in turn
+        for block in msg_schedule.blocks.iter() {
+            compress_block(&mut state, &MessageSchedule::expand(block));
         }
 
-        [a, b, c, d, e, f, g, h]
+        state
     }
 
-    /// Computes the digest from a given set of intermediate hash values.
-    /// This function adds each compressed chunk to its corresponding current hash value
-    /// from the provided intermediate hash matrix (`ihm`). It then appends all the resulting
-    /// hash values together to form a byte array representing the final hash.
+    /// Compresses a single expanded block into the intermediate hash value.
     ///
     /// # Arguments
+    /// * `state` - The intermediate hash value `H(i-1)`, updated in place to `H(i)`.
+    /// * `w` - The 64-word message schedule of the block.
+    pub fn compress_block(state: &mut [u32; 8], w: &[u32; 64]) {
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+        // Process each of the 64 rounds
+        for (&k, &w_t) in K.iter().zip(w.iter()) {
+            let t_1 = compute_t_1(e, f, g, h, k, w_t);
+            let t_2 = compute_t_2(a, b, c);
+
+            // Update the working variables according to the SHA-256 specifications
+            h = g;
+            g = f;
+            f = e;
+            e = add_mod_2_32(d, t_1);
+            d = c;
+            c = b;
+            b = a;
+            a = add_mod_2_32(t_1, t_2);
+        }
+
+        // Add the compressed chunk to the current hash value
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = add_mod_2_32(*s, v);
+        }
+    }
+
+    /// Computes the digest from the final intermediate hash value.
     ///
-    /// * `ihm` - An array of intermediate hash values, where each entry is a 4-byte array.
+    /// Each 32-bit word is serialized in big-endian order and the words are
+    /// appended together to form the byte representation of the hash.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// A 32-byte array representing the final hash value.
+    /// * `ihm` - The final intermediate hash value.
     ///
-    /// # Panics
+    /// # Returns
     ///
-    /// Panics if the provided `ihm` array does not have the expected size.
-    pub fn compute_bytes_digest(ihm: [[u8; 4]; 8]) -> [u8; 32] {
-        // Initialize a default hash matrix.
-        let mut h: [[u8; 4]; 8] = Default::default();
-
-        // Update the hash matrix by adding the compressed chunk to the corresponding
-        // current hash value from the intermediate hash matrix.
-        for i in 0..H.len() {
-            // Add the current hash value from ihm to the corresponding initial hash value
-            h[i] = add_mod_2_32(hex_to_byte_array(H[i]), ihm[i]);
+    /// A 32-byte array representing the final hash value.
+    pub fn compute_bytes_digest(ihm: [u32; 8]) -> [u8; 32] {
+        let mut digest = [0u8; 32];
+
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(ihm.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
         }
 
-        // Flatten, copy, and collect the hash matrix into a single byte array.
-        h.iter()
-            .flatten()
-            .copied()
-            .enumerate()
-            .fold([0u8; 32], |mut acc, (idx, byte)| {
-                acc[idx] = byte;
-                acc
-            })
+        digest
     }
 
-    fn compute_t_1(
-        e: [u8; 4],
-        f: [u8; 4],
-        g: [u8; 4],
-        h: [u8; 4],
-        k: [u8; 4],
-        w: [u8; 4],
-    ) -> [u8; 4] {
+    fn compute_t_1(e: u32, f: u32, g: u32, h: u32, k: u32, w: u32) -> u32 {
         let bssig1 = xor(xor(rotr(e, 6), rotr(e, 11)), rotr(e, 25)); // We can do this due to the associative property of XOR.
         let ch = xor(and(e, f), and(not(e), g));
 
@@ -197,7 +169,7 @@ pub mod compression {
         )
     }
 
-    fn compute_t_2(a: [u8; 4], b: [u8; 4], c: [u8; 4]) -> [u8; 4] {
+    fn compute_t_2(a: u32, b: u32, c: u32) -> u32 {
         let bssig0 = xor(xor(rotr(a, 2), rotr(a, 13)), rotr(a, 22));
 
         let maj = xor(xor(and(a, b), and(a, c)), and(b, c));
@@ -213,9 +185,39 @@ mod test {
 
     #[test]
     fn init_message_schedule() {
-        let processed_result = preprocess::preprocess_message("hello world");
+        let processed_result = preprocess::preprocess_message(b"hello world");
         let msg_schedule = message_schedule::MessageSchedule::new(processed_result);
 
-        assert_eq!(msg_schedule.w.len(), 64);
+        assert_eq!(msg_schedule.blocks.len(), 1);
+        assert_eq!(msg_schedule.blocks[0][0], 0x68656c6c);
+        assert_eq!(
+            message_schedule::MessageSchedule::expand(&msg_schedule.blocks[0])[0],
+            0x68656c6c
+        );
+    }
+
+    #[test]
+    fn init_multi_block_message_schedule() {
+        let message = "a".repeat(64);
+        let processed_result = preprocess::preprocess_message(message.as_bytes());
+        let msg_schedule = message_schedule::MessageSchedule::new(processed_result);
+
+        assert_eq!(msg_schedule.blocks.len(), 2);
+    }
+
+    #[test]
+    fn compress_matches_streaming_hasher() {
+        let message: Vec<u8> = (0..300u32).map(|i| (i * 13) as u8).collect();
+
+        for len in [0, 55, 56, 64, 119, 128, 300] {
+            let msg_schedule = message_schedule::MessageSchedule::new(
+                preprocess::preprocess_message(&message[..len]),
+            );
+            let digest = compression::compute_bytes_digest(compression::compress(msg_schedule));
+
+            let mut hasher = crate::Sha256::new();
+            hasher.update(&message[..len]);
+            assert_eq!(digest, hasher.finalize());
+        }
     }
 }
@@ -0,0 +1,351 @@
+use utils::block_buffer::{BlockBuffer, LengthEncoding, BLOCK_SIZE};
+
+use crate::hash_computation::{compression, message_schedule::MessageSchedule};
+
+/// An incremental SHA-256 hasher.
+///
+/// Data can be fed in any number of `update` calls; complete 512-bit blocks are
+/// compressed as soon as they are available, so only a single partial block is
+/// ever buffered.
+#[derive(Clone, Debug)]
+pub struct Sha256 {
+    state: [u32; 8],
+    buffer: BlockBuffer,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Sha256::new()
+    }
+}
+
+impl Sha256 {
+    /// Creates a hasher initialised with the SHA-256 initial hash value.
+    pub fn new() -> Self {
+        Sha256 {
+            state: MessageSchedule::init_working_vars(),
+            buffer: BlockBuffer::new(),
+        }
+    }
+
+    /// Creates a hasher for the BIP-340 tagged hash
+    /// `SHA-256(SHA-256(tag) || SHA-256(tag) || m)`, with the tag prefix already absorbed.
+    ///
+    /// The prefix fills exactly one block, so a hasher for a fixed tag can be
+    /// cloned to skip recomputing it.
+    ///
+    /// # Arguments
+    /// * `tag` - The tag naming the purpose of the hash, e.g. `"BIP0340/challenge"`.
+    pub fn new_tagged(tag: &str) -> Self {
+        let mut tag_hasher = Sha256::new();
+        tag_hasher.update(tag.as_bytes());
+        let tag_hash = tag_hasher.finalize();
+
+        let mut hasher = Sha256::new();
+        hasher.update(&tag_hash);
+        hasher.update(&tag_hash);
+        hasher
+    }
+
+    /// Resumes hashing from a previously exported midstate.
+    ///
+    /// The final padding of the resumed hasher encodes the total message length,
+    /// i.e. `bytes_processed` plus everything passed to `update` afterwards.
+    ///
+    /// # Arguments
+    /// * `h` - The intermediate hash value after the last compressed block.
+    /// * `bytes_processed` - The number of message bytes compressed into `h`.
+    ///
+    /// # Panics
+    /// Panics if `bytes_processed` is not a multiple of the 64-byte block size,
+    /// since a midstate only exists at block boundaries.
+    pub fn from_state(h: [u32; 8], bytes_processed: u64) -> Self {
+        assert!(
+            bytes_processed.is_multiple_of(BLOCK_SIZE as u64),
+            "Midstate must be taken at a block boundary"
+        );
+
+        Sha256 {
+            state: h,
+            buffer: BlockBuffer::resume(bytes_processed),
+        }
+    }
+
+    /// Exports the compression midstate.
+    ///
+    /// # Returns
+    /// The intermediate hash value and the number of bytes compressed into it.
+    /// Bytes still buffered in a partial block are not included.
+    pub fn state(&self) -> ([u32; 8], u64) {
+        (self.state, self.buffer.bytes_compressed())
+    }
+
+    /// Absorbs `data` into the hash computation.
+    ///
+    /// # Arguments
+    /// * `data` - The next chunk of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.buffer
+            .update(data, |block| process_block(&mut self.state, block));
+    }
+
+    /// Applies the final padding and returns the 32-byte digest.
+    pub fn finalize(mut self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        self.finalize_into(&mut out);
+        out
+    }
+
+    /// Writes the digest into `out` without allocating and resets the hasher,
+    /// so the same instance can be reused for the next message.
+    ///
+    /// # Arguments
+    /// * `out` - The buffer receiving the 32-byte digest.
+    pub fn finalize_into(&mut self, out: &mut [u8; 32]) {
+        self.buffer.finalize(LengthEncoding::BigEndian, |block| {
+            process_block(&mut self.state, block)
+        });
+
+        *out = compression::compute_bytes_digest(self.state);
+        self.reset();
+    }
+
+    /// Restores the initial hash value and discards any buffered partial block.
+    pub fn reset(&mut self) {
+        self.state = MessageSchedule::init_working_vars();
+        self.buffer = BlockBuffer::new();
+    }
+
+    /// Returns SHA-256 applied to the digest of the absorbed data, i.e. `SHA-256(SHA-256(m))`.
+    pub fn finalize_double(self) -> [u8; 32] {
+        let mut outer = Sha256::new();
+        outer.update(&self.finalize());
+        outer.finalize()
+    }
+}
+
+impl utils::digest::HashFunction for Sha256 {
+    const BLOCK_SIZE: usize = 64;
+
+    fn new() -> Self {
+        Sha256::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Sha256::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Sha256::finalize(self).to_vec()
+    }
+
+    fn output_size(&self) -> usize {
+        32
+    }
+}
+
+/// Compresses one 64-byte block into `state`.
+fn process_block(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    compression::compress_block(state, &MessageSchedule::expand(&words));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hash, preprocess::initial_sha256_padding, Digest};
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn digest_trait_properties() {
+        use utils::digest::HashFunction as _;
+
+        utils::digest::check_digest::<Sha256>();
+        assert_eq!(Sha256::digest(b"abc"), hash("abc").to_bytes().to_vec());
+    }
+
+    #[test]
+    fn matches_one_shot_hash() {
+        for len in 0..200 {
+            let message = "x".repeat(len);
+
+            let mut hasher = Sha256::new();
+            hasher.update(message.as_bytes());
+
+            assert_eq!(Digest(hasher.finalize()), hash(&message));
+        }
+    }
+
+    #[test]
+    fn reused_hasher() {
+        let mut hasher = Sha256::new();
+        let mut out = [0u8; 32];
+
+        for i in 0..1000 {
+            let message = i.to_string().repeat(i % 40);
+
+            hasher.update(message.as_bytes());
+            hasher.finalize_into(&mut out);
+
+            assert_eq!(Digest(out), hash(&message));
+        }
+
+        hasher.update(b"discarded");
+        hasher.reset();
+        hasher.update(b"abc");
+        assert_eq!(Digest(hasher.finalize()), hash("abc"));
+    }
+
+    #[test]
+    fn finalize_into_does_not_allocate() {
+        let mut hasher = Sha256::new();
+        let mut out = [0u8; 32];
+        let message = [0x61u8; 1000];
+
+        let allocations = counting_allocator::count(|| {
+            for _ in 0..100 {
+                hasher.update(&message);
+                hasher.finalize_into(&mut out);
+            }
+        });
+
+        assert_eq!(allocations, 0);
+
+        // The counter itself must observe allocations for the check above to mean anything.
+        let allocations = counting_allocator::count(|| {
+            std::hint::black_box(vec![0u8; 16]);
+        });
+        assert_eq!(allocations, 1);
+    }
+
+    #[test]
+    fn split_updates() {
+        let message = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+        for split in 0..message.len() {
+            let mut hasher = Sha256::new();
+            hasher.update(&message[..split]);
+            hasher.update(&message[split..]);
+
+            assert_eq!(
+                to_hex(&hasher.finalize()),
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+            );
+        }
+    }
+
+    #[test]
+    fn finalize_double() {
+        let mut hasher = Sha256::new();
+        hasher.update(b"hello");
+
+        assert_eq!(
+            to_hex(&hasher.finalize_double()),
+            "9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50"
+        );
+    }
+
+    #[test]
+    fn resume_from_state() {
+        let message = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".repeat(3);
+
+        let mut hasher = Sha256::new();
+        hasher.update(&message[..100]);
+        let (h, processed) = hasher.state();
+        assert_eq!(processed, 64);
+
+        let mut resumed = Sha256::from_state(h, processed);
+        resumed.update(&message[64..]);
+
+        let mut expected = Sha256::new();
+        expected.update(&message);
+
+        assert_eq!(resumed.finalize(), expected.finalize());
+    }
+
+    #[test]
+    fn length_extension_attack() {
+        // A naive MAC computed as SHA-256(secret || message).
+        let secret = b"super secret key";
+        let message = b"user=alice&role=user";
+        let extension = b"&role=admin";
+
+        let mut a = secret.to_vec();
+        a.extend_from_slice(message);
+
+        let mut mac = Sha256::new();
+        mac.update(&a);
+        let tag = mac.finalize();
+
+        // The attacker only knows the tag and the length of `secret || message`.
+        let padded_a = initial_sha256_padding(&a);
+        let mut h = [0u32; 8];
+        for (word, bytes) in h.iter_mut().zip(tag.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+
+        let mut forged = Sha256::from_state(h, padded_a.len() as u64);
+        forged.update(extension);
+        let forged_tag = forged.finalize();
+
+        // The forgery is a valid tag for `secret || message || pad || extension`.
+        let mut extended = padded_a;
+        extended.extend_from_slice(extension);
+
+        let mut honest = Sha256::new();
+        honest.update(&extended);
+
+        assert_eq!(forged_tag, honest.finalize());
+    }
+
+    #[test]
+    #[should_panic(expected = "Midstate must be taken at a block boundary")]
+    fn from_state_rejects_partial_block() {
+        Sha256::from_state([0u32; 8], 10);
+    }
+
+    mod counting_allocator {
+        use std::alloc::{GlobalAlloc, Layout, System};
+        use std::cell::Cell;
+
+        // Counts allocations made on the current thread while enabled, so
+        // concurrently running tests don't affect each other's counts.
+        struct CountingAllocator;
+
+        thread_local! {
+            static ENABLED: Cell<bool> = const { Cell::new(false) };
+            static COUNT: Cell<usize> = const { Cell::new(0) };
+        }
+
+        unsafe impl GlobalAlloc for CountingAllocator {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                if ENABLED.with(Cell::get) {
+                    COUNT.with(|count| count.set(count.get() + 1));
+                }
+                System.alloc(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                System.dealloc(ptr, layout)
+            }
+        }
+
+        #[global_allocator]
+        static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+        pub fn count(f: impl FnOnce()) -> usize {
+            COUNT.with(|count| count.set(0));
+            ENABLED.with(|enabled| enabled.set(true));
+            f();
+            ENABLED.with(|enabled| enabled.set(false));
+            COUNT.with(Cell::get)
+        }
+    }
+}
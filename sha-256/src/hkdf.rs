@@ -0,0 +1,104 @@
+//! This module implements HKDF (RFC 5869) over HMAC-SHA256: `extract`
+//! concentrates the entropy of the input keying material into a pseudorandom
+//! key, and `expand` stretches that key into as many output bytes as needed,
+//! with `info` binding the output to its context.
+use thiserror::Error;
+
+use crate::hmac::{hmac_sha256, HmacSha256};
+
+/// The largest output `expand` can produce, 255 blocks of 32 bytes.
+pub const MAX_OUTPUT_LEN: usize = 255 * 32;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum HkdfError {
+    #[error("Requested `{0}` bytes, which exceeds the HKDF output limit")]
+    OutputTooLong(usize),
+}
+
+/// Computes the pseudorandom key `PRK = HMAC(salt, ikm)`.
+///
+/// # Arguments
+/// * `salt` - An optional non-secret random value; an empty salt stands for
+///   32 zero bytes.
+/// * `ikm` - The input keying material, e.g. a Diffie-Hellman shared secret.
+pub fn extract(salt: &[u8], ikm: &[u8]) -> [u8; 32] {
+    hmac_sha256(salt, ikm)
+}
+
+/// Fills `out` with `T(1) || T(2) || ...`, where
+/// `T(i) = HMAC(prk, T(i - 1) || info || i)` and `T(0)` is empty.
+///
+/// # Arguments
+/// * `prk` - A pseudorandom key, usually the output of `extract`.
+/// * `info` - Context binding the output to its purpose.
+/// * `out` - The buffer to fill, at most `MAX_OUTPUT_LEN` bytes.
+pub fn expand(prk: &[u8], info: &[u8], out: &mut [u8]) -> Result<(), HkdfError> {
+    if out.len() > MAX_OUTPUT_LEN {
+        return Err(HkdfError::OutputTooLong(out.len()));
+    }
+
+    let mac = HmacSha256::new(prk);
+    let mut block: Vec<u8> = Vec::new();
+
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        let mut mac = mac.clone();
+        mac.update(&block);
+        mac.update(info);
+        mac.update(&[i as u8 + 1]);
+        block = mac.finalize().to_vec();
+
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+
+    Ok(())
+}
+
+/// Runs `extract` and then `expand` into `out`.
+///
+/// # Arguments
+/// * `salt` - The salt passed to `extract`.
+/// * `ikm` - The input keying material.
+/// * `info` - The context passed to `expand`.
+/// * `out` - The buffer to fill, at most `MAX_OUTPUT_LEN` bytes.
+pub fn hkdf(salt: &[u8], ikm: &[u8], info: &[u8], out: &mut [u8]) -> Result<(), HkdfError> {
+    expand(&extract(salt, ikm), info, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::decode_hex;
+
+    #[test]
+    fn rfc5869_vectors() {
+        // Test cases 1 and 3 of RFC 5869.
+        let ikm = [0x0b; 22];
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+
+        let prk = "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5";
+        let okm_1 =
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865";
+        let okm_3 =
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8";
+
+        assert_eq!(extract(&salt, &ikm).to_vec(), decode_hex(prk).unwrap());
+
+        let mut okm = [0u8; 42];
+        expand(&extract(&salt, &ikm), &info, &mut okm).unwrap();
+        assert_eq!(okm.to_vec(), decode_hex(okm_1).unwrap());
+
+        hkdf(&[], &ikm, &[], &mut okm).unwrap();
+        assert_eq!(okm.to_vec(), decode_hex(okm_3).unwrap());
+    }
+
+    #[test]
+    fn output_length_limit() {
+        let mut out = vec![0u8; MAX_OUTPUT_LEN + 1];
+        assert_eq!(
+            expand(&[0u8; 32], &[], &mut out),
+            Err(HkdfError::OutputTooLong(MAX_OUTPUT_LEN + 1))
+        );
+        assert!(expand(&[0u8; 32], &[], &mut out[..MAX_OUTPUT_LEN]).is_ok());
+    }
+}
@@ -0,0 +1,322 @@
+//! This module implements HMAC (RFC 2104) keyed hashing, generically as
+//! `Hmac<D>` over any `HashFunction` and as the allocation-free `HmacSha256`.
+//! HMAC(K, m) = H((K' ^ opad) || H((K' ^ ipad) || m)), where K' is the key
+//! padded (or first hashed, if longer than a block) to the block size of H.
+use utils::{
+    digest::HashFunction,
+    mac::{ct_eq, Mac, MacError},
+};
+
+use crate::Sha256;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+/// An incremental HMAC computation over the hash function `D`.
+///
+/// The inner and outer hashers are keyed once in `new`; `reset` restores
+/// that post-key state, so one instance (or clones of it) can authenticate
+/// any number of messages without reprocessing the key.
+#[derive(Clone, Debug)]
+pub struct Hmac<D> {
+    inner: D,
+    keyed_inner: D,
+    keyed_outer: D,
+}
+
+impl<D: HashFunction + Clone> Hmac<D> {
+    /// Creates an HMAC instance keyed with `key`.
+    ///
+    /// # Arguments
+    /// * `key` - The secret key. Keys longer than the block size of `D` are hashed first.
+    pub fn new(key: &[u8]) -> Self {
+        let mut block_key = vec![0u8; D::BLOCK_SIZE];
+
+        if key.len() > D::BLOCK_SIZE {
+            let hashed = D::digest(key);
+            block_key[..hashed.len()].copy_from_slice(&hashed);
+        } else {
+            block_key[..key.len()].copy_from_slice(key);
+        }
+
+        let mut keyed_inner = D::new();
+        keyed_inner.update(&block_key.iter().map(|byte| byte ^ IPAD).collect::<Vec<_>>());
+
+        let mut keyed_outer = D::new();
+        keyed_outer.update(&block_key.iter().map(|byte| byte ^ OPAD).collect::<Vec<_>>());
+
+        Hmac {
+            inner: keyed_inner.clone(),
+            keyed_inner,
+            keyed_outer,
+        }
+    }
+
+    /// Absorbs `data` into the MAC computation.
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// Returns the authentication tag, as long as a digest of `D`.
+    pub fn finalize(self) -> Vec<u8> {
+        let mut outer = self.keyed_outer;
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+
+    /// Discards any absorbed message data, keeping the key.
+    pub fn reset(&mut self) {
+        self.inner = self.keyed_inner.clone();
+    }
+}
+
+impl<D: HashFunction + Clone> Mac for Hmac<D> {
+    fn update(&mut self, data: &[u8]) {
+        Hmac::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Hmac::finalize(self)
+    }
+}
+
+/// Computes HMAC of `message` under `key` with the hash function `D`, in one shot.
+///
+/// # Arguments
+/// * `key` - The secret key.
+/// * `message` - The message to authenticate.
+///
+/// # Returns
+/// The authentication tag, as long as a digest of `D`.
+pub fn hmac<D: HashFunction + Clone>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<D>::new(key);
+    mac.update(message);
+    mac.finalize()
+}
+
+/// An incremental HMAC-SHA256 computation: `Hmac<Sha256>` with a fixed-size
+/// tag and `finalize_into`, which writes the tag and resets the instance
+/// without allocating.
+#[derive(Clone, Debug)]
+pub struct HmacSha256(Hmac<Sha256>);
+
+impl HmacSha256 {
+    /// Creates an HMAC instance keyed with `key`.
+    ///
+    /// # Arguments
+    /// * `key` - The secret key. Keys longer than the block size are hashed first.
+    pub fn new(key: &[u8]) -> Self {
+        HmacSha256(Hmac::new(key))
+    }
+
+    /// Absorbs `data` into the MAC computation.
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// Returns the 32-byte authentication tag.
+    pub fn finalize(mut self) -> [u8; 32] {
+        let mut tag = [0u8; 32];
+        self.finalize_into(&mut tag);
+        tag
+    }
+
+    /// Writes the authentication tag into `out` and resets the instance to
+    /// its post-key state, ready for the next message.
+    ///
+    /// # Arguments
+    /// * `out` - The buffer receiving the 32-byte tag.
+    pub fn finalize_into(&mut self, out: &mut [u8; 32]) {
+        let mut inner_digest = [0u8; 32];
+        self.0.inner.finalize_into(&mut inner_digest);
+
+        let mut outer = self.0.keyed_outer.clone();
+        outer.update(&inner_digest);
+        outer.finalize_into(out);
+
+        self.reset();
+    }
+
+    /// Checks the tag of the absorbed message against `tag` in constant
+    /// time, without allocating.
+    ///
+    /// # Arguments
+    /// * `tag` - The tag received alongside the message.
+    ///
+    /// # Returns
+    /// `Ok(())` if the tags are equal, `MacError::VerificationFailed` otherwise.
+    pub fn verify(mut self, tag: &[u8]) -> Result<(), MacError> {
+        let mut computed = [0u8; 32];
+        self.finalize_into(&mut computed);
+
+        if ct_eq(&computed, tag) {
+            Ok(())
+        } else {
+            Err(MacError::VerificationFailed)
+        }
+    }
+
+    /// Discards any absorbed message data, keeping the key.
+    pub fn reset(&mut self) {
+        self.0.reset();
+    }
+}
+
+impl Mac for HmacSha256 {
+    fn update(&mut self, data: &[u8]) {
+        HmacSha256::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        HmacSha256::finalize(self).to_vec()
+    }
+}
+
+/// Computes HMAC-SHA256 of `message` under `key` in one shot.
+///
+/// # Arguments
+/// * `key` - The secret key.
+/// * `message` - The message to authenticate.
+///
+/// # Returns
+/// The 32-byte authentication tag.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = HmacSha256::new(key);
+    mac.update(message);
+    mac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{digest::decode_hex, ripemd160::Ripemd160, sha1::Sha1, Digest};
+
+    #[test]
+    fn rfc4231_vectors() {
+        // Test cases 1, 2 and 6 of RFC 4231.
+        let vectors: [(Vec<u8>, &[u8], &str); 3] = [
+            (
+                vec![0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ];
+
+        for (key, message, expected) in vectors {
+            assert_eq!(
+                Digest(hmac_sha256(&key, message)),
+                Digest::from_hex(expected).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn generic_hmac_matches_hmac_sha256() {
+        for key in [&b"key"[..], &[0xaa; 64], &[0xaa; 131]] {
+            let message = b"The quick brown fox jumps over the lazy dog";
+            assert_eq!(hmac::<Sha256>(key, message), hmac_sha256(key, message));
+        }
+    }
+
+    #[test]
+    fn rfc2202_hmac_sha1_vectors() {
+        // Test cases 1, 2 and 6 of RFC 2202.
+        let vectors: [(Vec<u8>, &[u8], &str); 3] = [
+            (
+                vec![0x0b; 20],
+                b"Hi There",
+                "b617318655057264e28bc0b6fb378c8ef146be00",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?",
+                "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+            ),
+            (
+                vec![0xaa; 80],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+            ),
+        ];
+
+        for (key, message, expected) in vectors {
+            assert_eq!(hmac::<Sha1>(&key, message), decode_hex(expected).unwrap());
+        }
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let message = b"The quick brown fox jumps over the lazy dog";
+
+        let mut mac = HmacSha256::new(b"key");
+        for chunk in message.chunks(5) {
+            mac.update(chunk);
+        }
+
+        assert_eq!(mac.finalize(), hmac_sha256(b"key", message));
+    }
+
+    #[test]
+    fn mac_trait_properties() {
+        utils::mac::check_mac(|| HmacSha256::new(b"key"));
+        utils::mac::check_mac(|| HmacSha256::new(&[0xaa; 131]));
+        utils::mac::check_mac(|| Hmac::<Sha1>::new(b"key"));
+        utils::mac::check_mac(|| Hmac::<Ripemd160>::new(&[0xaa; 131]));
+    }
+
+    #[test]
+    fn keyed_instance_reuse() {
+        let keyed = HmacSha256::new(b"packet key");
+        let mut mac = keyed.clone();
+        let mut tag = [0u8; 32];
+
+        for i in 0..10_000u32 {
+            let message = i.to_le_bytes();
+
+            mac.update(&message);
+            mac.finalize_into(&mut tag);
+            assert_eq!(tag, hmac_sha256(b"packet key", &message));
+
+            let mut cloned = keyed.clone();
+            cloned.update(&message);
+            assert_eq!(cloned.finalize(), tag);
+        }
+    }
+
+    #[test]
+    fn verify_checks_tag() {
+        let tag = hmac_sha256(b"key", b"message");
+
+        let mut mac = HmacSha256::new(b"key");
+        mac.update(b"message");
+        assert_eq!(mac.clone().verify(&tag), Ok(()));
+
+        let mut tampered = tag;
+        tampered[31] ^= 1;
+        assert_eq!(
+            mac.clone().verify(&tampered),
+            Err(MacError::VerificationFailed)
+        );
+        assert_eq!(mac.verify(&tag[..16]), Err(MacError::VerificationFailed));
+    }
+
+    #[test]
+    fn reset_discards_message_state() {
+        let mut mac = HmacSha256::new(b"key");
+        mac.update(b"a message that is abandoned half way");
+        mac.reset();
+        mac.update(b"hello");
+
+        assert_eq!(mac.finalize(), hmac_sha256(b"key", b"hello"));
+    }
+}
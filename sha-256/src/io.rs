@@ -0,0 +1,234 @@
+//! This module provides `std::io` adapters that hash data as it is streamed.
+use std::io::{self, Read, Write};
+
+use crate::{ripemd160::Ripemd160, sha1::Sha1, Digest, Sha256};
+
+/// A reader that hashes everything read through it and checks the digest at EOF.
+///
+/// The final `read` returning end-of-file fails with `io::ErrorKind::InvalidData`
+/// if the SHA-256 digest of the stream does not equal the expected one, so a
+/// consumer copying the stream elsewhere notices corruption before finishing.
+pub struct VerifyingReader<R: Read> {
+    inner: R,
+    hasher: Sha256,
+    expected: [u8; 32],
+    verified: bool,
+}
+
+impl<R: Read> VerifyingReader<R> {
+    /// Wraps `inner`, expecting its contents to hash to `expected`.
+    ///
+    /// # Arguments
+    /// * `inner` - The reader supplying the data.
+    /// * `expected` - The expected SHA-256 digest of the whole stream.
+    pub fn new(inner: R, expected: [u8; 32]) -> Self {
+        VerifyingReader {
+            inner,
+            hasher: Sha256::new(),
+            expected,
+            verified: false,
+        }
+    }
+
+    /// Consumes the wrapper, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for VerifyingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.verified || buf.is_empty() {
+            return self.inner.read(buf);
+        }
+
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            self.hasher.update(&buf[..read]);
+            return Ok(read);
+        }
+
+        let mut digest = [0u8; 32];
+        self.hasher.finalize_into(&mut digest);
+
+        if !Digest(digest).ct_eq(&Digest(self.expected)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "SHA-256 digest of the stream does not match the expected value",
+            ));
+        }
+
+        self.verified = true;
+        Ok(0)
+    }
+}
+
+/// Feeding a `Sha256` through `Write` lets `io::copy` hash a file or socket
+/// in constant memory.
+impl Write for Sha256 {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Write for Sha1 {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Write for Ripemd160 {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hashes everything `reader` yields until end-of-file.
+///
+/// # Arguments
+/// * `reader` - The stream to hash, read in fixed-size chunks.
+///
+/// # Returns
+/// The 32-byte digest of the stream, or the first read error.
+pub fn hash_reader<R: Read>(mut reader: R) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Yields at most `max` bytes per read, to exercise short reads.
+    struct ShortReader<'a> {
+        data: &'a [u8],
+        max: usize,
+    }
+
+    impl Read for ShortReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.max.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    fn digest_of(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    fn stream() -> Vec<u8> {
+        (0..1000u32).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn matching_stream_succeeds() {
+        let data = stream();
+        let mut reader = VerifyingReader::new(data.as_slice(), digest_of(&data));
+
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+
+        assert_eq!(out, data);
+        assert_eq!(reader.read(&mut [0u8; 8]).unwrap(), 0);
+    }
+
+    #[test]
+    fn flipped_byte_fails_at_eof() {
+        let data = stream();
+        let expected = digest_of(&data);
+
+        for index in [0, 1, 63, 64, 500, data.len() - 1] {
+            let mut corrupted = data.clone();
+            corrupted[index] ^= 0x01;
+
+            let mut reader = VerifyingReader::new(corrupted.as_slice(), expected);
+            let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn short_reads_and_small_buffers() {
+        let data = stream();
+
+        for max in [1, 3, 64, 65] {
+            let inner = ShortReader { data: &data, max };
+            let mut reader = VerifyingReader::new(inner, digest_of(&data));
+
+            let mut out = Vec::new();
+            let mut buf = [0u8; 7];
+            loop {
+                let read = reader.read(&mut buf).unwrap();
+                if read == 0 {
+                    break;
+                }
+                out.extend_from_slice(&buf[..read]);
+                assert_eq!(reader.read(&mut []).unwrap(), 0);
+            }
+
+            assert_eq!(out, data);
+        }
+    }
+
+    #[test]
+    fn hash_reader_matches_update() {
+        let data = stream();
+
+        for max in [1, 64, 1000] {
+            let inner = ShortReader { data: &data, max };
+            assert_eq!(hash_reader(inner).unwrap(), digest_of(&data));
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.write_all(&data).unwrap();
+        assert_eq!(hasher.finalize(), digest_of(&data));
+    }
+
+    #[test]
+    fn other_hashers_accept_io_copy() {
+        let data = stream();
+
+        let mut sha1 = Sha1::new();
+        io::copy(
+            &mut ShortReader {
+                data: &data,
+                max: 65,
+            },
+            &mut sha1,
+        )
+        .unwrap();
+        assert_eq!(sha1.finalize(), crate::sha1::hash_bytes(&data));
+
+        let mut ripemd160 = Ripemd160::new();
+        io::copy(
+            &mut ShortReader {
+                data: &data,
+                max: 65,
+            },
+            &mut ripemd160,
+        )
+        .unwrap();
+        assert_eq!(ripemd160.finalize(), crate::ripemd160::hash_bytes(&data));
+    }
+}
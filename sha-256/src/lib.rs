@@ -1,14 +1,43 @@
+pub mod base58check;
+pub mod digest;
+pub mod drbg;
+pub mod hkdf;
+pub mod hmac;
+pub mod io;
+pub mod merkle;
+pub mod pbkdf2;
+pub mod ripemd160;
+pub mod sha1;
+pub mod sha512;
+
+#[cfg(test)]
+mod cavs;
 mod constants;
 mod hash_computation;
+mod hasher;
 mod preprocess;
 mod utilities;
 
+use num_bigint::BigUint;
+use rayon::prelude::*;
+use utils::encoding;
+
+pub use digest::{Digest, ParseError};
+pub use hasher::Sha256;
+pub use hmac::{hmac_sha256, Hmac, HmacSha256};
+pub use utils::encoding::EncodeError;
+
+// Batches smaller than this are hashed serially, since the cost of
+// dispatching to the thread pool outweighs hashing a few short messages.
+const PARALLEL_BATCH_THRESHOLD: usize = 32;
+
 /// `hash` computes a cryptographic hash of a given message.
 ///
 /// This function serves as the main interface to the hashing process. It
 /// preprocesses the input message, creates a message schedule, compresses
 /// the schedule, and then computes the digest bytes. The final hash
-/// is represented as a hexadecimal string.
+/// is returned as a `Digest`, whose `Display` implementation renders the
+/// lowercase hexadecimal string.
 ///
 /// # Arguments
 /// * `message` - A reference to the input message string. This is the data
@@ -19,14 +48,210 @@ mod utilities;
 /// 2. Generate a message schedule based on the preprocessed message.
 /// 3. Compress the message schedule to produce a fixed-size output.
 /// 4. Translate the compressed output into its byte representation.
-/// 5. Convert each byte into its hexadecimal string equivalent.
 ///
 /// # Returns
-/// A `String` containing the hexadecimal representation of the hash digest.
-pub fn hash(message: &str) -> String {
-    // Preprocess the message
-    let preprocessed_msg = preprocess::preprocess_message(message);
+/// The `Digest` of the message. Use `to_string()` for its hexadecimal representation.
+pub fn hash(message: &str) -> Digest {
+    digest_message(message.as_bytes())
+}
+
+/// `hash_bytes` computes the SHA-256 digest of arbitrary binary data, such as
+/// ciphertexts, keys or file contents that are not valid UTF-8.
+///
+/// # Arguments
+/// * `data` - The bytes to hash.
+///
+/// # Returns
+/// The 32-byte digest of `data`.
+pub fn hash_bytes(data: &[u8]) -> [u8; 32] {
+    digest_message(data).0
+}
+
+/// `hash_hex_input` computes the hash of a message supplied as a hexadecimal string.
+///
+/// The string is decoded into raw bytes first, so `hash_hex_input("616263")`
+/// is the digest of the three bytes `abc`, not of the six ASCII characters.
+///
+/// # Arguments
+/// * `hex` - The message encoded as hex, optionally prefixed with `0x`.
+///
+/// # Returns
+/// The `Digest` of the decoded bytes, or a `ParseError` if `hex` is not valid hex.
+pub fn hash_hex_input(hex: &str) -> Result<Digest, ParseError> {
+    let bytes = digest::decode_hex(hex)?;
+    Ok(digest_message(&bytes))
+}
+
+/// `hash_bits` computes the hash of a message whose length is given in bits.
+///
+/// FIPS 180-4 defines SHA-256 over bit strings; this entry point supports
+/// messages whose length is not a multiple of 8. Only the first `bit_len`
+/// bits of `data` are hashed and the unused low bits of the final partial
+/// byte are ignored.
+///
+/// # Arguments
+/// * `data` - The message bytes, most significant bit first.
+/// * `bit_len` - The number of message bits in `data`.
+///
+/// # Returns
+/// The 32-byte digest of the bit string.
+///
+/// # Panics
+/// Panics if `bit_len` exceeds `data.len() * 8`.
+pub fn hash_bits(data: &[u8], bit_len: usize) -> [u8; 32] {
+    let preprocessed_msg = preprocess::preprocess_message_bits(data, bit_len);
+
+    compute_digest(preprocessed_msg).0
+}
+
+/// `sha256d` computes the double SHA-256 digest `SHA-256(SHA-256(data))`
+/// used throughout Bitcoin-style protocols.
+///
+/// # Arguments
+/// * `data` - The bytes to hash.
+///
+/// # Returns
+/// The 32-byte digest of the first pass, hashed again.
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize_double()
+}
+
+/// `tagged_hash` computes the BIP-340 tagged hash
+/// `SHA-256(SHA-256(tag) || SHA-256(tag) || msg)`.
+///
+/// Hashes with different tags are independent, so a digest computed for one
+/// purpose, e.g. a Schnorr challenge, can never be replayed as another.
+///
+/// # Arguments
+/// * `tag` - The tag naming the purpose of the hash, e.g. `"BIP0340/challenge"`.
+/// * `msg` - The bytes to hash.
+///
+/// # Returns
+/// The 32-byte digest.
+pub fn tagged_hash(tag: &str, msg: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new_tagged(tag);
+    hasher.update(msg);
+    hasher.finalize()
+}
+
+/// `hash_truncated` computes the SHA-256 digest of `data` truncated to its
+/// leftmost `out_len` bytes, as FIPS 180-4 §7 prescribes for shorter outputs.
+///
+/// # Arguments
+/// * `data` - The bytes to hash.
+/// * `out_len` - The number of digest bytes to keep.
+///
+/// # Returns
+/// The first `out_len` bytes of the digest.
+///
+/// # Panics
+/// Panics if `out_len` exceeds the 32-byte digest size.
+pub fn hash_truncated(data: &[u8], out_len: usize) -> Vec<u8> {
+    assert!(out_len <= 32, "Truncated output cannot exceed 32 bytes");
+
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize()[..out_len].to_vec()
+}
+
+/// `sha224` computes the SHA-224 digest of `data`.
+///
+/// SHA-224 runs the SHA-256 compression function from its own initial hash
+/// value and keeps the leftmost 28 bytes of the result (FIPS 180-4 §6.3).
+///
+/// # Arguments
+/// * `data` - The bytes to hash.
+///
+/// # Returns
+/// The 28-byte digest.
+pub fn sha224(data: &[u8]) -> [u8; 28] {
+    let mut hasher = Sha256::from_state(constants::H_224, 0);
+    hasher.update(data);
+
+    let mut out = [0u8; 28];
+    out.copy_from_slice(&hasher.finalize()[..28]);
+    out
+}
+
+/// `hash_128` returns the leftmost 16 bytes of the SHA-256 digest of `data`.
+pub fn hash_128(data: &[u8]) -> [u8; 16] {
+    truncate(data)
+}
+
+/// `hash_160` returns the leftmost 20 bytes of the SHA-256 digest of `data`.
+pub fn hash_160(data: &[u8]) -> [u8; 20] {
+    truncate(data)
+}
+
+/// `hash_biguint` computes the SHA-256 digest of a big integer encoded as a
+/// fixed-width big-endian byte string.
+///
+/// Fixing the width makes the digest independent of how many leading zero
+/// bytes the value happens to have, which matters when hashing key material
+/// or signature components.
+///
+/// # Arguments
+/// * `n` - The value to hash.
+/// * `width` - The encoded length in bytes; `n` is left-padded with zeros.
+///
+/// # Returns
+/// The 32-byte digest, or `EncodeError::ValueTooLarge` if `n` does not fit in `width` bytes.
+pub fn hash_biguint(n: &BigUint, width: usize) -> Result<[u8; 32], EncodeError> {
+    let bytes = encoding::to_bytes_be_padded(n, width)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    Ok(hasher.finalize())
+}
+
+/// `hash_batch` computes the SHA-256 digests of many independent messages.
+///
+/// Large batches are distributed across the rayon thread pool; each message
+/// is still hashed serially. The digests are returned in the same order as
+/// the input messages.
+///
+/// # Arguments
+/// * `messages` - The messages to hash.
+///
+/// # Returns
+/// A vector holding the 32-byte digest of each message.
+pub fn hash_batch(messages: &[&[u8]]) -> Vec<[u8; 32]> {
+    let digest = |message: &&[u8]| {
+        let mut hasher = Sha256::new();
+        hasher.update(message);
+        hasher.finalize()
+    };
 
+    if messages.len() < PARALLEL_BATCH_THRESHOLD {
+        messages.iter().map(digest).collect()
+    } else {
+        messages.par_iter().map(digest).collect()
+    }
+}
+
+/// Hashes `data` and keeps the leftmost `N` bytes of the digest.
+fn truncate<const N: usize>(data: &[u8]) -> [u8; N] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+
+    let mut out = [0u8; N];
+    out.copy_from_slice(&hasher.finalize()[..N]);
+    out
+}
+
+/// Hashes the raw message bytes. The streaming hasher compresses the message
+/// in place, so no padded copy of it is ever made.
+fn digest_message(message: &[u8]) -> Digest {
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+
+    Digest(hasher.finalize())
+}
+
+/// Computes the digest of an already preprocessed message.
+fn compute_digest(preprocessed_msg: preprocess::PreprocessResult) -> Digest {
     // Create a message schedule
     let msg_schedule = hash_computation::message_schedule::MessageSchedule::new(preprocessed_msg);
 
@@ -36,10 +261,7 @@ pub fn hash(message: &str) -> String {
     // Compute the digest bytes
     let digest_bytes = hash_computation::compression::compute_bytes_digest(compressed_msg);
 
-    digest_bytes
-        .iter()
-        .map(|byte| format!("{:02x}", byte))
-        .collect::<String>()
+    Digest(digest_bytes)
 }
 
 #[cfg(test)]
@@ -51,6 +273,283 @@ mod test {
         let message = "hello world";
         let digest = hash(message);
 
-        println!("digest: {:?}", digest);
+        assert_eq!(
+            digest.to_string(),
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+    }
+
+    #[test]
+    fn hash_known_answers() {
+        let vectors = [
+            (
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                "abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+        ];
+
+        for (message, expected) in vectors {
+            assert_eq!(hash(message).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn hash_multi_block_msg() {
+        let message = "a".repeat(64);
+
+        assert_eq!(
+            hash(&message).to_string(),
+            "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb"
+        );
+    }
+
+    #[test]
+    fn hash_nist_multi_block_vectors() {
+        // FIPS 180-4 examples (NIST CSRC) of two-block messages, and the
+        // million-`a` message from the SHAVS long message test.
+        let million_a = "a".repeat(1_000_000);
+        let vectors = [
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+            (
+                "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+                 hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+            ),
+            (
+                million_a.as_str(),
+                "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
+            ),
+        ];
+
+        for (message, expected) in vectors {
+            assert_eq!(hash(message).to_string(), expected);
+
+            // The bit-oriented path compresses the same blocks without the
+            // streaming hasher.
+            let bits = hash_bits(message.as_bytes(), message.len() * 8);
+            assert_eq!(Digest(bits).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn sha224_known_answers() {
+        // FIPS 180-4 examples (NIST CSRC) for SHA-224.
+        for (message, expected) in [
+            (
+                "",
+                "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+            ),
+            (
+                "abc",
+                "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+            ),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525",
+            ),
+        ] {
+            assert_eq!(
+                sha224(message.as_bytes()).to_vec(),
+                digest::decode_hex(expected).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn double_hash() {
+        let to_hex = |bytes: [u8; 32]| {
+            bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        };
+
+        assert_eq!(
+            to_hex(sha256d(b"")),
+            "5df6e0e2761359d30a8275058e299fcc0381534545f55cf43e41983f5d4c9456"
+        );
+        assert_eq!(
+            to_hex(sha256d(b"hello")),
+            "9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50"
+        );
+        assert_ne!(to_hex(sha256d(b"hello")), hash("hello").to_string());
+    }
+
+    #[test]
+    fn tagged_hashes() {
+        assert_eq!(
+            tagged_hash("BIP0340/challenge", b"").to_vec(),
+            digest::decode_hex("c216d352f5818b7b4beacd4ae0a26fe888080823d2a598856661bcd54f1b3713")
+                .unwrap()
+        );
+        assert_eq!(
+            tagged_hash("TapLeaf", b"abc").to_vec(),
+            digest::decode_hex("83a56308a9c56f467e8df293da5ae5fdbc85b871952a83c4bf0575ee948ec230")
+                .unwrap()
+        );
+
+        let tag_hash = hash_bytes(b"TapLeaf");
+        assert_eq!(
+            tagged_hash("TapLeaf", b"abc"),
+            hash_bytes(&[&tag_hash[..], &tag_hash, b"abc"].concat())
+        );
+        assert_ne!(
+            tagged_hash("TapLeaf", b"abc"),
+            tagged_hash("TapBranch", b"abc")
+        );
+    }
+
+    #[test]
+    fn batch_matches_serial() {
+        let owned: Vec<Vec<u8>> = (0..500u32)
+            .map(|i| i.to_string().repeat(i as usize % 17).into_bytes())
+            .collect();
+
+        for len in [0, 1, PARALLEL_BATCH_THRESHOLD - 1, owned.len()] {
+            let messages: Vec<&[u8]> = owned[..len].iter().map(Vec::as_slice).collect();
+
+            let expected: Vec<[u8; 32]> = messages
+                .iter()
+                .map(|message| {
+                    let mut hasher = Sha256::new();
+                    hasher.update(message);
+                    hasher.finalize()
+                })
+                .collect();
+
+            assert_eq!(hash_batch(&messages), expected);
+        }
+    }
+
+    #[test]
+    fn hex_input_hashes_raw_bytes() {
+        let bytes: [u8; 5] = [0x00, 0xff, 0x10, 0x80, 0x7f];
+
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+        let expected = Digest(hasher.finalize());
+
+        assert_eq!(hash_hex_input("00ff10807f").unwrap(), expected);
+        assert_eq!(hash_hex_input("0x00FF10807F").unwrap(), expected);
+        assert_eq!(hash_hex_input("616263").unwrap(), hash("abc"));
+        assert_eq!(hash_hex_input("").unwrap(), hash(""));
+    }
+
+    #[test]
+    fn hash_non_utf8_bytes() {
+        // Not valid UTF-8, so out of reach of `hash`.
+        let bytes = [0xff, 0xfe, 0x00, 0x80];
+
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+        assert_eq!(hash_bytes(&bytes), hasher.finalize());
+
+        assert_eq!(Digest(hash_bytes(b"abc")), hash("abc"));
+    }
+
+    #[test]
+    fn hex_input_rejects_invalid_hex() {
+        assert_eq!(hash_hex_input("abc"), Err(ParseError::OddLength(3)));
+        assert_eq!(
+            hash_hex_input("0g"),
+            Err(ParseError::InvalidHexCharacter('g'))
+        );
+    }
+
+    #[test]
+    fn hash_bit_messages() {
+        let to_hex = |bytes: [u8; 32]| Digest(bytes).to_string();
+
+        // NIST CAVP SHA256ShortMsg (bit-oriented), Len = 5, Msg = 68.
+        assert_eq!(
+            to_hex(hash_bits(&[0x68], 5)),
+            "d6d3e02a31a84a8caa9718ed6c2057be09db45e7823eb5079ce7a573a3760f95"
+        );
+
+        // The unused low bits of the final byte do not affect the digest.
+        assert_eq!(hash_bits(&[0x6f], 5), hash_bits(&[0x68], 5));
+
+        // A 611-bit message whose padding spills into a second block. This is
+        // not a published vector: the digest is this implementation's own
+        // output, recorded to catch regressions, and the checks after it only
+        // show that every message bit, and no padding bit, is hashed.
+        let message: Vec<u8> = (0..77u32).map(|i| (i * 7 + 3) as u8).collect();
+        let digest = hash_bits(&message, 611);
+        assert_eq!(
+            to_hex(digest),
+            "72c10d12fc314571e134a5a93e633a700e8e468641046a88a67e49cf2ea88f10"
+        );
+        let mut flipped = message.clone();
+        flipped[76] ^= 0x20;
+        assert_ne!(hash_bits(&flipped, 611), digest);
+        flipped[76] ^= 0x20 | 0x1f;
+        assert_eq!(hash_bits(&flipped, 611), digest);
+        assert_ne!(hash_bits(&message, 610), digest);
+        assert_ne!(hash_bits(&message, 612), digest);
+
+        // Byte-aligned lengths match the byte-oriented API.
+        assert_eq!(Digest(hash_bits(b"abc", 24)), hash("abc"));
+        assert_eq!(Digest(hash_bits(b"abc", 0)), hash(""));
+    }
+
+    #[test]
+    fn truncated_outputs() {
+        let digest = hash("abc").to_bytes();
+
+        assert_eq!(hash_truncated(b"abc", 0), Vec::<u8>::new());
+        assert_eq!(hash_truncated(b"abc", 32), digest.to_vec());
+        assert_eq!(
+            digest::decode_hex("ba7816bf8f01cfea414140de5dae2223").unwrap(),
+            hash_128(b"abc")
+        );
+        assert_eq!(
+            digest::decode_hex("ba7816bf8f01cfea414140de5dae2223b00361a3").unwrap(),
+            hash_160(b"abc")
+        );
+        assert_eq!(hash_truncated(b"abc", 20), hash_160(b"abc"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Truncated output cannot exceed 32 bytes")]
+    fn truncated_output_too_long() {
+        hash_truncated(b"abc", 33);
+    }
+
+    #[test]
+    fn hash_biguint_is_width_dependent() {
+        let one = BigUint::from(1u8);
+
+        let mut padded = [0u8; 32];
+        padded[31] = 1;
+        assert_eq!(
+            Digest(hash_biguint(&one, 32).unwrap()),
+            digest_message(&padded)
+        );
+        assert_ne!(hash_biguint(&one, 32), hash_biguint(&one, 64));
+    }
+
+    #[test]
+    fn hash_biguint_exact_width() {
+        let bytes: Vec<u8> = (1..=32).collect();
+        let n = BigUint::from_bytes_be(&bytes);
+
+        assert_eq!(
+            Digest(hash_biguint(&n, 32).unwrap()),
+            digest_message(&bytes)
+        );
+        assert_eq!(
+            hash_biguint(&n, 31),
+            Err(EncodeError::ValueTooLarge {
+                needed: 32,
+                width: 31
+            })
+        );
     }
 }
@@ -0,0 +1,258 @@
+//! This module builds binary Merkle trees on top of SHA-256.
+//! Leaves are hashed with SHA-256 and each parent node is the SHA-256 digest
+//! of the concatenation of its two children.
+use thiserror::Error;
+
+use crate::Sha256;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MerkleError {
+    #[error("Cannot build a Merkle tree without leaves")]
+    NoLeaves,
+
+    #[error("Leaf index `{0}` is out of range")]
+    IndexOutOfRange(usize),
+}
+
+/// How a level with an odd number of nodes is paired up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OddNodePolicy {
+    /// The last node is paired with a copy of itself (Bitcoin style).
+    Duplicate,
+    /// The last node is carried up to the next level unchanged.
+    Promote,
+}
+
+/// Which side of the running hash a sibling sits on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// A single step of an inclusion proof: the sibling hash and its side.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProofNode {
+    pub hash: [u8; 32],
+    pub side: Side,
+}
+
+#[derive(Debug)]
+pub struct MerkleTree {
+    // Every level of the tree, from the leaf hashes up to the root.
+    levels: Vec<Vec<[u8; 32]>>,
+    policy: OddNodePolicy,
+}
+
+impl MerkleTree {
+    /// Builds a tree over `leaves`, duplicating the last node on odd levels.
+    ///
+    /// # Arguments
+    /// * `leaves` - The raw leaf data, hashed before being inserted into the tree.
+    ///
+    /// # Returns
+    /// The tree, or `MerkleError::NoLeaves` if `leaves` is empty.
+    pub fn from_leaves(leaves: &[Vec<u8>]) -> Result<Self, MerkleError> {
+        Self::from_leaves_with_policy(leaves, OddNodePolicy::Duplicate)
+    }
+
+    /// Builds a tree over `leaves` using the given odd-node policy.
+    pub fn from_leaves_with_policy(
+        leaves: &[Vec<u8>],
+        policy: OddNodePolicy,
+    ) -> Result<Self, MerkleError> {
+        if leaves.is_empty() {
+            return Err(MerkleError::NoLeaves);
+        }
+
+        let mut levels = vec![leaves
+            .iter()
+            .map(|leaf| hash_leaf(leaf))
+            .collect::<Vec<_>>()];
+
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = level
+                .chunks(2)
+                .map(|pair| match (pair, policy) {
+                    ([left, right], _) => hash_nodes(left, right),
+                    ([last], OddNodePolicy::Duplicate) => hash_nodes(last, last),
+                    ([last], OddNodePolicy::Promote) => *last,
+                    _ => unreachable!("chunks(2) yields one or two nodes"),
+                })
+                .collect();
+
+            levels.push(next);
+        }
+
+        Ok(MerkleTree { levels, policy })
+    }
+
+    /// Returns the Merkle root.
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Returns the inclusion proof for the leaf at `index`, ordered from the leaf upwards.
+    pub fn proof(&self, index: usize) -> Result<Vec<ProofNode>, MerkleError> {
+        if index >= self.levels[0].len() {
+            return Err(MerkleError::IndexOutOfRange(index));
+        }
+
+        let mut proof = Vec::with_capacity(self.levels.len() - 1);
+        let mut idx = index;
+
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = idx ^ 1;
+
+            if sibling < level.len() {
+                let side = if idx.is_multiple_of(2) {
+                    Side::Right
+                } else {
+                    Side::Left
+                };
+                proof.push(ProofNode {
+                    hash: level[sibling],
+                    side,
+                });
+            } else if self.policy == OddNodePolicy::Duplicate {
+                proof.push(ProofNode {
+                    hash: level[idx],
+                    side: Side::Right,
+                });
+            }
+
+            idx /= 2;
+        }
+
+        Ok(proof)
+    }
+}
+
+/// Checks that `leaf` is included under `root` according to `proof`.
+///
+/// # Arguments
+/// * `root` - The expected Merkle root.
+/// * `leaf` - The raw leaf data.
+/// * `proof` - The sibling hashes from the leaf up to the root.
+pub fn verify_proof(root: &[u8; 32], leaf: &[u8], proof: &[ProofNode]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(hash_leaf(leaf), |acc, node| match node.side {
+            Side::Left => hash_nodes(&node.hash, &acc),
+            Side::Right => hash_nodes(&acc, &node.hash),
+        });
+
+    &computed == root
+}
+
+fn hash_leaf(leaf: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(leaf);
+    hasher.finalize()
+}
+
+fn hash_nodes(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<Vec<u8>> {
+        [b"a", b"b", b"c", b"d"][..n]
+            .iter()
+            .map(|leaf| leaf.to_vec())
+            .collect()
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn single_leaf_root_is_leaf_hash() {
+        let tree = MerkleTree::from_leaves(&leaves(1)).unwrap();
+
+        assert_eq!(tree.root(), hash_leaf(b"a"));
+        assert!(tree.proof(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn known_roots() {
+        let expected = [
+            (
+                2,
+                "e5a01fee14e0ed5c48714f22180f25ad8365b53f9779f79dc4a3d7e93963f94a",
+            ),
+            (
+                3,
+                "d31a37ef6ac14a2db1470c4316beb5592e6afd4465022339adafda76a18ffabe",
+            ),
+            (
+                4,
+                "14ede5e8e97ad9372327728f5099b95604a39593cac3bd38a343ad76205213e7",
+            ),
+        ];
+
+        for (n, root) in expected {
+            let tree = MerkleTree::from_leaves(&leaves(n)).unwrap();
+            assert_eq!(to_hex(&tree.root()), root);
+        }
+    }
+
+    #[test]
+    fn promote_policy_root() {
+        let tree = MerkleTree::from_leaves_with_policy(&leaves(3), OddNodePolicy::Promote).unwrap();
+
+        assert_eq!(
+            to_hex(&tree.root()),
+            "7075152d03a5cd92104887b476862778ec0c87be5c2fa1c0a90f87c49fad6eff"
+        );
+    }
+
+    #[test]
+    fn proofs_verify() {
+        for policy in [OddNodePolicy::Duplicate, OddNodePolicy::Promote] {
+            for n in 1..=4 {
+                let data = leaves(n);
+                let tree = MerkleTree::from_leaves_with_policy(&data, policy).unwrap();
+
+                for (i, leaf) in data.iter().enumerate() {
+                    let proof = tree.proof(i).unwrap();
+                    assert!(verify_proof(&tree.root(), leaf, &proof));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tampered_proof_fails() {
+        let data = leaves(4);
+        let tree = MerkleTree::from_leaves(&data).unwrap();
+
+        let mut proof = tree.proof(2).unwrap();
+        proof[0].hash[0] ^= 1;
+
+        assert!(!verify_proof(&tree.root(), &data[2], &proof));
+        assert!(!verify_proof(
+            &tree.root(),
+            &data[1],
+            &tree.proof(2).unwrap()
+        ));
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        assert_eq!(
+            MerkleTree::from_leaves(&[]).unwrap_err(),
+            MerkleError::NoLeaves
+        );
+
+        let tree = MerkleTree::from_leaves(&leaves(3)).unwrap();
+        assert_eq!(tree.proof(3).unwrap_err(), MerkleError::IndexOutOfRange(3));
+    }
+}
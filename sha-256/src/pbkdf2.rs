@@ -0,0 +1,142 @@
+//! This module implements PBKDF2 (RFC 8018, section 5.2) with HMAC-SHA256 as
+//! the pseudorandom function. Each 32-byte output block is
+//! `T(i) = U(1) ^ U(2) ^ ... ^ U(c)`, where `U(1) = HMAC(password, salt || i)`
+//! and `U(j) = HMAC(password, U(j - 1))`; the iteration count `c` sets how
+//! expensive each password guess is.
+use thiserror::Error;
+
+use crate::hmac::HmacSha256;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Pbkdf2Error {
+    #[error("The iteration count must be at least 1")]
+    ZeroIterations,
+}
+
+/// Fills `out` with key material derived from `password` and `salt`.
+///
+/// # Arguments
+/// * `password` - The password bytes.
+/// * `salt` - A random value unique per password, stored alongside the output.
+/// * `iterations` - The number of HMAC invocations per output block.
+/// * `out` - The buffer to fill with the derived key.
+pub fn pbkdf2_hmac_sha256(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out: &mut [u8],
+) -> Result<(), Pbkdf2Error> {
+    if iterations == 0 {
+        return Err(Pbkdf2Error::ZeroIterations);
+    }
+
+    let mac = HmacSha256::new(password);
+
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        let mut u = mac.clone();
+        u.update(salt);
+        u.update(&(i as u32 + 1).to_be_bytes());
+        let mut u = u.finalize();
+        let mut block = u;
+
+        for _ in 1..iterations {
+            let mut next = mac.clone();
+            next.update(&u);
+            u = next.finalize();
+
+            for (byte, other) in block.iter_mut().zip(&u) {
+                *byte ^= other;
+            }
+        }
+
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+
+    Ok(())
+}
+
+/// Derives `len` bytes of key material from `password` and `salt`.
+///
+/// # Arguments
+/// * `password` - The password bytes.
+/// * `salt` - A random value unique per password.
+/// * `iterations` - The number of HMAC invocations per output block.
+/// * `len` - The number of bytes to derive.
+///
+/// # Returns
+/// The derived key, or `Pbkdf2Error::ZeroIterations`.
+pub fn pbkdf2(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    len: usize,
+) -> Result<Vec<u8>, Pbkdf2Error> {
+    let mut out = vec![0u8; len];
+    pbkdf2_hmac_sha256(password, salt, iterations, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::decode_hex;
+
+    #[test]
+    fn rfc6070_inputs() {
+        // Published PBKDF2-HMAC-SHA256 vectors for the RFC 6070 inputs.
+        for (password, salt, iterations, expected) in [
+            (
+                &b"password"[..],
+                &b"salt"[..],
+                1,
+                "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b",
+            ),
+            (
+                b"password",
+                b"salt",
+                2,
+                "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43",
+            ),
+            (
+                b"password",
+                b"salt",
+                4096,
+                "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a",
+            ),
+            (
+                b"passwordPASSWORDpassword",
+                b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c4e2a1fb8dd53e1c635518c7dac47e9",
+            ),
+        ] {
+            let expected = decode_hex(expected).unwrap();
+            let mut out = vec![0u8; expected.len()];
+            pbkdf2_hmac_sha256(password, salt, iterations, &mut out).unwrap();
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn output_length_is_a_prefix() {
+        // Shorter outputs are prefixes of longer ones under the same inputs.
+        let long = pbkdf2(b"password", b"salt", 2, 100).unwrap();
+        assert_eq!(long.len(), 100);
+
+        for len in [0, 1, 31, 32, 33, 64] {
+            assert_eq!(pbkdf2(b"password", b"salt", 2, len).unwrap(), long[..len]);
+        }
+    }
+
+    #[test]
+    fn rejects_zero_iterations() {
+        assert_eq!(
+            pbkdf2_hmac_sha256(b"password", b"salt", 0, &mut [0u8; 32]),
+            Err(Pbkdf2Error::ZeroIterations)
+        );
+        assert_eq!(
+            pbkdf2(b"password", b"salt", 0, 32),
+            Err(Pbkdf2Error::ZeroIterations)
+        );
+    }
+}
@@ -3,7 +3,6 @@
 /// 1. Padding a message.
 /// 2. Parsing the message into message blocks.
 /// 3. Setting the initial hash value `H_0`.
-
 const BLOCK_SIZE: usize = 64;
 const CHUNK_SIZE: usize = 4;
 
@@ -11,7 +10,7 @@ const CHUNK_SIZE: usize = 4;
 /// Represents the result of the preprocessing step.
 pub struct PreprocessResult(pub Vec<[[u8; 4]; 16]>);
 
-/// Converts a message to binary and pads the binary to SHA-256 specifications.
+/// Pads a message to SHA-256 specifications and parses it into message blocks.
 ///
 /// # Arguments
 ///
@@ -19,44 +18,90 @@ pub struct PreprocessResult(pub Vec<[[u8; 4]; 16]>);
 ///
 /// # Returns
 /// A PreprocessResult.
-pub fn preprocess_message(message: &str) -> PreprocessResult {
+pub fn preprocess_message(message: &[u8]) -> PreprocessResult {
     let padded_msg = initial_sha256_padding(message);
 
+    PreprocessResult(generate_message_blocks(padded_msg))
+}
+
+/// Pads a message of `bit_len` bits to SHA-256 specifications and parses it into message blocks.
+///
+/// # Arguments
+///
+/// * `message` - The message bytes; only the first `bit_len` bits are used.
+/// * `bit_len` - The length of the message in bits.
+///
+/// # Returns
+/// A PreprocessResult.
+pub fn preprocess_message_bits(message: &[u8], bit_len: usize) -> PreprocessResult {
+    let padded_msg = initial_sha256_padding_bits(message, bit_len);
+
     let preprocessed_msg = generate_message_blocks(padded_msg);
 
-    return PreprocessResult(preprocessed_msg);
+    PreprocessResult(preprocessed_msg)
 }
 
 /// Prepares a message for SHA-256 hashing by performing the initial padding.
 ///
+/// # Arguments
+///
+/// * `message` - The message bytes to be padded.
+///
+/// # Returns
+///
+/// A `Vec<u8>` containing the message after the initial padding, ready for further SHA-256 processing.
+pub fn initial_sha256_padding(message: &[u8]) -> Vec<u8> {
+    initial_sha256_padding_bits(message, message.len() * 8)
+}
+
+/// Prepares a message of `bit_len` bits for SHA-256 hashing by performing the initial padding.
+///
 /// This function implements the first phase of the SHA-256 preprocessing,
 /// where the input message undergoes the following transformations:
-/// 1. A '1' bit is appended to the end.
+/// 1. A '1' bit is appended immediately after the last message bit.
 /// 2. '0' bits are appended to make the total length congruent to 448 (mod 512).
-/// 3. The 64-bit big-endian representation of the original message length (in bits) is appended.
+/// 3. The 64-bit big-endian representation of `bit_len` is appended.
+///
+/// When `bit_len` is not a multiple of 8, the unused low bits of the final
+/// message byte are ignored and the '1' bit is placed inside that byte.
 ///
 /// # Arguments
 ///
-/// * `message` - A string slice containing the message to be padded.
+/// * `message` - The message bytes to be padded.
+/// * `bit_len` - The length of the message in bits.
 ///
 /// # Returns
 ///
 /// A `Vec<u8>` containing the message after the initial padding, ready for further SHA-256 processing.
-pub fn initial_sha256_padding(message: &str) -> Vec<u8> {
-    let mut buffer = message.as_bytes().to_vec();
-    // TODO: Potential error, look into this...
-    buffer.push(0x80); // Append 1 bit (0x80 in byte form)
+///
+/// # Panics
+/// Panics if `bit_len` exceeds the number of bits in `message`.
+pub fn initial_sha256_padding_bits(message: &[u8], bit_len: usize) -> Vec<u8> {
+    assert!(
+        bit_len <= message.len() * 8,
+        "Bit length exceeds the message length"
+    );
+
+    let mut buffer = message[..bit_len.div_ceil(8)].to_vec();
+
+    match (bit_len % 8, buffer.last_mut()) {
+        // Clear the unused low bits and set the bit right after the message.
+        (used_bits @ 1..=7, Some(last)) => {
+            *last = (*last & (0xff << (8 - used_bits))) | (0x80 >> used_bits);
+        }
+        // Append 1 bit (0x80 in byte form)
+        _ => buffer.push(0x80),
+    }
 
     // Calculate how many zero bytes we need to add so
     // that the current length is congruent to 448 mod 512
-    let zero_bytes_to_add = (56 - (buffer.len() % 64)) % 64;
+    let zero_bytes_to_add = (BLOCK_SIZE + 56 - (buffer.len() % BLOCK_SIZE)) % BLOCK_SIZE;
 
     // Add the required zero bytes
     buffer.extend(vec![0u8; zero_bytes_to_add]);
 
     // Append the original length of the message, in bits, as a 64-bit big-endian value
-    let original_bit_len = (message.len() as u64) * 8; // convert byte length to bit length
-    buffer.extend(original_bit_len.to_be_bytes().iter());
+    buffer.extend((bit_len as u64).to_be_bytes().iter());
 
     buffer
 }
@@ -66,67 +111,38 @@ pub fn initial_sha256_padding(message: &str) -> Vec<u8> {
 /// The function divides the message into N blocks of 512 bits each (64 bytes).
 /// Each of these blocks is further parsed into sixteen 32-bit (4 bytes) blocks.
 ///
+/// The padding functions above always produce a multiple of 64 bytes, so the
+/// exact chunking below never leaves a remainder.
+///
 /// # Arguments
 /// * `msg_pad` - The padded message to be processed.
 ///
 /// # Returns
 /// A vector of 512-bit blocks, where each block is an array of sixteen 4-byte arrays.
 fn generate_message_blocks(msg_pad: Vec<u8>) -> Vec<[[u8; 4]; 16]> {
+    debug_assert!(
+        msg_pad.len().is_multiple_of(BLOCK_SIZE),
+        "Padded message must be a whole number of blocks"
+    );
+
     msg_pad
-        .chunks(BLOCK_SIZE)
+        .chunks_exact(BLOCK_SIZE)
         .map(|block| {
             let mut array_block: [[u8; 4]; 16] = Default::default();
 
-            for (i, chunk) in block.chunks(CHUNK_SIZE).enumerate() {
-                array_block[i] = match chunk {
-                    &[a, b, c, d] => [a, b, c, d],
-                    _ => panic!("Expected a chunk of size 4!"),
-                };
+            for (word, chunk) in array_block.iter_mut().zip(block.chunks_exact(CHUNK_SIZE)) {
+                word.copy_from_slice(chunk);
             }
             array_block
         })
         .collect()
 }
 
-/// Converts a 32-bit hexadecimal string into a 4-byte array.
-///
-/// # Arguments
-/// * `h` - A 32-bit hexadecimal string.
-///
-/// # Returns
-/// A 4-byte array representing the hexadecimal string.
-///
-/// # Panics
-/// Panics if the input string `h` is not 8 characters long,
-/// or if the string cannot be converted to a byte array.
-pub fn hex_to_byte_array(hex: &str) -> [u8; 4] {
-    let mut h: &str = hex;
-
-    if hex.starts_with("0x") {
-        h = &hex[2..];
-    }
-
-    if h.len() != 8 {
-        panic!("Constant {:?} has wrong length", h);
-    }
-
-    let mut bytes = [0u8; 4];
-    for i in 0..4 {
-        let start = i * 2;
-        let end = start + 2;
-        let slice = &h[start..end];
-        bytes[i] = u8::from_str_radix(slice, 16).expect("Failed to convert to hexadecimal");
-    }
-
-    bytes
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants;
 
-    const MESSAGE: &str = "hello world";
+    const MESSAGE: &[u8] = b"hello world";
 
     #[test]
     fn pad_message() {
@@ -152,8 +168,41 @@ mod tests {
     }
 
     #[test]
-    fn convert_h() {
-        let bytes = hex_to_byte_array(constants::H[0]);
-        assert_eq!(bytes, [106, 9, 230, 103]);
+    fn pad_partial_byte() {
+        let padded_msg = initial_sha256_padding_bits(&[0x6f], 5);
+
+        assert_eq!(padded_msg.len(), 64);
+        assert_eq!(padded_msg[0], 0x6c);
+        assert_eq!(padded_msg[63], 5);
+    }
+
+    #[test]
+    fn pad_boundary_lengths() {
+        // 55 bytes is the longest message fitting in one block; from 56 bytes
+        // on the length trailer spills into a second block.
+        for (len, expected) in [(55, 64), (56, 128), (63, 128), (64, 128), (119, 128)] {
+            assert_eq!(initial_sha256_padding(&vec![0x61; len]).len(), expected);
+        }
+    }
+
+    #[test]
+    fn padded_length_sweep() {
+        for len in 0..=130 {
+            let message = vec![0xffu8; len];
+            let padded_msg = initial_sha256_padding(&message);
+
+            assert!(padded_msg.len().is_multiple_of(BLOCK_SIZE));
+            assert!(padded_msg.len() - len >= 9 && padded_msg.len() - len <= 72);
+            assert_eq!(&padded_msg[..len], message.as_slice());
+            assert_eq!(padded_msg[len], 0x80);
+
+            let (body, trailer) = padded_msg.split_at(padded_msg.len() - 8);
+            assert!(body[len + 1..].iter().all(|&byte| byte == 0));
+            assert_eq!(trailer, (len as u64 * 8).to_be_bytes());
+
+            let blocks = preprocess_message(&message).0;
+            assert_eq!(blocks.len(), padded_msg.len() / BLOCK_SIZE);
+            assert_eq!(blocks.concat().concat(), padded_msg);
+        }
     }
 }
@@ -0,0 +1,250 @@
+//! This module implements RIPEMD-160 and Bitcoin's `hash160`.
+//!
+//! RIPEMD-160 is a Merkle–Damgård hash with MD5's little-endian padding, but
+//! each block runs through two independent lines of five rounds whose
+//! results are combined at the end. Bitcoin uses it after SHA-256 to shorten
+//! public keys and scripts to 20 bytes.
+use utils::block_buffer::{BlockBuffer, LengthEncoding, BLOCK_SIZE};
+
+use crate::hash_bytes as sha256;
+
+/// The initial hash value.
+const H: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// The round constants of the left and right lines.
+const K_LEFT: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+const K_RIGHT: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// The message word selected at each step of the left and right lines.
+const R_LEFT: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+const R_RIGHT: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// The left rotation amount at each step of the left and right lines.
+const S_LEFT: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+const S_RIGHT: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+/// Computes the RIPEMD-160 digest of `message`.
+///
+/// # Arguments
+/// * `message` - The bytes to hash.
+///
+/// # Returns
+/// The 20-byte digest.
+pub fn hash_bytes(message: &[u8]) -> [u8; 20] {
+    let mut hasher = Ripemd160::new();
+    hasher.update(message);
+    hasher.finalize()
+}
+
+/// Computes Bitcoin's `hash160`, `RIPEMD-160(SHA-256(data))`.
+///
+/// This is the public key hash inside P2PKH addresses. Not to be confused
+/// with `crate::hash_160`, which truncates a single SHA-256 digest.
+///
+/// # Arguments
+/// * `data` - The bytes to hash, typically a SEC 1 encoded public key.
+///
+/// # Returns
+/// The 20-byte digest.
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    hash_bytes(&sha256(data))
+}
+
+/// An incremental RIPEMD-160 hasher.
+#[derive(Clone, Debug)]
+pub struct Ripemd160 {
+    state: [u32; 5],
+    buffer: BlockBuffer,
+}
+
+impl Default for Ripemd160 {
+    fn default() -> Self {
+        Ripemd160::new()
+    }
+}
+
+impl Ripemd160 {
+    /// Creates a hasher initialised with the RIPEMD-160 initial hash value.
+    pub fn new() -> Self {
+        Ripemd160 {
+            state: H,
+            buffer: BlockBuffer::new(),
+        }
+    }
+
+    /// Absorbs `data` into the hash computation.
+    ///
+    /// # Arguments
+    /// * `data` - The next chunk of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.buffer
+            .update(data, |block| compress_block(&mut self.state, block));
+    }
+
+    /// Applies the final padding and returns the 20-byte digest.
+    pub fn finalize(mut self) -> [u8; 20] {
+        self.buffer.finalize(LengthEncoding::LittleEndian, |block| {
+            compress_block(&mut self.state, block)
+        });
+
+        let mut digest = [0u8; 20];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+}
+
+impl utils::digest::HashFunction for Ripemd160 {
+    const BLOCK_SIZE: usize = 64;
+
+    fn new() -> Self {
+        Ripemd160::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Ripemd160::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Ripemd160::finalize(self).to_vec()
+    }
+
+    fn output_size(&self) -> usize {
+        20
+    }
+}
+
+/// The boolean function of round `round`; the right line uses them in
+/// reverse order.
+fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+/// Runs both lines of 80 steps over one block and combines them into `state`.
+fn compress_block(state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+    let mut x = [0u32; 16];
+    for (word, bytes) in x.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let [mut al, mut bl, mut cl, mut dl, mut el] = *state;
+    let [mut ar, mut br, mut cr, mut dr, mut er] = *state;
+
+    for j in 0..80 {
+        let round = j / 16;
+
+        let t = al
+            .wrapping_add(f(round, bl, cl, dl))
+            .wrapping_add(x[R_LEFT[j]])
+            .wrapping_add(K_LEFT[round])
+            .rotate_left(S_LEFT[j])
+            .wrapping_add(el);
+        (al, el, dl, cl, bl) = (el, dl, cl.rotate_left(10), bl, t);
+
+        let t = ar
+            .wrapping_add(f(4 - round, br, cr, dr))
+            .wrapping_add(x[R_RIGHT[j]])
+            .wrapping_add(K_RIGHT[round])
+            .rotate_left(S_RIGHT[j])
+            .wrapping_add(er);
+        (ar, er, dr, cr, br) = (er, dr, cr.rotate_left(10), br, t);
+    }
+
+    let t = state[1].wrapping_add(cl).wrapping_add(dr);
+    state[1] = state[2].wrapping_add(dl).wrapping_add(er);
+    state[2] = state[3].wrapping_add(el).wrapping_add(ar);
+    state[3] = state[4].wrapping_add(al).wrapping_add(br);
+    state[4] = state[0].wrapping_add(bl).wrapping_add(cr);
+    state[0] = t;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::decode_hex;
+
+    #[test]
+    fn known_answers() {
+        // From the RIPEMD-160 authors' test vectors.
+        let vectors: [(&[u8], &str); 6] = [
+            (b"", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+            (b"a", "0bdc9d2d256b3ee9daae347be6f4dc835a467ffe"),
+            (b"abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            (
+                b"message digest",
+                "5d0689ef49d2fae572b881b123a85ffa21595f36",
+            ),
+            (
+                b"abcdefghijklmnopqrstuvwxyz",
+                "f71c27109c692c1b56bbdceb5b9d2865b3708dbc",
+            ),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "12a053384a9c0c88e405a06c27dcf49ada62eb2b",
+            ),
+        ];
+
+        for (message, expected) in vectors {
+            assert_eq!(hash_bytes(message).to_vec(), decode_hex(expected).unwrap());
+        }
+    }
+
+    #[test]
+    fn million_a() {
+        let mut hasher = Ripemd160::new();
+        for _ in 0..1000 {
+            hasher.update(&[b'a'; 1000]);
+        }
+        assert_eq!(
+            hasher.finalize().to_vec(),
+            decode_hex("52783243c1697bdbe16d37f97f68f08325dc1528").unwrap()
+        );
+
+        utils::digest::check_digest::<Ripemd160>();
+    }
+
+    #[test]
+    fn hash160_of_generator() {
+        // The compressed secp256k1 generator, i.e. the public key of the
+        // private key 1, whose P2PKH address is 1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH.
+        let public_key =
+            decode_hex("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+
+        assert_eq!(
+            hash160(&public_key).to_vec(),
+            decode_hex("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap()
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! This module implements SHA-1 (FIPS 180-4 §6.1) for teaching purposes.
+//!
+//! SHA-1 is broken for security: practical collisions have been demonstrated,
+//! so it must not be used for signatures, certificates or integrity checks.
+//! It shares its preprocessing with SHA-256 (512-bit blocks and a 64-bit
+//! length trailer) and differs only in the schedule and compression function.
+use utils::block_buffer::{BlockBuffer, LengthEncoding, BLOCK_SIZE};
+
+use crate::preprocess;
+
+/// The initial hash value `H_0` (FIPS 180-4 §5.3.1).
+const H: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// The round constants, one per stage of 20 rounds (FIPS 180-4 §4.2.1).
+const K: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+/// Computes the SHA-1 digest of `message`.
+///
+/// **Not secure**: SHA-1 is vulnerable to collision attacks and is provided
+/// only to illustrate the Merkle–Damgård construction alongside SHA-256.
+///
+/// # Arguments
+/// * `message` - The bytes to hash.
+///
+/// # Returns
+/// The 20-byte digest.
+pub fn hash_bytes(message: &[u8]) -> [u8; 20] {
+    let preprocessed_msg = preprocess::preprocess_message(message);
+
+    let mut state = H;
+    for block in &preprocessed_msg.0 {
+        compress_block(&mut state, &expand(block));
+    }
+
+    to_bytes(state)
+}
+
+/// An incremental SHA-1 hasher, so SHA-1 can stand in wherever a
+/// `utils::digest::HashFunction` is expected. **Not secure**, see `hash_bytes`.
+#[derive(Clone, Debug)]
+pub struct Sha1 {
+    state: [u32; 5],
+    buffer: BlockBuffer,
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Sha1::new()
+    }
+}
+
+impl Sha1 {
+    /// Creates a hasher initialised with the SHA-1 initial hash value.
+    pub fn new() -> Self {
+        Sha1 {
+            state: H,
+            buffer: BlockBuffer::new(),
+        }
+    }
+
+    /// Absorbs `data` into the hash computation.
+    ///
+    /// # Arguments
+    /// * `data` - The next chunk of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.buffer
+            .update(data, |block| process_block(&mut self.state, block));
+    }
+
+    /// Applies the final padding and returns the 20-byte digest.
+    pub fn finalize(mut self) -> [u8; 20] {
+        self.buffer.finalize(LengthEncoding::BigEndian, |block| {
+            process_block(&mut self.state, block)
+        });
+
+        to_bytes(self.state)
+    }
+}
+
+/// Compresses one 64-byte block into `state`.
+fn process_block(state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+    let mut words = [[0u8; 4]; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        word.copy_from_slice(bytes);
+    }
+
+    compress_block(state, &expand(&words));
+}
+
+impl utils::digest::HashFunction for Sha1 {
+    const BLOCK_SIZE: usize = 64;
+
+    fn new() -> Self {
+        Sha1::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Sha1::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Sha1::finalize(self).to_vec()
+    }
+
+    fn output_size(&self) -> usize {
+        20
+    }
+}
+
+/// Serializes the state words big-endian.
+fn to_bytes(state: [u32; 5]) -> [u8; 20] {
+    let mut digest = [0u8; 20];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Expands a block into the 80-word message schedule.
+fn expand(block: &[[u8; 4]; 16]) -> [u32; 80] {
+    let mut w = [0u32; 80];
+
+    for (word, bytes) in w.iter_mut().zip(block) {
+        *word = u32::from_be_bytes(*bytes);
+    }
+
+    for t in 16..80 {
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+
+    w
+}
+
+/// Runs the 80 rounds over one expanded block and adds the result into `state`.
+fn compress_block(state: &mut [u32; 5], w: &[u32; 80]) {
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+
+    for (t, &word) in w.iter().enumerate() {
+        // Ch, Parity, Maj and Parity again, for rounds 0-19, 20-39, 40-59 and 60-79.
+        let f = match t / 20 {
+            0 => (b & c) | (!b & d),
+            2 => (b & c) | (b & d) | (c & d),
+            _ => b ^ c ^ d,
+        };
+
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(K[t / 20])
+            .wrapping_add(word);
+
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    for (h, v) in state.iter_mut().zip([a, b, c, d, e]) {
+        *h = h.wrapping_add(v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::decode_hex;
+
+    #[test]
+    fn known_answers() {
+        let vectors: [(&[u8], &str); 3] = [
+            (b"abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (b"", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            ),
+        ];
+
+        for (message, expected) in vectors {
+            assert_eq!(hash_bytes(message).to_vec(), decode_hex(expected).unwrap());
+        }
+    }
+
+    #[test]
+    fn multi_block_message() {
+        assert_eq!(
+            hash_bytes(&[b'a'; 1000]).to_vec(),
+            decode_hex("291e9a6c66994949b57ba5e650361e98fc36b1ba").unwrap()
+        );
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let message: Vec<u8> = (0..300u32).map(|i| (i * 7) as u8).collect();
+
+        for len in [0, 55, 56, 63, 64, 65, 300] {
+            let mut hasher = Sha1::new();
+            for chunk in message[..len].chunks(13) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize(), hash_bytes(&message[..len]));
+        }
+
+        utils::digest::check_digest::<Sha1>();
+    }
+
+    #[test]
+    fn million_a() {
+        // The long message from FIPS 180 appendix A.3.
+        let mut hasher = Sha1::new();
+        for _ in 0..1000 {
+            hasher.update(&[b'a'; 1000]);
+        }
+
+        assert_eq!(
+            hasher.finalize().to_vec(),
+            decode_hex("34aa973cd4c4daa4f61eeb2bdbad27316534016f").unwrap()
+        );
+    }
+}
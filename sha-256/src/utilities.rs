@@ -1,162 +1,147 @@
-/// Circularly right-shifts a 32-bit number by `n` positions.
+/// Circularly right-shifts a 32-bit word by `n` positions.
 ///
-/// This function takes a 32-bit number represented as a byte array `input` and circularly
-/// shifts it to the right by `n` positions.
 /// If `n` exceeds 32, it wraps around, so it essentially does `n % 32`.
 /// In SHA-256, the max value of n is 22, but this function is generalized to handle up to 32.
 ///
 /// # Arguments
 ///
-/// * `input` - A 4-byte array representing the 32-bit number to be shifted.
+/// * `input` - The 32-bit word to be shifted.
 /// * `n` - Number of positions to shift to the right. If exceeds 32, it wraps around.
 ///
 /// # Returns
-/// A 4-byte array representing the shifted number.
+/// The shifted word.
 #[inline]
-pub fn rotr(input: [u8; 4], n: usize) -> [u8; 4] {
-    // Ensure n is in range [0, 31] for predictable behavior.
-    let n = n % 32;
-
-    // Convert the input byte array to a 32-bit unsigned integer.
-    let num = u32::from_be_bytes(input);
-
-    // Perform the circular right shift.
-    let shifted = (num >> n) | (num << (32 - n));
-
-    // Convert the shifted 32-bit unsigned integer back to a byte array and return.
-    shifted.to_be_bytes()
+pub fn rotr(input: u32, n: u32) -> u32 {
+    input.rotate_right(n % 32)
 }
 
-/// Performs a bitwise AND operation between two 4-byte arrays.
+/// Performs a bitwise AND operation between two 32-bit words.
 ///
 /// # Arguments
-/// * `a` - The first byte array.
-/// * `b` - The second byte array.
+/// * `a` - The first word.
+/// * `b` - The second word.
 ///
 /// # Returns
-/// A new byte array that is the result of the AND operation.
+/// A new word that is the result of the AND operation.
 #[inline]
-pub fn and(a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
-    (u32::from_be_bytes(a) & u32::from_be_bytes(b)).to_be_bytes()
+pub fn and(a: u32, b: u32) -> u32 {
+    a & b
 }
 
-/// Performs a bitwise XOR operation between two 4-byte arrays.
+/// Performs a bitwise XOR operation between two 32-bit words.
 ///
 /// # Arguments
-/// * `a` - The first 4-byte array.
-/// * `b` - The second 4-byte array.
+/// * `a` - The first word.
+/// * `b` - The second word.
 ///
 /// # Returns
-/// A new 4-byte array that is the result of the XOR operation.
+/// A new word that is the result of the XOR operation.
 #[inline]
-pub fn xor(a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
-    (u32::from_be_bytes(a) ^ u32::from_be_bytes(b)).to_be_bytes()
+pub fn xor(a: u32, b: u32) -> u32 {
+    a ^ b
 }
 
 #[inline]
-pub fn not(arr: [u8; 4]) -> [u8; 4] {
-    (!u32::from_be_bytes(arr)).to_be_bytes()
+pub fn not(a: u32) -> u32 {
+    !a
 }
 
-/// Performs a right shift on a 32-bit number represented as a byte array.
+/// Performs a right shift on a 32-bit word.
 ///
 /// # Parameters
-/// - `input`: A 4-byte array representing a 32-bit number.
+/// - `input`: The 32-bit word.
 /// - `n`: The number of positions to shift to the right.
 ///
 /// # Returns
-/// A 4-byte array representing the shifted 32-bit number.
+/// The shifted 32-bit word.
 #[inline]
-pub fn shr(input: [u8; 4], n: usize) -> [u8; 4] {
-    let n = n % 32;
-    let shifted = u32::from_be_bytes(input) >> n;
-    shifted.to_be_bytes()
+pub fn shr(input: u32, n: u32) -> u32 {
+    input >> (n % 32)
 }
 
-/// Performs bitwise addition modulo 2 on two byte arrays of length 4.
+/// Performs bitwise addition modulo 2 on two 32-bit words.
 ///
 /// # Arguments
 ///
-/// * `a` - First byte array.
-/// * `b` - Second byte array.
+/// * `a` - First word.
+/// * `b` - Second word.
 ///
 /// # Returns
 ///
-/// A new byte array of length 4 where each byte is the result of
-/// the XOR operation on corresponding bytes of `a` and `b`.
+/// A new word where each bit is the result of the XOR operation
+/// on corresponding bits of `a` and `b`.
 #[inline]
-pub fn add_mod_2(a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
-    let mut result = [0u8; 4];
-    for i in 0..4 {
-        result[i] = a[i] ^ b[i];
-    }
-    result
+pub fn add_mod_2(a: u32, b: u32) -> u32 {
+    a ^ b
 }
 
-/// Adds two 32-bit numbers represented as byte arrays, modulo 2^32.
+/// Adds two 32-bit words modulo 2^32.
 ///
 /// # Parameters:
-/// - `a`: First number represented as a big-endian byte array.
-/// - `b`: Second number represented as a big-endian byte array.
+/// - `a`: First word.
+/// - `b`: Second word.
 ///
 /// # Returns:
-/// - A big-endian byte array representing the sum of `a` and `b`, modulo 2^32.
+/// - The sum of `a` and `b`, modulo 2^32.
 #[inline]
-pub fn add_mod_2_32(a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
-    // Convert byte arrays to u32 integers in big-endian format
-    let num_a = u32::from_be_bytes(a);
-    let num_b = u32::from_be_bytes(b);
-
+pub fn add_mod_2_32(a: u32, b: u32) -> u32 {
     // Perform addition with wrapping to handle overflow
-    let sum = num_a.wrapping_add(num_b);
-
-    // Convert the result back to a big-endian byte array
-    sum.to_be_bytes()
+    a.wrapping_add(b)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn word(bytes: [u8; 4]) -> u32 {
+        u32::from_be_bytes(bytes)
+    }
+
     #[test]
     fn circular_rotate_right() {
-        let result = rotr([11, 0, 24, 32], 3);
-        assert_eq!(result, [1, 96, 3, 4]);
+        let result = rotr(word([11, 0, 24, 32]), 3);
+        assert_eq!(result, word([1, 96, 3, 4]));
     }
 
     #[test]
     fn right_shift() {
-        let result = shr([12, 8, 16, 4], 3);
-        assert_eq!(result, [1, 129, 2, 0]);
+        let result = shr(word([12, 8, 16, 4]), 3);
+        assert_eq!(result, word([1, 129, 2, 0]));
     }
 
     #[test]
     fn mod_2() {
-        let result = add_mod_2([12, 10, 32, 6], [0, 4, 8, 2]);
-        assert_eq!(result, [12, 14, 40, 4]);
+        let result = add_mod_2(word([12, 10, 32, 6]), word([0, 4, 8, 2]));
+        assert_eq!(result, word([12, 14, 40, 4]));
     }
 
     #[test]
     fn mod_32() {
-        let result = add_mod_2_32([12, 10, 32, 6], [0, 4, 8, 2]);
-        assert_eq!(result, [12, 14, 40, 8]);
+        let result = add_mod_2_32(word([12, 10, 32, 6]), word([0, 4, 8, 2]));
+        assert_eq!(result, word([12, 14, 40, 8]));
+    }
+
+    #[test]
+    fn mod_32_wraps() {
+        let result = add_mod_2_32(0xffff_ffff, 2);
+        assert_eq!(result, 1);
     }
 
     #[test]
     fn and_a_b() {
-        let result = and([1, 143, 233, 5], [82, 101, 104, 66]);
-        assert_eq!(result, [0, 5, 104, 0]);
+        let result = and(word([1, 143, 233, 5]), word([82, 101, 104, 66]));
+        assert_eq!(result, word([0, 5, 104, 0]));
     }
 
     #[test]
     fn xor_a_b() {
-        let result = xor([1, 143, 233, 5], [82, 101, 104, 66]);
-        assert_eq!(result, [83, 234, 129, 71]);
+        let result = xor(word([1, 143, 233, 5]), word([82, 101, 104, 66]));
+        assert_eq!(result, word([83, 234, 129, 71]));
     }
 
     #[test]
     fn not_a() {
-        let result = not([1, 143, 233, 5]);
-        assert_eq!(result, [254, 112, 22, 250]);
+        let result = not(word([1, 143, 233, 5]));
+        assert_eq!(result, word([254, 112, 22, 250]));
     }
 }
@@ -0,0 +1,243 @@
+//! This crate implements the SHA-3 hash functions (FIPS 202) on the
+//! Keccak-f[1600] sponge.
+//!
+//! Unlike SHA-2, SHA-3 is not a Merkle–Damgård hash. The message is XORed,
+//! `rate` bytes at a time, into part of a 200-byte state that is permuted
+//! after every block; the remaining `capacity` bytes are never output
+//! directly, which is what makes SHA-3 immune to length extension. A SHA-3
+//! function with an `N`-byte output has a capacity of `2N` bytes.
+pub mod keccak;
+
+use std::io::{self, Write};
+
+use keccak::keccak_f1600;
+
+/// The size of the Keccak-f[1600] state in bytes.
+const STATE_SIZE: usize = 200;
+
+/// The SHA-3 domain separation bits `01`, followed by the first bit of the
+/// `pad10*1` padding.
+const DOMAIN_PADDING: u8 = 0x06;
+
+/// An incremental SHA-3 hasher with an `N`-byte digest.
+///
+/// Use the `Sha3_224`, `Sha3_256`, `Sha3_384` and `Sha3_512` aliases; other
+/// values of `N` are not SHA-3 functions.
+#[derive(Clone, Debug)]
+pub struct Sha3<const N: usize> {
+    state: [u64; 25],
+    /// The number of bytes absorbed into the current block.
+    position: usize,
+}
+
+pub type Sha3_224 = Sha3<28>;
+pub type Sha3_256 = Sha3<32>;
+pub type Sha3_384 = Sha3<48>;
+pub type Sha3_512 = Sha3<64>;
+
+impl<const N: usize> Default for Sha3<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Sha3<N> {
+    /// The number of message bytes absorbed per permutation.
+    const RATE: usize = STATE_SIZE - 2 * N;
+
+    /// Creates a hasher with the all-zero initial state.
+    pub fn new() -> Self {
+        Sha3 {
+            state: [0u64; 25],
+            position: 0,
+        }
+    }
+
+    /// Absorbs `data` into the sponge.
+    ///
+    /// # Arguments
+    /// * `data` - The next chunk of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.xor_byte(self.position, byte);
+            self.position += 1;
+
+            if self.position == Self::RATE {
+                keccak_f1600(&mut self.state);
+                self.position = 0;
+            }
+        }
+    }
+
+    /// Pads the message, permutes once more and squeezes the `N`-byte digest.
+    pub fn finalize(mut self) -> [u8; N] {
+        // The padding always fits in the current block: at least one byte
+        // of the rate is free, and a single byte can hold both its ends.
+        self.xor_byte(self.position, DOMAIN_PADDING);
+        self.xor_byte(Self::RATE - 1, 0x80);
+        keccak_f1600(&mut self.state);
+
+        // The digest is shorter than the rate, so one squeeze suffices.
+        let mut digest = [0u8; N];
+        for (i, byte) in digest.iter_mut().enumerate() {
+            *byte = (self.state[i / 8] >> (8 * (i % 8))) as u8;
+        }
+        digest
+    }
+
+    /// XORs `byte` into the state at byte offset `index`; lanes are little-endian.
+    fn xor_byte(&mut self, index: usize, byte: u8) {
+        self.state[index / 8] ^= (byte as u64) << (8 * (index % 8));
+    }
+}
+
+impl<const N: usize> utils::digest::HashFunction for Sha3<N> {
+    const BLOCK_SIZE: usize = Self::RATE;
+
+    fn new() -> Self {
+        Sha3::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Sha3::update(self, data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Sha3::finalize(self).to_vec()
+    }
+
+    fn output_size(&self) -> usize {
+        N
+    }
+}
+
+/// Feeding a `Sha3` through `Write` lets `io::copy` hash a file or socket in
+/// constant memory.
+impl<const N: usize> Write for Sha3<N> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Computes the SHA3-224 digest of `message`.
+pub fn sha3_224(message: &[u8]) -> [u8; 28] {
+    hash::<28>(message)
+}
+
+/// Computes the SHA3-256 digest of `message`.
+pub fn sha3_256(message: &[u8]) -> [u8; 32] {
+    hash::<32>(message)
+}
+
+/// Computes the SHA3-384 digest of `message`.
+pub fn sha3_384(message: &[u8]) -> [u8; 48] {
+    hash::<48>(message)
+}
+
+/// Computes the SHA3-512 digest of `message`.
+pub fn sha3_512(message: &[u8]) -> [u8; 64] {
+    hash::<64>(message)
+}
+
+fn hash<const N: usize>(message: &[u8]) -> [u8; N] {
+    let mut hasher = Sha3::<N>::new();
+    hasher.update(message);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fips202_examples() {
+        // The empty string and "abc", from the NIST SHA-3 example values.
+        assert_eq!(
+            hex::encode(sha3_224(b"")),
+            "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7"
+        );
+        assert_eq!(
+            hex::encode(sha3_224(b"abc")),
+            "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf"
+        );
+        assert_eq!(
+            hex::encode(sha3_256(b"")),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            hex::encode(sha3_256(b"abc")),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_eq!(
+            hex::encode(sha3_384(b"")),
+            "0c63a75b845e4f7d01107d852e4c2485c51a50aaaa94fc61\
+             995e71bbee983a2ac3713831264adb47fb6bd1e058d5f004"
+        );
+        assert_eq!(
+            hex::encode(sha3_384(b"abc")),
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c25\
+             96da7cf0e49be4b298d88cea927ac7f539f1edf228376d25"
+        );
+        assert_eq!(
+            hex::encode(sha3_512(b"")),
+            "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a6\
+             15b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26"
+        );
+        assert_eq!(
+            hex::encode(sha3_512(b"abc")),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+             10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        );
+    }
+
+    #[test]
+    fn block_boundaries() {
+        // SHA3-224 has a 144-byte rate: 143 bytes leave a single byte for
+        // the padding, 144 fill the block exactly. Cross-checked against an
+        // independent implementation.
+        assert_eq!(
+            hex::encode(sha3_224(&[b'a'; 143])),
+            "73b1b22b54f515f626a6abdde6af25cd4801dc6e9dc7fa3f77e1c122"
+        );
+        assert_eq!(
+            hex::encode(sha3_224(&[b'a'; 144])),
+            "f9019111996dcf160e284e320fd6d8825cabcd41a5ffdc4c5e9d64b6"
+        );
+        assert_eq!(
+            hex::encode(sha3_256(&[b'a'; 200])),
+            "cce34485baf2bf2aca99b94833892a4f52896d3d153f7b840cc4f9fe695f1387"
+        );
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let message: Vec<u8> = (0..500u32).map(|i| (i * 7) as u8).collect();
+
+        for len in [0, 71, 72, 73, 135, 136, 500] {
+            let mut hasher = Sha3_512::new();
+            for chunk in message[..len].chunks(13) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize(), sha3_512(&message[..len]));
+        }
+
+        utils::digest::check_digest::<Sha3_224>();
+        utils::digest::check_digest::<Sha3_256>();
+        utils::digest::check_digest::<Sha3_384>();
+        utils::digest::check_digest::<Sha3_512>();
+    }
+
+    #[test]
+    fn hashes_through_io_copy() {
+        let message: Vec<u8> = (0..500u32).map(|i| (i * 7) as u8).collect();
+
+        let mut hasher = Sha3_256::new();
+        io::copy(&mut message.as_slice(), &mut hasher).unwrap();
+        assert_eq!(hasher.finalize(), sha3_256(&message));
+    }
+}
@@ -0,0 +1,198 @@
+//! The message buffering and final padding shared by the Merkle–Damgård
+//! hashes over 512-bit blocks: MD5, SHA-1, SHA-256 and RIPEMD-160.
+//!
+//! All four append a `1` bit, zeros up to 56 bytes into the last block and
+//! the message length in bits as a 64-bit trailer. They differ only in the
+//! byte order of that trailer.
+
+/// The block size in bytes.
+pub const BLOCK_SIZE: usize = 64;
+
+// Position in the final block at which the 64-bit length trailer starts.
+const LENGTH_OFFSET: usize = BLOCK_SIZE - 8;
+
+/// The byte order of the length trailer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthEncoding {
+    /// SHA-1 and SHA-256.
+    BigEndian,
+    /// MD5 and RIPEMD-160.
+    LittleEndian,
+}
+
+/// Collects message bytes into whole blocks for a compression function and
+/// pads the final one.
+///
+/// Only a single partial block is ever buffered; complete blocks are handed
+/// to the compression function as soon as they are available.
+#[derive(Clone, Debug)]
+pub struct BlockBuffer {
+    buffer: [u8; BLOCK_SIZE],
+    buffer_len: usize,
+    /// The message length in bytes, including blocks already compressed.
+    length: u64,
+}
+
+impl Default for BlockBuffer {
+    fn default() -> Self {
+        BlockBuffer::new()
+    }
+}
+
+impl BlockBuffer {
+    /// Creates an empty buffer for a new message.
+    pub fn new() -> Self {
+        Self::resume(0)
+    }
+
+    /// Creates an empty buffer for a message of which `bytes_processed`
+    /// bytes have already been compressed, e.g. when resuming from a midstate.
+    ///
+    /// # Arguments
+    /// * `bytes_processed` - The number of message bytes already compressed.
+    pub fn resume(bytes_processed: u64) -> Self {
+        BlockBuffer {
+            buffer: [0u8; BLOCK_SIZE],
+            buffer_len: 0,
+            length: bytes_processed,
+        }
+    }
+
+    /// Returns the number of message bytes handed to the compression
+    /// function so far, excluding the buffered partial block.
+    pub fn bytes_compressed(&self) -> u64 {
+        self.length - self.buffer_len as u64
+    }
+
+    /// Appends `data` to the message, compressing every block it completes.
+    ///
+    /// # Arguments
+    /// * `data` - The next chunk of the message.
+    /// * `compress` - The compression function, called once per complete block.
+    pub fn update(&mut self, mut data: &[u8], mut compress: impl FnMut(&[u8; BLOCK_SIZE])) {
+        self.length = self.length.wrapping_add(data.len() as u64);
+
+        // Top up a previously buffered partial block first.
+        if self.buffer_len > 0 {
+            let take = (BLOCK_SIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+
+            if self.buffer_len < BLOCK_SIZE {
+                return;
+            }
+
+            compress(&self.buffer);
+            self.buffer_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(BLOCK_SIZE);
+        for block in blocks.by_ref() {
+            compress(block.try_into().expect("chunk is one block"));
+        }
+
+        let remainder = blocks.remainder();
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+        self.buffer_len = remainder.len();
+    }
+
+    /// Pads the message, compresses the final one or two blocks and resets
+    /// the buffer for a new message.
+    ///
+    /// # Arguments
+    /// * `encoding` - The byte order of the length trailer.
+    /// * `compress` - The compression function.
+    pub fn finalize(
+        &mut self,
+        encoding: LengthEncoding,
+        mut compress: impl FnMut(&[u8; BLOCK_SIZE]),
+    ) {
+        let bit_len = self.length.wrapping_mul(8);
+
+        // Append the '1' bit followed by enough '0' bits to leave room for the
+        // 64-bit length trailer in the last block.
+        let mut len = self.buffer_len;
+        self.buffer[len] = 0x80;
+        len += 1;
+
+        if len > LENGTH_OFFSET {
+            self.buffer[len..].fill(0);
+            compress(&self.buffer);
+            len = 0;
+        }
+
+        self.buffer[len..LENGTH_OFFSET].fill(0);
+        self.buffer[LENGTH_OFFSET..].copy_from_slice(&match encoding {
+            LengthEncoding::BigEndian => bit_len.to_be_bytes(),
+            LengthEncoding::LittleEndian => bit_len.to_le_bytes(),
+        });
+        compress(&self.buffer);
+
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `message` in chunks of `chunk` bytes and returns the blocks
+    /// handed to the compression function.
+    fn blocks(message: &[u8], chunk: usize, encoding: LengthEncoding) -> Vec<[u8; BLOCK_SIZE]> {
+        let mut buffer = BlockBuffer::new();
+        let mut blocks = Vec::new();
+
+        for part in message.chunks(chunk.max(1)) {
+            buffer.update(part, |block| blocks.push(*block));
+        }
+        buffer.finalize(encoding, |block| blocks.push(*block));
+        blocks
+    }
+
+    #[test]
+    fn pads_with_the_length_trailer() {
+        for len in [0, 1, 55, 56, 63, 64, 65, 119, 120, 200] {
+            let message: Vec<u8> = (0..len as u8).collect();
+            let padded = blocks(&message, 64, LengthEncoding::BigEndian).concat();
+
+            let expected_len = (len + 8) / BLOCK_SIZE * BLOCK_SIZE + BLOCK_SIZE;
+            assert_eq!(padded.len(), expected_len, "{len} bytes");
+            assert_eq!(padded[..len], message);
+            assert_eq!(padded[len], 0x80);
+            assert!(padded[len + 1..expected_len - 8].iter().all(|&b| b == 0));
+
+            let trailer: [u8; 8] = padded[expected_len - 8..].try_into().unwrap();
+            assert_eq!(u64::from_be_bytes(trailer), 8 * len as u64);
+
+            let little = blocks(&message, 64, LengthEncoding::LittleEndian).concat();
+            assert_eq!(little[..expected_len - 8], padded[..expected_len - 8]);
+            let trailer: [u8; 8] = little[expected_len - 8..].try_into().unwrap();
+            assert_eq!(u64::from_le_bytes(trailer), 8 * len as u64);
+        }
+    }
+
+    #[test]
+    fn chunking_does_not_change_the_blocks() {
+        let message: Vec<u8> = (0..300u32).map(|i| (i * 7) as u8).collect();
+        let whole = blocks(&message, message.len(), LengthEncoding::BigEndian);
+
+        for chunk in [1, 13, 63, 64, 65, 128] {
+            assert_eq!(blocks(&message, chunk, LengthEncoding::BigEndian), whole);
+        }
+    }
+
+    #[test]
+    fn resumes_and_resets() {
+        let mut buffer = BlockBuffer::resume(128);
+        buffer.update(&[0x61; 70], |_| {});
+        assert_eq!(buffer.bytes_compressed(), 192);
+
+        let mut trailer = [0u8; 8];
+        buffer.finalize(LengthEncoding::BigEndian, |block| {
+            trailer.copy_from_slice(&block[LENGTH_OFFSET..])
+        });
+        assert_eq!(u64::from_be_bytes(trailer), 8 * 198);
+        assert_eq!(buffer.bytes_compressed(), 0);
+    }
+}
@@ -0,0 +1,77 @@
+//! A common interface for incremental hash functions, so that constructions
+//! built on a hash (HMAC, Merkle trees, RSA-PSS) can be written once and
+//! instantiated with any of them.
+//!
+//! The trait is named `HashFunction` rather than `Digest` so that it does
+//! not clash with `sha_256::Digest`, the fixed-size SHA-256 output.
+
+pub trait HashFunction {
+    /// The size in bytes of the blocks the hash processes its input in, the
+    /// rate for a sponge. HMAC pads its key to this size.
+    const BLOCK_SIZE: usize;
+
+    /// Creates a hasher in its initial state.
+    fn new() -> Self
+    where
+        Self: Sized;
+
+    /// Absorbs `data` into the hash computation.
+    fn update(&mut self, data: &[u8]);
+
+    /// Consumes the hasher and returns the digest.
+    fn finalize(self) -> Vec<u8>;
+
+    /// The length of the digest in bytes.
+    fn output_size(&self) -> usize;
+
+    /// Hashes `data` in one shot.
+    fn digest(data: &[u8]) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut hasher = Self::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+/// Checks the behaviour every `HashFunction` implementation must share.
+///
+/// Intended to be called from the test suites of the crates implementing
+/// `HashFunction`, which enable the `test-support` feature from their
+/// dev-dependencies.
+///
+/// # Panics
+/// Panics if any of the properties does not hold.
+#[cfg(any(test, feature = "test-support"))]
+pub fn check_digest<D: HashFunction>() {
+    let message: Vec<u8> = (0..300u32).map(|i| (i * 13 + 7) as u8).collect();
+
+    let empty = D::new();
+    let output_size = empty.output_size();
+    assert!(output_size > 0, "Digest must not be empty");
+    assert!(D::BLOCK_SIZE > 0, "Block size must not be zero");
+    assert_eq!(empty.finalize().len(), output_size);
+
+    // Splitting the message across updates does not change the digest.
+    let digest = D::digest(&message);
+    assert_eq!(digest.len(), output_size);
+    for chunk_size in [1, 7, 64, 65, 128, message.len()] {
+        let mut hasher = D::new();
+        for chunk in message.chunks(chunk_size) {
+            hasher.update(chunk);
+            hasher.update(&[]);
+        }
+        assert_eq!(
+            hasher.finalize(),
+            digest,
+            "Chunk size {chunk_size} changed the digest"
+        );
+    }
+
+    // Any change to the message changes the digest.
+    let mut tampered = message.clone();
+    tampered[100] ^= 0x01;
+    assert_ne!(D::digest(&tampered), digest);
+    assert_ne!(D::digest(&message[..message.len() - 1]), digest);
+}
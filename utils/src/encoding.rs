@@ -0,0 +1,65 @@
+//! Fixed-width byte encodings of big integers, so that every crate hashing or
+//! serializing a `BigUint` agrees on the same padding convention.
+use num_bigint::BigUint;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EncodeError {
+    #[error("Value needs `{needed}` bytes, which does not fit in `{width}` bytes")]
+    ValueTooLarge { needed: usize, width: usize },
+}
+
+/// Encodes `n` as a big-endian byte string of exactly `width` bytes,
+/// left-padded with zeros (the I2OSP primitive of RFC 8017).
+///
+/// # Arguments
+/// * `n` - The value to encode.
+/// * `width` - The length of the output in bytes.
+///
+/// # Returns
+/// The padded encoding, or `EncodeError::ValueTooLarge` if `n` needs more than `width` bytes.
+pub fn to_bytes_be_padded(n: &BigUint, width: usize) -> Result<Vec<u8>, EncodeError> {
+    let needed = n.bits().div_ceil(8) as usize;
+    if needed > width {
+        return Err(EncodeError::ValueTooLarge { needed, width });
+    }
+
+    let mut bytes = vec![0u8; width];
+    if needed > 0 {
+        bytes[width - needed..].copy_from_slice(&n.to_bytes_be());
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_to_width() {
+        assert_eq!(
+            to_bytes_be_padded(&BigUint::from(0u8), 3),
+            Ok(vec![0, 0, 0])
+        );
+        assert_eq!(
+            to_bytes_be_padded(&BigUint::from(0x0102u16), 4),
+            Ok(vec![0, 0, 1, 2])
+        );
+        assert_eq!(
+            to_bytes_be_padded(&BigUint::from(0x010203u32), 3),
+            Ok(vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn rejects_values_wider_than_width() {
+        assert_eq!(
+            to_bytes_be_padded(&BigUint::from(0x010203u32), 2),
+            Err(EncodeError::ValueTooLarge {
+                needed: 3,
+                width: 2
+            })
+        );
+    }
+}
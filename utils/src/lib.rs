@@ -1,2 +1,7 @@
+pub mod block_buffer;
+pub mod digest;
+pub mod encoding;
+pub mod mac;
 pub mod modular_inverse;
 pub mod relative_prime;
+pub mod wipe;
@@ -0,0 +1,138 @@
+//! A common interface for message authentication codes, so that code built on
+//! top of a MAC (encrypt-then-MAC, key confirmation) can stay generic over the
+//! concrete algorithm.
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MacError {
+    #[error("MAC verification failed")]
+    VerificationFailed,
+}
+
+pub trait Mac {
+    /// Absorbs `data` into the MAC computation.
+    fn update(&mut self, data: &[u8]);
+
+    /// Consumes the MAC and returns the authentication tag.
+    fn finalize(self) -> Vec<u8>;
+
+    /// Consumes the MAC and checks the computed tag against `tag` in constant time.
+    ///
+    /// # Arguments
+    /// * `tag` - The tag received alongside the message.
+    ///
+    /// # Returns
+    /// `Ok(())` if the tags are equal, `MacError::VerificationFailed` otherwise.
+    fn verify(self, tag: &[u8]) -> Result<(), MacError>
+    where
+        Self: Sized,
+    {
+        if ct_eq(&self.finalize(), tag) {
+            Ok(())
+        } else {
+            Err(MacError::VerificationFailed)
+        }
+    }
+}
+
+/// Compares two byte strings without branching on their contents.
+///
+/// Only the lengths, which are public for a MAC tag, affect the timing.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+
+    diff == 0
+}
+
+/// Checks the behaviour every `Mac` implementation must share.
+///
+/// Intended to be called from the test suites of the crates implementing
+/// `Mac`, which enable the `test-support` feature from their
+/// dev-dependencies. `new` must return a fresh instance under the same key
+/// each time.
+///
+/// # Panics
+/// Panics if any of the properties does not hold.
+#[cfg(any(test, feature = "test-support"))]
+pub fn check_mac<M: Mac>(new: impl Fn() -> M) {
+    let message: Vec<u8> = (0..200u32).map(|i| (i * 13 + 7) as u8).collect();
+
+    let tag_of = |data: &[u8]| {
+        let mut mac = new();
+        mac.update(data);
+        mac.finalize()
+    };
+
+    // Empty messages produce a stable tag that verifies.
+    let empty = new().finalize();
+    assert!(!empty.is_empty(), "Tag must not be empty");
+    assert_eq!(tag_of(&[]), empty);
+    assert_eq!(new().verify(&empty), Ok(()));
+
+    // Splitting the message across updates does not change the tag.
+    let tag = tag_of(&message);
+    for chunk_size in [1, 7, 16, 64, 65, message.len()] {
+        let mut mac = new();
+        for chunk in message.chunks(chunk_size) {
+            mac.update(chunk);
+            mac.update(&[]);
+        }
+        assert_eq!(
+            mac.finalize(),
+            tag,
+            "Chunk size {chunk_size} changed the tag"
+        );
+    }
+
+    let verify = |data: &[u8], tag: &[u8]| {
+        let mut mac = new();
+        mac.update(data);
+        mac.verify(tag)
+    };
+
+    assert_eq!(verify(&message, &tag), Ok(()));
+
+    // Any flipped tag bit, truncation or extension of the tag is rejected.
+    for index in 0..tag.len() * 8 {
+        let mut tampered = tag.clone();
+        tampered[index / 8] ^= 1 << (index % 8);
+        assert_eq!(
+            verify(&message, &tampered),
+            Err(MacError::VerificationFailed)
+        );
+    }
+    assert_eq!(
+        verify(&message, &tag[..tag.len() - 1]),
+        Err(MacError::VerificationFailed)
+    );
+    assert_eq!(
+        verify(&message, &[tag.as_slice(), &[0]].concat()),
+        Err(MacError::VerificationFailed)
+    );
+
+    // Tampering with the message is rejected as well.
+    let mut tampered = message.clone();
+    tampered[100] ^= 0x80;
+    assert_eq!(verify(&tampered, &tag), Err(MacError::VerificationFailed));
+    assert_eq!(
+        verify(&message[..message.len() - 1], &tag),
+        Err(MacError::VerificationFailed)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ct_eq_compares_contents_and_length() {
+        assert!(ct_eq(&[], &[]));
+        assert!(ct_eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2]));
+    }
+}
@@ -3,11 +3,20 @@ use num_traits::{One, Zero};
 
 use super::relative_prime;
 
-pub fn mod_inverse(mut a: BigInt, mut m: BigInt) -> BigInt {
+pub fn mod_inverse(a: BigInt, m: BigInt) -> BigInt {
     if !relative_prime::is_co_prime(&a, &m) {
         panic!("{:?} and {:?} are not not co-prime", a, m);
     }
 
+    try_mod_inverse(a, m).unwrap()
+}
+
+/// Computes the inverse of `a` modulo `m`, or `None` if `a` and `m` are not co-prime.
+pub fn try_mod_inverse(mut a: BigInt, mut m: BigInt) -> Option<BigInt> {
+    if !relative_prime::is_co_prime(&a, &m) {
+        return None;
+    }
+
     let m0 = m.clone();
     let mut y = BigInt::zero();
     let mut x = BigInt::one();
@@ -32,7 +41,7 @@ pub fn mod_inverse(mut a: BigInt, mut m: BigInt) -> BigInt {
         x += m0;
     }
 
-    x
+    Some(x)
 }
 
 #[cfg(test)]
@@ -46,4 +55,11 @@ mod tests {
         let m = 11.to_bigint().unwrap();
         assert_eq!(mod_inverse(a, m), 4.to_bigint().unwrap());
     }
+
+    #[test]
+    fn try_mod_inverse_without_inverse() {
+        let a = 6.to_bigint().unwrap();
+        let m = 9.to_bigint().unwrap();
+        assert_eq!(try_mod_inverse(a, m), None);
+    }
 }
@@ -1,5 +1,5 @@
 use num_bigint::BigInt;
-use num_traits::{One, Zero};
+use num_traits::{One, Signed, Zero};
 
 pub fn is_co_prime(a: &BigInt, b: &BigInt) -> bool {
     gcd(a, b) == BigInt::one()
@@ -17,6 +17,18 @@ pub fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
     a
 }
 
+/// Computes the least common multiple of `a` and `b`.
+///
+/// # Returns
+/// `|a·b| / gcd(a, b)`, or 0 if either argument is 0.
+pub fn lcm(a: &BigInt, b: &BigInt) -> BigInt {
+    if a.is_zero() || b.is_zero() {
+        return BigInt::zero();
+    }
+
+    (a / gcd(a, b) * b).abs()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,4 +40,15 @@ mod tests {
         let b = 11.to_bigint().unwrap();
         assert!(is_co_prime(&a, &b));
     }
+
+    #[test]
+    fn check_lcm() {
+        let lcm_of = |a: i32, b: i32| lcm(&BigInt::from(a), &BigInt::from(b));
+
+        assert_eq!(lcm_of(60, 52), BigInt::from(780));
+        assert_eq!(lcm_of(4, 6), BigInt::from(12));
+        assert_eq!(lcm_of(7, 7), BigInt::from(7));
+        assert_eq!(lcm_of(-4, 6), BigInt::from(12));
+        assert_eq!(lcm_of(0, 6), BigInt::from(0));
+    }
 }
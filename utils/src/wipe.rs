@@ -0,0 +1,159 @@
+//! Best-effort wiping of secret big integers.
+//!
+//! `BigUint` does not expose its digit buffer, so it cannot implement
+//! `zeroize::Zeroize` directly. `wipe_biguint` overwrites the buffer in place
+//! through the public API before the allocation is released.
+//!
+//! `WatchingAllocator` and `freed_with` let a test suite check that a type's
+//! `Drop` really wipes its secrets before the memory is handed back. They
+//! are only built for tests, with the `test-support` feature.
+use std::hint::black_box;
+#[cfg(any(test, feature = "test-support"))]
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+/// Overwrites the digits of `value` and sets it to zero.
+///
+/// The buffer is refilled with as many digits as it held, all zero except
+/// the top one, so the value stays normalized and keeps its allocation
+/// while the old digits are overwritten. Copies made earlier, e.g. by
+/// arithmetic on the value, are not reached.
+///
+/// # Arguments
+/// * `value` - The secret to wipe.
+pub fn wipe_biguint(value: &mut BigUint) {
+    let len = value.bits().div_ceil(32) as usize;
+    if len == 0 {
+        return;
+    }
+
+    // Round up to whole 64-bit digits so every stored digit is rewritten.
+    let mut filler = vec![0u32; len.div_ceil(2) * 2];
+    *filler.last_mut().expect("non-empty") = 1;
+    value.assign_from_slice(&filler);
+    black_box(&*value);
+
+    value.set_zero();
+}
+
+/// A global allocator that hands out zeroed blocks and, while `freed_with`
+/// is running on the current thread, checks every block freed on that
+/// thread for a watched byte pattern.
+///
+/// Intended to be installed with `#[global_allocator]` in the test suites
+/// calling `freed_with`. Zeroing on allocation keeps every byte of a block
+/// initialized, so freed blocks can be scanned as a whole.
+#[cfg(any(test, feature = "test-support"))]
+pub struct WatchingAllocator;
+
+#[cfg(any(test, feature = "test-support"))]
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(any(test, feature = "test-support"))]
+thread_local! {
+    /// The pattern being watched for, as a pointer and length into the
+    /// caller's slice, or null outside `freed_with`.
+    static WATCHED: Cell<(*const u8, usize)> = const { Cell::new((std::ptr::null(), 0)) };
+    static FOUND: Cell<bool> = const { Cell::new(false) };
+}
+
+#[cfg(any(test, feature = "test-support"))]
+unsafe impl GlobalAlloc for WatchingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        INSTALLED.store(true, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // `try_with` fails only while the thread's locals are being torn
+        // down, when nothing is watched.
+        let _ = WATCHED.try_with(|watched| {
+            let (pattern, len) = watched.get();
+            if !pattern.is_null() && len <= layout.size() {
+                let block = std::slice::from_raw_parts(ptr, layout.size());
+                let pattern = std::slice::from_raw_parts(pattern, len);
+                if block.windows(len).any(|window| window == pattern) {
+                    FOUND.set(true);
+                }
+            }
+        });
+        System.dealloc(ptr, layout);
+    }
+}
+
+/// Runs `f` and reports whether any block freed on the current thread in the
+/// meantime still held `pattern`.
+///
+/// # Arguments
+/// * `pattern` - The secret bytes, or a distinctive part of them, as laid out in memory.
+/// * `f` - The code releasing the secret, typically a `drop`.
+///
+/// # Panics
+/// Panics if `WatchingAllocator` is not the global allocator, or if `pattern`
+/// is empty.
+#[cfg(any(test, feature = "test-support"))]
+pub fn freed_with(pattern: &[u8], f: impl FnOnce()) -> bool {
+    assert!(!pattern.is_empty(), "empty pattern");
+    drop(Box::new(0u8));
+    assert!(
+        INSTALLED.load(Ordering::Relaxed),
+        "WatchingAllocator is not the global allocator"
+    );
+
+    FOUND.set(false);
+    WATCHED.set((pattern.as_ptr(), pattern.len()));
+    f();
+    WATCHED.set((std::ptr::null(), 0));
+    FOUND.get()
+}
+
+/// The in-memory bytes of the lowest digits of `value`, for use as a
+/// `freed_with` pattern.
+///
+/// # Arguments
+/// * `value` - A secret of at least 128 bits.
+#[cfg(any(test, feature = "test-support"))]
+pub fn biguint_pattern(value: &BigUint) -> Vec<u8> {
+    value
+        .to_u64_digits()
+        .iter()
+        .take(2)
+        .flat_map(|digit| digit.to_ne_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: WatchingAllocator = WatchingAllocator;
+
+    #[test]
+    fn wipes_to_zero() {
+        for bits in [0u32, 1, 31, 32, 64, 65, 2048] {
+            let mut value = (BigUint::from(1u8) << bits) - 1u8;
+            wipe_biguint(&mut value);
+            assert!(value.is_zero(), "{bits} bits");
+        }
+    }
+
+    #[test]
+    fn watches_freed_blocks() {
+        let secret = (BigUint::from(0x5au8) << 2048) - 0x3du8;
+        let pattern = biguint_pattern(&secret);
+
+        let copy = secret.clone();
+        assert!(freed_with(&pattern, || drop(copy)));
+
+        let mut copy = secret.clone();
+        wipe_biguint(&mut copy);
+        assert!(!freed_with(&pattern, || drop(copy)));
+    }
+}